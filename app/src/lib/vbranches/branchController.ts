@@ -60,7 +60,7 @@ export class BranchController {
 				branch,
 				message,
 				ownership,
-				runHooks: runHooks
+				options: { runHooks }
 			});
 			posthog.capture('Commit Successful');
 		} catch (err: any) {
@@ -22,6 +22,7 @@ export class Hunk {
 	modifiedAt!: Date;
 	filePath!: string;
 	hash?: string;
+	stableId!: string;
 	locked!: boolean;
 	@Type(() => HunkLock)
 	lockedTo!: HunkLock[];
@@ -33,6 +34,8 @@ export class Hunk {
 export class HunkLock {
 	branchId!: string;
 	commitId!: string;
+	author!: string;
+	summary!: string;
 }
 
 export type AnyFile = LocalFile | RemoteFile;
@@ -1,8 +1,12 @@
 use std::borrow::Cow;
-use std::path::PathBuf;
-use std::{collections::HashMap, str};
+use std::path::{Path, PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str,
+    sync::{Arc, Mutex},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bstr::{BStr, BString, ByteSlice, ByteVec};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -13,7 +17,7 @@ use crate::virtual_branches::Branch;
 pub type DiffByPathMap = HashMap<PathBuf, FileDiff>;
 
 /// The type of change
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ChangeType {
     /// Entry does not exist in old version
@@ -100,6 +104,278 @@ impl GitHunk {
     }
 }
 
+/// Conversion
+impl GitHunk {
+    /// Return this hunk with its diff parsed into [lines](DiffLine) that know their line numbers,
+    /// for consumers that can't work with the unified diff text, or `None` if it's binary.
+    pub fn to_structured(&self) -> Option<StructuredHunk> {
+        if self.binary {
+            return None;
+        }
+        let (mut old_line, mut new_line) = (self.old_start, self.new_start);
+        let mut lines = Vec::new();
+        for line in self.diff_lines.lines() {
+            let (kind, content) = match line.split_first() {
+                Some((b'+', content)) => (DiffLineKind::Addition, content),
+                Some((b'-', content)) => (DiffLineKind::Deletion, content),
+                Some((b' ', content)) => (DiffLineKind::Context, content),
+                // the hunk header, or lines like `\ No newline at end of file`
+                _ => continue,
+            };
+            let (old, new) = match kind {
+                DiffLineKind::Addition => (None, Some(new_line)),
+                DiffLineKind::Deletion => (Some(old_line), None),
+                DiffLineKind::Context => (Some(old_line), Some(new_line)),
+            };
+            old_line += u32::from(old.is_some());
+            new_line += u32::from(new.is_some());
+            lines.push(DiffLine {
+                kind,
+                old_line: old,
+                new_line: new,
+                content: content.into(),
+            });
+        }
+        Some(StructuredHunk {
+            old_start: self.old_start,
+            old_lines: self.old_lines,
+            new_start: self.new_start,
+            new_lines: self.new_lines,
+            lines,
+        })
+    }
+
+    /// Return the words that were removed and added within the lines this hunk changes, so they
+    /// can be highlighted within the lines, or nothing if it's binary.
+    ///
+    /// Each run of removed lines that is directly followed by added lines is compared word by
+    /// word, with words being runs of alphanumeric characters or whitespace, and each other
+    /// character being a word of its own. Lines that are only removed or only added aren't
+    /// listed, as they changed as a whole. The diff of the hunk itself is left as it is, so
+    /// its [hash](crate::virtual_branches::branch::Hunk::hash_diff) doesn't depend on this.
+    pub fn word_diff(&self) -> Vec<WordDiffLine> {
+        let Some(structured) = self.to_structured() else {
+            return Vec::new();
+        };
+        let lines = &structured.lines;
+        let mut changes = Vec::new();
+        let mut index = 0;
+        while index < lines.len() {
+            let removed_end = index
+                + lines[index..]
+                    .iter()
+                    .take_while(|line| line.kind == DiffLineKind::Deletion)
+                    .count();
+            let added_end = removed_end
+                + lines[removed_end..]
+                    .iter()
+                    .take_while(|line| line.kind == DiffLineKind::Addition)
+                    .count();
+            if removed_end > index && added_end > removed_end {
+                add_word_changes(
+                    &lines[index..removed_end],
+                    &lines[removed_end..added_end],
+                    &mut changes,
+                );
+            }
+            index = added_end.max(index + 1);
+        }
+        changes
+    }
+}
+
+/// Whether to compute the changed words within changed lines, see [`GitHunk::word_diff()`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WordDiff {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+/// The words that were removed from or added to a line of a hunk, see [`GitHunk::word_diff()`].
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordDiffLine {
+    /// [`DiffLineKind::Deletion`] for a removed line, [`DiffLineKind::Addition`] for an added one.
+    pub kind: DiffLineKind,
+    /// The number of the line in the old file if it was removed, or in the new file if it was
+    /// added.
+    pub line: u32,
+    /// The byte ranges of the removed or added words within the line, without its prefix.
+    pub spans: Vec<WordSpan>,
+}
+
+/// A range of bytes within the content of a line, see [`WordDiffLine::spans`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The amount of word pairs above which the removed and added lines aren't compared word by
+/// word, but are considered changed as a whole, to bound the time and memory it takes.
+const WORD_DIFF_MAX_COMPARISONS: usize = 1_000_000;
+
+/// Compare the words of the `removed` lines with those of the `added` lines, and add the lines
+/// with words that aren't in both to `changes`.
+fn add_word_changes(removed: &[DiffLine], added: &[DiffLine], changes: &mut Vec<WordDiffLine>) {
+    // words are the line index and byte range within it, and lines end with an empty word so
+    // words of different lines aren't joined
+    let words_of = |lines: &[DiffLine]| -> Vec<(usize, usize, usize)> {
+        lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line_index, line)| {
+                tokenize_words(&line.content)
+                    .into_iter()
+                    .map(move |(start, end)| (line_index, start, end))
+                    .chain(Some((line_index, line.content.len(), line.content.len())))
+            })
+            .collect()
+    };
+    let (old_words, new_words) = (words_of(removed), words_of(added));
+
+    let mut old_changed = vec![true; old_words.len()];
+    let mut new_changed = vec![true; new_words.len()];
+    if old_words.len().saturating_mul(new_words.len()) <= WORD_DIFF_MAX_COMPARISONS {
+        // the length of the longest common subsequence of the words from each pair of positions
+        let width = new_words.len() + 1;
+        let mut common = vec![0u32; (old_words.len() + 1) * width];
+        for old in (0..old_words.len()).rev() {
+            for new in (0..new_words.len()).rev() {
+                common[old * width + new] =
+                    if word_at(removed, old_words[old]) == word_at(added, new_words[new]) {
+                        common[(old + 1) * width + new + 1] + 1
+                    } else {
+                        common[(old + 1) * width + new].max(common[old * width + new + 1])
+                    };
+            }
+        }
+        let (mut old, mut new) = (0, 0);
+        while old < old_words.len() && new < new_words.len() {
+            if word_at(removed, old_words[old]) == word_at(added, new_words[new]) {
+                old_changed[old] = false;
+                new_changed[new] = false;
+                old += 1;
+                new += 1;
+            } else if common[(old + 1) * width + new] >= common[old * width + new + 1] {
+                old += 1;
+            } else {
+                new += 1;
+            }
+        }
+    }
+
+    for (lines, words, changed) in [
+        (removed, &old_words, &old_changed),
+        (added, &new_words, &new_changed),
+    ] {
+        let mut spans_by_line = vec![Vec::<WordSpan>::new(); lines.len()];
+        for (&(line_index, start, end), &changed) in words.iter().zip(changed.iter()) {
+            if !changed || start == end {
+                continue;
+            }
+            let spans = &mut spans_by_line[line_index];
+            match spans.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => spans.push(WordSpan { start, end }),
+            }
+        }
+        for (line, spans) in lines.iter().zip(spans_by_line) {
+            if spans.is_empty() {
+                continue;
+            }
+            let line_number = match line.kind {
+                DiffLineKind::Deletion => line.old_line,
+                _ => line.new_line,
+            };
+            changes.push(WordDiffLine {
+                kind: line.kind,
+                line: line_number.unwrap_or_default(),
+                spans,
+            });
+        }
+    }
+}
+
+/// Return the content of the word at `line_index`, `start` and `end` of `lines`, and whether it's
+/// the empty word that marks the end of a line.
+fn word_at(lines: &[DiffLine], (line_index, start, end): (usize, usize, usize)) -> (bool, &[u8]) {
+    let content: &[u8] = &lines[line_index].content;
+    (start == end, &content[start..end])
+}
+
+/// Split `content` into the byte ranges of its words, which are runs of alphanumeric
+/// characters, underscores and non-ASCII characters, runs of whitespace, or single other
+/// characters.
+fn tokenize_words(content: &[u8]) -> Vec<(usize, usize)> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Whitespace,
+        Other,
+    }
+    let class_of = |byte: u8| {
+        if byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii() {
+            Class::Word
+        } else if byte.is_ascii_whitespace() {
+            Class::Whitespace
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut words = Vec::new();
+    let mut start = 0;
+    while start < content.len() {
+        let class = class_of(content[start]);
+        let mut end = start + 1;
+        if class != Class::Other {
+            while end < content.len() && class_of(content[end]) == class {
+                end += 1;
+            }
+        }
+        words.push((start, end));
+        start = end;
+    }
+    words
+}
+
+/// A [`GitHunk`] with its diff parsed into lines, see [`GitHunk::to_structured()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A single line of a [`StructuredHunk`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The number of the line in the old file, unless it was added.
+    pub old_line: Option<u32>,
+    /// The number of the line in the new file, unless it was removed.
+    pub new_line: Option<u32>,
+    /// The line without its `+`, `-` or ` ` prefix and line separator.
+    #[serde(serialize_with = "crate::serde::as_string_lossy")]
+    pub content: BString,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffLineKind {
+    Context,
+    Addition,
+    Deletion,
+}
+
 /// Comparison
 impl GitHunk {
     /// integration_intersects_unapplied is used to determine if a hunk from a diff between integration and the trunk intersects with an unapplied hunk.
@@ -119,12 +395,30 @@ impl GitHunk {
 // A hunk is locked when it depends on changes in commits that are in your
 // workspace. A hunk can be locked to more than one branch if it overlaps
 // with more than one committed hunk.
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HunkLock {
     pub branch_id: Id<Branch>,
     #[serde(with = "crate::serde::oid")]
     pub commit_id: git2::Oid,
+    /// The name of the author of the commit, as resolved through the `.mailmap`.
+    #[serde(default)]
+    pub author: String,
+    /// The first line of the message of the commit.
+    #[serde(default)]
+    pub summary: String,
+}
+
+impl HunkLock {
+    /// A lock to `commit_id` on `branch_id` whose author and summary are yet to be filled in.
+    pub fn new(branch_id: Id<Branch>, commit_id: git2::Oid) -> Self {
+        Self {
+            branch_id,
+            commit_id,
+            author: String::new(),
+            summary: String::new(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Default)]
@@ -141,15 +435,256 @@ pub struct FileDiff {
     pub binary: bool,
     pub old_size_bytes: u64,
     pub new_size_bytes: u64,
+    /// The modes of the file before and after if they differ, which is the only change of a file
+    /// that was renamed or had its executable bit toggled without changing its content.
+    pub mode_change: Option<ModeChange>,
+}
+
+/// The change of the mode of a file that exists on both sides of a diff.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModeChange {
+    /// The mode as git stores it, e.g. `0o100644` for regular files and `0o100755` for executable
+    /// ones.
+    pub old_mode: u32,
+    pub new_mode: u32,
+}
+
+impl ModeChange {
+    /// Return the mode change of `delta`, if any.
+    pub fn from_delta(delta: &git2::DiffDelta) -> Option<Self> {
+        let (old_mode, new_mode) = (delta.old_file().mode(), delta.new_file().mode());
+        let missing = git2::FileMode::Unreadable;
+        (old_mode != new_mode && old_mode != missing && new_mode != missing).then(|| ModeChange {
+            old_mode: old_mode.into(),
+            new_mode: new_mode.into(),
+        })
+    }
 }
 
 #[instrument(skip(repository))]
 pub fn workdir(repository: &git2::Repository, commit_oid: &git2::Oid) -> Result<DiffByPathMap> {
+    workdir_with_pathspecs(repository, commit_oid, &[])
+}
+
+/// Like [`workdir()`], but only diff the paths matching any of `pathspecs`, which are matched with
+/// git semantics, i.e. they are literal paths, directories or globs, and exclude all paths they
+/// match if prefixed with `!`. All paths are diffed if `pathspecs` is empty.
+#[instrument(skip(repository))]
+pub fn workdir_with_pathspecs(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    pathspecs: &[String],
+) -> Result<DiffByPathMap> {
+    workdir_diff(repository, commit_oid, pathspecs, false, false, 3, None)
+}
+
+/// Like [`workdir_with_pathspecs()`], but with the untracked files that are ignored as well.
+/// Tracked files are always diffed, even if they match an ignore pattern.
+#[instrument(skip(repository))]
+pub fn workdir_with_ignored(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    pathspecs: &[String],
+) -> Result<DiffByPathMap> {
+    workdir_diff(repository, commit_oid, pathspecs, false, true, 3, None)
+}
+
+/// Like [`workdir_with_pathspecs()`], but a file that was deleted and one that was added with at
+/// least `rename_threshold` percent similar content are listed once under the new path, with
+/// [`FileDiff::old_path`] set to the path it was renamed from and hunks that only show what
+/// changed between both versions. Ignored files are included if `include_ignored` is set.
+#[instrument(skip(repository))]
+pub fn workdir_with_renames(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    pathspecs: &[String],
+    include_ignored: bool,
+    rename_threshold: u16,
+) -> Result<DiffByPathMap> {
+    workdir_diff(
+        repository,
+        commit_oid,
+        pathspecs,
+        false,
+        include_ignored,
+        3,
+        Some(rename_threshold),
+    )
+}
+
+/// Like [`workdir()`], but with `context_lines` of unchanged lines around each hunk.
+#[instrument(skip(repository))]
+pub fn workdir_with_context_lines(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    context_lines: u32,
+) -> Result<DiffByPathMap> {
+    workdir_diff(
+        repository,
+        commit_oid,
+        &[],
+        false,
+        false,
+        context_lines,
+        None,
+    )
+}
+
+/// Like [`workdir_with_context_lines()`], but only diff the file at `path`, which is matched
+/// literally, and return `None` if it is unchanged.
+#[instrument(skip(repository))]
+pub fn workdir_file(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    path: &Path,
+    context_lines: u32,
+) -> Result<Option<FileDiff>> {
+    let pathspec = path
+        .to_str()
+        .with_context(|| format!("path contains invalid utf-8 characters: {path:?}"))?
+        .to_owned();
+    Ok(workdir_diff(
+        repository,
+        commit_oid,
+        &[pathspec],
+        true,
+        false,
+        context_lines,
+        None,
+    )?
+    .remove(path))
+}
+
+/// The size of worktree files above which their content isn't diffed, but they are listed as
+/// [skipped](FileDiff::skipped).
+const LARGE_FILE_LIMIT_BYTES: u64 = 50_000_000;
+
+/// The size of the diff of a single file above which [`worktree_changes_stream()`] is asked to
+/// only report its line counts by callers that need to bound the size of what they return.
+pub const DEFAULT_DIFF_LIMIT_BYTES: usize = 512 * 1024;
+
+/// The diff of a single file as produced by [`worktree_changes_stream()`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum StreamedDiff {
+    /// The complete diff of the file.
+    Full(FileDiff),
+    /// The diff exceeded the size limit, so only the amount of changed lines is known.
+    TooLarge {
+        /// The path the file had before, if it was renamed.
+        old_path: Option<PathBuf>,
+        diff_size_bytes: usize,
+        lines_added: usize,
+        lines_removed: usize,
+    },
+}
+
+/// Like [`workdir_with_renames()`], but diff one file at a time while the returned iterator is
+/// advanced, so the diffs of all files are never held in memory at once. Renames are only detected
+/// if `rename_threshold` is set.
+///
+/// Files whose diff, including headers and context lines, is larger than `diff_limit_bytes` are
+/// yielded as [`StreamedDiff::TooLarge`].
+pub fn worktree_changes_stream<'repo>(
+    repository: &'repo git2::Repository,
+    commit_oid: &git2::Oid,
+    pathspecs: &[String],
+    include_ignored: bool,
+    rename_threshold: Option<u16>,
+    diff_limit_bytes: Option<usize>,
+) -> Result<impl Iterator<Item = Result<(PathBuf, StreamedDiff)>> + 'repo> {
     let commit = repository
         .find_commit(*commit_oid)
         .context("failed to find commit")?;
     let tree = commit.tree().context("failed to find tree")?;
 
+    let mut diff_opts = workdir_diff_options(pathspecs, false, include_ignored, 3);
+    let mut diff = repository.diff_tree_to_workdir(Some(&tree), Some(&mut diff_opts))?;
+    if let Some(rename_threshold) = rename_threshold {
+        diff.find_similar(Some(
+            git2::DiffFindOptions::new()
+                .renames(true)
+                .for_untracked(true)
+                .rename_threshold(rename_threshold),
+        ))
+        .context("failed to detect renames")?;
+    }
+    Ok((0..diff.deltas().len())
+        .map(move |idx| streamed_file_diff(repository, &diff, idx, diff_limit_bytes)))
+}
+
+/// Compute the diff of the worktree file of the delta at `idx` in `diff` for
+/// [`worktree_changes_stream()`].
+fn streamed_file_diff(
+    repository: &git2::Repository,
+    diff: &git2::Diff,
+    idx: usize,
+    diff_limit_bytes: Option<usize>,
+) -> Result<(PathBuf, StreamedDiff)> {
+    let delta = diff.get_delta(idx).context("delta not found in diff")?;
+    let path = delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .context("failed to get file name from diff")?
+        .to_owned();
+    if delta.new_file().size() > LARGE_FILE_LIMIT_BYTES {
+        return Ok((path, StreamedDiff::Full(skipped_file_diff(&delta))));
+    }
+
+    let patch = git2::Patch::from_diff(diff, idx).context("failed to diff file")?;
+    if let (Some(patch), Some(diff_limit_bytes)) = (&patch, diff_limit_bytes) {
+        let diff_size_bytes = patch.size(true, true, true);
+        if diff_size_bytes > diff_limit_bytes {
+            let (_, lines_added, lines_removed) = patch.line_stats()?;
+            return Ok((
+                path,
+                StreamedDiff::TooLarge {
+                    old_path: delta.old_file().path().map(ToOwned::to_owned),
+                    diff_size_bytes,
+                    lines_added,
+                    lines_removed,
+                },
+            ));
+        }
+    }
+
+    let mut file = patch_diff(diff, idx, patch.as_ref())?;
+    if file.binary {
+        // like `hunks_by_filepath()`, keep the binary content the marker refers to
+        if let Some(workdir) = repository.workdir() {
+            let full_path = workdir.join(&path);
+            let id = delta.new_file().id();
+            if !id.is_zero() && full_path.exists() && repository.blob_path(&full_path)? != id {
+                bail!("the content of {path:?} changed while it was diffed");
+            }
+        }
+    }
+    file.old_path = delta.old_file().path().map(ToOwned::to_owned);
+    file.new_path = delta.new_file().path().map(ToOwned::to_owned);
+    file.mode_change = ModeChange::from_delta(&delta);
+    Ok((path, StreamedDiff::Full(file)))
+}
+
+fn tree_diff_options(context_lines: u32, interhunk_lines: u32) -> git2::DiffOptions {
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .recurse_untracked_dirs(true)
+        .include_untracked(true)
+        .show_binary(true)
+        .ignore_submodules(true)
+        .context_lines(context_lines)
+        .interhunk_lines(interhunk_lines)
+        .show_untracked_content(true);
+    diff_opts
+}
+
+fn workdir_diff_options(
+    pathspecs: &[String],
+    literal_pathspecs: bool,
+    include_ignored: bool,
+    context_lines: u32,
+) -> git2::DiffOptions {
     let mut diff_opts = git2::DiffOptions::new();
     diff_opts
         .recurse_untracked_dirs(true)
@@ -157,13 +692,53 @@ pub fn workdir(repository: &git2::Repository, commit_oid: &git2::Oid) -> Result<
         .show_binary(true)
         .show_untracked_content(true)
         .ignore_submodules(true)
-        .context_lines(3);
+        .include_ignored(include_ignored)
+        .recurse_ignored_dirs(include_ignored)
+        .disable_pathspec_match(literal_pathspecs)
+        .context_lines(context_lines);
+    for pathspec in pathspecs {
+        diff_opts.pathspec(pathspec);
+    }
+    diff_opts
+}
+
+fn workdir_diff(
+    repository: &git2::Repository,
+    commit_oid: &git2::Oid,
+    pathspecs: &[String],
+    literal_pathspecs: bool,
+    include_ignored: bool,
+    context_lines: u32,
+    rename_threshold: Option<u16>,
+) -> Result<DiffByPathMap> {
+    let commit = repository
+        .find_commit(*commit_oid)
+        .context("failed to find commit")?;
+    let tree = commit.tree().context("failed to find tree")?;
 
+    let mut diff_opts =
+        workdir_diff_options(pathspecs, literal_pathspecs, include_ignored, context_lines);
     let mut diff = repository.diff_tree_to_workdir(Some(&tree), Some(&mut diff_opts))?;
-    let (mut diff_opts, skipped_files) = without_large_files(50_000_000, &diff, diff_opts);
-    if !skipped_files.is_empty() {
+    // the small files already match `pathspecs`, so only they are diffed again
+    let (mut diff_opts, skipped_files) = without_large_files(
+        LARGE_FILE_LIMIT_BYTES,
+        &diff,
+        workdir_diff_options(&[], true, include_ignored, context_lines),
+    );
+    if skipped_files.len() == diff.deltas().len() {
+        diff = repository.diff_tree_to_tree(Some(&tree), Some(&tree), None)?;
+    } else if !skipped_files.is_empty() {
         diff = repository.diff_tree_to_workdir(Some(&tree), Some(&mut diff_opts))?;
     }
+    if let Some(rename_threshold) = rename_threshold {
+        diff.find_similar(Some(
+            git2::DiffFindOptions::new()
+                .renames(true)
+                .for_untracked(true)
+                .rename_threshold(rename_threshold),
+        ))
+        .context("failed to detect renames")?;
+    }
     let diff_files = hunks_by_filepath(Some(repository), &diff);
     diff_files.map(|mut df| {
         for (key, value) in skipped_files {
@@ -178,21 +753,328 @@ pub fn trees(
     old_tree: &git2::Tree,
     new_tree: &git2::Tree,
 ) -> Result<DiffByPathMap> {
-    let mut diff_opts = git2::DiffOptions::new();
-    diff_opts
-        .recurse_untracked_dirs(true)
-        .include_untracked(true)
-        .show_binary(true)
-        .ignore_submodules(true)
-        .context_lines(3)
-        .show_untracked_content(true);
+    trees_with_context_lines(repository, old_tree, new_tree, 3, 0)
+}
 
+/// Like [`trees()`], but with `context_lines` of unchanged lines around each hunk, and hunks
+/// separated by no more than `interhunk_lines` unchanged lines merged into one.
+///
+/// Merged hunks are meant for display only, as they lump together changes that are otherwise
+/// independent, which would make them lock to more commits than they touch.
+pub fn trees_with_context_lines(
+    repository: &git2::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    context_lines: u32,
+    interhunk_lines: u32,
+) -> Result<DiffByPathMap> {
+    let mut diff_opts = tree_diff_options(context_lines, interhunk_lines);
     let diff =
         repository.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(&mut diff_opts))?;
 
+    // blobs are immutable, so their diffs can be reused as long as they are computed the same way
+    let mut diff_files = HashMap::new();
+    for (idx, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .context("failed to get file name from diff")?;
+        let key = BlobDiffKey {
+            repository: repository.path().to_owned(),
+            old: delta.old_file().id(),
+            new: delta.new_file().id(),
+            path: path.to_owned(),
+            change_type: delta.status().into(),
+            context_lines,
+            interhunk_lines,
+        };
+        let cached = BLOB_DIFF_CACHE
+            .lock()
+            .ok()
+            .and_then(|mut cache| cache.get(&key));
+        let mut file = match cached {
+            Some(file) => FileDiff::clone(&file),
+            None => {
+                let file = blob_pair_diff(&diff, idx)?;
+                if let Ok(mut cache) = BLOB_DIFF_CACHE.lock() {
+                    cache.insert(key, Arc::new(file.clone()));
+                }
+                file
+            }
+        };
+        file.old_path = delta.old_file().path().map(ToOwned::to_owned);
+        file.new_path = delta.new_file().path().map(ToOwned::to_owned);
+        // modes aren't part of the blob pair the diff was cached for
+        file.mode_change = ModeChange::from_delta(&delta);
+        diff_files.insert(path.to_owned(), file);
+    }
+    Ok(diff_files)
+}
+
+/// Compute the diff of the blobs of the delta at `idx` in `diff` the way [`hunks_by_filepath()`]
+/// does, without the paths, which don't affect the content of the diff.
+fn blob_pair_diff(diff: &git2::Diff, idx: usize) -> Result<FileDiff> {
+    let patch = git2::Patch::from_diff(diff, idx).context("failed to diff blobs")?;
+    patch_diff(diff, idx, patch.as_ref())
+}
+
+/// Turn `patch` of the delta at `idx` in `diff` into a [`FileDiff`] without paths, or one with a
+/// [generic hunk](GitHunk::generic_new_file()) if there is no patch.
+fn patch_diff(diff: &git2::Diff, idx: usize, patch: Option<&git2::Patch>) -> Result<FileDiff> {
+    let delta = match patch {
+        Some(patch) => patch.delta(),
+        None => diff.get_delta(idx).context("delta not found in diff")?,
+    };
+    let change_type: ChangeType = delta.status().into();
+    let binary = delta.flags().is_binary();
+
+    let mut hunks = Vec::new();
+    if binary {
+        hunks.push(GitHunk::binary_marker(
+            delta.new_file().id().to_string(),
+            change_type,
+        ));
+    } else if let Some(patch) = patch {
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, num_lines) = patch.hunk(hunk_idx)?;
+            let mut diff_lines = BString::from(hunk.header());
+            for line_idx in 0..num_lines {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                use git2::DiffLineType as D;
+                match line.origin_value() {
+                    D::Addition | D::Deletion | D::Context => {
+                        diff_lines.push_char(line.origin());
+                        diff_lines.push_str(line.content());
+                    }
+                    D::ContextEOFNL | D::AddEOFNL | D::DeleteEOFNL => {
+                        diff_lines.push_str(line.content());
+                    }
+                    _ => {}
+                }
+            }
+            hunks.push(GitHunk {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                diff_lines,
+                binary: false,
+                change_type,
+                locked_to: Box::new([]),
+            });
+        }
+    }
+    if hunks.is_empty() {
+        hunks.push(GitHunk::generic_new_file());
+    }
+
+    Ok(FileDiff {
+        old_path: None,
+        new_path: None,
+        hunks,
+        skipped: false,
+        binary,
+        old_size_bytes: delta.old_file().size(),
+        new_size_bytes: delta.new_file().size(),
+        mode_change: None,
+    })
+}
+
+/// How much memory the diffs in the cache of blob diffs may occupy, roughly.
+const BLOB_DIFF_CACHE_LIMIT_BYTES: usize = 32 * 1024 * 1024;
+
+lazy_static::lazy_static! {
+    static ref BLOB_DIFF_CACHE: Mutex<BlobDiffCache> =
+        Mutex::new(BlobDiffCache::new(BLOB_DIFF_CACHE_LIMIT_BYTES));
+}
+
+/// What the diff of two blobs depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BlobDiffKey {
+    /// The `.gitattributes` and `diff.*` configuration of the repository affect the diff.
+    repository: PathBuf,
+    old: git2::Oid,
+    new: git2::Oid,
+    /// The attributes of the path decide whether the blobs are diffed as binary, and how.
+    path: PathBuf,
+    /// It's part of each hunk of the diff.
+    change_type: ChangeType,
+    context_lines: u32,
+    interhunk_lines: u32,
+}
+
+/// The diffs of blob pairs that were computed recently, limited to a total size and evicting the
+/// least recently used diffs first. There is no need for invalidation as blobs never change.
+struct BlobDiffCache {
+    limit_bytes: usize,
+    size_bytes: usize,
+    /// Incremented with each access, to know which entry was used least recently.
+    tick: u64,
+    entries: HashMap<BlobDiffKey, (u64, Arc<FileDiff>)>,
+    /// The keys of all entries by the tick they were last used at.
+    recency: BTreeMap<u64, BlobDiffKey>,
+    hits: u64,
+    misses: u64,
+}
+
+/// How well the cache of blob diffs works, see [`blob_diff_cache_stats()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobDiffCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    /// The approximate size of all cached diffs.
+    pub size_bytes: usize,
+}
+
+impl BlobDiffCacheStats {
+    /// The share of lookups that could be answered from the cache, between 0 and 1.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+/// Return the statistics of the cache of blob diffs which is used when diffing trees, for all
+/// repositories of this process.
+pub fn blob_diff_cache_stats() -> BlobDiffCacheStats {
+    BLOB_DIFF_CACHE
+        .lock()
+        .map(|cache| cache.stats())
+        .unwrap_or(BlobDiffCacheStats {
+            hits: 0,
+            misses: 0,
+            entries: 0,
+            size_bytes: 0,
+        })
+}
+
+impl BlobDiffCache {
+    fn new(limit_bytes: usize) -> Self {
+        BlobDiffCache {
+            limit_bytes,
+            size_bytes: 0,
+            tick: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn get(&mut self, key: &BlobDiffKey) -> Option<Arc<FileDiff>> {
+        self.tick += 1;
+        let Some((last_used, file)) = self.entries.get_mut(key) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.recency.remove(last_used);
+        self.recency.insert(self.tick, key.clone());
+        *last_used = self.tick;
+        Some(Arc::clone(file))
+    }
+
+    fn insert(&mut self, key: BlobDiffKey, file: Arc<FileDiff>) {
+        let size = Self::size_of(&file);
+        if size > self.limit_bytes {
+            return;
+        }
+        self.tick += 1;
+        if let Some((last_used, previous)) = self.entries.insert(key.clone(), (self.tick, file)) {
+            self.recency.remove(&last_used);
+            self.size_bytes -= Self::size_of(&previous);
+        }
+        self.recency.insert(self.tick, key);
+        self.size_bytes += size;
+
+        while self.size_bytes > self.limit_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some((_, evicted)) = self.entries.remove(&oldest) {
+                self.size_bytes -= Self::size_of(&evicted);
+            }
+        }
+    }
+
+    fn stats(&self) -> BlobDiffCacheStats {
+        BlobDiffCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            size_bytes: self.size_bytes,
+        }
+    }
+
+    fn size_of(file: &FileDiff) -> usize {
+        std::mem::size_of::<FileDiff>()
+            + file
+                .hunks
+                .iter()
+                .map(|hunk| std::mem::size_of::<GitHunk>() + hunk.diff_lines.len())
+                .sum::<usize>()
+    }
+}
+
+/// Return the added and removed lines of the diff of a hunk, with their `+` and `-` markers and
+/// line terminators, but without its header and context lines.
+pub fn changed_lines(diff_lines: &[u8]) -> Vec<&[u8]> {
+    diff_lines
+        .lines_with_terminator()
+        .filter(|line| matches!(line.first(), Some(b'+' | b'-')))
+        .collect()
+}
+
+/// Return the added and removed lines of each file in `diff`, without their position.
+pub fn changed_lines_by_path(diff: DiffByPathMap) -> BTreeMap<PathBuf, Vec<BString>> {
+    diff.into_iter()
+        .map(|(path, file)| {
+            let lines = file
+                .hunks
+                .iter()
+                .flat_map(|hunk| changed_lines(&hunk.diff_lines))
+                .map(BString::from)
+                .collect();
+            (path, lines)
+        })
+        .collect()
+}
+
+/// The similarity in percent above which git considers a deleted and an added file a rename.
+pub const DEFAULT_RENAME_THRESHOLD: u16 = 50;
+
+/// Like [`trees()`], but a file that was deleted and added with at least `rename_threshold` percent
+/// similar content is listed once under its new path, with [`FileDiff::old_path`] set to the
+/// path it was renamed from.
+pub fn trees_with_renames(
+    repository: &git2::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    rename_threshold: u16,
+) -> Result<DiffByPathMap> {
+    let mut diff_opts = tree_diff_options(3, 0);
+    let mut diff =
+        repository.diff_tree_to_tree(Some(old_tree), Some(new_tree), Some(&mut diff_opts))?;
+    diff.find_similar(Some(
+        git2::DiffFindOptions::new()
+            .renames(true)
+            .rename_threshold(rename_threshold),
+    ))
+    .context("failed to detect renames")?;
+
     hunks_by_filepath(None, &diff)
 }
 
+/// Return the diffs of the files in `diff` that are larger than `size_limit_bytes`, along with
+/// `diff_opts` restricted to the paths of all other files.
+///
+/// `diff_opts` shouldn't have pathspecs of its own, as files matching any of them would be
+/// diffed again along with the small ones.
 pub fn without_large_files(
     size_limit_bytes: u64,
     diff: &git2::Diff,
@@ -202,18 +1084,7 @@ pub fn without_large_files(
     for delta in diff.deltas() {
         if delta.new_file().size() > size_limit_bytes {
             if let Some(path) = delta.new_file().path() {
-                skipped_files.insert(
-                    path.to_path_buf(),
-                    FileDiff {
-                        old_path: delta.old_file().path().map(ToOwned::to_owned),
-                        new_path: delta.new_file().path().map(ToOwned::to_owned),
-                        hunks: Vec::new(),
-                        skipped: true,
-                        binary: true,
-                        old_size_bytes: delta.old_file().size(),
-                        new_size_bytes: delta.new_file().size(),
-                    },
-                );
+                skipped_files.insert(path.to_path_buf(), skipped_file_diff(&delta));
             }
         } else if let Some(path) = delta.new_file().path() {
             if let Some(path) = path.to_str() {
@@ -226,6 +1097,20 @@ pub fn without_large_files(
     (diff_opts, skipped_files)
 }
 
+/// The diff of a file that is too large to look at its content.
+fn skipped_file_diff(delta: &git2::DiffDelta) -> FileDiff {
+    FileDiff {
+        old_path: delta.old_file().path().map(ToOwned::to_owned),
+        new_path: delta.new_file().path().map(ToOwned::to_owned),
+        hunks: Vec::new(),
+        skipped: true,
+        binary: true,
+        old_size_bytes: delta.old_file().size(),
+        new_size_bytes: delta.new_file().size(),
+        mode_change: ModeChange::from_delta(delta),
+    }
+}
+
 /// Transform `diff` into a mapping of `worktree-relative path -> FileDiff`, where `FileDiff` is
 /// all the diff-related information one could ask for. This is mainly to workaround `git2`
 /// which doesn't provide a format that is easy to use or hunk-based, but it's line-by-line only.
@@ -303,6 +1188,7 @@ pub fn hunks_by_filepath(
                                 binary: delta.new_file().is_binary(),
                                 old_size_bytes: delta.old_file().size(),
                                 new_size_bytes: delta.new_file().size(),
+                                mode_change: ModeChange::from_delta(&delta),
                         });
                     if existing.is_some() {
                         err = Some(format!("Encountered an invalid internal state related to the diff: {existing:?}"));
@@ -374,6 +1260,58 @@ pub fn hunks_by_filepath(
     Ok(diff_files)
 }
 
+/// The size of the largest blob for which [`binary_delta_size()`] computes a delta by default.
+pub const BINARY_DELTA_SIZE_LIMIT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Return the size of the binary delta that turns blob `old` into blob `new`, which is roughly
+/// how many bytes differ between them, with `None` for a blob that doesn't exist on that side.
+///
+/// As computing deltas is expensive for large content, `None` is returned if either blob is larger
+/// than `size_limit_bytes`.
+pub fn binary_delta_size(
+    repository: &git2::Repository,
+    old: Option<git2::Oid>,
+    new: Option<git2::Oid>,
+    size_limit_bytes: u64,
+) -> Result<Option<u64>> {
+    // only read the headers to learn the sizes, so blobs over the limit are never loaded
+    let odb = repository.odb()?;
+    for id in old.iter().chain(new.iter()) {
+        let (size, _kind) = odb.read_header(*id)?;
+        if size as u64 > size_limit_bytes {
+            return Ok(None);
+        }
+    }
+    let old = old.map(|id| repository.find_blob(id)).transpose()?;
+    let new = new.map(|id| repository.find_blob(id)).transpose()?;
+
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts.show_binary(true).force_binary(true);
+
+    // identical blobs don't produce a binary patch, so they keep a delta of 0
+    let mut delta_size = 0;
+    let mut on_binary = |_delta: git2::DiffDelta<'_>, binary: git2::DiffBinary<'_>| {
+        delta_size = binary.new_file().inflated_len() as u64;
+        true
+    };
+    repository
+        .diff_blobs(
+            old.as_ref(),
+            None,
+            new.as_ref(),
+            None,
+            Some(&mut diff_opts),
+            None,
+            Some(
+                &mut on_binary as &mut dyn FnMut(git2::DiffDelta<'_>, git2::DiffBinary<'_>) -> bool,
+            ),
+            None,
+            None,
+        )
+        .context("failed to diff blobs")?;
+    Ok(Some(delta_size))
+}
+
 // returns None if it cannot reverse the patch header
 fn reverse_patch_header(header: &BStr) -> Option<BString> {
     let mut parts = header.split(|b| b.is_ascii_whitespace());
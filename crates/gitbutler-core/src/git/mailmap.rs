@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+
+/// The mailmap of a repository, used to map the names and emails that were recorded in commits
+/// to the canonical ones, like `git log` does.
+///
+/// It's read by `libgit2`, so besides the `.mailmap` file in the worktree it honors the
+/// `mailmap.file` and `mailmap.blob` configuration.
+pub struct Mailmap(git2::Mailmap);
+
+// SAFETY: the mailmap isn't modified after it was read and `libgit2` doesn't tie it to the thread
+// it was created on.
+unsafe impl Send for Mailmap {}
+
+impl Mailmap {
+    /// Read the mailmap of `repository`.
+    pub fn from_repository(repository: &git2::Repository) -> Result<Self> {
+        repository
+            .mailmap()
+            .map(Self)
+            .context("failed to read mailmap")
+    }
+
+    /// Return the canonical name and email for the author or committer `signature` of a commit.
+    pub fn resolve(&self, signature: &git2::Signature) -> (String, String) {
+        match self.0.resolve_signature(signature) {
+            Ok(resolved) => identity(&resolved),
+            Err(_) => identity(signature),
+        }
+    }
+}
+
+fn identity(signature: &git2::Signature) -> (String, String) {
+    (
+        String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+        String::from_utf8_lossy(signature.email_bytes()).into_owned(),
+    )
+}
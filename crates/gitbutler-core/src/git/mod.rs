@@ -15,3 +15,9 @@ pub use tree_ext::*;
 
 mod commit_ext;
 pub use commit_ext::*;
+
+mod trailers;
+pub use trailers::*;
+
+mod mailmap;
+pub use mailmap::*;
@@ -6,6 +6,7 @@ use tracing::instrument;
 use crate::{
     config::git::{GbConfig, GitConfig},
     error::Code,
+    project_repository::conflicts::ConflictStyle,
 };
 
 use super::Refname;
@@ -399,8 +400,14 @@ impl CheckoutIndexBuilder<'_> {
         self
     }
 
-    pub fn conflict_style_merge(&mut self) -> &mut Self {
-        self.checkout_builder.conflict_style_merge(true);
+    /// Write the conflict markers of conflicting files in `style`.
+    pub fn conflict_style(&mut self, style: ConflictStyle) -> &mut Self {
+        match style {
+            ConflictStyle::Merge => self.checkout_builder.conflict_style_merge(true),
+            ConflictStyle::Diff3 | ConflictStyle::Zdiff3 => {
+                self.checkout_builder.conflict_style_diff3(true)
+            }
+        };
         self
     }
 
@@ -0,0 +1,68 @@
+/// Append `trailers` as `key: value` lines to the trailer block of the commit `message`.
+///
+/// If the last paragraph of the message, which can't be the subject, already consists of
+/// trailers they are added to it, otherwise a new block is started after a blank line.
+/// Trailers that are already present with the same value, or that are passed more than once,
+/// are only added once. The message is returned unchanged if there is nothing to add.
+pub fn append_trailers(message: &str, trailers: Vec<(String, String)>) -> String {
+    let body = message.trim_end();
+    let (existing, has_trailer_block) = match body.rsplit_once("\n\n") {
+        Some((_, last_paragraph)) if is_trailer_block(last_paragraph) => {
+            (parse_trailers(last_paragraph), true)
+        }
+        _ => (Vec::new(), false),
+    };
+
+    let mut added: Vec<(String, String)> = Vec::new();
+    for (key, value) in trailers {
+        let (key, value) = (key.trim().to_owned(), value.trim().to_owned());
+        let is_known = existing
+            .iter()
+            .chain(added.iter())
+            .any(|(k, v)| k.eq_ignore_ascii_case(&key) && *v == value);
+        if !is_known {
+            added.push((key, value));
+        }
+    }
+    if added.is_empty() {
+        return message.to_owned();
+    }
+
+    let mut result = body.to_owned();
+    if !result.is_empty() {
+        result.push_str(if has_trailer_block { "\n" } else { "\n\n" });
+    }
+    let lines: Vec<_> = added
+        .iter()
+        .map(|(key, value)| format!("{key}: {value}"))
+        .collect();
+    result.push_str(&lines.join("\n"));
+    if message.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Return `true` if every line of `paragraph` is a trailer, or the continuation of one.
+fn is_trailer_block(paragraph: &str) -> bool {
+    let mut lines = paragraph.lines();
+    lines
+        .next()
+        .map_or(false, |first| trailer_line(first).is_some())
+        && lines.all(|line| trailer_line(line).is_some() || line.starts_with([' ', '\t']))
+}
+
+fn parse_trailers(paragraph: &str) -> Vec<(String, String)> {
+    paragraph
+        .lines()
+        .filter_map(trailer_line)
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
+
+/// Split `line` into key and value if it's a `key: value` trailer.
+fn trailer_line(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let is_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_key.then(|| (key, value.trim()))
+}
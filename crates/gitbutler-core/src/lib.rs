@@ -59,6 +59,17 @@ pub mod serde {
         format!("{v:x}").serialize(s)
     }
 
+    pub fn hash_from_hex<'de, D>(d: D) -> Result<HunkHash, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let hex = <String as serde::Deserialize>::deserialize(d)?;
+        let mut bytes = [0u8; 16];
+        hex::decode_to_slice(hex, &mut bytes)
+            .map_err(|err| serde::de::Error::custom(err.to_string()))?;
+        Ok(md5::Digest(bytes))
+    }
+
     pub fn as_time_seconds_from_unix_epoch<S>(v: &git2::Time, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -155,6 +155,7 @@ pub enum OperationKind {
     ReorderCommit,
     InsertBlankCommit,
     MoveCommitFile,
+    BatchOperations,
     FileChanges,
     #[default]
     Unknown,
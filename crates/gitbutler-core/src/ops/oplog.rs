@@ -7,8 +7,10 @@ use std::time::Duration;
 use std::{fs, path::PathBuf};
 
 use anyhow::Result;
+use itertools::Itertools;
 use tracing::instrument;
 
+use crate::error::Code;
 use crate::git::diff::FileDiff;
 use crate::virtual_branches::integration::{
     GITBUTLER_INTEGRATION_COMMIT_AUTHOR_EMAIL, GITBUTLER_INTEGRATION_COMMIT_AUTHOR_NAME,
@@ -519,6 +521,69 @@ impl Project {
         self.commit_snapshot(before_restore_snapshot_tree_id, details)
     }
 
+    /// Undo the most recent operation, like a commit, an amend or the creation of a branch, by
+    /// restoring the snapshot that was taken right before it with [`restore_snapshot`](Self::restore_snapshot).
+    /// Snapshots that were taken automatically for file changes don't belong to an operation and are skipped.
+    ///
+    /// Unless `force` is set, it's an error if the working directory changed since the operation, as
+    /// restoring the snapshot would discard these changes. The state right after the operation is that of
+    /// the next snapshot, or the workspace as recorded if there is none, so changes the operation made itself
+    /// don't count.
+    /// As restoring creates a snapshot itself, undoing again redoes the operation.
+    ///
+    /// Returns the restored snapshot, whose details describe the operation that was undone.
+    pub fn undo_last_operation(&self, force: bool) -> Result<Snapshot> {
+        let worktree_dir = self.path.as_path();
+        let repo = git2::Repository::open(worktree_dir)?;
+
+        let Some(oplog_head) = self.oplog_head()? else {
+            return Err(anyhow!("there is no operation to undo").context(Code::Validation));
+        };
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(oplog_head)?;
+        let mut snapshot_commit_id = None;
+        let mut next_snapshot_commit_id = None;
+        for commit_id in revwalk {
+            let commit_id = commit_id?;
+            let commit = repo.find_commit(commit_id)?;
+            let operation = commit
+                .message()
+                .and_then(|msg| SnapshotDetails::from_str(msg).ok())
+                .map(|details| details.operation);
+            if operation != Some(OperationKind::FileChanges) {
+                snapshot_commit_id = Some(commit_id);
+                break;
+            }
+            next_snapshot_commit_id = Some(commit_id);
+        }
+        let Some(snapshot_commit_id) = snapshot_commit_id else {
+            return Err(anyhow!("there is no operation to undo").context(Code::Validation));
+        };
+
+        if !force {
+            let after_operation_tree = match next_snapshot_commit_id {
+                Some(commit_id) => repo.find_commit(commit_id)?.tree()?,
+                None => repo.find_tree(self.prepare_snapshot()?)?,
+            };
+            let lost_files =
+                worktree_changes_not_in_snapshot(&repo, worktree_dir, &after_operation_tree)?;
+            if !lost_files.is_empty() {
+                return Err(anyhow!(
+                    "undoing would discard uncommitted changes to {}",
+                    lost_files.iter().map(|path| path.display()).join(", ")
+                ))
+                .context(Code::Validation);
+            }
+        }
+
+        let snapshot = self
+            .list_snapshots(1, Some(snapshot_commit_id))?
+            .pop()
+            .context("failed to read the snapshot to restore")?;
+        self.restore_snapshot(snapshot_commit_id)?;
+        Ok(snapshot)
+    }
+
     /// Determines if a new snapshot should be created due to file changes being created since the last snapshot.
     /// The needs for the automatic snapshotting are:
     ///  - It needs to facilitate backup of work in progress code
@@ -691,6 +756,44 @@ fn worktree_files_larger_than_limit_as_git2_ignore_rule(
     Ok(files_to_exclude.join(" "))
 }
 
+/// Returns the paths of the files in the working directory of `repo` which were added or modified
+/// compared to the `workdir` of `snapshot_tree`, ignoring the large untracked files that snapshots
+/// don't include. Deleted files aren't returned as restoring a snapshot brings them back.
+fn worktree_changes_not_in_snapshot(
+    repo: &git2::Repository,
+    worktree_dir: &Path,
+    snapshot_tree: &git2::Tree,
+) -> Result<Vec<PathBuf>> {
+    let wd_tree_entry = snapshot_tree
+        .get_name("workdir")
+        .context("failed to get workdir tree entry")?;
+    let wd_tree = repo.find_tree(wd_tree_entry.id())?;
+
+    let files_to_exclude =
+        worktree_files_larger_than_limit_as_git2_ignore_rule(repo, worktree_dir)?;
+    repo.add_ignore_rule(&files_to_exclude)?;
+
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .recurse_untracked_dirs(true)
+        .include_untracked(true)
+        .ignore_submodules(true);
+    let diff = repo.diff_tree_to_workdir(Some(&wd_tree), Some(&mut diff_opts))?;
+    Ok(diff
+        .deltas()
+        .filter(|delta| {
+            matches!(
+                delta.status(),
+                git2::Delta::Added
+                    | git2::Delta::Untracked
+                    | git2::Delta::Modified
+                    | git2::Delta::Typechange
+            )
+        })
+        .filter_map(|delta| delta.new_file().path().map(Path::to_owned))
+        .collect())
+}
+
 /// Returns the number of lines of code (added + removed) since the last snapshot in `project`.
 /// Includes untracked files.
 /// `repo` is an already opened project repository.
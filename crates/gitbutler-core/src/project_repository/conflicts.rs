@@ -7,11 +7,12 @@
 
 use std::{
     io::{BufRead, Write},
-    path::{Path, PathBuf},
+    path::Path,
 };
 
-use anyhow::Result;
-use itertools::Itertools;
+use anyhow::{anyhow, bail, Context, Result};
+use bstr::ByteSlice;
+use serde::Deserialize;
 
 use super::Repository;
 
@@ -61,26 +62,180 @@ pub fn merge_parent(repository: &Repository) -> Result<Option<git2::Oid>> {
 }
 
 pub fn resolve<P: AsRef<Path>>(repository: &Repository, path: P) -> Result<()> {
-    let path = path.as_ref();
     let conflicts_path = repository.repo().path().join("conflicts");
-    let file = std::fs::File::open(conflicts_path.clone())?;
-    let reader = std::io::BufReader::new(file);
-    let mut remaining = Vec::new();
-    for line in reader.lines().map_ok(PathBuf::from) {
-        let line = line?;
-        if line != path {
-            remaining.push(line);
+    let content = std::fs::read(conflicts_path)?;
+    resolve_with_content(repository, &content, path)?;
+    Ok(())
+}
+
+/// Return the content of the file that lists the conflicting paths, which is empty if there are
+/// no conflicts, to pass it to the `*_with_content()` functions instead of reading it each time.
+pub fn read(repository: &Repository) -> Result<Vec<u8>> {
+    let conflicts_path = repository.repo().path().join("conflicts");
+    match std::fs::read(conflicts_path) {
+        Ok(content) => Ok(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err).context("failed to read conflicts"),
+    }
+}
+
+/// Like [`resolve()`], but with the `content` of the conflicts file as it's known to be on disk,
+/// which is only written, not read. Return the new content, which is empty once all conflicts
+/// are resolved, to resolve more paths without reading it again.
+pub fn resolve_with_content<P: AsRef<Path>>(
+    repository: &Repository,
+    content: &[u8],
+    path: P,
+) -> Result<Vec<u8>> {
+    let path = path.as_ref().as_os_str().as_encoded_bytes();
+    let remaining: Vec<u8> = content
+        .lines()
+        .filter(|line| *line != path)
+        .flat_map(|line| line.iter().copied().chain(Some(b'\n')))
+        .collect();
+
+    let conflicts_path = repository.repo().path().join("conflicts");
+    if remaining.is_empty() {
+        if let Err(err) = std::fs::remove_file(conflicts_path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                return Err(err.into());
+            }
         }
+    } else {
+        std::fs::write(conflicts_path, &remaining)?;
     }
+    Ok(remaining)
+}
 
-    // remove file
-    std::fs::remove_file(conflicts_path)?;
+/// The version of a conflicting file to resolve it to wholesale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictSide {
+    /// The version of the workspace.
+    Ours,
+    /// The version that is merged into the workspace.
+    Theirs,
+    /// The version both sides are based on, only known if the conflicts show it.
+    Base,
+}
 
-    // re-write file if needed
-    if !remaining.is_empty() {
-        mark(repository, &remaining, None)?;
+/// The layout of the conflict markers written into conflicting files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictStyle {
+    /// Only our and their side of each conflict.
+    #[default]
+    Merge,
+    /// The base both sides are based on as well, between the markers `|||||||` and `=======`.
+    Diff3,
+    /// Like [`ConflictStyle::Diff3`], as git would trim lines that both sides have in common off
+    /// the conflict, but the checkout can't, so the markers are written like those of `Diff3`.
+    Zdiff3,
+}
+
+impl ConflictStyle {
+    /// Return the style set in `merge.conflictStyle` of the configuration of `repository`, or
+    /// [`ConflictStyle::Merge`] if it's unset or unknown, like git does.
+    pub fn from_config(repository: &git2::Repository) -> Self {
+        let style = repository
+            .config()
+            .and_then(|config| config.get_string("merge.conflictStyle"));
+        match style.as_deref() {
+            Ok("diff3") => ConflictStyle::Diff3,
+            Ok("zdiff3") => ConflictStyle::Zdiff3,
+            _ => ConflictStyle::Merge,
+        }
+    }
+
+    /// Return `style` if it's set, or the style configured in `repository` otherwise.
+    pub fn or_from_config(style: Option<Self>, repository: &git2::Repository) -> Self {
+        style.unwrap_or_else(|| Self::from_config(repository))
     }
-    Ok(())
+}
+
+/// Replace the worktree content of the conflicting `path` with `contents` and mark it as
+/// resolved. Return `true` if there are other unresolved conflicts.
+pub fn resolve_from_contents<P: AsRef<Path>>(
+    repository: &Repository,
+    path: P,
+    contents: &[u8],
+) -> Result<bool> {
+    let path = path.as_ref();
+    if !is_conflicting(repository, Some(path))? {
+        bail!("{} is not conflicting", path.display());
+    }
+    std::fs::write(repository.path().join(path), contents)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    resolve(repository, path)?;
+    is_conflicting(repository, None)
+}
+
+/// Resolve all conflicts in `path` by taking the content of `side` for each of them, and mark it
+/// as resolved. Return `true` if there are other unresolved conflicts.
+///
+/// It's an error if a conflict doesn't have content for `side`, which is the case for
+/// [`ConflictSide::Base`] unless the conflict markers include it.
+pub fn resolve_side<P: AsRef<Path>>(
+    repository: &Repository,
+    path: P,
+    side: ConflictSide,
+) -> Result<bool> {
+    let path = path.as_ref();
+    let conflicted = std::fs::read(repository.path().join(path))
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let resolved = take_side(&conflicted, side)
+        .with_context(|| format!("failed to resolve {}", path.display()))?;
+    resolve_from_contents(repository, path, &resolved)
+}
+
+/// Return `conflicted` with each conflict replaced by the lines of its `side`.
+fn take_side(conflicted: &[u8], side: ConflictSide) -> Result<Vec<u8>> {
+    /// Where a line is in relation to the conflicts.
+    #[derive(PartialEq)]
+    enum Section {
+        Outside,
+        Conflict(ConflictSide),
+    }
+
+    let mut resolved = Vec::with_capacity(conflicted.len());
+    let mut section = Section::Outside;
+    let mut conflict_count = 0;
+    let mut has_side = false;
+    for line in conflicted.lines_with_terminator() {
+        let next = if line.starts_with(b"<<<<<<<") {
+            conflict_count += 1;
+            has_side = side == ConflictSide::Ours;
+            Some(Section::Conflict(ConflictSide::Ours))
+        } else if section != Section::Outside && line.starts_with(b"|||||||") {
+            has_side |= side == ConflictSide::Base;
+            Some(Section::Conflict(ConflictSide::Base))
+        } else if section != Section::Outside && line.trim_end() == b"=======" {
+            has_side |= side == ConflictSide::Theirs;
+            Some(Section::Conflict(ConflictSide::Theirs))
+        } else if section != Section::Outside && line.starts_with(b">>>>>>>") {
+            if !has_side {
+                return Err(anyhow!("conflict {conflict_count} has no {side:?} version"));
+            }
+            Some(Section::Outside)
+        } else {
+            None
+        };
+        match next {
+            Some(next) => section = next,
+            None if section == Section::Outside || section == Section::Conflict(side) => {
+                resolved.extend_from_slice(line);
+            }
+            None => {}
+        }
+    }
+
+    if conflict_count == 0 {
+        bail!("there are no conflict markers");
+    }
+    if section != Section::Outside {
+        bail!("conflict {conflict_count} isn't terminated");
+    }
+    Ok(resolved)
 }
 
 pub fn conflicting_files(repository: &Repository) -> Result<Vec<String>> {
@@ -97,27 +252,15 @@ pub fn conflicting_files(repository: &Repository) -> Result<Vec<String>> {
 /// Check if `path` is conflicting in `repository`, or if `None`, check if there is any conflict.
 // TODO(ST): Should this not rather check the conflicting state in the index?
 pub fn is_conflicting(repository: &Repository, path: Option<&Path>) -> Result<bool> {
-    let conflicts_path = repository.repo().path().join("conflicts");
-    if !conflicts_path.exists() {
-        return Ok(false);
-    }
+    Ok(is_conflicting_with_content(&read(repository)?, path))
+}
 
-    let file = std::fs::File::open(conflicts_path)?;
-    let reader = std::io::BufReader::new(file);
-    // TODO(ST): This shouldn't work on UTF8 strings.
-    let mut files = reader.lines().map_ok(PathBuf::from);
-    if let Some(pathname) = path {
-        // check if pathname is one of the lines in conflicts_path file
-        for line in files {
-            let line = line?;
-
-            if line == pathname {
-                return Ok(true);
-            }
-        }
-        Ok(false)
-    } else {
-        Ok(files.next().transpose().map(|x| x.is_some())?)
+/// Like [`is_conflicting()`], but with the `content` of the conflicts file, see [`read()`].
+pub fn is_conflicting_with_content(content: &[u8], path: Option<&Path>) -> bool {
+    let mut files = content.lines();
+    match path {
+        Some(path) => files.any(|line| line == path.as_os_str().as_encoded_bytes()),
+        None => files.next().is_some(),
     }
 }
 
@@ -3,6 +3,6 @@ pub mod conflicts;
 mod repository;
 
 pub use config::Config;
-pub use repository::{LogUntil, Repository};
+pub use repository::{HeadInfo, HeadStatus, LogUntil, ObjectStats, Repository, VirtualBranchRef};
 
 pub mod signatures;
@@ -5,6 +5,7 @@ use std::{
 };
 
 use anyhow::{anyhow, Context, Result};
+use once_cell::unsync::OnceCell;
 
 use super::conflicts;
 use crate::error::Code;
@@ -20,6 +21,8 @@ use crate::{git::RepositoryExt, virtual_branches::errors::Marker};
 pub struct Repository {
     git_repository: git2::Repository,
     project: projects::Project,
+    /// The mailmap of the repository, read when it's first needed.
+    mailmap: OnceCell<git::Mailmap>,
 }
 
 impl Repository {
@@ -72,6 +75,26 @@ impl Repository {
         Ok(Self {
             git_repository: repo,
             project: project.clone(),
+            mailmap: OnceCell::new(),
+        })
+    }
+
+    /// Open the bare repository at the path of `project`, like a mirror of a GitButler project,
+    /// for read-only queries like [`Self::virtual_branch_refs()`].
+    ///
+    /// Operations that need a worktree fail with a validation error, see
+    /// [`Self::assure_worktree()`].
+    pub fn open_bare(project: &projects::Project) -> Result<Self> {
+        let repo = git2::Repository::open_bare(&project.path).with_context(|| {
+            format!(
+                "failed to open bare repository at {}",
+                project.path.display()
+            )
+        })?;
+        Ok(Self {
+            git_repository: repo,
+            project: project.clone(),
+            mailmap: OnceCell::new(),
         })
     }
 
@@ -95,6 +118,20 @@ impl Repository {
         }
     }
 
+    /// Fail with a validation error if the repository has no worktree, as it was opened with
+    /// [`Self::open_bare()`].
+    pub fn assure_worktree(&self) -> Result<()> {
+        if self.git_repository.is_bare() {
+            Err(anyhow!(
+                "repository at {} has no worktree",
+                self.path().display()
+            ))
+            .context(Code::Validation)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn path(&self) -> &path::Path {
         path::Path::new(&self.project.path)
     }
@@ -116,6 +153,136 @@ impl Repository {
         Ok(head)
     }
 
+    /// Count the objects of the object database and sum up the size it takes on disk.
+    ///
+    /// GitButler snapshots are written to the same object database, so their objects are
+    /// included as well.
+    pub fn object_stats(&self) -> Result<ObjectStats> {
+        let objects_dir = self.git_repository.path().join("objects");
+        let mut stats = ObjectStats::default();
+        for entry in std::fs::read_dir(&objects_dir)
+            .with_context(|| format!("failed to read {}", objects_dir.display()))?
+        {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == "pack" {
+                for pack_entry in std::fs::read_dir(entry.path())? {
+                    let pack_entry = pack_entry?;
+                    let path = pack_entry.path();
+                    stats.size_bytes += pack_entry.metadata()?.len();
+                    if path.extension().is_some_and(|ext| ext == "idx") {
+                        stats.packed_objects += pack_index_object_count(&path)
+                            .with_context(|| format!("failed to read {}", path.display()))?;
+                    }
+                }
+            } else if name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+                for object_entry in std::fs::read_dir(entry.path())? {
+                    let object_entry = object_entry?;
+                    stats.loose_objects += 1;
+                    stats.size_bytes += object_entry.metadata()?.len();
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Return what `HEAD` points to, which is a branch unless it was changed outside of GitButler.
+    pub fn head_status(&self) -> Result<HeadStatus> {
+        let repo = &self.git_repository;
+        let head = match repo.head() {
+            Ok(head) => head,
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => {
+                return Ok(HeadStatus::Unborn)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let head_id = head.peel_to_commit()?.id();
+        Ok(if repo.head_detached()? {
+            HeadStatus::Detached { head: head_id }
+        } else {
+            HeadStatus::OnBranch {
+                name: head
+                    .name()
+                    .context("branch name isn't valid UTF-8")?
+                    .to_owned(),
+                head: head_id,
+            }
+        })
+    }
+
+    /// Return the references GitButler keeps for the virtual branches, sorted by name.
+    ///
+    /// Unlike the state of the virtual branches, these are part of mirrors of the repository, so
+    /// they are available in repositories opened with [`Self::open_bare()`] as well.
+    pub fn virtual_branch_refs(&self) -> Result<Vec<VirtualBranchRef>> {
+        let mut refs = Vec::new();
+        for reference in self
+            .git_repository
+            .references_glob("refs/gitbutler/*")
+            .context("failed to list virtual branch references")?
+        {
+            let reference = reference?;
+            let (Some(name), Some(head)) = (reference.name(), reference.target()) else {
+                continue;
+            };
+            // the oplog is kept among them, but isn't a branch
+            if name.starts_with("refs/gitbutler/oplog/") {
+                continue;
+            }
+            // anything that isn't a virtual branch reference doesn't belong here
+            let Ok(name) = name.parse() else {
+                continue;
+            };
+            refs.push(VirtualBranchRef { name, head });
+        }
+        refs.sort_by(|a, b| a.name.branch().cmp(b.name.branch()));
+        Ok(refs)
+    }
+
+    /// Return what a status bar needs to know about `HEAD`: the branch it is on, how far it's
+    /// ahead and behind the default target, and whether the worktree has uncommitted changes.
+    ///
+    /// It's an error if `HEAD` is unborn or there is no default target.
+    pub fn head_info(&self) -> Result<HeadInfo> {
+        self.assure_worktree()?;
+        let repo = &self.git_repository;
+        let head = repo.head().context("failed to get repository head")?;
+        let head_commit = head.peel_to_commit()?;
+        let branch_name = if repo.head_detached()? {
+            None
+        } else {
+            Some(
+                head.shorthand()
+                    .context("branch name isn't valid UTF-8")?
+                    .to_owned(),
+            )
+        };
+        let short_id = head_commit
+            .as_object()
+            .short_id()?
+            .as_str()
+            .context("short id isn't valid UTF-8")?
+            .to_owned();
+
+        let default_target = self.project.virtual_branches().get_default_target()?;
+        let (ahead, behind) = repo
+            .graph_ahead_behind(head_commit.id(), default_target.sha)
+            .context("failed to count commits ahead and behind the default target")?;
+        let is_dirty = !git::diff::workdir(repo, &head_commit.id())
+            .context("failed to diff worktree")?
+            .is_empty();
+
+        Ok(HeadInfo {
+            branch_name,
+            head: head_commit.id(),
+            short_id,
+            ahead,
+            behind,
+            is_dirty,
+        })
+    }
+
     pub fn get_head(&self) -> Result<git2::Reference> {
         let head = self.git_repository.head()?;
         Ok(head)
@@ -333,6 +500,59 @@ impl Repository {
         Ok(oids.len().try_into()?)
     }
 
+    /// Return the commits on the path from `from` down to its ancestor `to`, both included and
+    /// ordered tip-to-base, or `None` if `to` isn't an ancestor of `from`.
+    ///
+    /// With `first_parent`, only first parents are followed, so merged-in commits are never part
+    /// of the path and `to` must be reachable that way. Otherwise all commits which are both
+    /// ancestors of `from` and descendants of `to` are returned, like `git log --ancestry-path`.
+    pub fn ancestry_path(
+        &self,
+        from: git2::Oid,
+        to: git2::Oid,
+        first_parent: bool,
+    ) -> Result<Option<Vec<git2::Oid>>> {
+        if from == to {
+            return Ok(Some(vec![from]));
+        }
+        let repo = &self.git_repository;
+        if !repo
+            .graph_descendant_of(from, to)
+            .context("failed to check ancestry")?
+        {
+            return Ok(None);
+        }
+
+        if first_parent {
+            let mut path = vec![from];
+            let mut commit = repo.find_commit(from)?;
+            while let Ok(parent) = commit.parent(0) {
+                path.push(parent.id());
+                if parent.id() == to {
+                    return Ok(Some(path));
+                }
+                commit = parent;
+            }
+            return Ok(None);
+        }
+
+        let mut revwalk = repo.revwalk().context("failed to create revwalk")?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+        revwalk
+            .push(from)
+            .context(format!("failed to push {}", from))?;
+        revwalk.hide(to).context(format!("failed to hide {}", to))?;
+        let mut path = Vec::new();
+        for oid in revwalk {
+            let oid = oid.context("failed to get oid")?;
+            if repo.graph_descendant_of(oid, to)? {
+                path.push(oid);
+            }
+        }
+        path.push(to);
+        Ok(Some(path))
+    }
+
     pub fn commit(
         &self,
         user: Option<&users::User>,
@@ -536,30 +756,78 @@ impl Repository {
         askpass: Option<String>,
     ) -> Result<()> {
         let refspec = format!("+refs/heads/*:refs/remotes/{}/*", remote_name);
+        self.fetch_refspecs(remote_name, &[refspec], credentials, askpass)
+    }
 
+    /// Like [`Self::fetch()`], but only fetch what's matched by `refspecs` instead of all
+    /// branches of the remote.
+    ///
+    /// Refspecs whose source doesn't exist on the remote anymore, like that of a deleted upstream,
+    /// are skipped, and the ref they would update is deleted as if it was pruned.
+    pub fn fetch_refspecs(
+        &self,
+        remote_name: &str,
+        refspecs: &[String],
+        credentials: &git::credentials::Helper,
+        askpass: Option<String>,
+    ) -> Result<()> {
         // NOTE(qix-): This is a nasty hack, however the codebase isn't structured
         // NOTE(qix-): in a way that allows us to really incorporate new backends
         // NOTE(qix-): without a lot of work. This is a temporary measure to
         // NOTE(qix-): work around a time-sensitive change that was necessary
         // NOTE(qix-): without having to refactor a large portion of the codebase.
         if self.project.preferred_key == AuthKey::SystemExecutable {
-            let path = self.path().to_path_buf();
-            let remote = remote_name.to_string();
-            return std::thread::spawn(move || {
-                tokio::runtime::Runtime::new()
-                    .unwrap()
-                    .block_on(gitbutler_git::fetch(
-                        path,
-                        gitbutler_git::tokio::TokioExecutor,
-                        &remote,
-                        gitbutler_git::RefSpec::parse(refspec).unwrap(),
-                        handle_git_prompt_fetch,
-                        askpass,
-                    ))
-            })
-            .join()
-            .unwrap()
-            .map_err(Into::into);
+            let mut refspecs = refspecs
+                .iter()
+                .map(|refspec| {
+                    gitbutler_git::RefSpec::parse(refspec)
+                        .with_context(|| format!("failed to parse refspec {refspec:?}"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // git fails the whole fetch if a single source ref is missing, so drop it and retry
+            loop {
+                let path = self.path().to_path_buf();
+                let remote = remote_name.to_string();
+                let fetch_refspecs = refspecs.clone();
+                let askpass = askpass.clone();
+                let result = std::thread::spawn(move || {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(gitbutler_git::fetch(
+                            path,
+                            gitbutler_git::tokio::TokioExecutor,
+                            &remote,
+                            &fetch_refspecs,
+                            handle_git_prompt_fetch,
+                            askpass,
+                        ))
+                })
+                .join()
+                .unwrap();
+                let missing_index = match &result {
+                    Err(gitbutler_git::Error::RefNotFound(refname)) => refspecs
+                        .iter()
+                        .position(|refspec| refspec.source.as_deref() == Some(refname.as_str())),
+                    _ => None,
+                };
+                let Some(missing_index) = missing_index else {
+                    return result.map_err(Into::into);
+                };
+                let missing = refspecs.remove(missing_index);
+                tracing::info!(project_id = %self.project.id, source = ?missing.source, "skipping refspec missing on the remote");
+                if let Some(destination) = missing.destination {
+                    match self.git_repository.find_reference(&destination) {
+                        Ok(mut reference) => {
+                            reference.delete().context("failed to prune reference")?;
+                        }
+                        Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                if refspecs.is_empty() {
+                    return Ok(());
+                }
+            }
         }
 
         let auth_flows = credentials.help(self, remote_name)?;
@@ -579,9 +847,9 @@ impl Repository {
                 fetch_opts.remote_callbacks(cbs);
                 fetch_opts.prune(git2::FetchPrune::On);
 
-                match remote.fetch(&[&refspec], Some(&mut fetch_opts), None) {
+                match remote.fetch(refspecs, Some(&mut fetch_opts), None) {
                     Ok(()) => {
-                        tracing::info!(project_id = %self.project.id, %refspec, "git fetched");
+                        tracing::info!(project_id = %self.project.id, ?refspecs, "git fetched");
                         return Ok(());
                     }
                     Err(err) => match err.class() {
@@ -623,6 +891,79 @@ impl Repository {
     pub fn repo(&self) -> &git2::Repository {
         &self.git_repository
     }
+
+    /// Return the mailmap to resolve commit authors with, read the first time it's needed.
+    pub fn mailmap(&self) -> Result<&git::Mailmap> {
+        self.mailmap
+            .get_or_try_init(|| git::Mailmap::from_repository(&self.git_repository))
+    }
+}
+
+/// The amount of objects in a repository and the space they take on disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectStats {
+    pub loose_objects: usize,
+    pub packed_objects: usize,
+    /// The size of all loose objects, packs and their indices, in bytes.
+    pub size_bytes: u64,
+}
+
+/// What `HEAD` of a repository points to, see [`Repository::head_status()`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum HeadStatus {
+    /// `HEAD` is the branch with the full reference `name`, e.g. `refs/heads/gitbutler/integration`.
+    OnBranch {
+        name: String,
+        #[serde(with = "crate::serde::oid")]
+        head: git2::Oid,
+    },
+    /// `HEAD` points to a commit directly, for instance after a `git checkout` of a commit.
+    Detached {
+        #[serde(with = "crate::serde::oid")]
+        head: git2::Oid,
+    },
+    /// `HEAD` points to a branch without commits.
+    Unborn,
+}
+
+/// The reference of a virtual branch, see [`Repository::virtual_branch_refs()`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualBranchRef {
+    pub name: git::VirtualRefname,
+    /// The commit the reference points to, which is a WIP commit on top of the head of the
+    /// branch if it has uncommitted changes.
+    #[serde(with = "crate::serde::oid")]
+    pub head: git2::Oid,
+}
+
+/// A summary of `HEAD` and the worktree, see [`Repository::head_info()`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadInfo {
+    /// The short name of the branch `HEAD` points to, like `gitbutler/integration`, or `None` if
+    /// it's detached.
+    pub branch_name: Option<String>,
+    #[serde(with = "crate::serde::oid")]
+    pub head: git2::Oid,
+    /// The abbreviated id of the `HEAD` commit.
+    pub short_id: String,
+    /// The amount of commits reachable from `HEAD` but not from the default target.
+    pub ahead: usize,
+    /// The amount of commits reachable from the default target but not from `HEAD`.
+    pub behind: usize,
+    /// Whether the worktree or index has changes compared to `HEAD`, including untracked files.
+    pub is_dirty: bool,
+}
+
+/// Read the amount of objects in the pack index at `path` from its fan-out table.
+///
+/// The index is memory-mapped, so only its header and fan-out table are read from disk.
+fn pack_index_object_count(path: &path::Path) -> Result<usize> {
+    let index = gix::odb::pack::index::File::at(path, gix::hash::Kind::Sha1)?;
+    Ok(index.num_objects() as usize)
 }
 
 type OidFilter = dyn Fn(&git2::Commit) -> Result<bool>;
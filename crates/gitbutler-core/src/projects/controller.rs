@@ -6,7 +6,9 @@ use std::{
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 
-use super::{storage, storage::UpdateRequest, Project, ProjectId};
+use super::{
+    storage, storage::UpdateRequest, NonDefaultSetting, Project, ProjectId, Setting, UiState,
+};
 use crate::git::RepositoryExt;
 use crate::projects::AuthKey;
 use crate::{error, project_repository};
@@ -19,6 +21,31 @@ pub trait Watchers {
     async fn stop(&self, id: ProjectId);
 }
 
+/// The error of [`Controller::add()`] if the path to add is nested in an existing project or
+/// contains one, which would make both share files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedProject {
+    /// The id of the project the path is nested in or contains.
+    pub existing: ProjectId,
+    /// The path of the existing project.
+    pub path: PathBuf,
+    /// The title of the existing project.
+    pub title: String,
+}
+
+impl std::fmt::Display for NestedProject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "path is nested in or contains project '{}' at {}",
+            self.title,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for NestedProject {}
+
 #[derive(Clone)]
 pub struct Controller {
     local_data_dir: PathBuf,
@@ -63,6 +90,29 @@ impl Controller {
         if !path.is_dir() {
             bail!("not a directory");
         }
+        // projects within each other would share files, so their data would end up in the
+        // wrong places
+        let canonical_path = path.canonicalize().context("failed to canonicalize path")?;
+        for existing in &all_projects {
+            // projects that were moved or deleted can't be canonicalized anymore
+            let existing_path = existing
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| existing.path.clone());
+            if existing_path == canonical_path {
+                bail!("project already exists");
+            }
+            if canonical_path.starts_with(&existing_path)
+                || existing_path.starts_with(&canonical_path)
+            {
+                return Err(NestedProject {
+                    existing: existing.id,
+                    path: existing.path.clone(),
+                    title: existing.title.clone(),
+                }
+                .into());
+            }
+        }
         match gix::open_opts(path, gix::open::Options::isolated()) {
             Ok(repo) if repo.is_bare() => {
                 bail!("bare repositories are unsupported");
@@ -182,9 +232,31 @@ impl Controller {
         self.projects_storage.list().map_err(Into::into)
     }
 
+    /// Unregister the project `id` and delete the data GitButler keeps for it, like
+    /// [`Self::remove()`] with `purge_data` does. It's not an error if there is no such project.
     pub async fn delete(&self, id: ProjectId) -> Result<()> {
-        let Some(project) = self.projects_storage.try_get(id)? else {
+        if self.projects_storage.try_get(id)?.is_none() {
             return Ok(());
+        }
+        self.remove(id, true).await.map(|_freed_bytes| ())
+    }
+
+    /// Unregister the project `id`, and if `purge_data` is set, delete the data GitButler keeps
+    /// for it as well: its directory within the local data directory, and the `gitbutler`
+    /// directory and `gitbutler.json` file within its `.git` directory. Return the amount of bytes
+    /// that were freed, which is `0` unless data is purged.
+    ///
+    /// Before anything is unregistered, each of these paths is checked to be directly within the
+    /// directory it belongs to, so a corrupted project path can't cause anything else to be
+    /// deleted.
+    /// Once the project is unregistered, failing to delete one of them is logged and doesn't keep
+    /// the others from being deleted.
+    pub async fn remove(&self, id: ProjectId, purge_data: bool) -> Result<u64> {
+        let project = self.projects_storage.get(id)?;
+        let data_paths = if purge_data {
+            self.managed_data_paths(&project)?
+        } else {
+            Vec::new()
         };
 
         if let Some(watchers) = &self.watchers {
@@ -195,25 +267,56 @@ impl Controller {
             .purge(project.id)
             .map_err(anyhow::Error::from)?;
 
-        if let Err(error) = std::fs::remove_dir_all(
-            self.local_data_dir
-                .join("projects")
-                .join(project.id.to_string()),
-        ) {
-            tracing::error!(project_id = %id, ?error, "failed to remove project data",);
+        let mut freed_bytes = 0;
+        for path in data_paths {
+            let size = disk_usage(&path);
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            match removed {
+                Ok(()) => freed_bytes += size.unwrap_or_default(),
+                Err(error) => {
+                    tracing::error!(project_id = %id, ?error, "failed to remove {}", path.display());
+                }
+            }
         }
+        Ok(freed_bytes)
+    }
 
-        if let Err(error) = std::fs::remove_file(project.path.join(".git/gitbutler.json")) {
-            tracing::error!(project_id = %project.id, ?error, "failed to remove .git/gitbutler.json data",);
+    /// Return the paths at which data of `project` exists, after making sure that each is located
+    /// directly within the directory GitButler keeps it in.
+    ///
+    /// If the repository of `project` can't be opened, the data within it is left alone.
+    fn managed_data_paths(&self, project: &Project) -> Result<Vec<PathBuf>> {
+        let projects_dir = self.local_data_dir.join("projects");
+        let mut candidates = vec![(projects_dir.join(project.id.to_string()), projects_dir)];
+        if let Ok(repo) = git2::Repository::open(&project.path) {
+            let git_dir = repo.path().to_owned();
+            candidates.push((project.gb_dir(), git_dir.clone()));
+            candidates.push((project.path.join(".git/gitbutler.json"), git_dir));
         }
 
-        if project.gb_dir().exists() {
-            if let Err(error) = std::fs::remove_dir_all(project.gb_dir()) {
-                tracing::error!(project_id = %project.id, ?error, "failed to remove {:?} on project delete", project.gb_dir());
+        let mut paths = Vec::new();
+        for (path, managed_dir) in candidates {
+            if std::fs::symlink_metadata(&path).is_err() {
+                continue;
             }
+            let canonical_path = path
+                .canonicalize()
+                .with_context(|| format!("failed to resolve {}", path.display()))?;
+            let managed_dir = managed_dir.canonicalize().unwrap_or(managed_dir);
+            if canonical_path.parent() != Some(managed_dir.as_path()) {
+                bail!(
+                    "refusing to delete {} as it isn't located in {}",
+                    path.display(),
+                    managed_dir.display()
+                );
+            }
+            paths.push(canonical_path);
         }
-
-        Ok(())
+        Ok(paths)
     }
 
     pub fn get_local_config(&self, id: ProjectId, key: &str) -> Result<Option<String>> {
@@ -230,6 +333,25 @@ impl Controller {
         repo.config().set_local(key, value)
     }
 
+    pub fn non_default_settings(&self, id: ProjectId) -> Result<Vec<NonDefaultSetting>> {
+        let project = self.projects_storage.get(id)?;
+        Ok(project.non_default_settings())
+    }
+
+    pub fn reset_setting(&self, id: ProjectId, setting: Setting) -> Result<Project> {
+        self.projects_storage.reset_setting(id, setting)
+    }
+
+    pub fn get_ui_state(&self, id: ProjectId) -> Result<UiState> {
+        let project = self.projects_storage.get(id)?;
+        project.ui_state().get()
+    }
+
+    pub fn set_ui_state(&self, id: ProjectId, state: UiState) -> Result<()> {
+        let project = self.projects_storage.get(id)?;
+        project.ui_state().set(state)
+    }
+
     pub fn check_signing_settings(&self, id: ProjectId) -> Result<bool> {
         let project = self.projects_storage.get(id)?;
 
@@ -241,3 +363,17 @@ impl Controller {
         }
     }
 }
+
+/// Return the size of the file at `path`, or of all files within it if it's a directory, without
+/// following symlinks.
+fn disk_usage(path: &Path) -> Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        size += disk_usage(&entry?.path())?;
+    }
+    Ok(size)
+}
@@ -1,7 +1,11 @@
 pub mod controller;
 mod project;
+mod settings;
 pub mod storage;
+mod ui_state;
 
 pub use controller::*;
 pub use project::{ApiProject, AuthKey, CodePushState, FetchResult, Project, ProjectId};
+pub use settings::{NonDefaultSetting, Setting};
 pub use storage::UpdateRequest;
+pub use ui_state::{DiffViewMode, UiState, UiStateHandle, UI_STATE_VERSION};
@@ -5,6 +5,7 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use super::UiStateHandle;
 use crate::{id::Id, types::default_true::DefaultTrue, virtual_branches::VirtualBranchesHandle};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
@@ -91,6 +92,17 @@ pub struct Project {
 
     #[serde(default = "default_true")]
     pub use_new_locking: bool,
+    /// If `true`, worktree hunks that only change whitespace don't lock to the commits they touch.
+    #[serde(default)]
+    pub ignore_whitespace_locks: bool,
+    /// If `true`, worktree hunks are told apart by their enclosing function as well when looking up
+    /// their locks, so hunks with the same content in different functions don't share locks.
+    #[serde(default)]
+    pub section_aware_locks: bool,
+    /// The number of unchanged lines between two hunks up to which diffs that are only displayed
+    /// show them as one hunk. If unset, it's the number of context lines of the diff.
+    #[serde(default)]
+    pub hunk_coalesce_distance: Option<u32>,
 }
 
 fn default_true() -> bool {
@@ -121,7 +133,18 @@ impl Project {
         VirtualBranchesHandle::new(self.gb_dir())
     }
 
+    /// Returns a handle to the persisted UI state of the project.
+    pub fn ui_state(&self) -> UiStateHandle {
+        UiStateHandle::new(self.gb_dir())
+    }
+
     pub fn snapshot_lines_threshold(&self) -> usize {
         self.snapshot_lines_threshold.unwrap_or(20)
     }
+
+    /// Return the number of unchanged lines up to which hunks of a diff with `context_lines` are
+    /// merged for display.
+    pub fn hunk_coalesce_distance(&self, context_lines: u32) -> u32 {
+        self.hunk_coalesce_distance.unwrap_or(context_lines)
+    }
 }
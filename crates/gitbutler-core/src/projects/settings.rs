@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{AuthKey, Project};
+
+/// The settings of a project which have a built-in default and can be reset to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Setting {
+    PreferredKey,
+    OkWithForcePush,
+    OmitCertificateCheck,
+    SnapshotLinesThreshold,
+    UseNewLocking,
+    IgnoreWhitespaceLocks,
+    SectionAwareLocks,
+    HunkCoalesceDistance,
+}
+
+/// A setting whose value differs from its default.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonDefaultSetting {
+    pub key: Setting,
+    /// The current value, redacted if it is sensitive.
+    pub value: Value,
+    pub default: Value,
+}
+
+/// The placeholder for values which shouldn't show up in bug reports.
+const REDACTED: &str = "<redacted>";
+
+impl Setting {
+    pub const ALL: [Setting; 8] = [
+        Setting::PreferredKey,
+        Setting::OkWithForcePush,
+        Setting::OmitCertificateCheck,
+        Setting::SnapshotLinesThreshold,
+        Setting::UseNewLocking,
+        Setting::IgnoreWhitespaceLocks,
+        Setting::SectionAwareLocks,
+        Setting::HunkCoalesceDistance,
+    ];
+
+    fn value(self, project: &Project) -> Value {
+        match self {
+            Setting::PreferredKey => json!(project.preferred_key),
+            Setting::OkWithForcePush => json!(bool::from(project.ok_with_force_push)),
+            Setting::OmitCertificateCheck => json!(project.omit_certificate_check),
+            Setting::SnapshotLinesThreshold => json!(project.snapshot_lines_threshold),
+            Setting::UseNewLocking => json!(project.use_new_locking),
+            Setting::IgnoreWhitespaceLocks => json!(project.ignore_whitespace_locks),
+            Setting::SectionAwareLocks => json!(project.section_aware_locks),
+            Setting::HunkCoalesceDistance => json!(project.hunk_coalesce_distance),
+        }
+    }
+
+    fn default_value(self) -> Value {
+        self.value(&Project::default().with_default_settings())
+    }
+
+    /// Return the value of this setting in `project` in a form that is safe to share, i.e. with
+    /// paths to private keys removed.
+    fn redacted_value(self, project: &Project) -> Value {
+        match (self, &project.preferred_key) {
+            (Setting::PreferredKey, AuthKey::Local { .. }) => json!({ "local": REDACTED }),
+            _ => self.value(project),
+        }
+    }
+
+    /// Set this setting in `project` back to its default.
+    pub(crate) fn reset(self, project: &mut Project) {
+        let defaults = Project::default().with_default_settings();
+        match self {
+            Setting::PreferredKey => project.preferred_key = defaults.preferred_key,
+            Setting::OkWithForcePush => project.ok_with_force_push = defaults.ok_with_force_push,
+            Setting::OmitCertificateCheck => {
+                project.omit_certificate_check = defaults.omit_certificate_check
+            }
+            Setting::SnapshotLinesThreshold => {
+                project.snapshot_lines_threshold = defaults.snapshot_lines_threshold
+            }
+            Setting::UseNewLocking => project.use_new_locking = defaults.use_new_locking,
+            Setting::IgnoreWhitespaceLocks => {
+                project.ignore_whitespace_locks = defaults.ignore_whitespace_locks
+            }
+            Setting::SectionAwareLocks => {
+                project.section_aware_locks = defaults.section_aware_locks
+            }
+            Setting::HunkCoalesceDistance => {
+                project.hunk_coalesce_distance = defaults.hunk_coalesce_distance
+            }
+        }
+    }
+}
+
+impl Project {
+    /// Return all settings of this project which differ from their default, with sensitive values redacted.
+    pub fn non_default_settings(&self) -> Vec<NonDefaultSetting> {
+        Setting::ALL
+            .into_iter()
+            .filter_map(|key| {
+                let default = key.default_value();
+                (key.value(self) != default).then(|| NonDefaultSetting {
+                    key,
+                    value: key.redacted_value(self),
+                    default,
+                })
+            })
+            .collect()
+    }
+
+    /// Apply the defaults of settings that `Default` doesn't know about, as they would be after
+    /// deserializing a project without them.
+    fn with_default_settings(mut self) -> Self {
+        self.use_new_locking = true;
+        self
+    }
+}
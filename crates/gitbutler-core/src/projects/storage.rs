@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use crate::{
-    projects::{project, ProjectId},
+    projects::{project, ProjectId, Setting},
     storage,
 };
 
@@ -29,6 +29,9 @@ pub struct UpdateRequest {
     pub use_diff_context: Option<bool>,
     pub snapshot_lines_threshold: Option<usize>,
     pub use_new_locking: Option<bool>,
+    pub ignore_whitespace_locks: Option<bool>,
+    pub section_aware_locks: Option<bool>,
+    pub hunk_coalesce_distance: Option<u32>,
 }
 
 impl Storage {
@@ -127,6 +130,18 @@ impl Storage {
             project.use_new_locking = use_new_locking;
         }
 
+        if let Some(ignore_whitespace_locks) = update_request.ignore_whitespace_locks {
+            project.ignore_whitespace_locks = ignore_whitespace_locks;
+        }
+
+        if let Some(section_aware_locks) = update_request.section_aware_locks {
+            project.section_aware_locks = section_aware_locks;
+        }
+
+        if let Some(hunk_coalesce_distance) = update_request.hunk_coalesce_distance {
+            project.hunk_coalesce_distance = Some(hunk_coalesce_distance);
+        }
+
         self.inner
             .write(PROJECTS_FILE, &serde_json::to_string_pretty(&projects)?)?;
 
@@ -137,6 +152,20 @@ impl Storage {
             .clone())
     }
 
+    pub fn reset_setting(&self, id: ProjectId, setting: Setting) -> Result<project::Project> {
+        let mut projects = self.list()?;
+        let project = projects
+            .iter_mut()
+            .find(|p| p.id == id)
+            .with_context(|| format!("project {id} not found for update"))?;
+        setting.reset(project);
+        let project = project.clone();
+
+        self.inner
+            .write(PROJECTS_FILE, &serde_json::to_string_pretty(&projects)?)?;
+        Ok(project)
+    }
+
     pub fn purge(&self, id: ProjectId) -> Result<()> {
         let mut projects = self.list()?;
         if let Some(index) = projects.iter().position(|p| p.id == id) {
@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{fs::read_toml_file_or_default, virtual_branches::BranchId};
+
+/// The version of the [`UiState`] schema written by this version of the application.
+pub const UI_STATE_VERSION: u32 = 1;
+
+/// How diffs are presented to the user.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffViewMode {
+    #[default]
+    Unified,
+    Split,
+}
+
+/// The state of the user interface of a single project, as persisted in a TOML file.
+///
+/// It's purely data owned by the frontend, nothing in the core acts on it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UiState {
+    /// The schema version of this state, which is `0` for files written before it was versioned.
+    pub version: u32,
+    /// The virtual branch that was selected last, if any.
+    pub selected_branch: Option<BranchId>,
+    /// How diffs should be displayed.
+    pub diff_view_mode: DiffViewMode,
+    /// The virtual branches that are collapsed.
+    pub collapsed_branches: Vec<BranchId>,
+}
+
+impl UiState {
+    /// Bring a state read from disk up to [the current schema](UI_STATE_VERSION).
+    ///
+    /// Unknown fields are dropped and missing ones are defaulted while deserializing,
+    /// so all that's left is to take care of fields that changed their meaning.
+    fn migrate(mut self) -> Self {
+        if self.version < 1 {
+            // Version 0 had the same fields, it just didn't record its version.
+            self.version = 1;
+        }
+        self
+    }
+}
+
+/// Just the version of a stored [`UiState`], which can be read no matter what else a newer
+/// version of the application put into the file.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct StoredVersion {
+    version: u32,
+}
+
+/// A handle to the UI state of a project.
+///
+/// If the state file does not exist, the default state is returned.
+pub struct UiStateHandle {
+    /// The path to the file containing the UI state.
+    file_path: PathBuf,
+}
+
+impl UiStateHandle {
+    /// Create a new handle to the UI state stored in `base_path`.
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        let file_path = base_path.as_ref().join("ui_state.toml");
+        Self { file_path }
+    }
+
+    /// Read the UI state, migrated to the current schema.
+    ///
+    /// Errors if the file cannot be read or parsed.
+    pub fn get(&self) -> Result<UiState> {
+        let state: UiState = read_toml_file_or_default(&self.file_path)?;
+        Ok(state.migrate())
+    }
+
+    /// Persist `state`, stamped with the current schema version.
+    ///
+    /// Errors if the file cannot be written, or if it was written by a newer version of the
+    /// application, as writing it would silently downgrade it.
+    pub fn set(&self, mut state: UiState) -> Result<()> {
+        let stored: StoredVersion = read_toml_file_or_default(&self.file_path)?;
+        if stored.version > UI_STATE_VERSION {
+            bail!(
+                "refusing to overwrite UI state of version {} with version {UI_STATE_VERSION}",
+                stored.version
+            );
+        }
+        state.version = UI_STATE_VERSION;
+        crate::fs::create_dirs_then_write(&self.file_path, toml::to_string(&state)?)?;
+        Ok(())
+    }
+}
@@ -11,7 +11,6 @@ use super::{
     },
     target, BranchId, RemoteCommit, VirtualBranchHunk, VirtualBranchesHandle,
 };
-use crate::{git::RepositoryExt, virtual_branches::errors::Marker};
 use crate::{
     git::{self, diff},
     project_repository::{self, LogUntil},
@@ -19,6 +18,10 @@ use crate::{
     users,
     virtual_branches::{branch::BranchOwnershipClaims, cherry_rebase},
 };
+use crate::{
+    git::{CommitExt, RepositoryExt},
+    virtual_branches::errors::Marker,
+};
 
 #[derive(Debug, Serialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -121,6 +124,7 @@ pub fn set_base_branch(
     project_repository: &project_repository::Repository,
     target_branch_ref: &git::RemoteRefname,
 ) -> Result<BaseBranch> {
+    project_repository.assure_worktree()?;
     let repo = project_repository.repo();
 
     // if target exists, and it is the same as the requested branch, we should go back
@@ -580,12 +584,14 @@ pub fn target_to_base_branch(
     let commit = branch.get().peel_to_commit()?;
     let oid = commit.id();
 
+    let mailmap = project_repository.mailmap()?;
+
     // gather a list of commits between oid and target.sha
     let upstream_commits = project_repository
         .log(oid, project_repository::LogUntil::Commit(target.sha))
         .context("failed to get upstream commits")?
         .iter()
-        .map(super::commit_to_remote_commit)
+        .map(|commit| super::commit_to_remote_commit(commit, mailmap))
         .collect::<Vec<_>>();
 
     // get some recent commits
@@ -593,7 +599,7 @@ pub fn target_to_base_branch(
         .log(target.sha, LogUntil::Take(20))
         .context("failed to get recent commits")?
         .iter()
-        .map(super::commit_to_remote_commit)
+        .map(|commit| super::commit_to_remote_commit(commit, mailmap))
         .collect::<Vec<_>>();
 
     // there has got to be a better way to do this.
@@ -630,6 +636,208 @@ pub fn target_to_base_branch(
     Ok(base)
 }
 
+/// How the history of the default target was rewritten upstream, see [`target_rewritten()`].
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetRewrite {
+    /// The commit of the target the workspace is based on.
+    #[serde(with = "crate::serde::oid")]
+    pub local_tip: git2::Oid,
+    /// The fetched head of the target branch, which doesn't contain `local_tip`.
+    #[serde(with = "crate::serde::oid")]
+    pub remote_tip: git2::Oid,
+    /// The most recent commit both tips have in common, if they share any history.
+    #[serde(with = "crate::serde::oid_opt")]
+    pub merge_base: Option<git2::Oid>,
+}
+
+/// Return how the target branch diverged if its fetched head isn't a descendant of the commit the
+/// workspace is based on anymore, as happens when it was force-pushed. Updating the base branch
+/// would then try to rebase onto a history that dropped some of the commits the branches are
+/// based on.
+///
+/// Return `None` if the target was only fast-forwarded, or not changed at all.
+pub fn target_rewritten(
+    project_repository: &project_repository::Repository,
+) -> Result<Option<TargetRewrite>> {
+    let target = default_target(&project_repository.project().gb_dir())?;
+    let repo = project_repository.repo();
+    let remote_tip = repo
+        .find_branch_by_refname(&target.branch.clone().into())?
+        .ok_or(anyhow!("failed to get branch"))?
+        .get()
+        .peel_to_commit()?
+        .id();
+
+    if remote_tip == target.sha
+        || repo
+            .graph_descendant_of(remote_tip, target.sha)
+            .context("failed to check ancestry of target")?
+    {
+        return Ok(None);
+    }
+
+    let merge_base = match repo.merge_base(remote_tip, target.sha) {
+        Ok(merge_base) => Some(merge_base),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+        Err(err) => return Err(err).context("failed to find merge base"),
+    };
+    Ok(Some(TargetRewrite {
+        local_tip: target.sha,
+        remote_tip,
+        merge_base,
+    }))
+}
+
+/// The least similarity of two commits without matching change-ids for one to be considered the
+/// rebased version of the other, see [`rebase_correspondence()`].
+const REBASE_SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// A commit of a branch along with the commit of the old target it was likely rebased from.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitCorrespondence {
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    #[serde(with = "crate::serde::oid")]
+    pub original_id: git2::Oid,
+    /// The similarity of the changes of both commits, or `None` if they were matched by their
+    /// change-id.
+    pub similarity: Option<f64>,
+}
+
+/// How the commits of a branch correspond to the commits of the old target, as returned by
+/// [`rebase_correspondence()`].
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RebaseCorrespondence {
+    /// The commits of the branch that were matched, from its head downwards.
+    pub matched: Vec<CommitCorrespondence>,
+    /// The commits of the branch without a likely original, from its head downwards.
+    #[serde(with = "crate::serde::oid_vec")]
+    pub unmatched: Vec<git2::Oid>,
+}
+
+/// Match the commits of `branch_id` to the commits they were likely rebased from, after the
+/// target was rewritten upstream as described by [`target_rewritten()`].
+///
+/// The candidates are the commits of the target the workspace is based on that aren't part of
+/// its fetched head anymore. A commit is matched to the candidate with the same change-id, or
+/// otherwise to the most similar one, see [`commit_similarity()`](super::commit_similarity),
+/// if they are at least half the same. Each candidate is matched at most once. This is meant to
+/// diagnose rebases that went wrong, so if the target wasn't rewritten, all commits are unmatched.
+pub fn rebase_correspondence(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<RebaseCorrespondence> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+    let repo = project_repository.repo();
+    let rewrite = target_rewritten(project_repository)?;
+
+    let commits_of = |tip: git2::Oid, hidden: &[git2::Oid]| -> Result<Vec<git2::Oid>> {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(tip)?;
+        for hidden in hidden {
+            revwalk.hide(*hidden)?;
+        }
+        revwalk
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to list commits")
+    };
+    let (commits, mut candidates) = match &rewrite {
+        Some(rewrite) => (
+            commits_of(branch.head, &[default_target.sha, rewrite.remote_tip])?,
+            commits_of(default_target.sha, &[rewrite.remote_tip])?,
+        ),
+        None => (commits_of(branch.head, &[default_target.sha])?, Vec::new()),
+    };
+
+    let mut matched = Vec::new();
+    let mut unmatched = Vec::new();
+    for commit_id in commits {
+        let mut best = None;
+        if let Some(change_id) = repo.find_commit(commit_id)?.change_id() {
+            for (idx, candidate) in candidates.iter().enumerate() {
+                if repo.find_commit(*candidate)?.change_id().as_ref() == Some(&change_id) {
+                    best = Some((idx, None));
+                    break;
+                }
+            }
+        }
+        if best.is_none() {
+            let mut best_similarity = REBASE_SIMILARITY_THRESHOLD;
+            for (idx, candidate) in candidates.iter().enumerate() {
+                let similarity = super::commit_similarity(repo, commit_id, *candidate)?;
+                if similarity > best_similarity
+                    || (best.is_none() && similarity >= REBASE_SIMILARITY_THRESHOLD)
+                {
+                    best_similarity = similarity;
+                    best = Some((idx, Some(similarity)));
+                }
+            }
+        }
+        match best {
+            Some((idx, similarity)) => matched.push(CommitCorrespondence {
+                commit_id,
+                original_id: candidates.remove(idx),
+                similarity,
+            }),
+            None => unmatched.push(commit_id),
+        }
+    }
+    Ok(RebaseCorrespondence { matched, unmatched })
+}
+
+/// The refspecs to fetch from a single remote, see [`minimal_fetch_refspecs()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchRefspecs {
+    pub remote: String,
+    pub refspecs: Vec<String>,
+}
+
+/// Return the refspecs needed to update the remote tracking branches of the default target and
+/// of the upstreams of all virtual branches, grouped by remote, with the remote of the target
+/// first and the others in the order they are first needed.
+///
+/// Fetching just these instead of all branches is much faster in repositories with many of them,
+/// but it doesn't discover new branches on the remote.
+pub fn minimal_fetch_refspecs(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<FetchRefspecs>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut fetch_refspecs: Vec<FetchRefspecs> = Vec::new();
+    for remote_branch in std::iter::once(&default_target.branch).chain(
+        branches
+            .iter()
+            .filter_map(|branch| branch.upstream.as_ref()),
+    ) {
+        let refspec = format!(
+            "+refs/heads/{branch}:refs/remotes/{remote}/{branch}",
+            remote = remote_branch.remote(),
+            branch = remote_branch.branch()
+        );
+        match fetch_refspecs
+            .iter_mut()
+            .find(|fetch| fetch.remote == remote_branch.remote())
+        {
+            Some(fetch) if fetch.refspecs.contains(&refspec) => {}
+            Some(fetch) => fetch.refspecs.push(refspec),
+            None => fetch_refspecs.push(FetchRefspecs {
+                remote: remote_branch.remote().to_owned(),
+                refspecs: vec![refspec],
+            }),
+        }
+    }
+    Ok(fetch_refspecs)
+}
+
 fn default_target(base_path: &Path) -> Result<target::Target> {
     VirtualBranchesHandle::new(base_path).get_default_target()
 }
@@ -0,0 +1,243 @@
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use super::{branch::BranchOwnershipClaims, BranchCommit, BranchId};
+use crate::{
+    error,
+    git::CommitExt,
+    ops::entry::{OperationKind, SnapshotDetails},
+    project_repository::{self, LogUntil},
+    users,
+};
+
+/// A way to refer to a commit of a virtual branch which stays valid while earlier operations of the
+/// same batch rewrite it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommitRef {
+    /// The change-id of the commit, which is retained when it's rebased or reworded.
+    ChangeId(String),
+    /// The id of the commit as it is right before the operation runs.
+    Oid(#[serde(with = "crate::serde::oid")] git2::Oid),
+}
+
+/// A single mutation of the workspace, to be executed as part of [`apply_operations()`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum WorkspaceOp {
+    /// Commit the changes of `branch_id`, or only those in `ownership` if set.
+    #[serde(rename_all = "camelCase")]
+    Commit {
+        branch_id: BranchId,
+        message: String,
+        ownership: Option<BranchOwnershipClaims>,
+    },
+    /// Change the message of `commit`.
+    #[serde(rename_all = "camelCase")]
+    Reword {
+        branch_id: BranchId,
+        commit: CommitRef,
+        message: String,
+    },
+    /// Move `commit` by `offset` within its branch, with negative values moving it towards the head.
+    #[serde(rename_all = "camelCase")]
+    Reorder {
+        branch_id: BranchId,
+        commit: CommitRef,
+        offset: i32,
+    },
+}
+
+impl fmt::Display for WorkspaceOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WorkspaceOp::Commit { .. } => "commit",
+            WorkspaceOp::Reword { .. } => "reword",
+            WorkspaceOp::Reorder { .. } => "reorder",
+        })
+    }
+}
+
+/// Execute all `ops` in order as a single unit.
+///
+/// A snapshot is taken before the first operation, and if any operation fails the workspace is
+/// restored from it, so either all operations are applied or none. The returned error names the
+/// index and kind of the failed operation.
+pub fn apply_operations(
+    project_repository: &project_repository::Repository,
+    ops: &[WorkspaceOp],
+    user: Option<&users::User>,
+) -> Result<()> {
+    let project = project_repository.project();
+    let snapshot_id = snapshot_for_rollback(project_repository, OperationKind::BatchOperations)?;
+
+    for (index, op) in ops.iter().enumerate() {
+        if let Err(err) = apply_operation(project_repository, op, user) {
+            project
+                .restore_snapshot(snapshot_id)
+                .context("failed to restore the workspace after a failed operation")?;
+            let message =
+                format!("Operation {index} ({op}) failed, no operation was applied: {err:#}");
+            return Err(err.context(error::Context::new(message)));
+        }
+    }
+    Ok(())
+}
+
+/// The changes to commit to a single branch as part of [`commit_across_branches()`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitAssignment {
+    pub branch_id: BranchId,
+    pub message: String,
+    /// The hunks of the branch to commit.
+    pub ownership: BranchOwnershipClaims,
+}
+
+/// Create one commit per assignment, each from the hunks listed for its branch, as a single unit.
+///
+/// It's an error if a hunk is assigned more than once. A snapshot is taken first, and if any of
+/// the commits fails the workspace is restored from it. Return the new head of each branch, in
+/// the order of `assignments`.
+pub fn commit_across_branches(
+    project_repository: &project_repository::Repository,
+    assignments: &[CommitAssignment],
+    user: Option<&users::User>,
+) -> Result<Vec<BranchCommit>> {
+    for (index, assignment) in assignments.iter().enumerate() {
+        for other in &assignments[index + 1..] {
+            for claim in &assignment.ownership.claims {
+                let duplicate = other
+                    .ownership
+                    .claims
+                    .iter()
+                    .filter(|other_claim| other_claim.file_path == claim.file_path)
+                    .flat_map(|other_claim| &other_claim.hunks)
+                    .find(|hunk| claim.hunks.contains(hunk));
+                if let Some(hunk) = duplicate {
+                    return Err(anyhow!(
+                        "hunk {}:{hunk} is assigned to both branch {} and branch {}",
+                        claim.file_path.display(),
+                        assignment.branch_id,
+                        other.branch_id
+                    ))
+                    .context(error::Code::Validation);
+                }
+            }
+        }
+    }
+
+    let project = project_repository.project();
+    let snapshot_id = snapshot_for_rollback(project_repository, OperationKind::CreateCommit)?;
+
+    let mut heads = Vec::with_capacity(assignments.len());
+    for assignment in assignments {
+        match super::commit(
+            project_repository,
+            assignment.branch_id,
+            &assignment.message,
+            Some(&assignment.ownership),
+            user,
+            &super::CommitOptions::default(),
+        ) {
+            Ok(created) => heads.push(BranchCommit {
+                branch_id: assignment.branch_id,
+                commit_id: created.commit_id,
+            }),
+            Err(err) => {
+                project
+                    .restore_snapshot(snapshot_id)
+                    .context("failed to restore the workspace after a failed commit")?;
+                let message = format!(
+                    "Committing to branch {} failed, no commit was created: {err:#}",
+                    assignment.branch_id
+                );
+                return Err(err.context(error::Context::new(message)));
+            }
+        }
+    }
+    Ok(heads)
+}
+
+/// Create a snapshot to restore if one of the following operations fails, and return its id.
+fn snapshot_for_rollback(
+    project_repository: &project_repository::Repository,
+    kind: OperationKind,
+) -> Result<git2::Oid> {
+    let project = project_repository.project();
+    match project
+        .create_snapshot(SnapshotDetails::new(kind))
+        .context("failed to create snapshot to restore on failure")?
+    {
+        Some(snapshot_id) => Ok(snapshot_id),
+        // nothing changed since the last snapshot, so it represents the current state
+        None => project
+            .oplog_head()?
+            .context("failed to find snapshot to restore on failure"),
+    }
+}
+
+fn apply_operation(
+    project_repository: &project_repository::Repository,
+    op: &WorkspaceOp,
+    user: Option<&users::User>,
+) -> Result<()> {
+    match op {
+        WorkspaceOp::Commit {
+            branch_id,
+            message,
+            ownership,
+        } => super::commit(
+            project_repository,
+            *branch_id,
+            message,
+            ownership.as_ref(),
+            user,
+            &super::CommitOptions::default(),
+        )
+        .map(|_| ()),
+        WorkspaceOp::Reword {
+            branch_id,
+            commit,
+            message,
+        } => {
+            let commit_id = resolve_commit(project_repository, *branch_id, commit)?;
+            super::update_commit_message(project_repository, *branch_id, commit_id, message)
+        }
+        WorkspaceOp::Reorder {
+            branch_id,
+            commit,
+            offset,
+        } => {
+            let commit_id = resolve_commit(project_repository, *branch_id, commit)?;
+            super::reorder_commit(project_repository, *branch_id, commit_id, *offset)
+        }
+    }
+}
+
+/// Find the commit `commit` refers to among the commits of `branch_id` that aren't part of the
+/// default target.
+fn resolve_commit(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    commit: &CommitRef,
+) -> Result<git2::Oid> {
+    match commit {
+        CommitRef::Oid(oid) => Ok(*oid),
+        CommitRef::ChangeId(change_id) => {
+            let vb_state = project_repository.project().virtual_branches();
+            let default_target = vb_state.get_default_target()?;
+            let branch = vb_state.get_branch(branch_id)?;
+            project_repository
+                .log(branch.head, LogUntil::Commit(default_target.sha))?
+                .into_iter()
+                .find(|commit| commit.change_id().as_deref() == Some(change_id.as_str()))
+                .map(|commit| commit.id())
+                .ok_or_else(|| {
+                    anyhow!("commit with change-id {change_id} not found in branch {branch_id}")
+                })
+        }
+    }
+}
@@ -1,12 +1,81 @@
-use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+use std::{
+    fmt::Display,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::{anyhow, Context, Result};
 use bstr::ByteSlice;
+use serde::{Deserialize, Serialize};
 
 use crate::git::diff;
 
 pub type HunkHash = md5::Digest;
 
+/// The identity of an uncommitted hunk, which is where it is along with what it changes.
+///
+/// Unlike a [`HunkHash`] of the diff, which is the same for hunks with the same content, no two
+/// distinct hunks share an identity. The content is only represented by its added and removed
+/// lines, so it doesn't matter how many context lines the diff of the hunk was produced with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkIdentity {
+    pub path: PathBuf,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// The hash of the added and removed lines of the hunk.
+    #[serde(
+        serialize_with = "crate::serde::hash_to_hex",
+        deserialize_with = "crate::serde::hash_from_hex"
+    )]
+    pub digest: HunkHash,
+}
+
+impl HunkIdentity {
+    /// Identify `hunk` of the file at `path`.
+    ///
+    /// If the diff of `hunk` doesn't start with a diff header, like for binary hunks or empty new
+    /// files, it's hashed as a whole, just like [`Hunk::hash_diff()`] does.
+    pub fn new(path: &Path, hunk: &diff::GitHunk) -> Self {
+        Self::with_digest(path, hunk, None)
+    }
+
+    /// Like [`Self::new()`], but the section heading of the diff header, i.e. the name of the
+    /// enclosing function git puts after the closing `@@`, is hashed along with the changed lines.
+    ///
+    /// This way, the same change in different functions gets a different digest. If the header
+    /// has no section heading, it's the same as [`Self::new()`].
+    pub fn with_section(path: &Path, hunk: &diff::GitHunk) -> Self {
+        Self::with_digest(path, hunk, section_heading(&hunk.diff_lines))
+    }
+
+    fn with_digest(path: &Path, hunk: &diff::GitHunk, section: Option<&[u8]>) -> Self {
+        let diff = hunk.diff_lines.as_bstr();
+        let digest = if hunk.binary || !diff.starts_with(b"@@") {
+            Hunk::hash(diff)
+        } else {
+            let mut ctx = md5::Context::new();
+            if let Some(section) = section {
+                ctx.consume(section);
+                ctx.consume(b"\0");
+            }
+            consume_changed_lines(&mut ctx, diff);
+            ctx.compute()
+        };
+        HunkIdentity {
+            path: path.to_owned(),
+            old_start: hunk.old_start,
+            old_lines: hunk.old_lines,
+            new_start: hunk.new_start,
+            new_lines: hunk.new_lines,
+            digest,
+        }
+    }
+}
+
 #[derive(Debug, Eq, Clone)]
 pub struct Hunk {
     pub hash: Option<HunkHash>,
@@ -162,6 +231,10 @@ impl Hunk {
     /// `diff` can also be entirely empty, or not contain a diff header which is when it will just be hashed
     /// with [`Self::hash()`].
     ///
+    /// Only the content of the added and removed lines is hashed, not their markers, the diff
+    /// header or the context lines, so the hash stays the same when the hunk moves within its file
+    /// or the lines around it change.
+    ///
     /// ### Notes on Persistence
     /// Note that there is danger in changing the hash function as this information is persisted
     /// in the virtual-branch toml file. Even if it can still be parsed or decoded,
@@ -172,9 +245,50 @@ impl Hunk {
             return Self::hash(diff);
         }
         let mut ctx = md5::Context::new();
-        diff.lines_with_terminator()
-            .skip(1) // skip the first line which is the diff header.
-            .for_each(|line| ctx.consume(line));
+        consume_changed_lines(&mut ctx, diff);
+        ctx.compute()
+    }
+
+    /// Produce an id for the hunk in `diff` of the file at `path` which remains the same while lines
+    /// are added or removed elsewhere in the file, as it doesn't depend on line numbers.
+    ///
+    /// It's the hash of `path` along with the anchors of the hunk, the context line right above
+    /// its first change and the context line right below its last change, each of which may be
+    /// missing if the hunk is at the beginning or end of the file. `diff` is **assumed to have a
+    /// one-line diff header**, and if it has no changed lines at all, like a binary hunk, the id is
+    /// derived from `path` and the whole of `diff` instead.
+    ///
+    /// ### Collisions
+    ///
+    /// As the changed lines aren't part of the id, it stays the same while the hunk itself is edited,
+    /// but two hunks of the same file share an id if they have the same anchors, for instance when
+    /// the hunks are surrounded by repeated content or both lack both anchors.
+    pub fn stable_id<S: AsRef<[u8]>>(path: &Path, diff: S) -> HunkHash {
+        let diff = diff.as_ref();
+        let lines: Vec<&[u8]> = diff
+            .lines()
+            .skip(if diff.starts_with(b"@@") { 1 } else { 0 })
+            .collect();
+        let is_change = |line: &&[u8]| matches!(line.first(), Some(b'+' | b'-'));
+
+        let mut ctx = md5::Context::new();
+        ctx.consume(path.to_string_lossy().as_bytes());
+        ctx.consume(b"\0");
+        match (
+            lines.iter().position(is_change),
+            lines.iter().rposition(is_change),
+        ) {
+            (Some(first), Some(last)) => {
+                let above = lines[..first].last().filter(|line| line.starts_with(b" "));
+                let below = lines[last + 1..]
+                    .first()
+                    .filter(|line| line.starts_with(b" "));
+                ctx.consume(above.copied().unwrap_or_default());
+                ctx.consume(b"\0");
+                ctx.consume(below.copied().unwrap_or_default());
+            }
+            _ => ctx.consume(diff),
+        }
         ctx.compute()
     }
 
@@ -184,3 +298,25 @@ impl Hunk {
         md5::compute(input.as_ref())
     }
 }
+
+/// Hash the content of the added and removed lines of `diff` without their `+` and `-` markers,
+/// skipping its diff header and context lines.
+fn consume_changed_lines(ctx: &mut md5::Context, diff: &[u8]) {
+    diff.lines_with_terminator()
+        .skip(1) // skip the first line which is the diff header.
+        .filter(|line| matches!(line.first(), Some(b'+' | b'-')))
+        .for_each(|line| ctx.consume(&line[1..]));
+}
+
+/// Return the section heading of the diff header of `diff`, i.e. the name of the enclosing function
+/// git puts after the closing `@@`, if there is one.
+fn section_heading(diff: &[u8]) -> Option<&[u8]> {
+    diff.lines()
+        .next()
+        .filter(|header| header.starts_with(b"@@"))
+        .and_then(|header| {
+            let end = header[2..].find(b"@@")? + 4;
+            Some(header[end..].trim())
+        })
+        .filter(|section| !section.is_empty())
+}
@@ -4,7 +4,7 @@ mod ownership;
 
 use anyhow::Result;
 pub use file_ownership::OwnershipClaim;
-pub use hunk::{Hunk, HunkHash};
+pub use hunk::{Hunk, HunkHash, HunkIdentity};
 pub use ownership::{reconcile_claims, BranchOwnershipClaims};
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +26,9 @@ pub struct Branch {
     // upstream_head is the last commit on we've pushed to the upstream branch
     #[serde(with = "crate::serde::oid_opt", default)]
     pub upstream_head: Option<git2::Oid>,
+    /// The remote to push to if it's not the push remote of the default target.
+    #[serde(default)]
+    pub push_remote: Option<String>,
     #[serde(
         serialize_with = "serialize_u128",
         deserialize_with = "deserialize_u128"
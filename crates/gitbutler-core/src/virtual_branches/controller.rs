@@ -6,11 +6,19 @@ use anyhow::Context;
 use tokio::{sync::Semaphore, task::JoinHandle};
 
 use super::{
-    branch::{BranchId, BranchOwnershipClaims},
-    target, target_to_base_branch, BaseBranch, Branch, RemoteBranchFile, VirtualBranchesHandle,
+    branch::{BranchId, BranchOwnershipClaims, Hunk, HunkIdentity},
+    target, target_to_base_branch, ActiveBranches, AmendTargets, BaseBranch, Branch, BranchCommit,
+    BranchCommitMetrics, BranchHunkCounts, BranchMergeStatus, BranchSegment, BranchWithStatus,
+    CommitAssignment, CommitOptions, CreatedCommit, CrossBranchLockedFile, DirStat, EmptiedCommit,
+    ForcePushSafety, HunkComparison, HunkGroup, HunkLocks, HunkRef, IntegrationOrder, LargeBinary,
+    LockImpact, PathCommit, PathMetadata, PrBase, PrDescription, PushResult, RebaseCorrespondence,
+    RedundantHunk, RemoteBranchFile, ReorderPreview, SplitHunks, SquashBlocker, SquashPreview,
+    SquashedDiff, StaleBranch, StaleRemoteBranch, TargetRewrite, UnownedFile,
+    VirtualBranchesHandle, WorkspaceChangeIds, WorkspaceOp, WorktreeChanges, WorktreeFileDiff,
 };
 use crate::{
-    git, project_repository,
+    git,
+    project_repository::{self, conflicts::ConflictStyle},
     projects::{self, ProjectId},
     users,
 };
@@ -54,11 +62,29 @@ impl Controller {
         branch_id: BranchId,
         message: &str,
         ownership: Option<&BranchOwnershipClaims>,
-        run_hooks: bool,
-    ) -> Result<git2::Oid> {
+        options: &CommitOptions,
+    ) -> Result<CreatedCommit> {
+        self.inner(project_id)
+            .await
+            .create_commit(project_id, branch_id, message, ownership, options)
+            .await
+    }
+
+    pub async fn apply_operations(&self, project_id: ProjectId, ops: &[WorkspaceOp]) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .apply_operations(project_id, ops)
+            .await
+    }
+
+    pub async fn commit_across_branches(
+        &self,
+        project_id: ProjectId,
+        assignments: &[CommitAssignment],
+    ) -> Result<Vec<BranchCommit>> {
         self.inner(project_id)
             .await
-            .create_commit(project_id, branch_id, message, ownership, run_hooks)
+            .commit_across_branches(project_id, assignments)
             .await
     }
 
@@ -120,418 +146,1476 @@ impl Controller {
             .get_base_branch_data(project_id)
     }
 
-    pub async fn list_remote_commit_files(
+    pub async fn newly_integrated_commits(
         &self,
         project_id: ProjectId,
-        commit_oid: git2::Oid,
-    ) -> Result<Vec<RemoteBranchFile>> {
+        since: git2::Oid,
+    ) -> Result<Vec<BranchCommit>> {
         self.inner(project_id)
             .await
-            .list_remote_commit_files(project_id, commit_oid)
+            .newly_integrated_commits(project_id, since)
     }
 
-    pub async fn set_base_branch(
+    pub async fn branch_merge_statuses(
         &self,
         project_id: ProjectId,
-        target_branch: &git::RemoteRefname,
-    ) -> Result<BaseBranch> {
+    ) -> Result<Vec<BranchMergeStatus>> {
         self.inner(project_id)
             .await
-            .set_base_branch(project_id, target_branch)
+            .branch_merge_statuses(project_id)
     }
 
-    pub async fn set_target_push_remote(
+    pub async fn branches_with_status(
         &self,
         project_id: ProjectId,
-        push_remote: &str,
-    ) -> Result<()> {
+    ) -> Result<Vec<BranchWithStatus>> {
         self.inner(project_id)
             .await
-            .set_target_push_remote(project_id, push_remote)
+            .branches_with_status(project_id)
     }
 
-    pub async fn integrate_upstream_commits(
+    pub async fn branch_commit_metrics(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-    ) -> Result<()> {
+    ) -> Result<BranchCommitMetrics> {
+        self.inner(project_id)
+            .await
+            .branch_commit_metrics(project_id, branch_id)
+    }
+
+    pub async fn workspace_tree_without_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<git2::Oid> {
+        self.inner(project_id)
+            .await
+            .workspace_tree_without_branch(project_id, branch_id)
+    }
+
+    pub async fn reorder_stack_branches(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        branch_names: &[String],
+    ) -> Result<Vec<Branch>> {
         self.inner(project_id)
             .await
-            .integrate_upstream_commits(project_id, branch_id)
+            .reorder_stack_branches(project_id, branch_id, branch_names)
             .await
     }
 
-    pub async fn update_base_branch(&self, project_id: ProjectId) -> Result<Vec<Branch>> {
+    pub async fn stale_branches(
+        &self,
+        project_id: ProjectId,
+        older_than: std::time::Duration,
+    ) -> Result<Vec<StaleBranch>> {
         self.inner(project_id)
             .await
-            .update_base_branch(project_id)
+            .stale_branches(project_id, older_than)
+    }
+
+    pub async fn stale_remote_branches(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<StaleRemoteBranch>> {
+        self.inner(project_id)
             .await
+            .stale_remote_branches(project_id)
     }
 
-    pub async fn update_virtual_branch(
+    pub async fn parallel_integration_groups(
         &self,
         project_id: ProjectId,
-        branch_update: super::branch::BranchUpdateRequest,
-    ) -> Result<()> {
+    ) -> Result<Vec<Vec<BranchId>>> {
         self.inner(project_id)
             .await
-            .update_virtual_branch(project_id, branch_update)
+            .parallel_integration_groups(project_id)
+    }
+
+    pub async fn suggested_integration_order(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<IntegrationOrder> {
+        self.inner(project_id)
             .await
+            .suggested_integration_order(project_id)
     }
-    pub async fn delete_virtual_branch(
+
+    pub async fn emptied_by_integration(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-    ) -> Result<()> {
+    ) -> Result<Vec<EmptiedCommit>> {
         self.inner(project_id)
             .await
-            .delete_virtual_branch(project_id, branch_id)
+            .emptied_by_integration(project_id, branch_id)
+    }
+
+    pub async fn minimal_fetch_refspecs(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<FetchRefspecs>> {
+        self.inner(project_id)
             .await
+            .minimal_fetch_refspecs(project_id)
     }
 
-    pub async fn apply_virtual_branch(
+    pub async fn target_rewritten(&self, project_id: ProjectId) -> Result<Option<TargetRewrite>> {
+        self.inner(project_id).await.target_rewritten(project_id)
+    }
+
+    pub async fn rebase_correspondence(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-    ) -> Result<()> {
+    ) -> Result<RebaseCorrespondence> {
         self.inner(project_id)
             .await
-            .apply_virtual_branch(project_id, branch_id)
+            .rebase_correspondence(project_id, branch_id)
+    }
+
+    pub async fn list_remote_commit_files(
+        &self,
+        project_id: ProjectId,
+        commit_oid: git2::Oid,
+        rename_threshold: Option<u16>,
+        with_attribution: bool,
+    ) -> Result<Vec<RemoteBranchFile>> {
+        self.inner(project_id).await.list_remote_commit_files(
+            project_id,
+            commit_oid,
+            rename_threshold,
+            with_attribution,
+        )
+    }
+
+    pub async fn commit_binary_delta_size(
+        &self,
+        project_id: ProjectId,
+        commit_oid: git2::Oid,
+        path: &Path,
+    ) -> Result<Option<u64>> {
+        self.inner(project_id)
             .await
+            .commit_binary_delta_size(project_id, commit_oid, path)
     }
 
-    pub async fn unapply_ownership(
+    pub async fn worktree_changes(
         &self,
         project_id: ProjectId,
-        ownership: &BranchOwnershipClaims,
-    ) -> Result<()> {
+        pathspecs: &[String],
+        with_submodules: bool,
+        include_ignored: bool,
+        rename_threshold: Option<u16>,
+        diff_limit_bytes: Option<usize>,
+    ) -> Result<WorktreeChanges> {
+        self.inner(project_id).await.worktree_changes(
+            project_id,
+            pathspecs,
+            with_submodules,
+            include_ignored,
+            rename_threshold,
+            diff_limit_bytes,
+        )
+    }
+
+    pub async fn file_diff(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+        context_lines: u32,
+        word_diff: git::diff::WordDiff,
+    ) -> Result<WorktreeFileDiff> {
         self.inner(project_id)
             .await
-            .unapply_ownership(project_id, ownership)
+            .file_diff(project_id, path, context_lines, word_diff)
+    }
+
+    pub async fn dirstat(&self, project_id: ProjectId) -> Result<Vec<DirStat>> {
+        self.inner(project_id).await.dirstat(project_id)
+    }
+
+    pub async fn porcelain_status(&self, project_id: ProjectId) -> Result<String> {
+        self.inner(project_id).await.porcelain_status(project_id)
+    }
+
+    pub async fn large_new_binaries(
+        &self,
+        project_id: ProjectId,
+        threshold_bytes: u64,
+    ) -> Result<Vec<LargeBinary>> {
+        self.inner(project_id)
             .await
+            .large_new_binaries(project_id, threshold_bytes)
     }
 
-    pub async fn reset_files(&self, project_id: ProjectId, files: &Vec<String>) -> Result<()> {
+    pub async fn commits_touching_path(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+    ) -> Result<Vec<PathCommit>> {
         self.inner(project_id)
             .await
-            .reset_files(project_id, files)
+            .commits_touching_path(project_id, path)
+    }
+
+    pub async fn path_metadata(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+        at: Option<git2::Oid>,
+    ) -> Result<Option<PathMetadata>> {
+        self.inner(project_id)
             .await
+            .path_metadata(project_id, path, at)
     }
 
-    pub async fn amend(
+    pub async fn force_push_safety(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        commit_oid: git2::Oid,
-        ownership: &BranchOwnershipClaims,
-    ) -> Result<git2::Oid> {
+    ) -> Result<ForcePushSafety> {
         self.inner(project_id)
             .await
-            .amend(project_id, branch_id, commit_oid, ownership)
+            .force_push_safety(project_id, branch_id)
+    }
+
+    pub async fn diff_branches(
+        &self,
+        project_id: ProjectId,
+        branch_a: BranchId,
+        branch_b: BranchId,
+        context_lines: u32,
+    ) -> Result<Vec<RemoteBranchFile>> {
+        self.inner(project_id)
+            .await
+            .diff_branches(project_id, branch_a, branch_b, context_lines)
             .await
     }
 
-    pub async fn move_commit_file(
+    pub async fn diff_against_remote(
         &self,
         project_id: ProjectId,
-        branch_id: BranchId,
-        from_commit_oid: git2::Oid,
-        to_commit_oid: git2::Oid,
-        ownership: &BranchOwnershipClaims,
-    ) -> Result<git2::Oid> {
+        remote: &str,
+        branch: &str,
+        branch_id: Option<BranchId>,
+        context_lines: u32,
+        two_dot: bool,
+    ) -> Result<Vec<RemoteBranchFile>> {
         self.inner(project_id)
             .await
-            .move_commit_file(
+            .diff_against_remote(
                 project_id,
+                remote,
+                branch,
                 branch_id,
-                from_commit_oid,
-                to_commit_oid,
-                ownership,
+                context_lines,
+                two_dot,
             )
             .await
     }
 
-    pub async fn undo_commit(
+    pub async fn workspace_change_ids(&self, project_id: ProjectId) -> Result<WorkspaceChangeIds> {
+        self.inner(project_id)
+            .await
+            .workspace_change_ids(project_id)
+    }
+
+    pub async fn branch_topological_order(&self, project_id: ProjectId) -> Result<Vec<BranchId>> {
+        self.inner(project_id)
+            .await
+            .branch_topological_order(project_id)
+    }
+
+    pub async fn active_branches(&self, project_id: ProjectId) -> Result<ActiveBranches> {
+        self.inner(project_id).await.active_branches(project_id)
+    }
+
+    pub async fn stack_pr_descriptions(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        commit_oid: git2::Oid,
-    ) -> Result<()> {
+    ) -> Result<Vec<PrDescription>> {
         self.inner(project_id)
             .await
-            .undo_commit(project_id, branch_id, commit_oid)
+            .stack_pr_descriptions(project_id, branch_id)
+    }
+
+    pub async fn pr_base_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<PrBase> {
+        self.inner(project_id)
             .await
+            .pr_base_branch(project_id, branch_id)
     }
 
-    pub async fn insert_blank_commit(
+    pub async fn branch_exclusive_commits(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        commit_oid: git2::Oid,
-        offset: i32,
-    ) -> Result<()> {
+    ) -> Result<Vec<BranchSegment>> {
         self.inner(project_id)
             .await
-            .insert_blank_commit(project_id, branch_id, commit_oid, offset)
+            .branch_exclusive_commits(project_id, branch_id)
+    }
+
+    pub async fn branches_merge_base(
+        &self,
+        project_id: ProjectId,
+        a: BranchId,
+        b: BranchId,
+    ) -> Result<git2::Oid> {
+        self.inner(project_id)
             .await
+            .branches_merge_base(project_id, a, b)
     }
 
-    pub async fn reorder_commit(
+    pub async fn safe_amend_targets(
         &self,
         project_id: ProjectId,
-        branch_id: BranchId,
-        commit_oid: git2::Oid,
-        offset: i32,
-    ) -> Result<()> {
+        file_path: &Path,
+        hunk: &Hunk,
+    ) -> Result<AmendTargets> {
         self.inner(project_id)
             .await
-            .reorder_commit(project_id, branch_id, commit_oid, offset)
+            .safe_amend_targets(project_id, file_path, hunk)
             .await
     }
 
-    pub async fn reset_virtual_branch(
+    pub async fn hunk_counts_by_branch(
         &self,
         project_id: ProjectId,
-        branch_id: BranchId,
-        target_commit_oid: git2::Oid,
-    ) -> Result<()> {
+    ) -> Result<Vec<BranchHunkCounts>> {
         self.inner(project_id)
             .await
-            .reset_virtual_branch(project_id, branch_id, target_commit_oid)
+            .hunk_counts_by_branch(project_id)
             .await
     }
 
-    pub async fn unapply_virtual_branch(
+    pub async fn hunk_locks(
+        &self,
+        project_id: ProjectId,
+        allow_stale: bool,
+        context_lines: Option<u32>,
+        allow_partial: bool,
+    ) -> Result<HunkLocks> {
+        self.inner(project_id)
+            .await
+            .hunk_locks(project_id, allow_stale, context_lines, allow_partial)
+            .await
+    }
+
+    pub async fn commit_dependency_order(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-    ) -> Result<()> {
+    ) -> Result<Vec<git2::Oid>> {
         self.inner(project_id)
             .await
-            .unapply_virtual_branch(project_id, branch_id)
+            .commit_dependency_order(project_id, branch_id)
+    }
+
+    pub async fn hunk_apply_order(&self, project_id: ProjectId) -> Result<Vec<HunkGroup>> {
+        self.inner(project_id)
+            .await
+            .hunk_apply_order(project_id)
             .await
     }
 
-    pub async fn push_virtual_branch(
+    pub async fn cross_branch_locked_hunks(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<CrossBranchLockedFile>> {
+        self.inner(project_id)
+            .await
+            .cross_branch_locked_hunks(project_id)
+            .await
+    }
+
+    pub async fn lock_impact_of_applying(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        with_force: bool,
-        askpass: Option<Option<BranchId>>,
-    ) -> Result<()> {
+    ) -> Result<Vec<LockImpact>> {
         self.inner(project_id)
             .await
-            .push_virtual_branch(project_id, branch_id, with_force, askpass)
+            .lock_impact_of_applying(project_id, branch_id)
             .await
     }
 
-    pub async fn cherry_pick(
+    pub async fn unowned_files(&self, project_id: ProjectId) -> Result<Vec<UnownedFile>> {
+        self.inner(project_id).await.unowned_files(project_id).await
+    }
+
+    pub async fn redundant_worktree_changes(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<RedundantHunk>> {
+        self.inner(project_id)
+            .await
+            .redundant_worktree_changes(project_id)
+            .await
+    }
+
+    pub async fn base_to_target_diff(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        commit_oid: git2::Oid,
-    ) -> Result<Option<git2::Oid>> {
+        context_lines: u32,
+    ) -> Result<SquashedDiff> {
+        self.inner(project_id)
+            .await
+            .base_to_target_diff(project_id, branch_id, context_lines)
+    }
+
+    pub async fn branch_squashed_diff(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        context_lines: u32,
+    ) -> Result<SquashedDiff> {
+        self.inner(project_id)
+            .await
+            .branch_squashed_diff(project_id, branch_id, context_lines)
+    }
+
+    pub async fn commit_similarity(
+        &self,
+        project_id: ProjectId,
+        a: git2::Oid,
+        b: git2::Oid,
+    ) -> Result<f64> {
+        self.inner(project_id)
+            .await
+            .commit_similarity(project_id, a, b)
+    }
+
+    pub async fn hunks_identical(
+        &self,
+        project_id: ProjectId,
+        a: &HunkRef,
+        b: &HunkRef,
+    ) -> Result<HunkComparison> {
+        self.inner(project_id)
+            .await
+            .hunks_identical(project_id, a, b)
+    }
+
+    pub async fn ancestry_path(
+        &self,
+        project_id: ProjectId,
+        from: git2::Oid,
+        to: git2::Oid,
+        first_parent: bool,
+    ) -> Result<Option<Vec<git2::Oid>>> {
+        self.inner(project_id)
+            .await
+            .ancestry_path(project_id, from, to, first_parent)
+    }
+
+    pub async fn set_base_branch(
+        &self,
+        project_id: ProjectId,
+        target_branch: &git::RemoteRefname,
+    ) -> Result<BaseBranch> {
         self.inner(project_id)
             .await
-            .cherry_pick(project_id, branch_id, commit_oid)
+            .set_base_branch(project_id, target_branch)
+    }
+
+    pub async fn set_target_push_remote(
+        &self,
+        project_id: ProjectId,
+        push_remote: &str,
+    ) -> Result<()> {
+        self.inner(project_id)
             .await
+            .set_target_push_remote(project_id, push_remote)
+    }
+
+    pub async fn integrate_upstream_commits(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .integrate_upstream_commits(project_id, branch_id, conflict_style)
+            .await
+    }
+
+    pub async fn update_base_branch(&self, project_id: ProjectId) -> Result<Vec<Branch>> {
+        self.inner(project_id)
+            .await
+            .update_base_branch(project_id)
+            .await
+    }
+
+    pub async fn update_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_update: super::branch::BranchUpdateRequest,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .update_virtual_branch(project_id, branch_update)
+            .await
+    }
+
+    pub async fn set_branch_order(
+        &self,
+        project_id: ProjectId,
+        branch_ids: &[BranchId],
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .set_branch_order(project_id, branch_ids)
+            .await
+    }
+
+    pub async fn delete_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .delete_virtual_branch(project_id, branch_id)
+            .await
+    }
+
+    pub async fn apply_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .apply_virtual_branch(project_id, branch_id, conflict_style)
+            .await
+    }
+
+    pub async fn unapply_ownership(
+        &self,
+        project_id: ProjectId,
+        ownership: &BranchOwnershipClaims,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .unapply_ownership(project_id, ownership)
+            .await
+    }
+
+    pub async fn reset_files(&self, project_id: ProjectId, files: &Vec<String>) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .reset_files(project_id, files)
+            .await
+    }
+
+    pub async fn amend(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+        ownership: &BranchOwnershipClaims,
+    ) -> Result<git2::Oid> {
+        self.inner(project_id)
+            .await
+            .amend(project_id, branch_id, commit_oid, ownership)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn split_hunk(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        hunk: &HunkIdentity,
+        old_lines: &[u32],
+        new_lines: &[u32],
+        first_commit: git2::Oid,
+        second_commit: git2::Oid,
+    ) -> Result<SplitHunks> {
+        self.inner(project_id)
+            .await
+            .split_hunk(
+                project_id,
+                branch_id,
+                hunk,
+                old_lines,
+                new_lines,
+                first_commit,
+                second_commit,
+            )
+            .await
+    }
+
+    pub async fn move_commit_file(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        from_commit_oid: git2::Oid,
+        to_commit_oid: git2::Oid,
+        ownership: &BranchOwnershipClaims,
+    ) -> Result<git2::Oid> {
+        self.inner(project_id)
+            .await
+            .move_commit_file(
+                project_id,
+                branch_id,
+                from_commit_oid,
+                to_commit_oid,
+                ownership,
+            )
+            .await
+    }
+
+    pub async fn undo_commit(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .undo_commit(project_id, branch_id, commit_oid)
+            .await
+    }
+
+    pub async fn insert_blank_commit(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+        offset: i32,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .insert_blank_commit(project_id, branch_id, commit_oid, offset)
+            .await
+    }
+
+    pub async fn reorder_commit(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+        offset: i32,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .reorder_commit(project_id, branch_id, commit_oid, offset)
+            .await
+    }
+
+    pub async fn reset_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        target_commit_oid: git2::Oid,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .reset_virtual_branch(project_id, branch_id, target_commit_oid)
+            .await
+    }
+
+    pub async fn unapply_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .unapply_virtual_branch(project_id, branch_id)
+            .await
+    }
+
+    pub async fn push_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        with_force: bool,
+        askpass: Option<Option<BranchId>>,
+    ) -> Result<PushResult> {
+        self.inner(project_id)
+            .await
+            .push_virtual_branch(project_id, branch_id, with_force, askpass)
+            .await
+    }
+
+    pub async fn set_branch_push_remote(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        remote: Option<&str>,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .set_branch_push_remote(project_id, branch_id, remote)
+            .await
+    }
+
+    pub async fn cherry_pick(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<Option<git2::Oid>> {
+        self.inner(project_id)
+            .await
+            .cherry_pick(project_id, branch_id, commit_oid, conflict_style)
+            .await
+    }
+
+    pub async fn list_remote_branches(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<super::RemoteBranch>> {
+        self.inner(project_id)
+            .await
+            .list_remote_branches(project_id)
+    }
+
+    pub async fn get_remote_branch_data(
+        &self,
+        project_id: ProjectId,
+        refname: &git::Refname,
+    ) -> Result<super::RemoteBranchData> {
+        self.inner(project_id)
+            .await
+            .get_remote_branch_data(project_id, refname)
+    }
+
+    pub async fn can_squash_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<Vec<SquashBlocker>> {
+        self.inner(project_id)
+            .await
+            .can_squash_branch(project_id, branch_id)
+    }
+
+    pub async fn squash(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .squash(project_id, branch_id, commit_oid)
+            .await
+    }
+
+    pub async fn preview_squash(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+    ) -> Result<SquashPreview> {
+        self.inner(project_id)
+            .await
+            .preview_squash(project_id, branch_id, commit_oid)
+    }
+
+    pub async fn preview_reorder(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<ReorderPreview> {
+        self.inner(project_id)
+            .await
+            .preview_reorder(project_id, branch_id, from_index, to_index)
+    }
+
+    pub async fn update_commit_message(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+        message: &str,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .update_commit_message(project_id, branch_id, commit_oid, message)
+            .await
+    }
+
+    pub async fn fetch_from_remotes(
+        &self,
+        project_id: ProjectId,
+        askpass: Option<String>,
+        fetch_all: bool,
+    ) -> Result<BaseBranch> {
+        self.inner(project_id)
+            .await
+            .fetch_from_remotes(project_id, askpass, fetch_all)
+            .await
+    }
+
+    pub async fn move_commit(
+        &self,
+        project_id: ProjectId,
+        target_branch_id: BranchId,
+        commit_oid: git2::Oid,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .move_commit(project_id, target_branch_id, commit_oid)
+            .await
+    }
+
+    pub async fn move_commit_between_branches(
+        &self,
+        project_id: ProjectId,
+        source_branch_id: BranchId,
+        commit_oid: git2::Oid,
+        target_branch_id: BranchId,
+        position: usize,
+    ) -> Result<()> {
+        self.inner(project_id)
+            .await
+            .move_commit_between_branches(
+                project_id,
+                source_branch_id,
+                commit_oid,
+                target_branch_id,
+                position,
+            )
+            .await
+    }
+}
+
+#[derive(Clone)]
+struct ControllerInner {
+    semaphore: Arc<Semaphore>,
+
+    projects: projects::Controller,
+    users: users::Controller,
+    helper: git::credentials::Helper,
+}
+
+impl ControllerInner {
+    pub fn new(
+        projects: &projects::Controller,
+        users: &users::Controller,
+        helper: &git::credentials::Helper,
+    ) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(1)),
+            projects: projects.clone(),
+            users: users.clone(),
+            helper: helper.clone(),
+        }
+    }
+
+    pub async fn create_commit(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        message: &str,
+        ownership: Option<&BranchOwnershipClaims>,
+        options: &CommitOptions,
+    ) -> Result<CreatedCommit> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, user| {
+            let snapshot_tree = project_repository.project().prepare_snapshot();
+            let result = super::commit(
+                project_repository,
+                branch_id,
+                message,
+                ownership,
+                user,
+                options,
+            )
+            .map_err(Into::into);
+            let _ = snapshot_tree.and_then(|snapshot_tree| {
+                project_repository.project().snapshot_commit_creation(
+                    snapshot_tree,
+                    result.as_ref().err(),
+                    message.to_owned(),
+                    None,
+                )
+            });
+            result
+        })
+    }
+
+    pub async fn apply_operations(&self, project_id: ProjectId, ops: &[WorkspaceOp]) -> Result<()> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, user| {
+            super::apply_operations(project_repository, ops, user)
+        })
+    }
+
+    pub async fn commit_across_branches(
+        &self,
+        project_id: ProjectId,
+        assignments: &[CommitAssignment],
+    ) -> Result<Vec<BranchCommit>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, user| {
+            super::commit_across_branches(project_repository, assignments, user)
+        })
+    }
+
+    pub fn can_apply_remote_branch(
+        &self,
+        project_id: ProjectId,
+        branch_name: &git::RemoteRefname,
+    ) -> Result<bool> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::is_remote_branch_mergeable(&project_repository, branch_name).map_err(Into::into)
+    }
+
+    pub fn can_apply_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<bool> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::is_virtual_branch_mergeable(&project_repository, branch_id).map_err(Into::into)
+    }
+
+    pub async fn list_virtual_branches(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<(Vec<super::VirtualBranch>, Vec<git::diff::FileDiff>)> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::list_virtual_branches(project_repository).map_err(Into::into)
+        })
+    }
+
+    pub async fn create_virtual_branch(
+        &self,
+        project_id: ProjectId,
+        create: &super::branch::BranchCreateRequest,
+    ) -> Result<BranchId> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            let branch_id = super::create_virtual_branch(project_repository, create)?.id;
+            Ok(branch_id)
+        })
+    }
+
+    pub async fn create_virtual_branch_from_branch(
+        &self,
+        project_id: ProjectId,
+        branch: &git::Refname,
+    ) -> Result<BranchId> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, user| {
+            super::create_virtual_branch_from_branch(project_repository, branch, user)
+                .map_err(Into::into)
+        })
+    }
+
+    pub fn get_base_branch_data(&self, project_id: ProjectId) -> Result<BaseBranch> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::get_base_branch_data(&project_repository)
+    }
+
+    pub fn newly_integrated_commits(
+        &self,
+        project_id: ProjectId,
+        since: git2::Oid,
+    ) -> Result<Vec<BranchCommit>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::newly_integrated_commits(&project_repository, since)
+    }
+
+    pub fn branch_merge_statuses(&self, project_id: ProjectId) -> Result<Vec<BranchMergeStatus>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branch_merge_statuses(&project_repository)
+    }
+
+    pub fn branches_with_status(&self, project_id: ProjectId) -> Result<Vec<BranchWithStatus>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branches_with_status(&project_repository)
+    }
+
+    pub fn branch_commit_metrics(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<BranchCommitMetrics> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branch_commit_metrics(&project_repository, branch_id)
+    }
+
+    pub fn workspace_tree_without_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<git2::Oid> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::integration::workspace_tree_without_branch(&project_repository, branch_id)
+    }
+
+    pub async fn reorder_stack_branches(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        branch_names: &[String],
+    ) -> Result<Vec<Branch>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::reorder_stack_branches(project_repository, branch_id, branch_names)
+        })
+    }
+
+    pub fn stale_branches(
+        &self,
+        project_id: ProjectId,
+        older_than: std::time::Duration,
+    ) -> Result<Vec<StaleBranch>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::stale_branches(&project_repository, older_than)
+    }
+
+    pub fn stale_remote_branches(&self, project_id: ProjectId) -> Result<Vec<StaleRemoteBranch>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::stale_remote_branches(&project_repository)
+    }
+
+    pub fn parallel_integration_groups(&self, project_id: ProjectId) -> Result<Vec<Vec<BranchId>>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::parallel_integration_groups(&project_repository)
+    }
+
+    pub fn suggested_integration_order(&self, project_id: ProjectId) -> Result<IntegrationOrder> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::suggested_integration_order(&project_repository)
+    }
+
+    pub fn emptied_by_integration(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<Vec<EmptiedCommit>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::emptied_by_integration(&project_repository, branch_id)
+    }
+
+    pub fn minimal_fetch_refspecs(&self, project_id: ProjectId) -> Result<Vec<FetchRefspecs>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::minimal_fetch_refspecs(&project_repository)
+    }
+
+    pub fn target_rewritten(&self, project_id: ProjectId) -> Result<Option<TargetRewrite>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::target_rewritten(&project_repository)
+    }
+
+    pub fn rebase_correspondence(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<RebaseCorrespondence> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::rebase_correspondence(&project_repository, branch_id)
+    }
+
+    pub fn list_remote_commit_files(
+        &self,
+        project_id: ProjectId,
+        commit_oid: git2::Oid,
+        rename_threshold: Option<u16>,
+        with_attribution: bool,
+    ) -> Result<Vec<RemoteBranchFile>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::list_remote_commit_files(
+            &project_repository,
+            commit_oid,
+            rename_threshold,
+            with_attribution,
+        )
+        .map_err(Into::into)
+    }
+
+    pub fn commit_binary_delta_size(
+        &self,
+        project_id: ProjectId,
+        commit_oid: git2::Oid,
+        path: &Path,
+    ) -> Result<Option<u64>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::commit_binary_delta_size(project_repository.repo(), commit_oid, path)
+    }
+
+    pub fn worktree_changes(
+        &self,
+        project_id: ProjectId,
+        pathspecs: &[String],
+        with_submodules: bool,
+        include_ignored: bool,
+        rename_threshold: Option<u16>,
+        diff_limit_bytes: Option<usize>,
+    ) -> Result<WorktreeChanges> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::worktree_changes(
+            &project_repository,
+            pathspecs,
+            with_submodules,
+            include_ignored,
+            rename_threshold,
+            diff_limit_bytes,
+        )
+    }
+
+    pub fn file_diff(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+        context_lines: u32,
+        word_diff: git::diff::WordDiff,
+    ) -> Result<WorktreeFileDiff> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::file_diff(&project_repository, path, context_lines, word_diff)
+    }
+
+    pub fn dirstat(&self, project_id: ProjectId) -> Result<Vec<DirStat>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::dirstat(&project_repository)
+    }
+
+    pub fn porcelain_status(&self, project_id: ProjectId) -> Result<String> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::porcelain_status(&project_repository)
+    }
+
+    pub fn large_new_binaries(
+        &self,
+        project_id: ProjectId,
+        threshold_bytes: u64,
+    ) -> Result<Vec<LargeBinary>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::large_new_binaries(&project_repository, threshold_bytes)
+    }
+
+    pub fn commits_touching_path(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+    ) -> Result<Vec<PathCommit>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::commits_touching_path(&project_repository, path)
+    }
+
+    pub fn path_metadata(
+        &self,
+        project_id: ProjectId,
+        path: &Path,
+        at: Option<git2::Oid>,
+    ) -> Result<Option<PathMetadata>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::path_metadata(project_repository.repo(), path, at)
+    }
+
+    pub fn force_push_safety(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<ForcePushSafety> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::force_push_safety(&project_repository, branch_id)
+    }
+
+    pub async fn diff_branches(
+        &self,
+        project_id: ProjectId,
+        branch_a: BranchId,
+        branch_b: BranchId,
+        context_lines: u32,
+    ) -> Result<Vec<RemoteBranchFile>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::diff_branches(project_repository, branch_a, branch_b, context_lines)
+        })
+    }
+
+    pub async fn diff_against_remote(
+        &self,
+        project_id: ProjectId,
+        remote: &str,
+        branch: &str,
+        branch_id: Option<BranchId>,
+        context_lines: u32,
+        two_dot: bool,
+    ) -> Result<Vec<RemoteBranchFile>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::diff_against_remote(
+                project_repository,
+                remote,
+                branch,
+                branch_id,
+                context_lines,
+                two_dot,
+            )
+        })
+    }
+
+    pub fn workspace_change_ids(&self, project_id: ProjectId) -> Result<WorkspaceChangeIds> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::workspace_change_ids(&project_repository)
+    }
+
+    pub fn branch_topological_order(&self, project_id: ProjectId) -> Result<Vec<BranchId>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branch_topological_order(&project_repository)
     }
 
-    pub async fn list_remote_branches(
-        &self,
-        project_id: ProjectId,
-    ) -> Result<Vec<super::RemoteBranch>> {
-        self.inner(project_id)
-            .await
-            .list_remote_branches(project_id)
+    pub fn active_branches(&self, project_id: ProjectId) -> Result<ActiveBranches> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::active_branches(&project_repository)
     }
 
-    pub async fn get_remote_branch_data(
+    pub fn stack_pr_descriptions(
         &self,
         project_id: ProjectId,
-        refname: &git::Refname,
-    ) -> Result<super::RemoteBranchData> {
-        self.inner(project_id)
-            .await
-            .get_remote_branch_data(project_id, refname)
+        branch_id: BranchId,
+    ) -> Result<Vec<PrDescription>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::stack_pr_descriptions(&project_repository, branch_id)
     }
 
-    pub async fn squash(
-        &self,
-        project_id: ProjectId,
-        branch_id: BranchId,
-        commit_oid: git2::Oid,
-    ) -> Result<()> {
-        self.inner(project_id)
-            .await
-            .squash(project_id, branch_id, commit_oid)
-            .await
+    pub fn pr_base_branch(&self, project_id: ProjectId, branch_id: BranchId) -> Result<PrBase> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::pr_base_branch(&project_repository, branch_id)
     }
 
-    pub async fn update_commit_message(
+    pub fn branch_exclusive_commits(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
-        commit_oid: git2::Oid,
-        message: &str,
-    ) -> Result<()> {
-        self.inner(project_id)
-            .await
-            .update_commit_message(project_id, branch_id, commit_oid, message)
-            .await
+    ) -> Result<Vec<BranchSegment>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branch_exclusive_commits(&project_repository, branch_id)
     }
 
-    pub async fn fetch_from_remotes(
+    pub fn branches_merge_base(
         &self,
         project_id: ProjectId,
-        askpass: Option<String>,
-    ) -> Result<BaseBranch> {
-        self.inner(project_id)
-            .await
-            .fetch_from_remotes(project_id, askpass)
-            .await
+        a: BranchId,
+        b: BranchId,
+    ) -> Result<git2::Oid> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branches_merge_base(&project_repository, a, b)
     }
 
-    pub async fn move_commit(
+    pub async fn safe_amend_targets(
         &self,
         project_id: ProjectId,
-        target_branch_id: BranchId,
-        commit_oid: git2::Oid,
-    ) -> Result<()> {
-        self.inner(project_id)
-            .await
-            .move_commit(project_id, target_branch_id, commit_oid)
-            .await
-    }
-}
+        file_path: &Path,
+        hunk: &Hunk,
+    ) -> Result<AmendTargets> {
+        let _permit = self.semaphore.acquire().await;
 
-#[derive(Clone)]
-struct ControllerInner {
-    semaphore: Arc<Semaphore>,
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::safe_amend_targets(project_repository, file_path, hunk)
+        })
+    }
 
-    projects: projects::Controller,
-    users: users::Controller,
-    helper: git::credentials::Helper,
-}
+    pub async fn hunk_counts_by_branch(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<Vec<BranchHunkCounts>> {
+        let _permit = self.semaphore.acquire().await;
 
-impl ControllerInner {
-    pub fn new(
-        projects: &projects::Controller,
-        users: &users::Controller,
-        helper: &git::credentials::Helper,
-    ) -> Self {
-        Self {
-            semaphore: Arc::new(Semaphore::new(1)),
-            projects: projects.clone(),
-            users: users.clone(),
-            helper: helper.clone(),
-        }
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::hunk_counts_by_branch(project_repository)
+        })
     }
 
-    pub async fn create_commit(
+    pub async fn hunk_locks(
         &self,
         project_id: ProjectId,
-        branch_id: BranchId,
-        message: &str,
-        ownership: Option<&BranchOwnershipClaims>,
-        run_hooks: bool,
-    ) -> Result<git2::Oid> {
+        allow_stale: bool,
+        context_lines: Option<u32>,
+        allow_partial: bool,
+    ) -> Result<HunkLocks> {
         let _permit = self.semaphore.acquire().await;
 
-        self.with_verify_branch(project_id, |project_repository, user| {
-            let snapshot_tree = project_repository.project().prepare_snapshot();
-            let result = super::commit(
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::hunk_locks(
                 project_repository,
-                branch_id,
-                message,
-                ownership,
-                user,
-                run_hooks,
+                allow_stale,
+                context_lines,
+                allow_partial,
             )
-            .map_err(Into::into);
-            let _ = snapshot_tree.and_then(|snapshot_tree| {
-                project_repository.project().snapshot_commit_creation(
-                    snapshot_tree,
-                    result.as_ref().err(),
-                    message.to_owned(),
-                    None,
-                )
-            });
-            result
         })
     }
 
-    pub fn can_apply_remote_branch(
+    pub fn commit_dependency_order(
         &self,
         project_id: ProjectId,
-        branch_name: &git::RemoteRefname,
-    ) -> Result<bool> {
+        branch_id: BranchId,
+    ) -> Result<Vec<git2::Oid>> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
-        super::is_remote_branch_mergeable(&project_repository, branch_name).map_err(Into::into)
+        Ok(super::commit_dependencies(&project_repository, branch_id)?.dependency_order()?)
     }
 
-    pub fn can_apply_virtual_branch(
-        &self,
-        project_id: ProjectId,
-        branch_id: BranchId,
-    ) -> Result<bool> {
-        let project = self.projects.get(project_id)?;
-        let project_repository = project_repository::Repository::open(&project)?;
-        super::is_virtual_branch_mergeable(&project_repository, branch_id).map_err(Into::into)
+    pub async fn hunk_apply_order(&self, project_id: ProjectId) -> Result<Vec<HunkGroup>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::hunk_apply_order(project_repository)
+        })
     }
 
-    pub async fn list_virtual_branches(
+    pub async fn cross_branch_locked_hunks(
         &self,
         project_id: ProjectId,
-    ) -> Result<(Vec<super::VirtualBranch>, Vec<git::diff::FileDiff>)> {
+    ) -> Result<Vec<CrossBranchLockedFile>> {
         let _permit = self.semaphore.acquire().await;
 
         self.with_verify_branch(project_id, |project_repository, _| {
-            super::list_virtual_branches(project_repository).map_err(Into::into)
+            super::cross_branch_locked_hunks(project_repository)
         })
     }
 
-    pub async fn create_virtual_branch(
+    pub async fn lock_impact_of_applying(
         &self,
         project_id: ProjectId,
-        create: &super::branch::BranchCreateRequest,
-    ) -> Result<BranchId> {
+        branch_id: BranchId,
+    ) -> Result<Vec<LockImpact>> {
         let _permit = self.semaphore.acquire().await;
 
         self.with_verify_branch(project_id, |project_repository, _| {
-            let branch_id = super::create_virtual_branch(project_repository, create)?.id;
-            Ok(branch_id)
+            super::lock_impact_of_applying(project_repository, branch_id)
         })
     }
 
-    pub async fn create_virtual_branch_from_branch(
+    pub async fn unowned_files(&self, project_id: ProjectId) -> Result<Vec<UnownedFile>> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::unowned_files(project_repository)
+        })
+    }
+
+    pub async fn redundant_worktree_changes(
         &self,
         project_id: ProjectId,
-        branch: &git::Refname,
-    ) -> Result<BranchId> {
+    ) -> Result<Vec<RedundantHunk>> {
         let _permit = self.semaphore.acquire().await;
 
-        self.with_verify_branch(project_id, |project_repository, user| {
-            super::create_virtual_branch_from_branch(project_repository, branch, user)
-                .map_err(Into::into)
+        self.with_verify_branch(project_id, |project_repository, _| {
+            super::redundant_worktree_changes(project_repository)
         })
     }
 
-    pub fn get_base_branch_data(&self, project_id: ProjectId) -> Result<BaseBranch> {
+    pub fn base_to_target_diff(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        context_lines: u32,
+    ) -> Result<SquashedDiff> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
-        super::get_base_branch_data(&project_repository)
+        super::base_to_target_diff(&project_repository, branch_id, context_lines)
     }
 
-    pub fn list_remote_commit_files(
+    pub fn branch_squashed_diff(
         &self,
         project_id: ProjectId,
-        commit_oid: git2::Oid,
-    ) -> Result<Vec<RemoteBranchFile>> {
+        branch_id: BranchId,
+        context_lines: u32,
+    ) -> Result<SquashedDiff> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::branch_squashed_diff(&project_repository, branch_id, context_lines)
+    }
+
+    pub fn commit_similarity(
+        &self,
+        project_id: ProjectId,
+        a: git2::Oid,
+        b: git2::Oid,
+    ) -> Result<f64> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::commit_similarity(project_repository.repo(), a, b)
+    }
+
+    pub fn hunks_identical(
+        &self,
+        project_id: ProjectId,
+        a: &HunkRef,
+        b: &HunkRef,
+    ) -> Result<HunkComparison> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::hunks_identical(&project_repository, a, b)
+    }
+
+    pub fn ancestry_path(
+        &self,
+        project_id: ProjectId,
+        from: git2::Oid,
+        to: git2::Oid,
+        first_parent: bool,
+    ) -> Result<Option<Vec<git2::Oid>>> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
-        super::list_remote_commit_files(project_repository.repo(), commit_oid).map_err(Into::into)
+        project_repository.ancestry_path(from, to, first_parent)
     }
 
     pub fn set_base_branch(
@@ -557,6 +1641,7 @@ impl ControllerInner {
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
+        conflict_style: Option<ConflictStyle>,
     ) -> Result<()> {
         let _permit = self.semaphore.acquire().await;
 
@@ -564,7 +1649,7 @@ impl ControllerInner {
             let _ = project_repository
                 .project()
                 .create_snapshot(SnapshotDetails::new(OperationKind::MergeUpstream));
-            super::integrate_upstream_commits(project_repository, branch_id, user)
+            super::integrate_upstream_commits(project_repository, branch_id, user, conflict_style)
                 .map_err(Into::into)
         })
     }
@@ -608,6 +1693,21 @@ impl ControllerInner {
         })
     }
 
+    pub async fn set_branch_order(
+        &self,
+        project_id: ProjectId,
+        branch_ids: &[BranchId],
+    ) -> Result<()> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            let _ = project_repository
+                .project()
+                .create_snapshot(SnapshotDetails::new(OperationKind::ReorderBranches));
+            super::set_branch_order(project_repository, branch_ids)
+        })
+    }
+
     pub async fn delete_virtual_branch(
         &self,
         project_id: ProjectId,
@@ -624,13 +1724,14 @@ impl ControllerInner {
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
+        conflict_style: Option<ConflictStyle>,
     ) -> Result<()> {
         let _permit = self.semaphore.acquire().await;
 
         self.with_verify_branch(project_id, |project_repository, user| {
             let snapshot_tree = project_repository.project().prepare_snapshot();
-            let result =
-                super::apply_branch(project_repository, branch_id, user).map_err(Into::into);
+            let result = super::apply_branch(project_repository, branch_id, user, conflict_style)
+                .map_err(Into::into);
 
             let _ = snapshot_tree.and_then(|snapshot_tree| {
                 project_repository
@@ -684,6 +1785,35 @@ impl ControllerInner {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub async fn split_hunk(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        hunk: &HunkIdentity,
+        old_lines: &[u32],
+        new_lines: &[u32],
+        first_commit: git2::Oid,
+        second_commit: git2::Oid,
+    ) -> Result<SplitHunks> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            let _ = project_repository
+                .project()
+                .create_snapshot(SnapshotDetails::new(OperationKind::AmendCommit));
+            super::split_hunk(
+                project_repository,
+                branch_id,
+                hunk,
+                old_lines,
+                new_lines,
+                first_commit,
+                second_commit,
+            )
+        })
+    }
+
     pub async fn move_commit_file(
         &self,
         project_id: ProjectId,
@@ -810,7 +1940,7 @@ impl ControllerInner {
         branch_id: BranchId,
         with_force: bool,
         askpass: Option<Option<BranchId>>,
-    ) -> Result<()> {
+    ) -> Result<PushResult> {
         let _permit = self.semaphore.acquire().await;
         let helper = self.helper.clone();
         self.with_verify_branch_async(project_id, move |project_repository, _| {
@@ -819,11 +1949,28 @@ impl ControllerInner {
         .await?
     }
 
+    pub async fn set_branch_push_remote(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        remote: Option<&str>,
+    ) -> Result<()> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            let _ = project_repository
+                .project()
+                .create_snapshot(SnapshotDetails::new(OperationKind::UpdateBranchRemoteName));
+            super::set_push_remote(project_repository, branch_id, remote)
+        })
+    }
+
     pub async fn cherry_pick(
         &self,
         project_id: ProjectId,
         branch_id: BranchId,
         commit_oid: git2::Oid,
+        conflict_style: Option<ConflictStyle>,
     ) -> Result<Option<git2::Oid>> {
         let _permit = self.semaphore.acquire().await;
 
@@ -831,7 +1978,8 @@ impl ControllerInner {
             let _ = project_repository
                 .project()
                 .create_snapshot(SnapshotDetails::new(OperationKind::CherryPick));
-            super::cherry_pick(project_repository, branch_id, commit_oid).map_err(Into::into)
+            super::cherry_pick(project_repository, branch_id, commit_oid, conflict_style)
+                .map_err(Into::into)
         })
     }
 
@@ -851,6 +1999,16 @@ impl ControllerInner {
         super::get_branch_data(&project_repository, refname)
     }
 
+    pub fn can_squash_branch(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<Vec<SquashBlocker>> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::can_squash_branch(&project_repository, branch_id)
+    }
+
     pub async fn squash(
         &self,
         project_id: ProjectId,
@@ -867,6 +2025,29 @@ impl ControllerInner {
         })
     }
 
+    pub fn preview_squash(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        commit_oid: git2::Oid,
+    ) -> Result<SquashPreview> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::preview_squash(&project_repository, branch_id, commit_oid)
+    }
+
+    pub fn preview_reorder(
+        &self,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<ReorderPreview> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        super::preview_reorder(&project_repository, branch_id, from_index, to_index)
+    }
+
     pub async fn update_commit_message(
         &self,
         project_id: ProjectId,
@@ -888,15 +2069,30 @@ impl ControllerInner {
         &self,
         project_id: ProjectId,
         askpass: Option<String>,
+        fetch_all: bool,
     ) -> Result<BaseBranch> {
         let project = self.projects.get(project_id)?;
         let mut project_repository = project_repository::Repository::open(&project)?;
 
-        let remotes = project_repository.remotes()?;
-        let fetch_results: Vec<Result<(), _>> = remotes
-            .iter()
-            .map(|remote| project_repository.fetch(remote, &self.helper, askpass.clone()))
-            .collect();
+        let fetch_results: Vec<Result<(), _>> = if fetch_all {
+            project_repository
+                .remotes()?
+                .iter()
+                .map(|remote| project_repository.fetch(remote, &self.helper, askpass.clone()))
+                .collect()
+        } else {
+            super::minimal_fetch_refspecs(&project_repository)?
+                .iter()
+                .map(|fetch| {
+                    project_repository.fetch_refspecs(
+                        &fetch.remote,
+                        &fetch.refspecs,
+                        &self.helper,
+                        askpass.clone(),
+                    )
+                })
+                .collect()
+        };
 
         let project_data_last_fetched = if fetch_results.iter().any(Result::is_err) {
             projects::FetchResult::Error {
@@ -918,8 +2114,13 @@ impl ControllerInner {
 
         let default_target = default_target(&project_repository.project().gb_dir())?;
 
-        // if we have a push remote, let's fetch from this too
-        if let Some(push_remote) = &default_target.push_remote_name {
+        // if we have a push remote, let's fetch from this too,
+        // otherwise only the upstreams on it were fetched above
+        if let Some(push_remote) = default_target
+            .push_remote_name
+            .as_ref()
+            .filter(|_| fetch_all)
+        {
             if let Err(err) = project_repository.fetch(push_remote, &self.helper, askpass.clone()) {
                 tracing::warn!(?err, "fetch from push-remote failed");
             }
@@ -959,6 +2160,30 @@ impl ControllerInner {
                 .map_err(Into::into)
         })
     }
+
+    pub async fn move_commit_between_branches(
+        &self,
+        project_id: ProjectId,
+        source_branch_id: BranchId,
+        commit_oid: git2::Oid,
+        target_branch_id: BranchId,
+        position: usize,
+    ) -> Result<()> {
+        let _permit = self.semaphore.acquire().await;
+
+        self.with_verify_branch(project_id, |project_repository, _| {
+            let _ = project_repository
+                .project()
+                .create_snapshot(SnapshotDetails::new(OperationKind::MoveCommit));
+            super::move_commit_between_branches(
+                project_repository,
+                source_branch_id,
+                commit_oid,
+                target_branch_id,
+                position,
+            )
+        })
+    }
 }
 
 impl ControllerInner {
@@ -969,6 +2194,7 @@ impl ControllerInner {
     ) -> Result<T> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
+        project_repository.assure_worktree()?;
         let user = self.users.get_user()?;
         super::integration::verify_branch(&project_repository)?;
         action(&project_repository, user.as_ref())
@@ -983,6 +2209,7 @@ impl ControllerInner {
     ) -> Result<JoinHandle<Result<T>>> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
+        project_repository.assure_worktree()?;
         let user = self.users.get_user()?;
         super::integration::verify_branch(&project_repository)?;
         Ok(tokio::task::spawn_blocking(move || {
@@ -13,6 +13,8 @@ pub enum Marker {
     ProjectConflict,
     /// An indicator that a branch conflicted during applying to the workspace.
     BranchConflict,
+    /// An indicator that two histories have no common ancestor.
+    UnrelatedHistories,
 }
 
 impl std::fmt::Display for Marker {
@@ -21,6 +23,7 @@ impl std::fmt::Display for Marker {
             Marker::VerificationFailure => f.write_str("<verification-failed>"),
             Marker::ProjectConflict => f.write_str("<project-conflict>"),
             Marker::BranchConflict => f.write_str("<branch-conflict>"),
+            Marker::UnrelatedHistories => f.write_str("<unrelated-histories>"),
         }
     }
 }
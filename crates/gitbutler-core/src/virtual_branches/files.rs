@@ -1,22 +1,61 @@
-use std::path;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path,
+};
 
 use anyhow::{anyhow, Context, Result};
-use serde::Serialize;
+use bstr::{BString, ByteSlice};
+use serde::{Deserialize, Serialize};
 
-use crate::git::diff;
+use super::{BranchId, VirtualBranchHunk};
+use crate::{
+    git::{self, diff, RepositoryExt},
+    project_repository::{self, conflicts, LogUntil},
+};
 
 #[derive(Debug, PartialEq, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoteBranchFile {
     pub path: path::PathBuf,
+    /// The path the file had before, if it was renamed.
+    pub old_path: Option<path::PathBuf>,
     pub hunks: Vec<diff::GitHunk>,
     pub binary: bool,
+    /// The modes of the file before and after, if they differ.
+    pub mode_change: Option<diff::ModeChange>,
+    /// Who last touched the lines changed by each of the `hunks`, if it was requested.
+    pub hunk_attribution: Option<Vec<Option<HunkAttribution>>>,
 }
 
+/// The commit that last touched the lines a hunk changes, before the commit of the hunk.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkAttribution {
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    pub author_name: String,
+    pub author_email: String,
+    /// `true` if the lines were last touched by the author of the commit of the hunk, so the
+    /// change is a revision of their own work.
+    pub by_commit_author: bool,
+}
+
+/// List the files changed by `commit_id` compared to its parent.
+///
+/// If `rename_threshold` is set, renamed files are detected with it, see
+/// [`diff::trees_with_renames()`], so that a file which was renamed and edited is listed once,
+/// with the changes to its content.
+///
+/// If `with_attribution` is set, the parent is blamed for the lines each hunk changes, see
+/// [`RemoteBranchFile::hunk_attribution`]. This is expensive, and hunks which only add lines or
+/// can't be blamed aren't attributed.
 pub fn list_remote_commit_files(
-    repository: &git2::Repository,
+    project_repository: &project_repository::Repository,
     commit_id: git2::Oid,
+    rename_threshold: Option<u16>,
+    with_attribution: bool,
 ) -> Result<Vec<RemoteBranchFile>> {
+    let repository = project_repository.repo();
     let commit = repository
         .find_commit(commit_id)
         .map_err(|err| match err.code() {
@@ -32,17 +71,1245 @@ pub fn list_remote_commit_files(
     let parent = commit.parent(0).context("failed to get parent commit")?;
     let commit_tree = commit.tree().context("failed to get commit tree")?;
     let parent_tree = parent.tree().context("failed to get parent tree")?;
-    let diff_files = diff::trees(repository, &parent_tree, &commit_tree)?;
+    let diff_files = match rename_threshold {
+        Some(threshold) => {
+            diff::trees_with_renames(repository, &parent_tree, &commit_tree, threshold)?
+        }
+        None => diff::trees(repository, &parent_tree, &commit_tree)?,
+    };
+
+    diff_files
+        .into_iter()
+        .map(|(path, file)| {
+            let binary = file.hunks.iter().any(|h| h.binary);
+            let old_path = file.old_path.filter(|old_path| *old_path != path);
+            let hunk_attribution = with_attribution
+                .then(|| {
+                    let mailmap = project_repository.mailmap()?;
+                    file.hunks
+                        .iter()
+                        .map(|hunk| {
+                            attribute_hunk(
+                                repository,
+                                mailmap,
+                                &commit,
+                                old_path.as_deref().unwrap_or(&path),
+                                hunk,
+                            )
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?;
+            Ok(RemoteBranchFile {
+                old_path,
+                path,
+                hunks: file.hunks,
+                binary,
+                mode_change: file.mode_change,
+                hunk_attribution,
+            })
+        })
+        .collect()
+}
+
+/// Blame the lines `hunk` of `commit` changes in its parent, where the file was at `path`, and
+/// return the most recent commit that touched them, with authors resolved through `mailmap`.
+fn attribute_hunk(
+    repository: &git2::Repository,
+    mailmap: &git::Mailmap,
+    commit: &git2::Commit,
+    path: &path::Path,
+    hunk: &diff::GitHunk,
+) -> Result<Option<HunkAttribution>> {
+    if hunk.binary || hunk.old_lines == 0 {
+        return Ok(None);
+    }
+    let parent = commit.parent(0).context("failed to get parent commit")?;
+    let mut opts = git2::BlameOptions::new();
+    opts.newest_commit(parent.id())
+        .min_line(hunk.old_start as usize)
+        .max_line((hunk.old_start + hunk.old_lines - 1) as usize);
+    let blame = match repository.blame_file(path, Some(&mut opts)) {
+        Ok(blame) => blame,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err).context("failed to blame hunk"),
+    };
+
+    let mut latest: Option<git2::Commit> = None;
+    for blame_hunk in blame.iter() {
+        let candidate = repository.find_commit(blame_hunk.final_commit_id())?;
+        if latest
+            .as_ref()
+            .map_or(true, |latest| candidate.time() > latest.time())
+        {
+            latest = Some(candidate);
+        }
+    }
+    Ok(latest.map(|latest| {
+        let (author_name, author_email) = mailmap.resolve(&latest.author());
+        let (_, commit_author_email) = mailmap.resolve(&commit.author());
+        HunkAttribution {
+            commit_id: latest.id(),
+            author_name,
+            by_commit_author: author_email == commit_author_email,
+            author_email,
+        }
+    }))
+}
+
+/// Return how large the binary delta for `path` in `commit_id` is compared to its first parent,
+/// see [`diff::binary_delta_size()`], or `None` if the file is too large to compute it.
+pub fn commit_binary_delta_size(
+    repository: &git2::Repository,
+    commit_id: git2::Oid,
+    path: &path::Path,
+) -> Result<Option<u64>> {
+    let commit = repository
+        .find_commit(commit_id)
+        .with_context(|| format!("commit {commit_id} not found"))?;
+    let old_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().context("failed to get parent tree")?),
+        Err(_) => None,
+    };
+    let new_tree = commit.tree().context("failed to get commit tree")?;
+    let blob_at = |tree: &git2::Tree| tree.get_path(path).ok().map(|entry| entry.id());
+
+    diff::binary_delta_size(
+        repository,
+        old_tree.as_ref().and_then(blob_at),
+        blob_at(&new_tree),
+        diff::BINARY_DELTA_SIZE_LIMIT_BYTES,
+    )
+}
+
+/// The uncommitted changes of the worktree, as returned by [`worktree_changes()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeChanges {
+    pub files: Vec<RemoteBranchFile>,
+    /// The files whose diff exceeded the requested size limit, sorted by path.
+    pub too_large: Vec<TooLargeFile>,
+    /// The submodules with uncommitted changes in their own worktree, sorted by path, if they
+    /// were requested.
+    pub submodules: Option<Vec<SubmoduleChanges>>,
+    /// The submodules which have another commit checked out than the workspace records, sorted
+    /// by path.
+    ///
+    /// They aren't part of `files` as they have no content to diff, so they also have no hunks
+    /// that could be partially locked or moved: a submodule change is always taken as a whole.
+    pub moved_submodules: Vec<MovedSubmodule>,
+}
+
+/// A submodule whose checked out commit differs from the one the workspace records for it, see
+/// [`WorktreeChanges::moved_submodules`].
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedSubmodule {
+    pub path: path::PathBuf,
+    /// The commit the workspace records, or `None` if the submodule was added.
+    #[serde(with = "crate::serde::oid_opt")]
+    pub old_id: Option<git2::Oid>,
+    /// The commit checked out in the submodule.
+    #[serde(with = "crate::serde::oid")]
+    pub new_id: git2::Oid,
+}
+
+/// A changed file whose diff is too large to be returned by [`worktree_changes()`], so only the
+/// amount of changed lines is known.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TooLargeFile {
+    pub path: path::PathBuf,
+    /// The path the file had before, if it was renamed.
+    pub old_path: Option<path::PathBuf>,
+    pub diff_size_bytes: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// A summary of the uncommitted changes within the worktree of a submodule.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SubmoduleChanges {
+    /// The worktree of the submodule has `changed_files` modified, added, deleted or untracked files.
+    #[serde(rename_all = "camelCase")]
+    Dirty {
+        path: path::PathBuf,
+        changed_files: usize,
+    },
+    /// The submodule couldn't be looked into, for the reason given in `note`.
+    #[serde(rename_all = "camelCase")]
+    Skipped { path: path::PathBuf, note: String },
+}
+
+impl SubmoduleChanges {
+    /// The path of the submodule relative to the root of the worktree.
+    pub fn path(&self) -> &path::Path {
+        match self {
+            SubmoduleChanges::Dirty { path, .. } | SubmoduleChanges::Skipped { path, .. } => path,
+        }
+    }
+}
+
+/// Return the uncommitted changes of the files matching any of `pathspecs`, see
+/// [`diff::workdir_with_pathspecs()`], sorted by path.
+///
+/// The pathspecs limit what is diffed in the first place, so this is cheaper than filtering all
+/// changes if the selection is small.
+///
+/// Submodules which have another commit checked out than the workspace records are listed in
+/// [`WorktreeChanges::moved_submodules`]. If `with_submodules` is set, the worktrees of matching
+/// submodules are inspected as well, and those with changes of their own are summarized in
+/// [`WorktreeChanges::submodules`].
+///
+/// Untracked files that are ignored by `.gitignore` and the other exclude files are omitted
+/// unless `include_ignored` is set, while tracked files are always listed.
+///
+/// If `rename_threshold` is set, a deleted file and an added one with at least this percentage of
+/// similar content are listed once under the new path, with [`RemoteBranchFile::old_path`] set
+/// and hunks for only what changed in between. Rewrites below the threshold stay a deletion and
+/// an addition.
+///
+/// Files whose worktree content matches the committed one once line endings are normalized as
+/// git would when adding them, i.e. due to the `text` or `eol` attributes or `core.autocrlf`,
+/// aren't changed and are omitted.
+///
+/// Files are diffed one at a time, and those with a diff larger than `diff_limit_bytes` are listed
+/// in [`WorktreeChanges::too_large`] instead, so the result stays small even if huge files like
+/// regenerated lockfiles changed.
+///
+/// It's a validation error if the repository has no worktree.
+pub fn worktree_changes(
+    project_repository: &project_repository::Repository,
+    pathspecs: &[String],
+    with_submodules: bool,
+    include_ignored: bool,
+    rename_threshold: Option<u16>,
+    diff_limit_bytes: Option<usize>,
+) -> Result<WorktreeChanges> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+
+    let repo = project_repository.repo();
+    let tree = repo
+        .find_commit(integration_commit_id)?
+        .tree()
+        .context("failed to find workspace tree")?;
+    let changes = diff::worktree_changes_stream(
+        repo,
+        &integration_commit_id,
+        pathspecs,
+        include_ignored,
+        rename_threshold,
+        diff_limit_bytes,
+    )?;
+    let (mut files, mut too_large) = (Vec::new(), Vec::new());
+    for change in changes {
+        let (path, change) = change?;
+        if is_line_ending_change_only(repo, &tree, &path)? {
+            continue;
+        }
+        match change {
+            diff::StreamedDiff::Full(file) => files.push(RemoteBranchFile {
+                // files too large to be diffed have no hunks, but are binary as well
+                binary: file.binary || file.hunks.iter().any(|hunk| hunk.binary),
+                old_path: file.old_path.filter(|old_path| *old_path != path),
+                path,
+                hunks: file.hunks,
+                mode_change: file.mode_change,
+                hunk_attribution: None,
+            }),
+            diff::StreamedDiff::TooLarge {
+                old_path,
+                diff_size_bytes,
+                lines_added,
+                lines_removed,
+            } => too_large.push(TooLargeFile {
+                old_path: old_path.filter(|old_path| *old_path != path),
+                path,
+                diff_size_bytes,
+                lines_added,
+                lines_removed,
+            }),
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    too_large.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let submodules = if with_submodules {
+        Some(dirty_submodules(project_repository.repo(), pathspecs)?)
+    } else {
+        None
+    };
+    let moved_submodules = moved_submodules(repo, &tree, pathspecs)?;
+    Ok(WorktreeChanges {
+        files,
+        too_large,
+        submodules,
+        moved_submodules,
+    })
+}
+
+/// The uncommitted changes of a single file, as returned by [`file_diff()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum WorktreeFileDiff {
+    /// The file has no uncommitted changes.
+    Unchanged,
+    /// The file is binary or too large to be diffed, so only its sizes are known.
+    #[serde(rename_all = "camelCase")]
+    Binary {
+        old_size_bytes: u64,
+        new_size_bytes: u64,
+    },
+    /// The file changed in `hunks`.
+    #[serde(rename_all = "camelCase")]
+    Patch {
+        hunks: Vec<diff::GitHunk>,
+        /// The [changed words](diff::GitHunk::word_diff()) of each of `hunks`, if requested.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        word_diffs: Option<Vec<Vec<diff::WordDiffLine>>>,
+    },
+}
+
+/// Return the uncommitted changes of the file at `path` with `context_lines` of unchanged lines
+/// around each hunk.
+///
+/// Only this file is diffed, so this is much cheaper than [`worktree_changes()`] for previewing a
+/// single file. With [`WordDiff::Enabled`](diff::WordDiff::Enabled), the changed words within
+/// the changed lines of each hunk are returned as well.
+pub fn file_diff(
+    project_repository: &project_repository::Repository,
+    path: &path::Path,
+    context_lines: u32,
+    word_diff: diff::WordDiff,
+) -> Result<WorktreeFileDiff> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+
+    let Some(file) = diff::workdir_file(
+        project_repository.repo(),
+        &integration_commit_id,
+        path,
+        context_lines,
+    )?
+    else {
+        return Ok(WorktreeFileDiff::Unchanged);
+    };
+    Ok(if file.binary || file.skipped {
+        WorktreeFileDiff::Binary {
+            old_size_bytes: file.old_size_bytes,
+            new_size_bytes: file.new_size_bytes,
+        }
+    } else {
+        let word_diffs = (word_diff == diff::WordDiff::Enabled)
+            .then(|| file.hunks.iter().map(diff::GitHunk::word_diff).collect());
+        WorktreeFileDiff::Patch {
+            hunks: file.hunks,
+            word_diffs,
+        }
+    })
+}
+
+/// Return `true` if the worktree file at `path` has the content of its blob in `tree` after its
+/// line endings are converted like git's clean filter does, so it isn't actually changed.
+fn is_line_ending_change_only(
+    repository: &git2::Repository,
+    tree: &git2::Tree,
+    path: &path::Path,
+) -> Result<bool> {
+    let (Some(workdir), Ok(entry)) = (repository.workdir(), tree.get_path(path)) else {
+        return Ok(false);
+    };
+    if entry.kind() != Some(git2::ObjectType::Blob) {
+        return Ok(false);
+    }
+    let Ok(content) = std::fs::read(workdir.join(path)) else {
+        return Ok(false);
+    };
+    if !content.contains_str("\r\n") || !normalizes_line_endings(repository, path, &content)? {
+        return Ok(false);
+    }
+    let blob = repository.find_blob(entry.id())?;
+    Ok(content.replace("\r\n", "\n") == blob.content())
+}
+
+/// Return `true` if git converts CRLF to LF when adding `content` of the file at `path`, which
+/// depends on its `text` and `eol` attributes and falls back to `core.autocrlf`. Files are
+/// considered binary if they contain a NUL byte, which is what git checks for as well.
+fn normalizes_line_endings(
+    repository: &git2::Repository,
+    path: &path::Path,
+    content: &[u8],
+) -> Result<bool> {
+    let attribute = |name: &str| {
+        repository
+            .get_attr(path, name, git2::AttrCheckFlags::FILE_THEN_INDEX)
+            .map(|value| value.map(ToOwned::to_owned))
+            .with_context(|| format!("failed to read attribute '{name}' of {path:?}"))
+    };
+    let is_binary = content.contains(&0);
+    let text = attribute("text")?;
+    Ok(match git2::AttrValue::from_string(text.as_deref()) {
+        git2::AttrValue::True => true,
+        git2::AttrValue::False => false,
+        git2::AttrValue::String("auto") => !is_binary,
+        _ if matches!(
+            git2::AttrValue::from_string(attribute("eol")?.as_deref()),
+            git2::AttrValue::String(_)
+        ) =>
+        {
+            true
+        }
+        _ => {
+            let autocrlf = repository.config()?.get_string("core.autocrlf").ok();
+            !is_binary && matches!(autocrlf.as_deref(), Some("true" | "input"))
+        }
+    })
+}
+
+/// Return a summary of each submodule matching `pathspecs` that has uncommitted changes in its
+/// worktree. Submodules that aren't initialized are listed as skipped.
+fn dirty_submodules(
+    repository: &git2::Repository,
+    pathspecs: &[String],
+) -> Result<Vec<SubmoduleChanges>> {
+    let mut submodules = Vec::new();
+    for submodule in submodules_matching(repository, pathspecs)? {
+        let path = submodule.path().to_owned();
+        let Ok(submodule_repository) = submodule.open() else {
+            submodules.push(SubmoduleChanges::Skipped {
+                path,
+                note: "the submodule isn't initialized".to_owned(),
+            });
+            continue;
+        };
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .exclude_submodules(true);
+        let changed_files = submodule_repository
+            .statuses(Some(&mut status_opts))
+            .with_context(|| format!("failed to get status of submodule {}", path.display()))?
+            .iter()
+            .filter(|entry| !entry.status().is_ignored())
+            .count();
+        if changed_files > 0 {
+            submodules.push(SubmoduleChanges::Dirty {
+                path,
+                changed_files,
+            });
+        }
+    }
+    submodules.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(submodules)
+}
+
+/// Return the submodules of `tree` whose checked out commit differs from the one `tree` records
+/// for them, sorted by path. Submodules which aren't initialized are considered unchanged.
+fn moved_submodules(
+    repository: &git2::Repository,
+    tree: &git2::Tree,
+    pathspecs: &[String],
+) -> Result<Vec<MovedSubmodule>> {
+    let mut moved = Vec::new();
+    for submodule in submodules_matching(repository, pathspecs)? {
+        let Some(new_id) = submodule.workdir_id() else {
+            continue;
+        };
+        let path = submodule.path().to_owned();
+        let old_id = tree
+            .get_path(&path)
+            .ok()
+            .filter(|entry| entry.filemode() == i32::from(git2::FileMode::Commit))
+            .map(|entry| entry.id());
+        if old_id != Some(new_id) {
+            moved.push(MovedSubmodule {
+                path,
+                old_id,
+                new_id,
+            });
+        }
+    }
+    moved.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(moved)
+}
+
+/// Return the submodules of `repository` whose path matches any of `pathspecs`, or all of them if
+/// there are none.
+fn submodules_matching<'repo>(
+    repository: &'repo git2::Repository,
+    pathspecs: &[String],
+) -> Result<Vec<git2::Submodule<'repo>>> {
+    let pathspec = (!pathspecs.is_empty())
+        .then(|| git2::Pathspec::new(pathspecs))
+        .transpose()
+        .context("invalid pathspecs")?;
+    Ok(repository
+        .submodules()
+        .context("failed to list submodules")?
+        .into_iter()
+        .filter(|submodule| {
+            pathspec.as_ref().map_or(true, |pathspec| {
+                pathspec.matches_path(submodule.path(), git2::PathspecFlags::DEFAULT)
+            })
+        })
+        .collect())
+}
+
+/// Return the uncommitted changes of the worktree relative to the workspace, like
+/// [`worktree_changes()`], in the format of `git status --porcelain=v2`, so tools that parse it
+/// work with GitButler projects as well.
+///
+/// GitButler doesn't stage changes, so all of them are unstaged, with the index matching the
+/// workspace commit, and files that aren't part of it are untracked. Files that were deleted and
+/// added with similar content are reported as renames, and conflicted files as unmerged with the
+/// stages of the index, if it has them. Paths are quoted like git does, and no headers are written.
+pub fn porcelain_status(project_repository: &project_repository::Repository) -> Result<String> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+    let repo = project_repository.repo();
+    let tree = repo
+        .find_commit(integration_commit_id)
+        .and_then(|commit| commit.tree())
+        .context("failed to get workspace tree")?;
+
+    let mut diff_opts = git2::DiffOptions::new();
+    diff_opts
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .ignore_submodules(true);
+    let mut diff = repo
+        .diff_tree_to_workdir(Some(&tree), Some(&mut diff_opts))
+        .context("failed to diff workdir")?;
+    diff.find_similar(Some(
+        git2::DiffFindOptions::new()
+            .renames(true)
+            .for_untracked(true)
+            .rename_threshold(diff::DEFAULT_RENAME_THRESHOLD),
+    ))
+    .context("failed to detect renames")?;
+
+    let conflicted: BTreeSet<BString> = conflicts::conflicting_files(project_repository)?
+        .into_iter()
+        .map(BString::from)
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut untracked = Vec::new();
+    for delta in diff.deltas() {
+        let path = BString::from(
+            delta
+                .new_file()
+                .path_bytes()
+                .or_else(|| delta.old_file().path_bytes())
+                .context("change without a path")?,
+        );
+        if conflicted.contains(&path) {
+            continue;
+        }
+        let head_mode = u32::from(delta.old_file().mode());
+        let worktree_mode = u32::from(delta.new_file().mode());
+        let head_id = delta.old_file().id();
+        let ordinary = |xy: &str| {
+            format!(
+                "1 {xy} N... {head_mode:06o} {head_mode:06o} {worktree_mode:06o} {head_id} {head_id} {}",
+                quote_path(&path)
+            )
+        };
+        let line = match delta.status() {
+            git2::Delta::Untracked | git2::Delta::Added => {
+                untracked.push((path.clone(), format!("? {}", quote_path(&path))));
+                continue;
+            }
+            git2::Delta::Modified => ordinary(".M"),
+            git2::Delta::Deleted => ordinary(".D"),
+            git2::Delta::Typechange => ordinary(".T"),
+            git2::Delta::Renamed => {
+                let old_path = delta.old_file().path_bytes().unwrap_or_default();
+                let score = rename_score(repo, head_id, &path)?;
+                format!(
+                    "2 .R N... {head_mode:06o} {head_mode:06o} {worktree_mode:06o} {head_id} {head_id} R{score} {}\t{}",
+                    quote_path(&path),
+                    quote_path(old_path)
+                )
+            }
+            _ => continue,
+        };
+        changed.push((path, line));
+    }
+
+    let index = repo.index().context("failed to read index")?;
+    let mut stages = HashMap::new();
+    if index.has_conflicts() {
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let path = [&conflict.ancestor, &conflict.our, &conflict.their]
+                .into_iter()
+                .flatten()
+                .map(|entry| BString::from(entry.path.clone()))
+                .next();
+            if let Some(path) = path {
+                stages.insert(path, conflict);
+            }
+        }
+    }
+    for path in conflicted {
+        let (xy, [base, ours, theirs]) = match stages.get(&path) {
+            Some(conflict) => {
+                let xy = match (&conflict.ancestor, &conflict.our, &conflict.their) {
+                    (None, Some(_), Some(_)) => "AA",
+                    (Some(_), None, Some(_)) => "DU",
+                    (Some(_), Some(_), None) => "UD",
+                    (None, Some(_), None) => "AU",
+                    (None, None, Some(_)) => "UA",
+                    (Some(_), None, None) => "DD",
+                    _ => "UU",
+                };
+                let stage = |entry: &Option<git2::IndexEntry>| {
+                    entry
+                        .as_ref()
+                        .map_or((0, git2::Oid::zero()), |entry| (entry.mode, entry.id))
+                };
+                (
+                    xy,
+                    [
+                        stage(&conflict.ancestor),
+                        stage(&conflict.our),
+                        stage(&conflict.their),
+                    ],
+                )
+            }
+            None => ("UU", [(0, git2::Oid::zero()); 3]),
+        };
+        let worktree_mode = if project_repository
+            .path()
+            .join(path.to_path_lossy())
+            .exists()
+        {
+            [ours, theirs, base]
+                .into_iter()
+                .map(|(mode, _)| mode)
+                .find(|mode| *mode != 0)
+                .unwrap_or(0o100644)
+        } else {
+            0
+        };
+        let line = format!(
+            "u {xy} N... {:06o} {:06o} {:06o} {worktree_mode:06o} {} {} {} {}",
+            base.0,
+            ours.0,
+            theirs.0,
+            base.1,
+            ours.1,
+            theirs.1,
+            quote_path(&path)
+        );
+        changed.push((path, line));
+    }
+
+    changed.sort();
+    untracked.sort();
+    Ok(changed
+        .into_iter()
+        .chain(untracked)
+        .map(|(_, line)| line + "\n")
+        .collect())
+}
+
+/// Return how similar the worktree file at `path` is to the blob `old_id`, in percent of the lines
+/// of the longer of both which are unchanged.
+fn rename_score(repo: &git2::Repository, old_id: git2::Oid, path: &[u8]) -> Result<usize> {
+    let old = repo.find_blob(old_id).context("failed to find blob")?;
+    let workdir = repo.workdir().context("repository has no worktree")?;
+    let new = std::fs::read(workdir.join(path.to_path_lossy()))
+        .with_context(|| format!("failed to read {}", path.as_bstr()))?;
+    let patch = git2::Patch::from_blob_and_buffer(&old, None, &new, None, None)?;
+    let (_, _, deletions) = patch.line_stats()?;
+    let old_lines = old.content().lines().count();
+    let lines = old_lines.max(new.lines().count());
+    Ok(if lines == 0 {
+        100
+    } else {
+        (old_lines - deletions) * 100 / lines
+    })
+}
+
+/// Return `path` quoted like git does by default if it contains special characters, including
+/// non-ASCII bytes, or as is otherwise.
+fn quote_path(path: &[u8]) -> String {
+    let needs_quotes = path
+        .iter()
+        .any(|byte| !(0x20..0x7f).contains(byte) || matches!(byte, b'"' | b'\\'));
+    if !needs_quotes {
+        return path.to_str_lossy().into_owned();
+    }
+    let mut quoted = String::from("\"");
+    for &byte in path {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\x07' => quoted.push_str("\\a"),
+            b'\x08' => quoted.push_str("\\b"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\x0b' => quoted.push_str("\\v"),
+            b'\x0c' => quoted.push_str("\\f"),
+            b'\r' => quoted.push_str("\\r"),
+            0x20..=0x7e => quoted.push(char::from(byte)),
+            _ => quoted.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Why a file is considered binary by [`large_new_binaries()`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BinaryReason {
+    /// The content was classified as binary while diffing it.
+    Content,
+    /// The file is too large to be diffed, and is treated as binary without looking at it.
+    TooLargeToDiff,
+}
+
+/// A binary file in the worktree that isn't part of the workspace yet.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeBinary {
+    pub path: path::PathBuf,
+    pub size_bytes: u64,
+    pub reason: BinaryReason,
+}
+
+/// Return the binary files larger than `threshold_bytes` which are new to the workspace, i.e.
+/// untracked or added since the last commit, sorted by path.
+///
+/// This allows to suggest adding such files to `.gitignore`, or storing them elsewhere, before
+/// they are committed and bloat the history.
+pub fn large_new_binaries(
+    project_repository: &project_repository::Repository,
+    threshold_bytes: u64,
+) -> Result<Vec<LargeBinary>> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+    let repo = project_repository.repo();
+    let integration_tree = repo
+        .find_commit(integration_commit_id)
+        .and_then(|commit| commit.tree())
+        .context("failed to find tree of workspace")?;
+
+    let mut binaries: Vec<_> = diff::workdir(repo, &integration_commit_id)?
+        .into_iter()
+        .filter(|(path, file)| {
+            file.binary
+                && file.new_size_bytes > threshold_bytes
+                && integration_tree.get_path(path).is_err()
+        })
+        .map(|(path, file)| LargeBinary {
+            path,
+            size_bytes: file.new_size_bytes,
+            reason: if file.skipped {
+                BinaryReason::TooLargeToDiff
+            } else {
+                BinaryReason::Content
+            },
+        })
+        .collect();
+    binaries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(binaries)
+}
+
+/// Totals over all files of a diff.
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffStat {
+    /// Count a changed file with `hunks` towards the totals. Binary hunks don't add lines.
+    fn add_file(&mut self, hunks: &[diff::GitHunk]) {
+        self.files_changed += 1;
+        for line in hunks
+            .iter()
+            .filter(|hunk| !hunk.binary)
+            .flat_map(|hunk| hunk.diff_lines.lines())
+        {
+            match line.first() {
+                Some(b'+') => self.insertions += 1,
+                Some(b'-') => self.deletions += 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SquashedDiff {
+    pub files: Vec<RemoteBranchFile>,
+    pub stat: DiffStat,
+}
 
-    Ok(diff_files
+/// Return the changes of all commits of the virtual branch `branch_id` as if they were squashed
+/// into one, that is the diff between the merge-base of the branch with the default target and
+/// the head of the branch, with `context_lines` around each hunk.
+///
+/// Hunks separated by no more than the project's [coalesce distance][distance] are shown as one.
+/// Uncommitted changes are not included. Files are sorted by path.
+///
+/// [distance]: crate::projects::Project::hunk_coalesce_distance()
+pub fn branch_squashed_diff(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    context_lines: u32,
+) -> Result<SquashedDiff> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+
+    let repo = project_repository.repo();
+    let merge_base = repo
+        .merge_base(default_target.sha, branch.head)
+        .context("failed to find merge base")?;
+    let base_tree = repo
+        .find_commit(merge_base)
+        .and_then(|commit| commit.tree())
+        .context("failed to get base tree")?;
+    let head_tree = repo
+        .find_commit(branch.head)
+        .and_then(|commit| commit.tree())
+        .context("failed to get branch head tree")?;
+    squashed_diff(project_repository, &base_tree, &head_tree, context_lines)
+}
+
+/// Return the changes that were added to the target branch since the virtual branch `branch_id`
+/// was based on it, that is the diff between the merge-base of the branch with the remote target
+/// branch and the tip of the latter, with `context_lines` around each hunk.
+///
+/// These are the changes the branch will be rebased onto when updating the base branch. Hunks are
+/// coalesced like in [`branch_squashed_diff()`], and files are sorted by path.
+pub fn base_to_target_diff(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    context_lines: u32,
+) -> Result<SquashedDiff> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+
+    let repo = project_repository.repo();
+    let target_tip = repo
+        .find_branch_by_refname(&default_target.branch.clone().into())?
+        .ok_or(anyhow!("failed to get branch"))?
+        .get()
+        .peel_to_commit()?;
+    let merge_base = repo
+        .merge_base(target_tip.id(), branch.head)
+        .context("failed to find merge base")?;
+    let base_tree = repo
+        .find_commit(merge_base)
+        .and_then(|commit| commit.tree())
+        .context("failed to get base tree")?;
+    let target_tree = target_tip.tree().context("failed to get target tree")?;
+    squashed_diff(project_repository, &base_tree, &target_tree, context_lines)
+}
+
+fn squashed_diff(
+    project_repository: &project_repository::Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    context_lines: u32,
+) -> Result<SquashedDiff> {
+    let interhunk_lines = project_repository
+        .project()
+        .hunk_coalesce_distance(context_lines);
+    let diff_files = diff::trees_with_context_lines(
+        project_repository.repo(),
+        old_tree,
+        new_tree,
+        context_lines,
+        interhunk_lines,
+    )?;
+
+    let mut stat = DiffStat::default();
+    let mut files: Vec<_> = diff_files
         .into_iter()
         .map(|(path, file)| {
+            stat.add_file(&file.hunks);
             let binary = file.hunks.iter().any(|h| h.binary);
             RemoteBranchFile {
                 path,
+                old_path: None,
                 hunks: file.hunks,
                 binary,
+                mode_change: file.mode_change,
+                hunk_attribution: None,
             }
         })
-        .collect())
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(SquashedDiff { files, stat })
+}
+
+/// The changes within a directory and all of its subdirectories, see [`dirstat()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirStat {
+    /// The path of the directory relative to the root of the worktree, which is empty for the root
+    /// itself.
+    pub path: path::PathBuf,
+    /// The changes committed to applied virtual branches, counted once per branch.
+    pub committed: DiffStat,
+    /// The uncommitted changes of the worktree.
+    pub uncommitted: DiffStat,
+}
+
+/// Return the changed files and lines per directory, rolled up from the files within it, with the
+/// commits of all applied virtual branches and the uncommitted changes counted separately.
+///
+/// Only directories with changes are listed, sorted by path so parents come before their children.
+pub fn dirstat(project_repository: &project_repository::Repository) -> Result<Vec<DirStat>> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+
+    let mut dirs = BTreeMap::<path::PathBuf, DirStat>::new();
+    let mut add_file = |file_path: &path::Path, hunks: &[diff::GitHunk], committed: bool| {
+        for dir in file_path.ancestors().skip(1) {
+            let dirstat = dirs.entry(dir.to_owned()).or_insert_with(|| DirStat {
+                path: dir.to_owned(),
+                committed: DiffStat::default(),
+                uncommitted: DiffStat::default(),
+            });
+            if committed {
+                dirstat.committed.add_file(hunks);
+            } else {
+                dirstat.uncommitted.add_file(hunks);
+            }
+        }
+    };
+
+    for branch in vb_state
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| branch.applied)
+    {
+        let merge_base = repo
+            .merge_base(default_target.sha, branch.head)
+            .context("failed to find merge base")?;
+        let base_tree = repo
+            .find_commit(merge_base)
+            .and_then(|commit| commit.tree())
+            .context("failed to get base tree")?;
+        let head_tree = repo
+            .find_commit(branch.head)
+            .and_then(|commit| commit.tree())
+            .context("failed to get branch head tree")?;
+        for (file_path, file) in diff::trees(repo, &base_tree, &head_tree)? {
+            add_file(&file_path, &file.hunks, true);
+        }
+    }
+
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+    for (file_path, file) in diff::workdir(repo, &integration_commit_id)? {
+        add_file(&file_path, &file.hunks, false);
+    }
+
+    Ok(dirs.into_values().collect())
+}
+
+/// A commit of a virtual branch along with its changes to a particular path.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathCommit {
+    pub branch_id: BranchId,
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    pub hunks: Vec<diff::GitHunk>,
+}
+
+/// Return all commits of all virtual branches which change `path`, along with the hunks they
+/// change it with.
+///
+/// Branches are listed in their display order, and the commits of each branch from its head
+/// towards its base. Merge commits are compared to their first parent.
+pub fn commits_touching_path(
+    project_repository: &project_repository::Repository,
+    path: &path::Path,
+) -> Result<Vec<PathCommit>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let repo = project_repository.repo();
+    let mut commits = Vec::new();
+    for branch in branches {
+        for commit in project_repository
+            .log(branch.head, LogUntil::Commit(default_target.sha))
+            .context("failed to list branch commits")?
+        {
+            let commit_tree = commit.tree().context("failed to get commit tree")?;
+            let parent_tree = commit
+                .parent(0)
+                .and_then(|parent| parent.tree())
+                .context("failed to get parent tree")?;
+            if commit_tree.get_path(path).ok().map(|entry| entry.id())
+                == parent_tree.get_path(path).ok().map(|entry| entry.id())
+            {
+                continue;
+            }
+            let mut diff_files = diff::trees(repo, &parent_tree, &commit_tree)?;
+            if let Some(file) = diff_files.remove(path) {
+                commits.push(PathCommit {
+                    branch_id: branch.id,
+                    commit_id: commit.id(),
+                    hunks: file.hunks,
+                });
+            }
+        }
+    }
+    Ok(commits)
+}
+
+/// Score how similar the changes introduced by the commits `a` and `b` are, from `0.0` (nothing in
+/// common) to `1.0` (the same changes).
+///
+/// Each commit is diffed against its first parent, or against the empty tree if it has none, and
+/// reduced to the set of its added and removed lines, keyed by path and the kind of change.
+/// Context lines are ignored, which makes the score insensitive to the commit having been rebased
+/// onto a different base. The score is the size of the intersection of both sets divided by the
+/// size of their union, so it doesn't depend on the order of `a` and `b`.
+/// Two commits without any line changes are considered the same.
+pub fn commit_similarity(repository: &git2::Repository, a: git2::Oid, b: git2::Oid) -> Result<f64> {
+    let a = changed_lines(repository, a)?;
+    let b = changed_lines(repository, b)?;
+    let union = a.union(&b).count();
+    if union == 0 {
+        return Ok(1.0);
+    }
+    let intersection = a.intersection(&b).count();
+    Ok(intersection as f64 / union as f64)
+}
+
+/// Return all lines added or removed by `commit_id`, each prefixed with the file path and the
+/// `+` or `-` marker of the line. Binary files are represented by the id of their new blob.
+fn changed_lines(repository: &git2::Repository, commit_id: git2::Oid) -> Result<BTreeSet<BString>> {
+    let commit = repository
+        .find_commit(commit_id)
+        .map_err(|err| match err.code() {
+            git2::ErrorCode::NotFound => anyhow!("commit {commit_id} not found"),
+            _ => err.into(),
+        })?;
+    let commit_tree = commit.tree().context("failed to get commit tree")?;
+    let parent_tree = match commit.parents().next() {
+        Some(parent) => parent.tree().context("failed to get parent tree")?,
+        None => repository
+            .treebuilder(None)
+            .and_then(|builder| builder.write())
+            .and_then(|tree_id| repository.find_tree(tree_id))
+            .context("failed to create empty tree")?,
+    };
+    let diff_files = diff::trees(repository, &parent_tree, &commit_tree)?;
+
+    let mut lines = BTreeSet::new();
+    for (path, file) in diff_files {
+        let path = path.to_string_lossy();
+        for hunk in file.hunks {
+            if hunk.binary {
+                // binary hunks only carry the id of the new blob
+                let mut key = BString::from(path.as_bytes());
+                key.push(b'\0');
+                key.extend_from_slice(&hunk.diff_lines);
+                lines.insert(key);
+                continue;
+            }
+            for line in diff::changed_lines(&hunk.diff_lines) {
+                let mut key = BString::from(path.as_bytes());
+                key.push(b'\0');
+                key.extend_from_slice(line);
+                lines.insert(key);
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// An uncommitted hunk of the worktree.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkRef {
+    pub path: path::PathBuf,
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+}
+
+/// How the changes of two hunks compare, see [`hunks_identical()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkComparison {
+    /// Whether both hunks add and remove exactly the same lines in the same order.
+    pub identical: bool,
+    /// How similar the changes are, from `0.0` (nothing in common) to `1.0` (the same changes).
+    pub similarity: f64,
+}
+
+/// Compare the added and removed lines of the uncommitted hunks `a` and `b`, which may be in
+/// different files, to find changes that were made in more than one place.
+///
+/// Like the [hunk hash](super::branch::Hunk::hash_diff()), the diff header is ignored, and so are
+/// context lines, but the lines are compared in full so equal hashes can't be mistaken for equal
+/// changes. The similarity is computed like in [`commit_similarity()`], from the sets of changed
+/// lines, and is `1.0` for identical hunks.
+pub fn hunks_identical(
+    project_repository: &project_repository::Repository,
+    a: &HunkRef,
+    b: &HunkRef,
+) -> Result<HunkComparison> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id =
+        super::integration::get_workspace_head(&vb_state, project_repository)?;
+    let diff_files = diff::workdir(project_repository.repo(), &integration_commit_id)?;
+    let changed_lines_of = |hunk_ref: &HunkRef| -> Result<Vec<BString>> {
+        let hunk = diff_files
+            .get(&hunk_ref.path)
+            .and_then(|file| {
+                file.hunks.iter().find(|hunk| {
+                    VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines) == hunk_ref.hunk_id
+                })
+            })
+            .with_context(|| {
+                format!(
+                    "hunk {} of {} has no uncommitted changes",
+                    hunk_ref.hunk_id,
+                    hunk_ref.path.display()
+                )
+            })?;
+        if hunk.binary {
+            // binary hunks only carry the id of the new blob
+            return Ok(vec![hunk.diff_lines.clone()]);
+        }
+        Ok(hunk
+            .diff_lines
+            .lines()
+            .skip(1)
+            .filter(|line| matches!(line.first(), Some(b'+' | b'-')))
+            .map(BString::from)
+            .collect())
+    };
+    let a = changed_lines_of(a)?;
+    let b = changed_lines_of(b)?;
+    if a == b {
+        return Ok(HunkComparison {
+            identical: true,
+            similarity: 1.0,
+        });
+    }
+
+    let a: BTreeSet<_> = a.into_iter().collect();
+    let b: BTreeSet<_> = b.into_iter().collect();
+    let union = a.union(&b).count();
+    let similarity = if union == 0 {
+        1.0
+    } else {
+        a.intersection(&b).count() as f64 / union as f64
+    };
+    Ok(HunkComparison {
+        identical: false,
+        similarity,
+    })
+}
+
+/// The kind of object a tree entry points to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathKind {
+    Regular,
+    Executable,
+    Symlink,
+    Submodule,
+    Directory,
+}
+
+/// Information about a path as it's stored in a tree.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathMetadata {
+    /// The file mode of the tree entry.
+    pub mode: i32,
+    #[serde(with = "crate::serde::oid")]
+    pub id: git2::Oid,
+    /// The size of the blob in bytes, or `None` if the entry isn't a blob.
+    pub size: Option<usize>,
+    pub kind: PathKind,
+}
+
+/// Return the metadata of the tree entry at `path` in the tree of commit `at`, or in the tree of the
+/// worktree if `at` is `None`. Return `None` if there is no such entry.
+///
+/// The size is taken from the object header, so blobs aren't read to answer this.
+pub fn path_metadata(
+    repository: &git2::Repository,
+    path: &path::Path,
+    at: Option<git2::Oid>,
+) -> Result<Option<PathMetadata>> {
+    let tree = match at {
+        Some(commit_id) => repository
+            .find_commit(commit_id)
+            .map_err(|err| match err.code() {
+                git2::ErrorCode::NotFound => anyhow!("commit {commit_id} not found"),
+                _ => err.into(),
+            })?
+            .tree()
+            .context("failed to get commit tree")?,
+        None => repository
+            .get_wd_tree()
+            .context("failed to get worktree tree")?,
+    };
+    let entry = match tree.get_path(path) {
+        Ok(entry) => entry,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let kind = match entry.filemode() {
+        0o100755 => PathKind::Executable,
+        0o120000 => PathKind::Symlink,
+        0o160000 => PathKind::Submodule,
+        0o040000 => PathKind::Directory,
+        _ => PathKind::Regular,
+    };
+    let size = if entry.kind() == Some(git2::ObjectType::Blob) {
+        let (size, _kind) = repository
+            .odb()?
+            .read_header(entry.id())
+            .context("failed to read blob header")?;
+        Some(size)
+    } else {
+        None
+    };
+
+    Ok(Some(PathMetadata {
+        mode: entry.filemode(),
+        id: entry.id(),
+        size,
+        kind,
+    }))
 }
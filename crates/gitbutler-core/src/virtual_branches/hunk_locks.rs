@@ -0,0 +1,412 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use bstr::ByteSlice;
+use git2::ErrorCode;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    branch::Hunk, get_status_by_branch_with_locks, integration::get_workspace_head, BranchId,
+    VirtualBranchHunk,
+};
+use crate::{
+    fs::{create_dirs_then_write, read_toml_file_or_default},
+    git::{
+        diff::{self, HunkLock},
+        RepositoryExt,
+    },
+    project_repository::{self, LogUntil},
+};
+
+/// An uncommitted hunk of an applied branch along with the commits it is locked to.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedHunk {
+    pub path: PathBuf,
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+    pub locked_to: Vec<HunkLock>,
+    /// The diff of the hunk with the context lines that were requested, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// An uncommitted hunk of an applied branch which isn't locked to any commit, so it can be moved
+/// to any branch.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnassignedHunk {
+    pub path: PathBuf,
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+    /// The diff of the hunk with the context lines that were requested, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// An uncommitted change of a binary file of an applied branch, which has no hunks that could be
+/// locked.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryChange {
+    pub path: PathBuf,
+    pub branch_id: BranchId,
+}
+
+/// The locked hunks of the workspace, see [`hunk_locks()`].
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkLocks {
+    pub hunks: Vec<LockedHunk>,
+    /// The uncommitted hunks that don't intersect with the changes of any commit, sorted by path
+    /// and position.
+    pub unassigned: Vec<UnassignedHunk>,
+    /// The binary files with uncommitted changes, sorted by path, which are neither locked nor
+    /// unassigned.
+    pub binary_changes: Vec<BinaryChange>,
+    /// `true` if the locks were computed earlier and may not reflect the current worktree.
+    pub stale: bool,
+}
+
+/// The locks as they were last computed, along with the state of the branches they were
+/// computed for. It only contains hunk ids and the commits they are locked to, not their content.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Cache {
+    heads: Vec<BranchHead>,
+    hunks: Vec<LockedHunk>,
+    unassigned: Vec<UnassignedHunk>,
+    binary_changes: Vec<BinaryChange>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BranchHead {
+    branch_id: BranchId,
+    #[serde(with = "crate::serde::oid")]
+    head: git2::Oid,
+}
+
+/// Return the uncommitted hunks of applied branches which are locked to commits, sorted by path
+/// and position, along with those which aren't locked to any commit.
+///
+/// Computing the locks gets expensive in large repositories. If `allow_stale` is set, the locks
+/// computed last are returned instead, marked as [stale](HunkLocks::stale), as long as no branch
+/// was added, removed or got new commits since, so they can be shown right away while fresh locks
+/// are requested in a second call.
+///
+/// If `context_lines` is set, the [diff](LockedHunk::diff) of each hunk is returned with as many
+/// lines of context. This is for display only: locks are always computed from the hunks of the
+/// workspace, no matter how many context lines are requested.
+///
+/// It's an error if the changes of all branches failed to diff, so nothing could be locked,
+/// unless `allow_partial` is set to get the hunks as if they weren't locked instead.
+pub fn hunk_locks(
+    project_repository: &project_repository::Repository,
+    allow_stale: bool,
+    context_lines: Option<u32>,
+    allow_partial: bool,
+) -> Result<HunkLocks> {
+    let cache_path = project_repository
+        .project()
+        .gb_dir()
+        .join("hunk_locks.toml");
+    let vb_state = project_repository.project().virtual_branches();
+    let mut heads: Vec<_> = vb_state
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| branch.applied)
+        .map(|branch| BranchHead {
+            branch_id: branch.id,
+            head: branch.head,
+        })
+        .collect();
+    heads.sort_by_key(|head| head.branch_id.to_string());
+
+    if allow_stale {
+        // a cache that can't be read is as good as none, it's overwritten below
+        let cache: Cache = read_toml_file_or_default(&cache_path).unwrap_or_default();
+        if !cache.heads.is_empty() && cache.heads == heads {
+            let mut locks = HunkLocks {
+                hunks: cache.hunks,
+                unassigned: cache.unassigned,
+                binary_changes: cache.binary_changes,
+                stale: true,
+            };
+            if let Some(context_lines) = context_lines {
+                let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+                add_diffs(
+                    project_repository,
+                    &integration_commit_id,
+                    &mut locks,
+                    context_lines,
+                )?;
+            }
+            return Ok(locks);
+        }
+    }
+
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch_with_locks(
+        project_repository,
+        Some(&integration_commit_id),
+        allow_partial,
+    )
+    .context("failed to get status by branch")?;
+    let mut uncommitted: Vec<_> = statuses
+        .iter()
+        .filter(|(branch, _)| branch.applied)
+        .flat_map(|(branch, files)| files.iter().map(move |file| (branch.id, file)))
+        .flat_map(|(branch_id, (path, hunks))| {
+            hunks.iter().map(move |hunk| (branch_id, path, hunk))
+        })
+        .collect();
+    uncommitted.sort_by(|(_, a_path, a), (_, b_path, b)| {
+        (a_path, a.new_start).cmp(&(b_path, b.new_start))
+    });
+
+    let mut hunks = Vec::new();
+    let mut unassigned = Vec::new();
+    let mut binary_changes = Vec::new();
+    for (branch_id, path, hunk) in uncommitted {
+        if hunk.binary {
+            binary_changes.push(BinaryChange {
+                path: path.clone(),
+                branch_id,
+            });
+            continue;
+        }
+        let hunk_id = VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines);
+        if hunk.locked_to.is_empty() {
+            unassigned.push(UnassignedHunk {
+                path: path.clone(),
+                hunk_id,
+                diff: None,
+            });
+        } else {
+            hunks.push(LockedHunk {
+                path: path.clone(),
+                hunk_id,
+                locked_to: hunk.locked_to.to_vec(),
+                diff: None,
+            });
+        }
+    }
+
+    let cache = Cache {
+        heads,
+        hunks: hunks.clone(),
+        unassigned: unassigned.clone(),
+        binary_changes: binary_changes.clone(),
+    };
+    create_dirs_then_write(&cache_path, toml::to_string(&cache)?)
+        .context("failed to write hunk locks cache")?;
+
+    let mut locks = HunkLocks {
+        hunks,
+        unassigned,
+        binary_changes,
+        stale: false,
+    };
+    if let Some(context_lines) = context_lines {
+        add_diffs(
+            project_repository,
+            &integration_commit_id,
+            &mut locks,
+            context_lines,
+        )?;
+    }
+    Ok(locks)
+}
+
+/// Set the diff of all hunks of `locks` to the hunks of the worktree compared to
+/// `integration_commit_id` with `context_lines` which overlap with them.
+///
+/// With fewer context lines, a hunk may be split into several, and with more, several hunks may
+/// be merged into one, so the diff of a hunk is made of all hunks it overlaps with, and hunks
+/// that don't exist anymore have no diff.
+fn add_diffs(
+    project_repository: &project_repository::Repository,
+    integration_commit_id: &git2::Oid,
+    locks: &mut HunkLocks,
+    context_lines: u32,
+) -> Result<()> {
+    let diffs = diff::workdir_with_context_lines(
+        project_repository.repo(),
+        integration_commit_id,
+        context_lines,
+    )
+    .context("failed to diff workdir")?;
+    let diff_of = |path: &PathBuf, hunk_id: &str| -> Option<String> {
+        let hunk = Hunk::from_str(hunk_id).ok()?;
+        let overlapping: Vec<_> = diffs
+            .get(path)?
+            .hunks
+            .iter()
+            .filter(|display| {
+                // hunks that only remove lines cover no new lines, but are still somewhere
+                let display_end = display.new_start + display.new_lines.max(1);
+                display.new_start < hunk.end.max(hunk.start + 1) && hunk.start < display_end
+            })
+            .map(|display| display.diff_lines.to_str_lossy().into_owned())
+            .collect();
+        (!overlapping.is_empty()).then(|| overlapping.concat())
+    };
+    for hunk in &mut locks.hunks {
+        hunk.diff = diff_of(&hunk.path, &hunk.hunk_id);
+    }
+    for hunk in &mut locks.unassigned {
+        hunk.diff = diff_of(&hunk.path, &hunk.hunk_id);
+    }
+    Ok(())
+}
+
+/// The commits of a virtual branch along with the earlier commits of the same branch whose lines
+/// they change, see [`commit_dependencies()`].
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct CommitDependencies {
+    /// The commits of the branch that aren't part of the default target, oldest first.
+    pub commits: Vec<git2::Oid>,
+    /// The commits of the branch each commit of the branch depends on, oldest first, if it
+    /// depends on any.
+    pub dependencies: HashMap<git2::Oid, Vec<git2::Oid>>,
+}
+
+/// The commits of a [`CommitDependencies`] which depend on each other, so they can't be ordered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleError {
+    /// The commits that couldn't be ordered, oldest first.
+    pub commits: Vec<git2::Oid>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let commits = self
+            .commits
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "commits {commits} depend on each other and can't be ordered"
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+impl CommitDependencies {
+    /// Return the commits ordered so that a commit comes after all commits it depends on, and
+    /// otherwise in the order they were committed in.
+    ///
+    /// Dependencies always point to earlier commits, but if they don't, the commits that depend
+    /// on each other are reported instead of ordering them arbitrarily.
+    pub fn dependency_order(&self) -> Result<Vec<git2::Oid>, CycleError> {
+        let mut ordered = Vec::with_capacity(self.commits.len());
+        let mut is_ordered = HashSet::new();
+        while ordered.len() < self.commits.len() {
+            let next = self.commits.iter().find(|commit_id| {
+                !is_ordered.contains(*commit_id)
+                    && self
+                        .dependencies
+                        .get(*commit_id)
+                        .into_iter()
+                        .flatten()
+                        // dependencies outside of the branch don't have to be ordered
+                        .all(|dependency| {
+                            is_ordered.contains(dependency) || !self.commits.contains(dependency)
+                        })
+            });
+            let Some(next) = next else {
+                return Err(CycleError {
+                    commits: self
+                        .commits
+                        .iter()
+                        .filter(|commit_id| !is_ordered.contains(*commit_id))
+                        .copied()
+                        .collect(),
+                });
+            };
+            is_ordered.insert(*next);
+            ordered.push(*next);
+        }
+        Ok(ordered)
+    }
+}
+
+/// Return the commits of `branch_id` that aren't part of the default target along with the
+/// earlier commits of the branch each of them depends on.
+///
+/// A commit depends on another one if it changes or removes lines that one introduced, as found
+/// by blaming the lines of each of its hunks in its parent. Commits that only add lines don't
+/// depend on anything.
+pub fn commit_dependencies(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<CommitDependencies> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+    let repo = project_repository.repo();
+
+    let mut commits = project_repository.l(branch.head, LogUntil::Commit(default_target.sha))?;
+    commits.reverse();
+    let merge_base = repo
+        .merge_base(default_target.sha, branch.head)
+        .context("failed to find merge base")?;
+
+    let mut dependencies = HashMap::new();
+    for commit_id in &commits {
+        let commit = repo.find_commit(*commit_id)?;
+        let Ok(parent) = commit.parent(0) else {
+            continue;
+        };
+        let mut diff_opts = git2::DiffOptions::new();
+        diff_opts.ignore_submodules(true).context_lines(0);
+        let diff = repo.diff_tree_to_tree(
+            Some(&parent.tree()?),
+            Some(&commit.tree()?),
+            Some(&mut diff_opts),
+        )?;
+
+        let mut commit_dependencies = Vec::new();
+        for (path, file) in diff::hunks_by_filepath(Some(repo), &diff)? {
+            let old_path = file.old_path.unwrap_or(path);
+            for hunk in file.hunks.iter().filter(|hunk| hunk.old_lines > 0) {
+                let blame = match repo.blame(
+                    &old_path,
+                    hunk.old_start,
+                    hunk.old_start + hunk.old_lines - 1,
+                    merge_base,
+                    parent.id(),
+                ) {
+                    Ok(blame) => blame,
+                    // the file was added by the commit
+                    Err(err) if err.code() == ErrorCode::NotFound => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                for blame_hunk in blame.iter() {
+                    let blamed_id = blame_hunk.orig_commit_id();
+                    if commits.contains(&blamed_id) && !commit_dependencies.contains(&blamed_id) {
+                        commit_dependencies.push(blamed_id);
+                    }
+                }
+            }
+        }
+        commit_dependencies
+            .sort_by_key(|dependency| commits.iter().position(|commit_id| commit_id == dependency));
+        if !commit_dependencies.is_empty() {
+            dependencies.insert(*commit_id, commit_dependencies);
+        }
+    }
+    Ok(CommitDependencies {
+        commits,
+        dependencies,
+    })
+}
@@ -4,7 +4,7 @@ use anyhow::{anyhow, bail, Context, Result};
 use bstr::ByteSlice;
 use lazy_static::lazy_static;
 
-use super::VirtualBranchesHandle;
+use super::{Branch, BranchId, VirtualBranchesHandle};
 use crate::git::RepositoryExt;
 use crate::virtual_branches::errors::Marker;
 use crate::{
@@ -60,17 +60,7 @@ pub fn get_workspace_head(
         let merge_base = repo.merge_base(first_branch.head, merge_parent)?;
         workspace_tree = repo.find_commit(merge_base)?.tree()?;
     } else {
-        for branch in &applied_branches {
-            let branch_tree = repo.find_commit(branch.head)?.tree()?;
-            let merge_tree = repo.find_commit(target.sha)?.tree()?;
-            let mut index = repo.merge_trees(&merge_tree, &workspace_tree, &branch_tree, None)?;
-
-            if !index.has_conflicts() {
-                workspace_tree = repo.find_tree(index.write_tree_to(repo)?)?;
-            } else {
-                return Err(anyhow!("Merge conflict between base and {:?}", branch.name));
-            }
-        }
+        workspace_tree = merge_branch_heads(repo, target.sha, &applied_branches)?;
     }
 
     let branch_heads = applied_branches
@@ -113,6 +103,59 @@ pub fn get_workspace_head(
     Ok(workspace_head_id)
 }
 
+/// Return the tree of the workspace as if `branch_id` wasn't applied, i.e. the heads of all other
+/// applied branches merged into the default target.
+///
+/// Diffing it against the tree of the [workspace head](get_workspace_head()) shows exactly what
+/// the branch contributes to the workspace, even if its changes are entangled with those of other
+/// branches.
+pub fn workspace_tree_without_branch(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<git2::Oid> {
+    project_repository.assure_resolved()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let target = vb_state
+        .get_default_target()
+        .context("failed to get target")?;
+
+    let all_branches = vb_state.list_branches()?;
+    if !all_branches
+        .iter()
+        .any(|branch| branch.id == branch_id && branch.applied)
+    {
+        bail!("could not find applied branch with id {branch_id}");
+    }
+    let other_branches = all_branches
+        .iter()
+        .filter(|branch| branch.applied && branch.id != branch_id)
+        .collect::<Vec<_>>();
+
+    let tree = merge_branch_heads(project_repository.repo(), target.sha, &other_branches)?;
+    Ok(tree.id())
+}
+
+/// Merge the heads of `branches` one after another into the tree of the `target` commit.
+fn merge_branch_heads<'repo>(
+    repo: &'repo git2::Repository,
+    target: git2::Oid,
+    branches: &[&Branch],
+) -> Result<git2::Tree<'repo>> {
+    let merge_tree = repo.find_commit(target)?.tree()?;
+    let mut workspace_tree = merge_tree.clone();
+    for branch in branches {
+        let branch_tree = repo.find_commit(branch.head)?.tree()?;
+        let mut index = repo.merge_trees(&merge_tree, &workspace_tree, &branch_tree, None)?;
+
+        if !index.has_conflicts() {
+            workspace_tree = repo.find_tree(index.write_tree_to(repo)?)?;
+        } else {
+            return Err(anyhow!("Merge conflict between base and {:?}", branch.name));
+        }
+    }
+    Ok(workspace_tree)
+}
+
 // Before switching the user to our gitbutler integration branch we save
 // the current branch into a text file. It is used in generating the commit
 // message for integration branch, as a helpful hint about how to get back
@@ -7,12 +7,23 @@ pub mod errors;
 mod files;
 pub use files::*;
 
+mod hunk_locks;
+pub use hunk_locks::{
+    commit_dependencies, hunk_locks, BinaryChange, CommitDependencies, CycleError, HunkLocks,
+    LockedHunk, UnassignedHunk,
+};
+
 pub mod integration;
 pub use integration::GITBUTLER_INTEGRATION_REFERENCE;
 
 mod base;
 pub use base::*;
 
+mod batch;
+pub use batch::{
+    apply_operations, commit_across_branches, CommitAssignment, CommitRef, WorkspaceOp,
+};
+
 pub mod controller;
 pub use controller::Controller;
 
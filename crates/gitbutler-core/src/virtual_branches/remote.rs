@@ -63,6 +63,7 @@ pub fn list_remote_branches(
 ) -> Result<Vec<RemoteBranch>> {
     let default_target = default_target(&project_repository.project().gb_dir())?;
 
+    let mailmap = project_repository.mailmap()?;
     let mut remote_branches = vec![];
     for (branch, _) in project_repository
         .repo()
@@ -70,7 +71,7 @@ pub fn list_remote_branches(
         .context("failed to list remote branches")?
         .flatten()
     {
-        let branch = branch_to_remote_branch(&branch)?;
+        let branch = branch_to_remote_branch(&branch, mailmap)?;
 
         if let Some(branch) = branch {
             let branch_is_trunk = branch.name.branch() == Some(default_target.branch.branch())
@@ -102,7 +103,10 @@ pub fn get_branch_data(
         .context("failed to get branch data")
 }
 
-pub fn branch_to_remote_branch(branch: &git2::Branch) -> Result<Option<RemoteBranch>> {
+pub fn branch_to_remote_branch(
+    branch: &git2::Branch,
+    mailmap: &git::Mailmap,
+) -> Result<Option<RemoteBranch>> {
     let commit = match branch.get().peel_to_commit() {
         Ok(c) => c,
         Err(err) => {
@@ -137,7 +141,8 @@ pub fn branch_to_remote_branch(branch: &git2::Branch) -> Result<Option<RemoteBra
                     last_commit_author: commit
                         .author()
                         .name()
-                        .map(std::string::ToString::to_string),
+                        .is_some()
+                        .then(|| mailmap.resolve(&commit.author()).0),
                 })
             })
             .transpose(),
@@ -164,6 +169,7 @@ pub fn branch_to_remote_branch_data(
                 .distance(base, sha)
                 .context("failed to get behind count")?;
 
+            let mailmap = project_repository.mailmap()?;
             let fork_point = ahead.last().and_then(|c| c.parent(0).ok()).map(|c| c.id());
 
             Ok(RemoteBranchData {
@@ -177,7 +183,7 @@ pub fn branch_to_remote_branch_data(
                 behind: count_behind,
                 commits: ahead
                     .into_iter()
-                    .map(|commit| commit_to_remote_commit(&commit))
+                    .map(|commit| commit_to_remote_commit(&commit, mailmap))
                     .collect::<Vec<_>>(),
                 fork_point,
             })
@@ -185,13 +191,14 @@ pub fn branch_to_remote_branch_data(
         .transpose()
 }
 
-pub fn commit_to_remote_commit(commit: &git2::Commit) -> RemoteCommit {
+/// Convert `commit` into a [`RemoteCommit`], with its author resolved through `mailmap`.
+pub fn commit_to_remote_commit(commit: &git2::Commit, mailmap: &git::Mailmap) -> RemoteCommit {
     let parent_ids: Vec<git2::Oid> = commit.parents().map(|c| c.id()).collect::<Vec<_>>();
     RemoteCommit {
         id: commit.id().to_string(),
         description: commit.message_bstr().to_owned(),
         created_at: commit.time().seconds().try_into().unwrap(),
-        author: commit.author().into(),
+        author: Author::with_mailmap(&commit.author(), mailmap),
         change_id: commit.change_id(),
         parent_ids,
     }
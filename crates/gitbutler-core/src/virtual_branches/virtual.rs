@@ -3,28 +3,32 @@ use std::borrow::Borrow;
 use std::os::unix::prelude::PermissionsExt;
 use std::time::SystemTime;
 use std::{
-    collections::HashMap,
+    collections::{hash_map, BTreeMap, HashMap, HashSet},
     hash::Hash,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
     time, vec,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
-use bstr::{BString, ByteSlice, ByteVec};
+use bstr::{BStr, BString, ByteSlice, ByteVec};
 use diffy::{apply_bytes as diffy_apply, Line, Patch};
 use git2::ErrorCode;
 use git2_hooks::HookResult;
 use hex::ToHex;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::integration::get_workspace_head;
 use super::{
     branch::{
-        self, Branch, BranchCreateRequest, BranchId, BranchOwnershipClaims, Hunk, OwnershipClaim,
+        self, Branch, BranchCreateRequest, BranchId, BranchOwnershipClaims, Hunk, HunkIdentity,
+        OwnershipClaim,
     },
-    branch_to_remote_branch, target, RemoteBranch, VirtualBranchesHandle,
+    branch_to_remote_branch, target, DiffStat, RemoteBranch, RemoteBranchFile,
+    VirtualBranchesHandle,
 };
+use crate::config::git::GitConfig;
 use crate::error::Code;
 use crate::git::diff::GitHunk;
 use crate::git::diff::{diff_files_into_hunks, trees, FileDiff};
@@ -154,6 +158,9 @@ pub struct VirtualBranchHunk {
     pub file_path: PathBuf,
     #[serde(serialize_with = "crate::serde::hash_to_hex")]
     pub hash: HunkHash,
+    /// An id that survives lines being added or removed around the hunk, see [`Hunk::stable_id()`].
+    #[serde(serialize_with = "crate::serde::hash_to_hex")]
+    pub stable_id: HunkHash,
     pub old_start: u32,
     pub start: u32,
     pub end: u32,
@@ -175,6 +182,7 @@ impl VirtualBranchHunk {
         mtimes: &mut MTimeCache,
     ) -> Self {
         let hash = Hunk::hash_diff(&hunk.diff_lines);
+        let stable_id = Hunk::stable_id(&file_path, &hunk.diff_lines);
         Self {
             id: Self::gen_id(hunk.new_start, hunk.new_lines),
             modified_at: mtimes.mtime_by_path(project_path.join(&file_path)),
@@ -185,6 +193,7 @@ impl VirtualBranchHunk {
             end: hunk.new_start + hunk.new_lines,
             binary: hunk.binary,
             hash,
+            stable_id,
             locked: hunk.locked_to.len() > 0,
             locked_to: Some(hunk.locked_to),
             change_type: hunk.change_type,
@@ -204,7 +213,18 @@ impl From<git2::Signature<'_>> for Author {
     fn from(value: git2::Signature) -> Self {
         let name = value.name().unwrap_or_default().to_string();
         let email = value.email().unwrap_or_default().to_string();
+        Author::new(name, email)
+    }
+}
+
+impl Author {
+    /// Create the author of `signature` with the canonical name and email of `mailmap`.
+    pub fn with_mailmap(signature: &git2::Signature, mailmap: &git::Mailmap) -> Self {
+        let (name, email) = mailmap.resolve(signature);
+        Author::new(name, email)
+    }
 
+    fn new(name: String, email: String) -> Self {
         let gravatar_url = url::Url::parse(&format!(
             "https://www.gravatar.com/avatar/{:x}?s=100&r=g&d=retro",
             md5::compute(email.to_lowercase())
@@ -224,10 +244,13 @@ pub fn normalize_branch_name(name: &str) -> String {
     pattern.replace_all(name, "-").to_string()
 }
 
+/// Apply `branch_id` to the workspace. Conflicts are written with markers in `conflict_style`, or
+/// in the style configured in `merge.conflictStyle` if it's `None`.
 pub fn apply_branch(
     project_repository: &project_repository::Repository,
     branch_id: BranchId,
     user: Option<&users::User>,
+    conflict_style: Option<conflicts::ConflictStyle>,
 ) -> Result<String> {
     project_repository.assure_resolved()?;
     let repo = project_repository.repo();
@@ -290,7 +313,10 @@ pub fn apply_branch(
             // checkout the conflicts
             repo.checkout_index_builder(&mut merge_index)
                 .allow_conflicts()
-                .conflict_style_merge()
+                .conflict_style(conflicts::ConflictStyle::or_from_config(
+                    conflict_style,
+                    repo,
+                ))
                 .force()
                 .checkout()
                 .context("failed to checkout index")?;
@@ -804,7 +830,9 @@ pub fn list_virtual_branches(
         }
 
         let upstream = upstream_branch
-            .map(|upstream_branch| branch_to_remote_branch(&upstream_branch))
+            .map(|upstream_branch| {
+                branch_to_remote_branch(&upstream_branch, project_repository.mailmap()?)
+            })
             .transpose()?
             .flatten();
 
@@ -858,7 +886,8 @@ pub fn list_virtual_branches(
     }
 
     let mut branches = branches_with_large_files_abridged(branches);
-    branches.sort_by(|a, b| a.order.cmp(&b.order));
+    // branches can share an order, so their ids keep the listing stable
+    branches.sort_by_key(|branch| (branch.order, branch.id));
 
     Ok((branches, skipped_files))
 }
@@ -956,7 +985,7 @@ fn commit_to_vbranch_commit(
     let commit = VirtualBranchCommit {
         id: commit.id(),
         created_at: timestamp * 1000,
-        author: commit.author().into(),
+        author: Author::with_mailmap(&commit.author(), repository.mailmap()?),
         description: message,
         is_remote,
         files,
@@ -1052,6 +1081,7 @@ pub fn create_virtual_branch(
         applied: true,
         upstream: None,
         upstream_head: None,
+        push_remote: None,
         tree: tree.id(),
         head: default_target.sha,
         created_timestamp_ms: now,
@@ -1096,10 +1126,13 @@ pub fn create_virtual_branch(
 /// might introduce more conflicts, but there is no need to commit at the
 /// end since there will only be one parent commit.
 ///
+/// Conflicts are written with markers in `conflict_style`, or in the style configured in
+/// `merge.conflictStyle` if it's `None`.
 pub fn integrate_upstream_commits(
     project_repository: &project_repository::Repository,
     branch_id: BranchId,
     user: Option<&users::User>,
+    conflict_style: Option<conflicts::ConflictStyle>,
 ) -> Result<()> {
     conflicts::is_conflicting(project_repository, None)?;
 
@@ -1186,6 +1219,7 @@ pub fn integrate_upstream_commits(
                 &mut branch,
                 &upstream_commit,
                 merge_base,
+                conflict_style,
             )
             .map(Into::into)
         }
@@ -1212,7 +1246,10 @@ pub fn integrate_upstream_commits(
     if merge_index.has_conflicts() {
         repo.checkout_index_builder(&mut merge_index)
             .allow_conflicts()
-            .conflict_style_merge()
+            .conflict_style(conflicts::ConflictStyle::or_from_config(
+                conflict_style,
+                repo,
+            ))
             .force()
             .checkout()?;
     } else {
@@ -1246,6 +1283,7 @@ pub fn integrate_with_merge(
     branch: &mut Branch,
     upstream_commit: &git2::Commit,
     merge_base: git2::Oid,
+    conflict_style: Option<conflicts::ConflictStyle>,
 ) -> Result<git2::Oid> {
     let wd_tree = project_repository.repo().get_wd_tree()?;
     let repo = project_repository.repo();
@@ -1271,7 +1309,10 @@ pub fn integrate_with_merge(
         )?;
         repo.checkout_index_builder(&mut merge_index)
             .allow_conflicts()
-            .conflict_style_merge()
+            .conflict_style(conflicts::ConflictStyle::or_from_config(
+                conflict_style,
+                repo,
+            ))
             .force()
             .checkout()?;
         return Err(anyhow!("merge problem")).context(Marker::ProjectConflict);
@@ -1371,6 +1412,48 @@ pub fn update_branch(
     Ok(branch)
 }
 
+/// Persist `branch_ids`, which must name each applied branch exactly once, as the order in which
+/// the applied branches are shown, from first to last.
+pub fn set_branch_order(
+    project_repository: &project_repository::Repository,
+    branch_ids: &[BranchId],
+) -> Result<()> {
+    let vb_state = project_repository.project().virtual_branches();
+    let applied_branches: Vec<_> = vb_state
+        .list_branches()
+        .context("failed to read virtual branches")?
+        .into_iter()
+        .filter(|branch| branch.applied)
+        .collect();
+
+    let mut unique_ids = branch_ids.to_vec();
+    unique_ids.sort();
+    unique_ids.dedup();
+    if unique_ids.len() != branch_ids.len()
+        || branch_ids.len() != applied_branches.len()
+        || !applied_branches
+            .iter()
+            .all(|branch| branch_ids.contains(&branch.id))
+    {
+        return Err(anyhow!(
+            "the branch order must list each applied branch exactly once"
+        ))
+        .context(Code::Validation);
+    }
+
+    for mut branch in applied_branches {
+        let order = branch_ids
+            .iter()
+            .position(|id| *id == branch.id)
+            .expect("checked above");
+        if branch.order != order {
+            branch.order = order;
+            vb_state.set_branch(branch)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn delete_branch(
     project_repository: &project_repository::Repository,
     branch_id: BranchId,
@@ -1522,6 +1605,19 @@ pub fn get_status_by_branch(
     project_repository: &project_repository::Repository,
     integration_commit: Option<&git2::Oid>,
 ) -> Result<(AppliedStatuses, Vec<diff::FileDiff>)> {
+    get_status_by_branch_with_locks(project_repository, integration_commit, true)
+}
+
+/// Like [`get_status_by_branch()`], but unless `allow_partial_locks` is set, fail if not a single
+/// lock range could be computed because the changes of every applied branch failed to diff,
+/// instead of returning hunks that look like they aren't locked to anything.
+#[allow(clippy::type_complexity)]
+pub(crate) fn get_status_by_branch_with_locks(
+    project_repository: &project_repository::Repository,
+    integration_commit: Option<&git2::Oid>,
+    allow_partial_locks: bool,
+) -> Result<(AppliedStatuses, Vec<diff::FileDiff>)> {
+    project_repository.assure_worktree()?;
     let vb_state = project_repository.project().virtual_branches();
 
     let default_target = vb_state.get_default_target()?;
@@ -1536,12 +1632,13 @@ pub fn get_status_by_branch(
         .cloned()
         .collect::<Vec<_>>();
 
-    let (applied_status, skipped_files) = get_applied_status(
+    let (applied_status, skipped_files) = get_applied_status_with_locks(
         project_repository,
         // TODO: Keep this optional or update lots of tests?
         integration_commit.unwrap_or(&default_target.sha),
         &default_target.sha,
         applied_virtual_branches,
+        allow_partial_locks,
     )?;
 
     let non_applied_virtual_branches = virtual_branches
@@ -1594,11 +1691,162 @@ fn get_non_applied_status(
         .collect::<Result<Vec<_>>>()
 }
 
-fn new_compute_locks(
+/// Everything the locks of uncommitted hunks are computed from besides the hunks themselves, so
+/// callers that need the locks of several sets of hunks can prepare it once and pass it to
+/// [`compute_hunk_locks_with_context()`] each time.
+struct WorkspaceContext {
+    integration_commit: git2::Oid,
+    target_sha: git2::Oid,
+    ranges: Arc<LockRanges>,
+    /// Why the ranges of some of the branches couldn't be computed, which leaves their hunks
+    /// without locks.
+    errors: Vec<String>,
+}
+
+/// What uncommitted hunks are matched against to find the commits they are locked to, which
+/// depends on the locking configured for the project.
+enum LockRanges {
+    /// The changes of the head of each applied branch compared to the target, by path, along with
+    /// the lock each of them leads to.
+    BranchHunks(HashMap<PathBuf, Vec<(diff::GitHunk, diff::HunkLock)>>),
+    /// The commit to blame from, and the branch each of the commits since the target belongs to.
+    Blame {
+        merge_base: git2::Oid,
+        commit_to_branch: HashMap<git2::Oid, BranchId>,
+    },
+}
+
+lazy_static::lazy_static! {
+    /// The lock ranges computed last by the path of the project, so they can be reused as long as
+    /// none of the commits they were computed from changed.
+    static ref LOCK_RANGES_CACHE: Mutex<HashMap<PathBuf, (LockRangesKey, Arc<LockRanges>)>> =
+        Mutex::default();
+}
+
+/// The commits [`LockRanges`] are computed from, which are immutable, so the ranges don't change
+/// unless one of them does.
+#[derive(Debug, PartialEq)]
+struct LockRangesKey {
+    use_new_locking: bool,
+    target: git2::Oid,
+    /// The id, head and whether it's applied of each branch, in the order they were passed.
+    heads: Vec<(BranchId, git2::Oid, bool)>,
+}
+
+/// Forget the lock ranges cached for the project of `project_repository`.
+///
+/// This is done whenever commits are created or rewritten, which would also change the key of the
+/// cache, to return the memory they occupy early.
+fn invalidate_lock_ranges(project_repository: &project_repository::Repository) {
+    if let Ok(mut cache) = LOCK_RANGES_CACHE.lock() {
+        cache.remove(project_repository.path());
+    }
+}
+
+impl WorkspaceContext {
+    /// Prepare the context for the locks of hunks against `virtual_branches`, reusing the ranges
+    /// computed last for the project if neither the target nor any of the branch heads changed.
+    fn new(
+        project_repository: &project_repository::Repository,
+        integration_commit: &git2::Oid,
+        target_sha: &git2::Oid,
+        virtual_branches: &Vec<branch::Branch>,
+    ) -> Result<Self> {
+        let use_new_locking = project_repository.project().use_new_locking;
+        let key = LockRangesKey {
+            use_new_locking,
+            target: if use_new_locking {
+                project_repository.repo().target_commit()?.id()
+            } else {
+                *target_sha
+            },
+            heads: virtual_branches
+                .iter()
+                .map(|branch| (branch.id, branch.head, branch.applied))
+                .collect(),
+        };
+        let cached = LOCK_RANGES_CACHE.lock().ok().and_then(|cache| {
+            cache
+                .get(project_repository.path())
+                .filter(|(cached_key, _)| *cached_key == key)
+                .map(|(_, ranges)| Arc::clone(ranges))
+        });
+        if let Some(ranges) = cached {
+            return Ok(WorkspaceContext {
+                integration_commit: *integration_commit,
+                target_sha: *target_sha,
+                ranges,
+                errors: Vec::new(),
+            });
+        }
+
+        let mut errors = Vec::new();
+        let ranges = if use_new_locking {
+            let (hunks_by_path, branch_errors) =
+                branch_hunks_by_path(project_repository.repo(), virtual_branches)?;
+            errors = branch_errors;
+            LockRanges::BranchHunks(hunks_by_path)
+        } else {
+            let merge_base = compute_merge_base(project_repository, target_sha, virtual_branches)?;
+            let mut commit_to_branch = HashMap::new();
+            for branch in virtual_branches {
+                for commit in project_repository.log(branch.head, LogUntil::Commit(*target_sha))? {
+                    commit_to_branch.insert(commit.id(), branch.id);
+                }
+            }
+            LockRanges::Blame {
+                merge_base,
+                commit_to_branch,
+            }
+        };
+        for error in &errors {
+            tracing::warn!("failed to compute lock ranges: {error}");
+        }
+        let ranges = Arc::new(ranges);
+        // ranges that are missing branches are retried next time
+        if errors.is_empty() {
+            if let Ok(mut cache) = LOCK_RANGES_CACHE.lock() {
+                cache.insert(
+                    project_repository.path().to_owned(),
+                    (key, Arc::clone(&ranges)),
+                );
+            }
+        }
+        Ok(WorkspaceContext {
+            integration_commit: *integration_commit,
+            target_sha: *target_sha,
+            ranges,
+            errors,
+        })
+    }
+
+    /// Fail with all errors if there are any and not a single range to lock hunks to could be
+    /// computed, as all hunks would look unlocked otherwise.
+    fn ensure_ranges_computed(&self) -> Result<()> {
+        let nothing_computed = match self.ranges.as_ref() {
+            LockRanges::BranchHunks(hunks_by_path) => hunks_by_path.is_empty(),
+            LockRanges::Blame { .. } => false,
+        };
+        if nothing_computed && !self.errors.is_empty() {
+            bail!(
+                "failed to compute the locks of all branches: {}",
+                self.errors.join("; ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Return the changes of each applied branch compared to the target by path, along with the
+/// errors of the branches whose changes couldn't be computed.
+#[allow(clippy::type_complexity)]
+fn branch_hunks_by_path(
     repository: &git2::Repository,
-    unstaged_hunks_by_path: &HashMap<PathBuf, Vec<diff::GitHunk>>,
     virtual_branches: &[branch::Branch],
-) -> Result<HashMap<HunkHash, Vec<diff::HunkLock>>> {
+) -> Result<(
+    HashMap<PathBuf, Vec<(diff::GitHunk, diff::HunkLock)>>,
+    Vec<String>,
+)> {
     // If we cant find the integration commit and subsequently the target commit, we can't find any locks
     let target_tree = repository.target_commit()?.tree()?;
 
@@ -1608,64 +1856,79 @@ fn new_compute_locks(
         .ignore_submodules(true)
         .context_lines(3);
 
+    let mut errors = Vec::new();
     let branch_path_diffs = virtual_branches
         .iter()
         .filter(|branch| branch.applied)
-        .filter_map(|branch| {
-            let commit = repository.find_commit(branch.head).ok()?;
-            let tree = commit.tree().ok()?;
-            let diff = repository
-                .diff_tree_to_tree(Some(&tree), Some(&target_tree), Some(opts))
-                .ok()?;
-            let hunks_by_filepath = diff::hunks_by_filepath(Some(repository), &diff).ok()?;
-
-            Some((branch, hunks_by_filepath))
-        })
+        .filter_map(
+            |branch| match branch_changes(repository, branch, &target_tree, opts) {
+                Ok(hunks_by_filepath) => Some((branch, hunks_by_filepath)),
+                Err(err) => {
+                    errors.push(format!("branch {}: {err:#}", branch.id));
+                    None
+                }
+            },
+        )
         .collect::<Vec<_>>();
 
     let mut integration_hunks_by_path =
-        HashMap::<PathBuf, Vec<(diff::GitHunk, &branch::Branch)>>::new();
+        HashMap::<PathBuf, Vec<(diff::GitHunk, diff::HunkLock)>>::new();
 
     for (branch, hunks_by_filepath) in branch_path_diffs {
+        let lock = diff::HunkLock::new(branch.id, branch.head);
         for (path, hunks) in hunks_by_filepath {
-            integration_hunks_by_path.entry(path).or_default().extend(
-                hunks
-                    .hunks
-                    .iter()
-                    .map(|hunk| (hunk.clone(), branch))
-                    .collect::<Vec<_>>(),
-            );
+            integration_hunks_by_path
+                .entry(path)
+                .or_default()
+                .extend(hunks.hunks.into_iter().map(|hunk| (hunk, lock.clone())));
         }
     }
+    Ok((integration_hunks_by_path, errors))
+}
 
-    let locked_hunks = unstaged_hunks_by_path
-        .iter()
-        .filter_map(|(path, hunks)| {
-            let integration_hunks = integration_hunks_by_path.get(path)?;
-
-            let (unapplied_hunk, branch) = hunks.iter().find_map(|unapplied_hunk| {
-                // Find the first intersecting hunk
-                for (integration_hunk, branch) in integration_hunks {
-                    if GitHunk::integration_intersects_unapplied(integration_hunk, unapplied_hunk) {
-                        return Some((unapplied_hunk, branch));
-                    };
-                }
+fn branch_changes(
+    repository: &git2::Repository,
+    branch: &branch::Branch,
+    target_tree: &git2::Tree,
+    opts: &mut git2::DiffOptions,
+) -> Result<diff::DiffByPathMap> {
+    let tree = repository.find_commit(branch.head)?.tree()?;
+    let diff = repository.diff_tree_to_tree(Some(&tree), Some(target_tree), Some(opts))?;
+    diff::hunks_by_filepath(Some(repository), &diff)
+}
 
-                None
-            })?;
+fn new_compute_locks(
+    unstaged_hunks_by_path: &HashMap<PathBuf, Vec<diff::GitHunk>>,
+    integration_hunks_by_path: &HashMap<PathBuf, Vec<(diff::GitHunk, diff::HunkLock)>>,
+    with_section: bool,
+) -> HashMap<HunkIdentity, Vec<diff::HunkLock>> {
+    let mut locked_hunks = HashMap::new();
+    for (path, hunks) in unstaged_hunks_by_path {
+        let Some(integration_hunks) = integration_hunks_by_path.get(path) else {
+            continue;
+        };
 
-            let hash = Hunk::hash_diff(&unapplied_hunk.diff_lines);
-            let lock = diff::HunkLock {
-                branch_id: branch.id,
-                commit_id: branch.head,
-            };
+        let locked = hunks.iter().find_map(|unapplied_hunk| {
+            // Find the first intersecting hunk
+            for (integration_hunk, lock) in integration_hunks {
+                if GitHunk::integration_intersects_unapplied(integration_hunk, unapplied_hunk) {
+                    return Some((unapplied_hunk, lock));
+                };
+            }
 
-            // For now we're returning an array of locks to align with the original type, even though this implementation doesn't give multiple locks for the same hunk
-            Some((hash, vec![lock]))
-        })
-        .collect::<HashMap<_, _>>();
+            None
+        });
+        let Some((unapplied_hunk, lock)) = locked else {
+            continue;
+        };
 
-    Ok(locked_hunks)
+        // For now we're returning an array of locks to align with the original type, even though this implementation doesn't give multiple locks for the same hunk
+        locked_hunks.insert(
+            lock_key(path, unapplied_hunk, with_section),
+            vec![lock.clone()],
+        );
+    }
+    locked_hunks
 }
 
 fn compute_merge_base(
@@ -1694,17 +1957,11 @@ fn compute_locks(
     integration_commit: &git2::Oid,
     target_sha: &git2::Oid,
     base_diffs: &BranchStatus,
-    virtual_branches: &Vec<branch::Branch>,
-) -> Result<HashMap<HunkHash, Vec<diff::HunkLock>>> {
-    let merge_base = compute_merge_base(project_repository, target_sha, virtual_branches)?;
-    let mut locked_hunk_map = HashMap::<HunkHash, Vec<diff::HunkLock>>::new();
-
-    let mut commit_to_branch = HashMap::new();
-    for branch in virtual_branches {
-        for commit in project_repository.log(branch.head, LogUntil::Commit(*target_sha))? {
-            commit_to_branch.insert(commit.id(), branch.id);
-        }
-    }
+    merge_base: git2::Oid,
+    commit_to_branch: &HashMap<git2::Oid, BranchId>,
+    with_section: bool,
+) -> Result<HashMap<HunkIdentity, Vec<diff::HunkLock>>> {
+    let mut locked_hunk_map = HashMap::<HunkIdentity, Vec<diff::HunkLock>>::new();
 
     for (path, hunks) in base_diffs.clone().into_iter() {
         for hunk in hunks {
@@ -1730,34 +1987,232 @@ fn compute_locks(
                 if commit_id == *integration_commit || commit_id == *target_sha {
                     continue;
                 }
-                let hash = Hunk::hash_diff(&hunk.diff_lines);
+                let key = lock_key(&path, &hunk, with_section);
                 let Some(branch_id) = commit_to_branch.get(&commit_id) else {
                     continue;
                 };
 
-                let hunk_lock = diff::HunkLock {
-                    branch_id: *branch_id,
-                    commit_id,
-                };
                 locked_hunk_map
-                    .entry(hash)
-                    .and_modify(|locks| {
-                        locks.push(hunk_lock);
-                    })
-                    .or_insert(vec![hunk_lock]);
+                    .entry(key)
+                    .or_default()
+                    .push(diff::HunkLock::new(*branch_id, commit_id));
             }
         }
     }
     Ok(locked_hunk_map)
 }
 
+/// Return the key of `hunk` of the file at `path` in the locks returned by
+/// [`compute_hunk_locks()`].
+///
+/// If `with_section` is set, the enclosing function of the hunk is part of it, see
+/// [`HunkIdentity::with_section()`].
+fn lock_key(path: &Path, hunk: &GitHunk, with_section: bool) -> HunkIdentity {
+    if with_section {
+        HunkIdentity::with_section(path, hunk)
+    } else {
+        HunkIdentity::new(path, hunk)
+    }
+}
+
+/// Return the commits of `virtual_branches` each of the `base_diffs` is locked to, keyed by the
+/// [lock key](lock_key()) of the hunk, using the locking configured for the project.
+///
+/// Use [`compute_hunk_locks_with_context()`] to compute the locks of several sets of hunks.
+fn compute_hunk_locks(
+    project_repository: &project_repository::Repository,
+    integration_commit: &git2::Oid,
+    target_sha: &git2::Oid,
+    base_diffs: &BranchStatus,
+    virtual_branches: &Vec<branch::Branch>,
+) -> Result<HashMap<HunkIdentity, Vec<diff::HunkLock>>> {
+    let context = WorkspaceContext::new(
+        project_repository,
+        integration_commit,
+        target_sha,
+        virtual_branches,
+    )?;
+    compute_hunk_locks_with_context(project_repository, &context, base_diffs)
+}
+
+/// Like [`compute_hunk_locks()`], but with the branches, merge-base and ranges to match against
+/// taken from `context` instead of computing them.
+fn compute_hunk_locks_with_context(
+    project_repository: &project_repository::Repository,
+    context: &WorkspaceContext,
+    base_diffs: &BranchStatus,
+) -> Result<HashMap<HunkIdentity, Vec<diff::HunkLock>>> {
+    let with_section = project_repository.project().section_aware_locks;
+    let mut locks = compute_locks_against_ranges(project_repository, context, base_diffs)?;
+    lock_renamed_files(project_repository, context, base_diffs, &mut locks)?;
+
+    if project_repository.project().ignore_whitespace_locks {
+        // reformatting shouldn't entangle a hunk with the commits that last touched its lines
+        for (path, hunks) in base_diffs {
+            for hunk in hunks {
+                if is_whitespace_only_change(hunk.diff_lines.as_bstr()) {
+                    locks.remove(&lock_key(path, hunk, with_section));
+                }
+            }
+        }
+    }
+    describe_locked_commits(project_repository, locks.values_mut().flatten())?;
+    Ok(locks)
+}
+
+/// Match `base_diffs` against the ranges of `context`, the way the project is configured to.
+fn compute_locks_against_ranges(
+    project_repository: &project_repository::Repository,
+    context: &WorkspaceContext,
+    base_diffs: &BranchStatus,
+) -> Result<HashMap<HunkIdentity, Vec<diff::HunkLock>>> {
+    let with_section = project_repository.project().section_aware_locks;
+    match context.ranges.as_ref() {
+        LockRanges::BranchHunks(integration_hunks_by_path) => Ok(new_compute_locks(
+            base_diffs,
+            integration_hunks_by_path,
+            with_section,
+        )),
+        LockRanges::Blame {
+            merge_base,
+            commit_to_branch,
+        } => compute_locks(
+            project_repository,
+            &context.integration_commit,
+            &context.target_sha,
+            base_diffs,
+            *merge_base,
+            commit_to_branch,
+            with_section,
+        ),
+    }
+}
+
+/// Lock the hunks of the files in `base_diffs` that were renamed, and possibly edited, to the
+/// commits that touched the lines changed along with the rename, which are matched against the
+/// ranges of the path the file had before.
+///
+/// Without this, the deletion of the old path would lock to all commits that touched the file,
+/// and the addition of the new path to none.
+fn lock_renamed_files(
+    project_repository: &project_repository::Repository,
+    context: &WorkspaceContext,
+    base_diffs: &BranchStatus,
+    locks: &mut HashMap<HunkIdentity, Vec<diff::HunkLock>>,
+) -> Result<()> {
+    let paths_with = |change_type: diff::ChangeType| -> HashSet<&PathBuf> {
+        base_diffs
+            .iter()
+            .filter(|(_, hunks)| {
+                !hunks.is_empty() && hunks.iter().all(|hunk| hunk.change_type == change_type)
+            })
+            .map(|(path, _)| path)
+            .collect()
+    };
+    let deleted = paths_with(diff::ChangeType::Deleted);
+    let added = paths_with(diff::ChangeType::Added);
+    if deleted.is_empty() || added.is_empty() {
+        return Ok(());
+    }
+
+    let pathspecs = deleted
+        .iter()
+        .chain(&added)
+        .filter_map(|path| path.to_str().map(ToOwned::to_owned))
+        .collect::<Vec<_>>();
+    let renames = diff::workdir_with_renames(
+        project_repository.repo(),
+        &context.integration_commit,
+        &pathspecs,
+        false,
+        diff::DEFAULT_RENAME_THRESHOLD,
+    )
+    .context("failed to detect renamed files")?;
+
+    let with_section = project_repository.project().section_aware_locks;
+    for (new_path, file) in renames {
+        let Some(old_path) = file.old_path.filter(|old_path| {
+            deleted.contains(old_path) && added.contains(&new_path) && *old_path != new_path
+        }) else {
+            continue;
+        };
+        // the hunks of the rename only show what changed compared to the old path
+        let changes = BranchStatus::from([(old_path.clone(), file.hunks)]);
+        let mut rename_locks = Vec::new();
+        for lock in compute_locks_against_ranges(project_repository, context, &changes)?
+            .into_values()
+            .flatten()
+        {
+            if !rename_locks.contains(&lock) {
+                rename_locks.push(lock);
+            }
+        }
+
+        for path in [&old_path, &new_path] {
+            for hunk in &base_diffs[path] {
+                let key = lock_key(path, hunk, with_section);
+                if rename_locks.is_empty() {
+                    locks.remove(&key);
+                } else {
+                    locks.insert(key, rename_locks.clone());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fill in the author and summary of the commits that `locks` point to, reading each commit only
+/// once.
+fn describe_locked_commits<'a>(
+    project_repository: &project_repository::Repository,
+    locks: impl IntoIterator<Item = &'a mut diff::HunkLock>,
+) -> Result<()> {
+    let repo = project_repository.repo();
+    let mailmap = project_repository.mailmap()?;
+    let mut descriptions = HashMap::<git2::Oid, (String, String)>::new();
+    for lock in locks {
+        let (author, summary) = match descriptions.entry(lock.commit_id) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => {
+                let commit = repo
+                    .find_commit(lock.commit_id)
+                    .context("failed to find locked commit")?;
+                let (author, _email) = mailmap.resolve(&commit.author());
+                entry.insert((author, commit.summary().unwrap_or_default().to_owned()))
+            }
+        };
+        lock.author.clone_from(author);
+        lock.summary.clone_from(summary);
+    }
+    Ok(())
+}
+
 // Returns branches and their associated file changes, in addition to a list
 // of skipped files.
 fn get_applied_status(
+    project_repository: &project_repository::Repository,
+    integration_commit: &git2::Oid,
+    target_sha: &git2::Oid,
+    virtual_branches: Vec<branch::Branch>,
+) -> Result<(AppliedStatuses, Vec<diff::FileDiff>)> {
+    get_applied_status_with_locks(
+        project_repository,
+        integration_commit,
+        target_sha,
+        virtual_branches,
+        true,
+    )
+}
+
+/// Like [`get_applied_status()`], but fail if no lock could be computed at all unless
+/// `allow_partial_locks` is set, see [`get_status_by_branch_with_locks()`].
+fn get_applied_status_with_locks(
     project_repository: &project_repository::Repository,
     integration_commit: &git2::Oid,
     target_sha: &git2::Oid,
     mut virtual_branches: Vec<branch::Branch>,
+    allow_partial_locks: bool,
 ) -> Result<(AppliedStatuses, Vec<diff::FileDiff>)> {
     let base_file_diffs = diff::workdir(project_repository.repo(), &integration_commit.to_owned())
         .context("failed to diff workdir")?;
@@ -1788,17 +2243,17 @@ fn get_applied_status(
 
     let mut mtimes = MTimeCache::default();
 
-    let locks = if project_repository.project().use_new_locking {
-        new_compute_locks(project_repository.repo(), &base_diffs, &virtual_branches)?
-    } else {
-        compute_locks(
-            project_repository,
-            integration_commit,
-            target_sha,
-            &base_diffs,
-            &virtual_branches,
-        )?
-    };
+    let context = WorkspaceContext::new(
+        project_repository,
+        integration_commit,
+        target_sha,
+        &virtual_branches,
+    )?;
+    if !allow_partial_locks {
+        context.ensure_ranges_computed()?;
+    }
+    let locks = compute_hunk_locks_with_context(project_repository, &context, &base_diffs)?;
+    let with_section = project_repository.project().section_aware_locks;
 
     for branch in &mut virtual_branches {
         if !branch.applied {
@@ -1808,9 +2263,9 @@ fn get_applied_status(
         let old_claims = branch.ownership.claims.clone();
         let new_claims = old_claims
             .iter()
-            .filter_map(|claim| {
+            .map(|claim| -> Result<Option<OwnershipClaim>> {
                 let git_diff_hunks = match base_diffs.get_mut(&claim.file_path) {
-                    None => return None,
+                    None => return Ok(None),
                     Some(hunks) => hunks,
                 };
 
@@ -1819,12 +2274,13 @@ fn get_applied_status(
                 let claimed_hunks: Vec<Hunk> = claim
                     .hunks
                     .iter()
-                    .filter_map(|claimed_hunk| {
+                    .map(|claimed_hunk| -> Result<Option<Hunk>> {
                         // if any of the current hunks intersects with the owned hunk, we want to keep it
                         for (i, git_diff_hunk) in git_diff_hunks.iter().enumerate() {
                             let hash = Hunk::hash_diff(&git_diff_hunk.diff_lines);
-                            if locks.contains_key(&hash) {
-                                return None; // Defer allocation to unclaimed hunks processing
+                            let key = lock_key(&claim.file_path, git_diff_hunk, with_section);
+                            if locks.contains_key(&key) {
+                                return Ok(None); // Defer allocation to unclaimed hunks processing
                             }
                             if claimed_hunk.eq(&Hunk::from(git_diff_hunk)) {
                                 let timestamp = claimed_hunk.timestamp_ms().unwrap_or(mtime);
@@ -1836,12 +2292,12 @@ fn get_applied_status(
                                     .push(git_diff_hunk.clone());
 
                                 git_diff_hunks.remove(i);
-                                return Some(
+                                return Ok(Some(
                                     claimed_hunk
                                         .clone()
                                         .with_timestamp(timestamp)
                                         .with_hash(hash),
-                                );
+                                ));
                             } else if claimed_hunk.intersects(git_diff_hunk) {
                                 diffs_by_branch
                                     .entry(branch.id)
@@ -1857,24 +2313,26 @@ fn get_applied_status(
                                     locked_to: git_diff_hunk.locked_to.to_vec(),
                                 };
                                 git_diff_hunks.remove(i);
-                                return Some(updated_hunk);
+                                return Ok(Some(updated_hunk));
                             }
                         }
-                        None
+                        Ok(None)
                     })
-                    .collect();
+                    .filter_map(Result::transpose)
+                    .collect::<Result<_>>()?;
 
                 if claimed_hunks.is_empty() {
                     // No need for an empty claim
-                    None
+                    Ok(None)
                 } else {
-                    Some(OwnershipClaim {
+                    Ok(Some(OwnershipClaim {
                         file_path: claim.file_path.clone(),
                         hunks: claimed_hunks,
-                    })
+                    }))
                 }
             })
-            .collect();
+            .filter_map(Result::transpose)
+            .collect::<Result<_>>()?;
 
         branch.ownership = BranchOwnershipClaims { claims: new_claims };
     }
@@ -1893,8 +2351,7 @@ fn get_applied_status(
     // process the remaining ones.
     for (filepath, hunks) in base_diffs {
         for hunk in hunks {
-            let hash = Hunk::hash_diff(&hunk.diff_lines);
-            let locked_to = locks.get(&hash);
+            let locked_to = locks.get(&lock_key(&filepath, &hunk, with_section));
 
             let vbranch_pos = if let Some(locks) = locked_to {
                 let p = virtual_branches
@@ -2102,13 +2559,28 @@ pub fn write_tree_onto_tree(
     base_tree: &git2::Tree,
     files: impl IntoIterator<Item = (impl Borrow<PathBuf>, impl Borrow<Vec<diff::GitHunk>>)>,
 ) -> Result<git2::Oid> {
-    let git_repository = project_repository.repo();
+    write_tree_onto_tree_in(
+        project_repository.repo(),
+        project_repository.path(),
+        base_tree,
+        files,
+    )
+}
+
+/// Like [`write_tree_onto_tree()`], but write the objects to `git_repository`, with the files read
+/// from `worktree_dir`.
+fn write_tree_onto_tree_in(
+    git_repository: &git2::Repository,
+    worktree_dir: &Path,
+    base_tree: &git2::Tree,
+    files: impl IntoIterator<Item = (impl Borrow<PathBuf>, impl Borrow<Vec<diff::GitHunk>>)>,
+) -> Result<git2::Oid> {
     let mut builder = git2::build::TreeUpdateBuilder::new();
     // now update the index with content in the working directory for each file
     for (rel_path, hunks) in files {
         let rel_path = rel_path.borrow();
         let hunks = hunks.borrow();
-        let full_path = project_repository.path().join(rel_path);
+        let full_path = worktree_dir.join(rel_path);
 
         let is_submodule = full_path.is_dir()
             && hunks.len() == 1
@@ -2155,7 +2627,7 @@ pub fn write_tree_onto_tree(
 
                 // if the link target is inside the project repository, make it relative
                 let link_target = link_target
-                    .strip_prefix(project_repository.path())
+                    .strip_prefix(worktree_dir)
                     .unwrap_or(&link_target);
 
                 let blob_oid = git_repository.blob(
@@ -2236,7 +2708,7 @@ pub fn write_tree_onto_tree(
 
     // now write out the tree
     let tree_oid = builder
-        .create_updated(project_repository.repo(), base_tree)
+        .create_updated(git_repository, base_tree)
         .context("failed to write updated tree")?;
 
     Ok(tree_oid)
@@ -2263,19 +2735,49 @@ fn _print_tree(repo: &git2::Repository, tree: &git2::Tree) -> Result<()> {
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+/// How [`commit()`] creates a commit.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommitOptions {
+    /// Trailers to append to the message with [`git::append_trailers()`].
+    pub trailers: Vec<(String, String)>,
+    /// Whether to run the `commit-msg`, `pre-commit` and `post-commit` hooks.
+    pub run_hooks: bool,
+    /// Whether to commit files that still contain conflict markers.
+    pub allow_conflict_markers: bool,
+    /// Whether to strip trailing whitespace from the added lines and drop blank lines added at the
+    /// end of files, like `git apply --whitespace=fix` does.
+    ///
+    /// Only the committed content is fixed and the worktree stays as it is, so the removed
+    /// whitespace shows up as an uncommitted change that is locked to the new commit.
+    pub fix_whitespace: bool,
+}
+
+/// A commit created by [`commit()`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedCommit {
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    /// The number of lines whose whitespace was fixed, if [`CommitOptions::fix_whitespace`] was
+    /// set.
+    pub adjusted_lines: Option<usize>,
+}
+
+/// Commit the changes of the branch with `branch_id`, or only those claimed by `ownership`, with
+/// `message`, as configured by `options`.
 pub fn commit(
     project_repository: &project_repository::Repository,
     branch_id: BranchId,
     message: &str,
     ownership: Option<&branch::BranchOwnershipClaims>,
     user: Option<&users::User>,
-    run_hooks: bool,
-) -> Result<git2::Oid> {
-    let mut message_buffer = message.to_owned();
+    options: &CommitOptions,
+) -> Result<CreatedCommit> {
+    let mut message_buffer = git::append_trailers(message, options.trailers.clone());
     let vb_state = project_repository.project().virtual_branches();
 
-    if run_hooks {
+    if options.run_hooks {
         let hook_result = git2_hooks::hooks_commit_msg(
             project_repository.repo(),
             Some(&["../.husky"]),
@@ -2312,38 +2814,72 @@ pub fn commit(
 
     project_repository.assure_unconflicted()?;
 
-    let tree_oid = if let Some(ownership) = ownership {
-        let files = files.into_iter().filter_map(|(filepath, hunks)| {
-            let hunks = hunks
-                .into_iter()
-                .filter(|hunk| {
-                    ownership
-                        .claims
-                        .iter()
-                        .find(|f| f.file_path.eq(&filepath))
-                        .map_or(false, |f| {
-                            f.hunks.iter().any(|h| {
-                                h.start == hunk.new_start
-                                    && h.end == hunk.new_start + hunk.new_lines
+    let files: Vec<(PathBuf, Vec<GitHunk>)> = if let Some(ownership) = ownership {
+        files
+            .into_iter()
+            .filter_map(|(filepath, hunks)| {
+                let hunks = hunks
+                    .into_iter()
+                    .filter(|hunk| {
+                        ownership
+                            .claims
+                            .iter()
+                            .find(|f| f.file_path.eq(&filepath))
+                            .map_or(false, |f| {
+                                f.hunks.iter().any(|h| {
+                                    h.start == hunk.new_start
+                                        && h.end == hunk.new_start + hunk.new_lines
+                                })
                             })
-                        })
-                })
-                .collect::<Vec<_>>();
-            if hunks.is_empty() {
-                None
-            } else {
-                Some((filepath, hunks))
-            }
-        });
-        write_tree_onto_commit(project_repository, branch.head, files)?
+                    })
+                    .collect::<Vec<_>>();
+                if hunks.is_empty() {
+                    None
+                } else {
+                    Some((filepath, hunks))
+                }
+            })
+            .collect()
     } else {
-        write_tree_onto_commit(project_repository, branch.head, files)?
+        files.into_iter().collect()
     };
 
+    if !options.allow_conflict_markers {
+        let markers = find_conflict_markers(&files);
+        if !markers.is_empty() {
+            let locations = markers
+                .iter()
+                .map(|(path, line)| format!("{}:{}", path.display(), line))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(anyhow!(
+                "refusing to commit unresolved conflict markers at {locations}"
+            ))
+            .context(Code::Validation);
+        }
+    }
+
+    let paths = files
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let tree_oid = write_tree_onto_commit(project_repository, branch.head, files)?;
+
     let git_repository = project_repository.repo();
     let parent_commit = git_repository
         .find_commit(branch.head)
         .context(format!("failed to find commit {:?}", branch.head))?;
+    let (tree_oid, adjusted_lines) = if options.fix_whitespace {
+        let (tree_oid, adjusted_lines) = fix_whitespace_in_tree(
+            git_repository,
+            &parent_commit.tree()?,
+            &git_repository.find_tree(tree_oid)?,
+            &paths,
+        )?;
+        (tree_oid, Some(adjusted_lines))
+    } else {
+        (tree_oid, None)
+    };
     let tree = git_repository
         .find_tree(tree_oid)
         .context(format!("failed to find tree {:?}", tree_oid))?;
@@ -2370,7 +2906,7 @@ pub fn commit(
         None => project_repository.commit(user, message, &tree, &[&parent_commit], None)?,
     };
 
-    if run_hooks {
+    if options.run_hooks {
         git2_hooks::hooks_post_commit(project_repository.repo(), Some(&["../.husky"]))
             .context("failed to run hook")?;
     }
@@ -2383,75 +2919,2033 @@ pub fn commit(
 
     super::integration::update_gitbutler_integration(&vb_state, project_repository)
         .context("failed to update gitbutler integration")?;
+    invalidate_lock_ranges(project_repository);
 
-    Ok(commit_oid)
+    Ok(CreatedCommit {
+        commit_id: commit_oid,
+        adjusted_lines,
+    })
 }
 
-pub fn push(
-    project_repository: &project_repository::Repository,
-    branch_id: BranchId,
-    with_force: bool,
-    credentials: &git::credentials::Helper,
-    askpass: Option<Option<BranchId>>,
+/// Fix the whitespace of the lines that `tree` adds to `base_tree` in the files at `paths`, and
+/// return the id of the fixed tree along with the number of adjusted lines.
+///
+/// Binary files, symlinks and submodules are left alone.
+fn fix_whitespace_in_tree(
+    repo: &git2::Repository,
+    base_tree: &git2::Tree,
+    tree: &git2::Tree,
+    paths: &[PathBuf],
+) -> Result<(git2::Oid, usize)> {
+    let mut builder = git2::build::TreeUpdateBuilder::new();
+    let mut adjusted_lines = 0;
+    for path in paths {
+        let Ok(entry) = tree.get_path(path) else {
+            // the file was deleted
+            continue;
+        };
+        let filemode = match entry.filemode() {
+            mode if mode == i32::from(git2::FileMode::Blob) => git2::FileMode::Blob,
+            mode if mode == i32::from(git2::FileMode::BlobExecutable) => {
+                git2::FileMode::BlobExecutable
+            }
+            _ => continue,
+        };
+        let new_blob = repo.find_blob(entry.id())?;
+        if new_blob.is_binary() {
+            continue;
+        }
+        let old_blob = match base_tree.get_path(path) {
+            Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => {
+                Some(repo.find_blob(entry.id())?)
+            }
+            _ => None,
+        };
+        let (content, adjusted) = fix_whitespace_of_added_lines(
+            old_blob.as_ref().map_or(&[][..], |blob| blob.content()),
+            new_blob.content(),
+        )?;
+        if adjusted > 0 {
+            builder.upsert(path, repo.blob(&content)?, filemode);
+            adjusted_lines += adjusted;
+        }
+    }
+    let tree_oid = builder
+        .create_updated(repo, tree)
+        .context("failed to write tree with fixed whitespace")?;
+    Ok((tree_oid, adjusted_lines))
+}
+
+/// Strip trailing spaces and tabs from the lines of `new` that aren't in `old`, and drop blank
+/// lines that were added at its end. Return the fixed content and the number of lines that were
+/// changed or dropped.
+fn fix_whitespace_of_added_lines(old: &[u8], new: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(0);
+    let patch = git2::Patch::from_buffers(old, None, new, None, Some(&mut opts))
+        .context("failed to diff file contents")?;
+    let mut added_lines = HashSet::new();
+    for hunk_idx in 0..patch.num_hunks() {
+        for line_idx in 0..patch.num_lines_in_hunk(hunk_idx)? {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            if let (git2::DiffLineType::Addition, Some(line_number)) =
+                (line.origin_value(), line.new_lineno())
+            {
+                added_lines.insert(line_number as usize);
+            }
+        }
+    }
+    if added_lines.is_empty() {
+        return Ok((new.to_vec(), 0));
+    }
+
+    // each line with its line ending, whether it was added, and whether it was changed
+    let mut lines = new
+        .split_inclusive(|b| *b == b'\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            if !added_lines.contains(&(idx + 1)) {
+                return (line.to_vec(), false, false);
+            }
+            let eol_len = if line.ends_with(b"\r\n") {
+                2
+            } else {
+                usize::from(line.ends_with(b"\n"))
+            };
+            let (content, eol) = line.split_at(line.len() - eol_len);
+            let trimmed = content.trim_end_with(|c| c == ' ' || c == '\t');
+            let mut fixed = trimmed.to_vec();
+            fixed.extend_from_slice(eol);
+            (fixed, true, trimmed.len() != content.len())
+        })
+        .collect::<Vec<_>>();
+
+    let mut adjusted = 0;
+    while let Some((line, true, _)) = lines.last() {
+        if !line.trim().is_empty() {
+            break;
+        }
+        lines.pop();
+        adjusted += 1;
+    }
+    adjusted += lines.iter().filter(|(_, _, changed)| *changed).count();
+    Ok((
+        lines.into_iter().flat_map(|(line, _, _)| line).collect(),
+        adjusted,
+    ))
+}
+
+/// What was pushed where by [`push()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushResult {
+    /// The remote that was pushed to, which is the push remote of the branch if it has one.
+    pub remote: String,
+    /// The remote tracking branch of the pushed branch.
+    pub refname: git::RemoteRefname,
+}
+
+pub fn push(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    with_force: bool,
+    credentials: &git::credentials::Helper,
+    askpass: Option<Option<BranchId>>,
+) -> Result<PushResult> {
+    let vb_state = project_repository.project().virtual_branches();
+
+    let mut vbranch = vb_state.get_branch(branch_id)?;
+    // an upstream on another remote than the one the branch should be pushed to is ignored
+    let upstream = vbranch.upstream.clone().filter(|upstream| {
+        vbranch
+            .push_remote
+            .as_deref()
+            .map_or(true, |push_remote| upstream.remote() == push_remote)
+    });
+    let remote_branch = if let Some(upstream_branch) = upstream {
+        upstream_branch
+    } else {
+        let default_target = vb_state.get_default_target()?;
+        let upstream_remote = match vbranch
+            .push_remote
+            .clone()
+            .or(default_target.push_remote_name)
+        {
+            Some(remote) => remote,
+            None => default_target.branch.remote().to_owned(),
+        };
+
+        let remote_branch = format!(
+            "refs/remotes/{}/{}",
+            upstream_remote,
+            normalize_branch_name(&vbranch.name)
+        )
+        .parse::<git::RemoteRefname>()
+        .context("failed to parse remote branch name")?;
+
+        let remote_branches = project_repository.git_remote_branches()?;
+        let existing_branches = remote_branches
+            .iter()
+            .map(RemoteRefname::branch)
+            .map(str::to_lowercase) // git is weird about case sensitivity here, assume not case sensitive
+            .collect::<Vec<_>>();
+
+        remote_branch.with_branch(&dedup_fmt(
+            &existing_branches
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            remote_branch.branch(),
+            "-",
+        ))
+    };
+
+    project_repository.push(
+        &vbranch.head,
+        &remote_branch,
+        with_force,
+        credentials,
+        None,
+        askpass,
+    )?;
+
+    vbranch.upstream = Some(remote_branch.clone());
+    vbranch.upstream_head = Some(vbranch.head);
+    vb_state
+        .set_branch(vbranch.clone())
+        .context("failed to write target branch after push")?;
+    project_repository.fetch(
+        remote_branch.remote(),
+        credentials,
+        askpass.map(|_| "modal".to_string()),
+    )?;
+
+    Ok(PushResult {
+        remote: remote_branch.remote().to_owned(),
+        refname: remote_branch,
+    })
+}
+
+/// Make `branch_id` push to `remote` instead of the push remote of the default target, or stop
+/// doing so if `remote` is `None`.
+///
+/// Errors if `remote` isn't a configured remote.
+pub fn set_push_remote(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    remote: Option<&str>,
 ) -> Result<()> {
+    if let Some(remote) = remote {
+        if !project_repository
+            .remotes()?
+            .iter()
+            .any(|name| name == remote)
+        {
+            return Err(anyhow!("remote '{remote}' doesn't exist")).context(Code::Validation);
+        }
+    }
+
+    let vb_state = project_repository.project().virtual_branches();
+    let mut branch = vb_state.get_branch(branch_id)?;
+    branch.push_remote = remote.map(ToOwned::to_owned);
+    vb_state
+        .set_branch(branch)
+        .context("failed to write branch")?;
+    Ok(())
+}
+
+/// The outcome of checking what a force-push of a virtual branch would do to its upstream.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ForcePushSafety {
+    /// The upstream has no commits that aren't also part of the virtual branch.
+    Safe,
+    /// These commits of the upstream, newest first, aren't part of the virtual branch and would be
+    /// lost by a force-push.
+    WouldOverwrite(#[serde(with = "crate::serde::oid_vec")] Vec<git2::Oid>),
+}
+
+/// Check if force-pushing `branch_id` would discard commits that only exist on its upstream,
+/// for instance because someone else pushed to it.
+///
+/// This only looks at the remote-tracking branch, so it's as current as the last fetch.
+/// Branches without upstream, or whose upstream doesn't exist locally, are always safe.
+pub fn force_push_safety(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<ForcePushSafety> {
+    let vb_state = project_repository.project().virtual_branches();
+    let branch = vb_state.get_branch(branch_id)?;
+    let Some(upstream) = branch.upstream else {
+        return Ok(ForcePushSafety::Safe);
+    };
+
+    let repo = project_repository.repo();
+    let Some(upstream_branch) = repo.find_branch_by_refname(&upstream.into())? else {
+        return Ok(ForcePushSafety::Safe);
+    };
+    let upstream_head = upstream_branch
+        .get()
+        .peel_to_commit()
+        .context("failed to find upstream commit")?
+        .id();
+
+    let mut revwalk = repo.revwalk().context("failed to create revwalk")?;
+    revwalk
+        .push(upstream_head)
+        .context(format!("failed to push {upstream_head}"))?;
+    revwalk
+        .hide(branch.head)
+        .context(format!("failed to hide {}", branch.head))?;
+    let at_risk = revwalk
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to list upstream commits")?;
+
+    Ok(if at_risk.is_empty() {
+        ForcePushSafety::Safe
+    } else {
+        ForcePushSafety::WouldOverwrite(at_risk)
+    })
+}
+
+/// Return the changes between the trees of `branch_a` and `branch_b`, each including its
+/// uncommitted changes, so two alternative implementations can be compared.
+///
+/// Both trees are [only kept in memory](in_memory_repository()) without touching the worktree.
+/// Hunks are coalesced like in [`super::branch_squashed_diff()`], and the files are sorted by path.
+pub fn diff_branches(
+    project_repository: &project_repository::Repository,
+    branch_a: BranchId,
+    branch_b: BranchId,
+    context_lines: u32,
+) -> Result<Vec<RemoteBranchFile>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let repo = in_memory_repository(project_repository.repo())?;
+    let preview_tree = |branch_id: BranchId| -> Result<git2::Tree> {
+        let (branch, files) = statuses
+            .iter()
+            .find(|(branch, _)| branch.id == branch_id)
+            .with_context(|| format!("branch {branch_id} not found"))?;
+        let base_tree = repo.find_commit(branch.head)?.tree()?;
+        let tree_id = write_tree_onto_tree_in(&repo, project_repository.path(), &base_tree, files)?;
+        repo.find_tree(tree_id)
+            .with_context(|| format!("failed to find tree of branch {branch_id}"))
+    };
+    let tree_a = preview_tree(branch_a)?;
+    let tree_b = preview_tree(branch_b)?;
+
+    let interhunk_lines = project_repository
+        .project()
+        .hunk_coalesce_distance(context_lines);
+    let mut files: Vec<_> =
+        diff::trees_with_context_lines(&repo, &tree_a, &tree_b, context_lines, interhunk_lines)?
+            .into_iter()
+            .map(|(path, file)| RemoteBranchFile {
+                binary: file.hunks.iter().any(|hunk| hunk.binary),
+                path,
+                old_path: None,
+                hunks: file.hunks,
+                mode_change: file.mode_change,
+                hunk_attribution: None,
+            })
+            .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Return the changes of the workspace, or of the applied virtual branch `branch_id` if set,
+/// compared to the remote-tracking branch `branch` of `remote`, each including its uncommitted
+/// changes and sorted by path.
+///
+/// By default only the changes since the merge-base with the remote branch are returned as with
+/// `git diff remote/branch...`, while `two_dot` compares to the tip of the remote branch
+/// directly. It's an error if the remote branch wasn't fetched yet.
+pub fn diff_against_remote(
+    project_repository: &project_repository::Repository,
+    remote: &str,
+    branch: &str,
+    branch_id: Option<BranchId>,
+    context_lines: u32,
+    two_dot: bool,
+) -> Result<Vec<RemoteBranchFile>> {
+    let repo = project_repository.repo();
+    let remote_refname = git::RemoteRefname::new(remote, branch);
+    let remote_commit = repo
+        .find_branch_by_refname(&remote_refname.clone().into())?
+        .ok_or_else(|| {
+            anyhow!("remote branch '{remote}/{branch}' not found, it may have to be fetched first")
+        })
+        .context(Code::Validation)?
+        .get()
+        .peel_to_commit()?;
+
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (head_id, tree) = match branch_id {
+        Some(branch_id) => {
+            let (statuses, _) =
+                get_status_by_branch(project_repository, Some(&integration_commit_id))
+                    .context("failed to get status by branch")?;
+            let (branch, files) = statuses
+                .iter()
+                .find(|(branch, _)| branch.id == branch_id)
+                .with_context(|| format!("branch {branch_id} not found"))?;
+            let tree_id = write_tree(project_repository, &branch.head, files)?;
+            (branch.head, repo.find_tree(tree_id)?)
+        }
+        None => (integration_commit_id, repo.get_wd_tree()?),
+    };
+
+    let base_tree = if two_dot {
+        remote_commit.tree()?
+    } else {
+        let merge_base = repo
+            .merge_base(remote_commit.id(), head_id)
+            .with_context(|| format!("failed to find merge-base with '{remote}/{branch}'"))?;
+        repo.find_commit(merge_base)?.tree()?
+    };
+
+    let interhunk_lines = project_repository
+        .project()
+        .hunk_coalesce_distance(context_lines);
+    let mut files: Vec<_> =
+        diff::trees_with_context_lines(repo, &base_tree, &tree, context_lines, interhunk_lines)?
+            .into_iter()
+            .map(|(path, file)| RemoteBranchFile {
+                binary: file.hunks.iter().any(|hunk| hunk.binary),
+                path,
+                old_path: None,
+                hunks: file.hunks,
+                mode_change: file.mode_change,
+                hunk_attribution: None,
+            })
+            .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// The number of worktree hunks of an applied virtual branch.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchHunkCounts {
+    pub branch_id: BranchId,
+    /// The hunks which depend on commits of this branch and thus can't be moved elsewhere.
+    pub locked: usize,
+    /// All uncommitted hunks assigned to this branch, including the locked ones.
+    pub owned: usize,
+}
+
+/// Count the worktree hunks of each applied virtual branch, in the order of the branches.
+///
+/// The locks are read from the hunks as they come out of the status computation, so this is as
+/// expensive as listing the branches once.
+pub fn hunk_counts_by_branch(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<BranchHunkCounts>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let mut applied: Vec<_> = statuses
+        .iter()
+        .filter(|(branch, _)| branch.applied)
+        .collect();
+    applied.sort_by_key(|(branch, _)| branch.order);
+
+    let all_hunks = || {
+        statuses
+            .iter()
+            .flat_map(|(_, files)| files.values().flatten())
+    };
+    Ok(applied
+        .into_iter()
+        .map(|(branch, files)| BranchHunkCounts {
+            branch_id: branch.id,
+            locked: all_hunks()
+                .filter(|hunk| {
+                    hunk.locked_to
+                        .iter()
+                        .any(|lock| lock.branch_id == branch.id)
+                })
+                .count(),
+            owned: files.values().map(Vec::len).sum(),
+        })
+        .collect())
+}
+
+/// Uncommitted hunks of a file which have to be committed together.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkGroup {
+    pub branch_id: BranchId,
+    pub path: PathBuf,
+    /// The ids of the hunks, as in [`VirtualBranchHunk::id`], from the top of the file.
+    pub hunk_ids: Vec<String>,
+}
+
+/// Return the uncommitted hunks of all applied branches as groups which can be committed one
+/// after another, ordered by branch, path and position within the file.
+///
+/// Hunks of the same branch and file whose line ranges touch or overlap share lines, so
+/// committing one without the other can't be applied cleanly and they end up in the same group.
+pub fn hunk_apply_order(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<HunkGroup>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let mut applied: Vec<_> = statuses
+        .into_iter()
+        .filter(|(branch, _)| branch.applied)
+        .collect();
+    applied.sort_by_key(|(branch, _)| branch.order);
+
+    let mut groups = Vec::new();
+    for (branch, files) in applied {
+        let mut files: Vec<_> = files.into_iter().collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, hunks) in files {
+            groups.extend(group_joined_hunks(&hunks).into_iter().map(|hunks| {
+                HunkGroup {
+                    branch_id: branch.id,
+                    path: path.clone(),
+                    hunk_ids: hunks
+                        .into_iter()
+                        .map(|hunk| VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines))
+                        .collect(),
+                }
+            }));
+        }
+    }
+    Ok(groups)
+}
+
+/// Split the hunks of a single file into runs of [joined] hunks, from the top of the file.
+fn group_joined_hunks(hunks: &[GitHunk]) -> Vec<Vec<&GitHunk>> {
+    let mut hunks: Vec<_> = hunks.iter().collect();
+    hunks.sort_by_key(|hunk| (hunk.new_start, hunk.new_lines));
+
+    let mut groups: Vec<Vec<&GitHunk>> = Vec::new();
+    for hunk in hunks {
+        let joins_previous =
+            groups
+                .last()
+                .and_then(|group| group.last())
+                .map_or(false, |previous| {
+                    joined(
+                        previous.new_start,
+                        previous.new_start + previous.new_lines,
+                        hunk.new_start,
+                        hunk.new_start + hunk.new_lines,
+                    )
+                });
+        match groups.last_mut() {
+            Some(group) if joins_previous => group.push(hunk),
+            _ => groups.push(vec![hunk]),
+        }
+    }
+    groups
+}
+
+/// Return the ids of all virtual branches ordered so that a branch comes after the branches it is
+/// based on, and otherwise in the order they are shown in.
+///
+/// Virtual branches are based on the default target, but one can also contain all commits of
+/// another one, for instance when it was created from a branch that was built on top of it. It
+/// then has to be integrated after that one. Branches with the same head, other than the target,
+/// are based on each other, which is reported as an error.
+pub fn branch_topological_order(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<BranchId>> {
     let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let bases = branch_bases(project_repository.repo(), default_target.sha, &branches)?;
+
+    let mut ordered = Vec::with_capacity(branches.len());
+    let mut is_ordered = vec![false; branches.len()];
+    while ordered.len() < branches.len() {
+        let Some(next) = (0..branches.len())
+            .find(|&index| !is_ordered[index] && bases[index].iter().all(|&base| is_ordered[base]))
+        else {
+            let cycle = branches
+                .iter()
+                .zip(&is_ordered)
+                .filter(|(_, is_ordered)| !**is_ordered)
+                .map(|(branch, _)| format!("'{}'", branch.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(anyhow!(
+                "branches {cycle} are based on each other and can't be ordered"
+            ));
+        };
+        is_ordered[next] = true;
+        ordered.push(branches[next].id);
+    }
+    Ok(ordered)
+}
+
+/// Return the indices of the branches each of `branches` is based on, i.e. those other than
+/// `target` whose head it contains.
+fn branch_bases(
+    repo: &git2::Repository,
+    target: git2::Oid,
+    branches: &[branch::Branch],
+) -> Result<Vec<Vec<usize>>> {
+    let mut bases = vec![Vec::new(); branches.len()];
+    for (index, branch) in branches.iter().enumerate() {
+        for (base_index, base) in branches.iter().enumerate() {
+            if base_index == index || base.head == target {
+                continue;
+            }
+            if base.head == branch.head
+                || repo
+                    .graph_descendant_of(branch.head, base.head)
+                    .context("failed to check ancestry of branches")?
+            {
+                bases[index].push(base_index);
+            }
+        }
+    }
+    Ok(bases)
+}
+
+/// The virtual branches of a project, split by whether they are part of the worktree.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveBranches {
+    /// The applied branches, whose changes make up the worktree.
+    pub active: Vec<BranchId>,
+    /// The branches that are known but not applied.
+    pub inactive: Vec<BranchId>,
+}
+
+/// Return the ids of all virtual branches, split into those that are applied and thus contribute
+/// to the worktree, and those that aren't, each in the order they are shown in.
+///
+/// Only the active branches are considered when computing [hunk locks](super::hunk_locks()).
+pub fn active_branches(
+    project_repository: &project_repository::Repository,
+) -> Result<ActiveBranches> {
+    let mut branches = project_repository
+        .project()
+        .virtual_branches()
+        .list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+    let (active, inactive): (Vec<_>, Vec<_>) =
+        branches.into_iter().partition(|branch| branch.applied);
+    Ok(ActiveBranches {
+        active: active.into_iter().map(|branch| branch.id).collect(),
+        inactive: inactive.into_iter().map(|branch| branch.id).collect(),
+    })
+}
+
+/// The generated description of a pull request for one branch of a stack.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrDescription {
+    pub branch_id: BranchId,
+    /// The markdown body of the pull request.
+    pub body: String,
+}
+
+/// Generate pull request descriptions for each branch of the stack `branch_id` is part of, from the
+/// bottom of the stack to its top.
+///
+/// The stack consists of `branch_id`, the branches it's based on and the branches based on it, see
+/// [`branch_topological_order()`]. Each description names the branch right below it, lists the
+/// subjects of the commits that the branch adds and has a checklist of the whole stack, so they
+/// can be pre-filled when creating the pull requests.
+pub fn stack_pr_descriptions(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<Vec<PrDescription>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let stack = branch_stack(project_repository, &default_target, branch_id)?;
+
+    let checklist = |current: &Branch| {
+        stack
+            .iter()
+            .map(|(branch, _)| {
+                let marker = if branch.id == current.id {
+                    " (this pull request)"
+                } else {
+                    ""
+                };
+                format!("- [ ] `{}`{marker}\n", branch.name)
+            })
+            .collect::<String>()
+    };
+
+    let mut descriptions = Vec::with_capacity(stack.len());
+    for (current, below) in &stack {
+        let below = below.map(|index| &stack[index].0);
+        let mut body = match below {
+            Some(below) => format!("Stacked on `{}`.\n", below.name),
+            None => format!("Based on `{}`.\n", default_target.branch.branch()),
+        };
+        body.push_str("\n## Commits\n\n");
+        let until = below.map_or(default_target.sha, |below| below.head);
+        for commit in project_repository
+            .log(current.head, LogUntil::Commit(until))?
+            .iter()
+            .rev()
+        {
+            let message = commit.message_bstr();
+            let subject = message.lines().next().unwrap_or_default();
+            body.push_str(&format!("- {}\n", subject.to_str_lossy()));
+        }
+        body.push_str("\n## Stack\n\n");
+        body.push_str(&checklist(current));
+
+        descriptions.push(PrDescription {
+            branch_id: current.id,
+            body,
+        });
+    }
+    Ok(descriptions)
+}
+
+/// The branch the pull request of a branch of a stack should target, see [`pr_base_branch()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrBase {
+    /// The branch right below in the stack, or `None` if it's the default target.
+    pub branch_id: Option<BranchId>,
+    /// The name of the branch on the remote.
+    pub branch_name: String,
+    #[serde(with = "crate::serde::oid")]
+    pub head: git2::Oid,
+}
+
+/// Return the branch the pull request of `branch_id` should target, which is the branch right
+/// below it in its stack, see [`stack_pr_descriptions()`], or the default target for the bottom
+/// branch.
+///
+/// Branches that weren't pushed yet are named like [`push()`] would name them by default.
+pub fn pr_base_branch(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<PrBase> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let stack = branch_stack(project_repository, &default_target, branch_id)?;
+    let below = stack
+        .iter()
+        .find(|(branch, _)| branch.id == branch_id)
+        .and_then(|(_, below)| below.map(|index| &stack[index].0));
+
+    Ok(match below {
+        Some(below) => PrBase {
+            branch_id: Some(below.id),
+            branch_name: below.upstream.as_ref().map_or_else(
+                || normalize_branch_name(&below.name),
+                |upstream| upstream.branch().to_owned(),
+            ),
+            head: below.head,
+        },
+        None => PrBase {
+            branch_id: None,
+            branch_name: default_target.branch.branch().to_owned(),
+            head: default_target.sha,
+        },
+    })
+}
+
+/// The commits a branch of a stack adds on top of the branch below it, see
+/// [`branch_exclusive_commits()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchSegment {
+    pub branch_id: BranchId,
+    /// The commits of only this branch, from its head down.
+    #[serde(with = "crate::serde::oid_vec")]
+    pub commits: Vec<git2::Oid>,
+}
+
+/// Return the commits each branch of the stack `branch_id` is part of adds to the branch right
+/// below it, or to the default target for the bottom branch, from the bottom of the stack to its
+/// top.
+///
+/// The stack is found as for [`stack_pr_descriptions()`], so the commits of each segment are also
+/// the range of its pull request.
+pub fn branch_exclusive_commits(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<Vec<BranchSegment>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let stack = branch_stack(project_repository, &default_target, branch_id)?;
+    stack
+        .iter()
+        .map(|(branch, below)| {
+            let until = below.map_or(default_target.sha, |index| stack[index].0.head);
+            Ok(BranchSegment {
+                branch_id: branch.id,
+                commits: project_repository.l(branch.head, LogUntil::Commit(until))?,
+            })
+        })
+        .collect()
+}
+
+/// Return the merge base of the heads of the virtual branches `a` and `b`, where their histories
+/// diverge.
+///
+/// If they have no common ancestor, the error is marked with [`Marker::UnrelatedHistories`].
+pub fn branches_merge_base(
+    project_repository: &project_repository::Repository,
+    a: BranchId,
+    b: BranchId,
+) -> Result<git2::Oid> {
+    let vb_state = project_repository.project().virtual_branches();
+    let a = vb_state.get_branch(a)?;
+    let b = vb_state.get_branch(b)?;
+    match project_repository.repo().merge_base(a.head, b.head) {
+        Ok(merge_base) => Ok(merge_base),
+        Err(err) if err.code() == ErrorCode::NotFound => Err(anyhow!(
+            "branches '{}' and '{}' have no common ancestor",
+            a.name,
+            b.name
+        ))
+        .context(Marker::UnrelatedHistories),
+        Err(err) => Err(err).context("failed to find merge base"),
+    }
+}
+
+/// Change the order of the branches of the stack `branch_id` is part of to `branch_names`, from
+/// the top of the stack to its bottom, and return the branches in their new order.
+///
+/// `branch_names` must name each branch of the stack exactly once, and as the order of branches
+/// based on each other is given by their commits, only branches that aren't based on each other
+/// can swap places. The new order is persisted as the order the branches are shown in.
+pub fn reorder_stack_branches(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    branch_names: &[String],
+) -> Result<Vec<Branch>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let stack: Vec<_> = branch_stack(project_repository, &default_target, branch_id)?
+        .into_iter()
+        .map(|(branch, _)| branch)
+        .collect();
+
+    let mut top_to_bottom = Vec::with_capacity(branch_names.len());
+    for (index, name) in branch_names.iter().enumerate() {
+        if branch_names[..index].contains(name) {
+            return Err(anyhow!("branch '{name}' is listed more than once"))
+                .context(Code::Validation);
+        }
+        let Some(branch) = stack.iter().find(|branch| branch.name == *name) else {
+            return Err(anyhow!("branch '{name}' isn't part of the stack"))
+                .context(Code::Validation);
+        };
+        top_to_bottom.push(branch);
+    }
+    if let Some(missing) = stack
+        .iter()
+        .find(|branch| !branch_names.contains(&branch.name))
+    {
+        return Err(anyhow!("branch '{}' of the stack is missing", missing.name))
+            .context(Code::Validation);
+    }
+
+    let repo = project_repository.repo();
+    for (index, upper) in top_to_bottom.iter().enumerate() {
+        for lower in &top_to_bottom[index + 1..] {
+            if is_based_on(repo, &default_target, lower, upper)? {
+                return Err(anyhow!(
+                    "branch '{}' is based on branch '{}' and has to be above it",
+                    lower.name,
+                    upper.name
+                ))
+                .context(Code::Validation);
+            }
+        }
+    }
+
+    // the stack keeps the positions it's shown at, only which branch is at which one changes
+    let mut orders: Vec<_> = stack.iter().map(|branch| branch.order).collect();
+    orders.sort();
+    for (branch, order) in top_to_bottom.into_iter().rev().zip(orders) {
+        if branch.order != order {
+            let mut branch = branch.clone();
+            branch.order = order;
+            vb_state.set_branch(branch)?;
+        }
+    }
+
+    let mut branches: Vec<_> = branch_stack(project_repository, &default_target, branch_id)?
+        .into_iter()
+        .map(|(branch, _)| branch)
+        .collect();
+    branches.reverse();
+    Ok(branches)
+}
+
+/// Return the branches of the stack `branch_id` is part of in [topological
+/// order](branch_topological_order()), each with the index of the branch right below it, if any.
+///
+/// The stack consists of `branch_id`, the branches it's based on and the branches based on it.
+fn branch_stack(
+    project_repository: &project_repository::Repository,
+    default_target: &target::Target,
+    branch_id: BranchId,
+) -> Result<Vec<(Branch, Option<usize>)>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let branch = vb_state.get_branch(branch_id)?;
+
+    let repo = project_repository.repo();
+    let is_based_on =
+        |branch: &Branch, base: &Branch| is_based_on(repo, default_target, branch, base);
+
+    let mut stack = Vec::new();
+    for id in branch_topological_order(project_repository)? {
+        let other = vb_state.get_branch(id)?;
+        if other.id == branch.id || is_based_on(&branch, &other)? || is_based_on(&other, &branch)? {
+            stack.push(other);
+        }
+    }
+
+    let mut belows = Vec::with_capacity(stack.len());
+    for (index, current) in stack.iter().enumerate() {
+        let mut below = None;
+        for (base_index, base) in stack[..index].iter().enumerate().rev() {
+            if is_based_on(current, base)? {
+                below = Some(base_index);
+                break;
+            }
+        }
+        belows.push(below);
+    }
+    Ok(stack.into_iter().zip(belows).collect())
+}
+
+/// Return `true` if `branch` contains the commits of `base`, which has commits of its own.
+fn is_based_on(
+    repo: &git2::Repository,
+    default_target: &target::Target,
+    branch: &Branch,
+    base: &Branch,
+) -> Result<bool> {
+    Ok(base.head != default_target.sha
+        && base.id != branch.id
+        && (base.head == branch.head
+            || repo
+                .graph_descendant_of(branch.head, base.head)
+                .context("failed to check ancestry of branches")?))
+}
+
+/// A commit of a virtual branch.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchCommit {
+    pub branch_id: BranchId,
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+}
+
+/// The commits a locked hunk can be amended into, see [`safe_amend_targets()`].
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendTargets {
+    pub targets: Vec<BranchCommit>,
+    /// The commits the hunk is locked to but which it can't be amended into.
+    pub excluded: Vec<ExcludedAmendTarget>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedAmendTarget {
+    pub branch_id: BranchId,
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    /// Why amending into this commit would break the order of the changes.
+    pub reason: String,
+}
+
+/// Return the commits that the worktree `hunk` of `file_path` is locked to and which it can be
+/// amended into without changing the order in which the lines it touches were changed.
+///
+/// A hunk is locked to all commits that changed its lines, so it can only be amended into the
+/// latest of them, as amending into an earlier one would place the change before commits that
+/// depend on these lines. If the locks span more than one branch, there is no such commit.
+pub fn safe_amend_targets(
+    project_repository: &project_repository::Repository,
+    file_path: &Path,
+    hunk: &Hunk,
+) -> Result<AmendTargets> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let git_hunk = statuses
+        .iter()
+        .filter_map(|(_, files)| files.get(file_path))
+        .flatten()
+        .find(|git_hunk| {
+            git_hunk.new_start == hunk.start && git_hunk.new_start + git_hunk.new_lines == hunk.end
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "hunk {}:{hunk} not found in the worktree",
+                file_path.display()
+            )
+        })
+        .context(Code::Validation)?;
+
+    let mut locks: Vec<diff::HunkLock> = Vec::new();
+    for lock in git_hunk.locked_to.iter() {
+        if !locks.contains(lock) {
+            locks.push(lock.clone());
+        }
+    }
+
+    let branch_name = |branch_id: BranchId| {
+        statuses
+            .iter()
+            .find(|(branch, _)| branch.id == branch_id)
+            .map_or_else(|| branch_id.to_string(), |(branch, _)| branch.name.clone())
+    };
+    let repo = project_repository.repo();
+    let mut amend_targets = AmendTargets::default();
+    for lock in &locks {
+        let other_branch = locks.iter().find(|other| other.branch_id != lock.branch_id);
+        let mut later_commit = None;
+        for other in locks
+            .iter()
+            .filter(|other| other.commit_id != lock.commit_id)
+        {
+            if repo.graph_descendant_of(other.commit_id, lock.commit_id)? {
+                later_commit = Some(other.commit_id);
+                break;
+            }
+        }
+
+        let reason = match (other_branch, later_commit) {
+            (Some(other), _) => Some(format!(
+                "the hunk also depends on branch '{}'",
+                branch_name(other.branch_id)
+            )),
+            (None, Some(later)) => Some(format!(
+                "commit {later} changes the same lines later, so the change would be amended below it"
+            )),
+            (None, None) => None,
+        };
+        match reason {
+            Some(reason) => amend_targets.excluded.push(ExcludedAmendTarget {
+                branch_id: lock.branch_id,
+                commit_id: lock.commit_id,
+                reason,
+            }),
+            None => amend_targets.targets.push(BranchCommit {
+                branch_id: lock.branch_id,
+                commit_id: lock.commit_id,
+            }),
+        }
+    }
+    Ok(amend_targets)
+}
+
+/// The change-ids of all commits in the workspace, see [`workspace_change_ids()`].
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceChangeIds {
+    /// The commit each change-id belongs to.
+    pub by_change_id: BTreeMap<String, BranchCommit>,
+    /// The commits without a change-id, like the ones created outside of GitButler.
+    pub without_change_id: Vec<BranchCommit>,
+}
+
+/// Collect the change-ids of the commits of all applied virtual branches that aren't part of the
+/// default target, which identify a commit across rebases and between local and remote branches.
+///
+/// If commits share a change-id, the first one in the order of branches, from tip to base, is used.
+pub fn workspace_change_ids(
+    project_repository: &project_repository::Repository,
+) -> Result<WorkspaceChangeIds> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut branches: Vec<_> = vb_state
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| branch.applied)
+        .collect();
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut change_ids = WorkspaceChangeIds::default();
+    for branch in branches {
+        for commit in project_repository
+            .log(branch.head, LogUntil::Commit(default_target.sha))
+            .context("failed to list branch commits")?
+        {
+            let branch_commit = BranchCommit {
+                branch_id: branch.id,
+                commit_id: commit.id(),
+            };
+            match commit.change_id() {
+                Some(change_id) => {
+                    change_ids
+                        .by_change_id
+                        .entry(change_id)
+                        .or_insert(branch_commit);
+                }
+                None => change_ids.without_change_id.push(branch_commit),
+            }
+        }
+    }
+    Ok(change_ids)
+}
+
+/// A worktree hunk which is locked to commits of more than one branch.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossBranchLockedHunk {
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+    /// The branch the hunk is assigned to.
+    pub owner: BranchId,
+    /// The distinct branches the hunk is locked to, in display order.
+    pub locked_to: Vec<BranchId>,
+}
+
+/// The [`CrossBranchLockedHunk`]s of a single file.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossBranchLockedFile {
+    pub path: PathBuf,
+    pub hunks: Vec<CrossBranchLockedHunk>,
+}
+
+/// Return the worktree hunks whose locks span more than one branch, grouped by file and sorted
+/// by path.
+///
+/// Such hunks depend on changes of several branches at once, so they can't be committed to any of
+/// them without making it depend on another one.
+pub fn cross_branch_locked_hunks(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<CrossBranchLockedFile>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let mut branch_order: Vec<_> = statuses
+        .iter()
+        .map(|(branch, _)| (branch.order, branch.id))
+        .collect();
+    branch_order.sort();
+
+    let mut files = BTreeMap::<PathBuf, Vec<CrossBranchLockedHunk>>::new();
+    for (branch, branch_files) in statuses.iter().filter(|(branch, _)| branch.applied) {
+        for (path, hunks) in branch_files {
+            for hunk in hunks {
+                let locked_to: Vec<_> = branch_order
+                    .iter()
+                    .map(|(_, branch_id)| *branch_id)
+                    .filter(|branch_id| {
+                        hunk.locked_to
+                            .iter()
+                            .any(|lock| lock.branch_id == *branch_id)
+                    })
+                    .collect();
+                if locked_to.len() > 1 {
+                    files
+                        .entry(path.clone())
+                        .or_default()
+                        .push(CrossBranchLockedHunk {
+                            hunk_id: VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines),
+                            owner: branch.id,
+                            locked_to,
+                        });
+                }
+            }
+        }
+    }
+    Ok(files
+        .into_iter()
+        .map(|(path, hunks)| CrossBranchLockedFile { path, hunks })
+        .collect())
+}
+
+/// An uncommitted hunk whose locks would change if a branch was applied.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockImpact {
+    pub path: PathBuf,
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+    /// The commits the hunk is locked to now.
+    pub locked_to_before: Vec<diff::HunkLock>,
+    /// The commits the hunk would be locked to with the branch applied.
+    pub locked_to_after: Vec<diff::HunkLock>,
+}
+
+/// Preview how applying the unapplied virtual branch `branch_id` would change the locks of the
+/// uncommitted hunks of the worktree, and return the hunks whose locks would differ, sorted by
+/// path and position.
+///
+/// Nothing is applied. The locks are computed once for the applied branches and once as if
+/// `branch_id` was applied in addition, for the uncommitted changes as they are now.
+pub fn lock_impact_of_applying(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<Vec<LockImpact>> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut branch = vb_state.get_branch(branch_id)?;
+    if branch.applied {
+        return Err(anyhow!("branch '{}' is already applied", branch.name))
+            .context(Code::Validation);
+    }
+
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let base_diffs: BranchStatus = diff_files_into_hunks(
+        diff::workdir(project_repository.repo(), &integration_commit_id)
+            .context("failed to diff workdir")?,
+    )
+    .collect();
+
+    let mut branches: Vec<_> = vb_state
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| branch.applied)
+        .collect();
+    let locks_before = compute_hunk_locks(
+        project_repository,
+        &integration_commit_id,
+        &default_target.sha,
+        &base_diffs,
+        &branches,
+    )?;
+    branch.applied = true;
+    branches.push(branch);
+    let locks_after = compute_hunk_locks(
+        project_repository,
+        &integration_commit_id,
+        &default_target.sha,
+        &base_diffs,
+        &branches,
+    )?;
+
+    let with_section = project_repository.project().section_aware_locks;
+    let mut impact = Vec::new();
+    for (path, hunks) in &base_diffs {
+        for hunk in hunks {
+            let key = lock_key(path, hunk, with_section);
+            let locked_to_before = locks_before.get(&key).cloned().unwrap_or_default();
+            let locked_to_after = locks_after.get(&key).cloned().unwrap_or_default();
+            let unchanged = locked_to_before.len() == locked_to_after.len()
+                && locked_to_before
+                    .iter()
+                    .all(|lock| locked_to_after.contains(lock));
+            if !unchanged {
+                impact.push((
+                    hunk.new_start,
+                    LockImpact {
+                        path: path.clone(),
+                        hunk_id: VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines),
+                        locked_to_before,
+                        locked_to_after,
+                    },
+                ));
+            }
+        }
+    }
+    impact.sort_by(|(a_start, a), (b_start, b)| (&a.path, a_start).cmp(&(&b.path, b_start)));
+    Ok(impact.into_iter().map(|(_, hunk)| hunk).collect())
+}
+
+/// An uncommitted file that isn't associated with any applied virtual branch.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnownedFile {
+    pub path: PathBuf,
+    pub change_type: diff::ChangeType,
+}
+
+/// Return the uncommitted files, sorted by path, of which no applied virtual branch claims any
+/// hunk and none of whose hunks is locked to a commit.
+///
+/// Unlike the status of the virtual branches this uses the ownership as it is stored, so files
+/// aren't assigned to the default branch first, which lets a brand-new file show up as wholly
+/// unassigned.
+pub fn unowned_files(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<UnownedFile>> {
+    project_repository.assure_worktree()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let file_diffs = diff::workdir(project_repository.repo(), &integration_commit_id)
+        .context("failed to diff workdir")?;
+
+    let branches: Vec<_> = vb_state
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| branch.applied)
+        .collect();
+    let base_diffs: BranchStatus = file_diffs
+        .iter()
+        .map(|(path, file)| (path.clone(), file.hunks.clone()))
+        .collect();
+    let locks = compute_hunk_locks(
+        project_repository,
+        &integration_commit_id,
+        &default_target.sha,
+        &base_diffs,
+        &branches,
+    )?;
+
+    let mut files: Vec<_> = file_diffs
+        .into_iter()
+        .filter(|(path, _)| {
+            !branches.iter().any(|branch| {
+                branch
+                    .ownership
+                    .claims
+                    .iter()
+                    .any(|claim| claim.file_path == *path)
+            })
+        })
+        .filter(|(path, _)| !locks.keys().any(|key| key.path == *path))
+        .map(|(path, file)| {
+            // all hunks of a file share the change type of its delta
+            let change_type = file
+                .hunks
+                .first()
+                .map_or(diff::ChangeType::Modified, |hunk| hunk.change_type);
+            UnownedFile { path, change_type }
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// A worktree hunk whose change is already part of a commit of its branch.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedundantHunk {
+    pub branch_id: BranchId,
+    pub path: PathBuf,
+    /// The id of the hunk, as in [`VirtualBranchHunk::id`].
+    pub hunk_id: String,
+    /// The commit that already contains the change.
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+}
+
+/// Find the worktree hunks of applied branches which add and remove exactly the same lines as a
+/// hunk of the same file in one of the branch's commits, which typically happens when a change is
+/// accidentally applied a second time, so they can be discarded.
+pub fn redundant_worktree_changes(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<RedundantHunk>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (statuses, _) = get_status_by_branch(project_repository, Some(&integration_commit_id))
+        .context("failed to get status by branch")?;
+
+    let repo = project_repository.repo();
+    let mut redundant = Vec::new();
+    for (branch, files) in statuses
+        .iter()
+        .filter(|(branch, files)| branch.applied && !files.is_empty())
+    {
+        let mut committed = Vec::new();
+        for commit in project_repository.log(branch.head, LogUntil::Commit(default_target.sha))? {
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => parent.tree()?,
+                Err(_) => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+            };
+            let commit_diff = diff::trees(repo, &parent_tree, &commit.tree()?)
+                .with_context(|| format!("failed to diff commit {}", commit.id()))?;
+            committed.push((commit.id(), commit_diff));
+        }
+
+        let mut files: Vec<_> = files.iter().collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+        for (path, hunks) in files {
+            for hunk in hunks.iter().filter(|hunk| !hunk.binary) {
+                let changes = diff::changed_lines(&hunk.diff_lines);
+                if changes.is_empty() {
+                    continue;
+                }
+                let commit_id = committed.iter().find_map(|(commit_id, commit_diff)| {
+                    commit_diff
+                        .get(path)?
+                        .hunks
+                        .iter()
+                        .any(|committed| diff::changed_lines(&committed.diff_lines) == changes)
+                        .then_some(*commit_id)
+                });
+                if let Some(commit_id) = commit_id {
+                    redundant.push(RedundantHunk {
+                        branch_id: branch.id,
+                        path: path.clone(),
+                        hunk_id: VirtualBranchHunk::gen_id(hunk.new_start, hunk.new_lines),
+                        commit_id,
+                    });
+                }
+            }
+        }
+    }
+    Ok(redundant)
+}
+
+/// Return the commits of all virtual branches which are integrated into the target branch now,
+/// but weren't yet when it was at `since`, in the order of the branches and from their heads
+/// towards their base.
+///
+/// This is useful to let the user know that commits landed upstream since the target was last
+/// looked at, and can be dropped from their branches.
+pub fn newly_integrated_commits(
+    project_repository: &project_repository::Repository,
+    since: git2::Oid,
+) -> Result<Vec<BranchCommit>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+    let since_tree = repo
+        .find_commit(since)
+        .with_context(|| format!("commit {since} not found"))
+        .context(Code::Validation)?
+        .tree()?;
+    let remote_head = repo
+        .find_branch_by_refname(&default_target.branch.clone().into())?
+        .ok_or(anyhow!("failed to get branch"))?
+        .get()
+        .peel_to_commit()?;
+    // only what landed upstream after `since` can newly integrate a commit
+    let upstream_commits = project_repository.l(remote_head.id(), LogUntil::Commit(since))?;
+    if upstream_commits.is_empty() {
+        return Ok(Vec::new());
+    }
+    let upstream_tree = remote_head.tree()?;
+
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut integrated = Vec::new();
+    for branch in branches {
+        for commit in project_repository.log(branch.head, LogUntil::Commit(default_target.sha))? {
+            let was_integrated = commit.id() == since
+                || repo
+                    .graph_descendant_of(since, commit.id())
+                    .context("failed to check ancestry of commit")?;
+            if was_integrated {
+                continue;
+            }
+            let is_integrated = upstream_commits.contains(&commit.id()) || {
+                let merge_base = repo.merge_base(since, commit.id())?;
+                let base_tree = repo.find_commit(merge_base)?.tree()?;
+                let commit_tree = commit.tree()?;
+                // the changes of commits that were squash-merged before `since` are in it already
+                !merges_without_changes(repo, &base_tree, &commit_tree, &since_tree)?
+                    && merges_without_changes(repo, &base_tree, &commit_tree, &upstream_tree)?
+            };
+            if is_integrated {
+                integrated.push(BranchCommit {
+                    branch_id: branch.id,
+                    commit_id: commit.id(),
+                });
+            }
+        }
+    }
+    Ok(integrated)
+}
+
+/// How much of a virtual branch has landed in the target branch, see [`branch_merge_statuses()`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStatus {
+    /// All commits of the branch are integrated, or the branch was squash-merged.
+    Merged,
+    /// Some, but not all commits of the branch are integrated.
+    PartiallyMerged,
+    /// None of the commits of the branch are integrated.
+    Open,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchMergeStatus {
+    pub branch_id: BranchId,
+    pub status: MergeStatus,
+}
+
+/// Return for each virtual branch with commits whether it was merged into the remote target
+/// branch, in the order of the branches.
+///
+/// A branch whose commits aren't integrated one by one still counts as merged if one of the new
+/// commits of the target introduces exactly the changes of all its commits combined, which is
+/// what squash-merging it does. Merged branches are candidates to be archived.
+pub fn branch_merge_statuses(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<BranchMergeStatus>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+    let remote_head = repo
+        .find_branch_by_refname(&default_target.branch.clone().into())?
+        .ok_or(anyhow!("failed to get branch"))?
+        .get()
+        .peel_to_commit()?;
+    let upstream_commits =
+        project_repository.l(remote_head.id(), LogUntil::Commit(default_target.sha))?;
+
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut statuses = Vec::new();
+    for branch in branches {
+        let commits = project_repository.log(branch.head, LogUntil::Commit(default_target.sha))?;
+        if commits.is_empty() {
+            continue;
+        }
+        let mut integrated = 0;
+        for commit in &commits {
+            if is_commit_integrated(project_repository, &default_target, commit)? {
+                integrated += 1;
+            }
+        }
+        let status = if integrated == commits.len() {
+            MergeStatus::Merged
+        } else if integrated > 0 {
+            MergeStatus::PartiallyMerged
+        } else if is_squash_merged(repo, &default_target, &branch, &upstream_commits)? {
+            MergeStatus::Merged
+        } else {
+            MergeStatus::Open
+        };
+        statuses.push(BranchMergeStatus {
+            branch_id: branch.id,
+            status,
+        });
+    }
+    Ok(statuses)
+}
+
+/// The size of the changes of a single commit, see [`branch_commit_metrics()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMetrics {
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    /// The changes of the commit compared to its first parent.
+    pub stat: DiffStat,
+    /// Whether the commit is integrated into the remote target branch.
+    pub is_integrated: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchCommitMetrics {
+    /// The commits of the branch that aren't part of the default target, starting at the head.
+    pub commits: Vec<CommitMetrics>,
+    /// The sum of the lines changed by all `commits`, with each file that any of them changes
+    /// counted once.
+    pub total: DiffStat,
+}
+
+/// Return how many files and lines each commit of `branch_id` changes and whether it's integrated,
+/// along with the totals of the branch, so commit lists can be shown without querying each commit.
+///
+/// Like with [`list_virtual_branches()`], the commits below an integrated commit are considered
+/// integrated without checking them.
+pub fn branch_commit_metrics(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<BranchCommitMetrics> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+    let repo = project_repository.repo();
+
+    let mut total = DiffStat::default();
+    let mut changed_paths = HashSet::new();
+    let mut is_integrated = false;
+    let mut commits = Vec::new();
+    for commit in project_repository.log(branch.head, LogUntil::Commit(default_target.sha))? {
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)
+            .context("failed to diff commit with its parent")?;
+        let stats = diff.stats()?;
+        changed_paths.extend(diff.deltas().filter_map(|delta| {
+            delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(ToOwned::to_owned)
+        }));
+        total.insertions += stats.insertions();
+        total.deletions += stats.deletions();
+
+        is_integrated =
+            is_integrated || is_commit_integrated(project_repository, &default_target, &commit)?;
+        commits.push(CommitMetrics {
+            commit_id: commit.id(),
+            stat: DiffStat {
+                files_changed: stats.files_changed(),
+                insertions: stats.insertions(),
+                deletions: stats.deletions(),
+            },
+            is_integrated,
+        });
+    }
+    total.files_changed = changed_paths.len();
+
+    Ok(BranchCommitMetrics { commits, total })
+}
+
+/// A virtual branch as listed by [`branches_with_status()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchWithStatus {
+    pub id: BranchId,
+    pub name: String,
+    #[serde(with = "crate::serde::oid")]
+    pub head: git2::Oid,
+    pub applied: bool,
+    /// Whether the head of the branch is integrated into the remote target branch.
+    pub is_integrated: bool,
+}
+
+/// Return all virtual branches in the order they are shown in, each with whether its head was
+/// integrated into the remote target branch, so merged branches can be told apart without
+/// querying each of them.
+///
+/// This is much cheaper than [`branch_merge_statuses()`] as only the head of each branch is
+/// checked, and nothing is written to the object database.
+pub fn branches_with_status(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<BranchWithStatus>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+    branches
+        .into_iter()
+        .map(|branch| {
+            let head = repo
+                .find_commit(branch.head)
+                .context("failed to find branch head")?;
+            Ok(BranchWithStatus {
+                is_integrated: is_commit_integrated(project_repository, &default_target, &head)?,
+                id: branch.id,
+                name: branch.name,
+                head: branch.head,
+                applied: branch.applied,
+            })
+        })
+        .collect()
+}
+
+/// Partition the virtual branches with commits into groups of branches that don't conflict with
+/// each other, so each group can be integrated in one go, in the order of the branches.
+///
+/// Two branches conflict if merging the trees of their heads, with their merge-base as ancestor,
+/// has conflicts. Each branch is added to the first group it doesn't conflict with, so the
+/// grouping is advisory and not necessarily the one with the fewest groups.
+pub fn parallel_integration_groups(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<Vec<BranchId>>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut groups: Vec<Vec<branch::Branch>> = Vec::new();
+    for branch in branches {
+        if project_repository
+            .l(branch.head, LogUntil::Commit(default_target.sha))?
+            .is_empty()
+        {
+            continue;
+        }
+        let mut group_index = None;
+        for (index, group) in groups.iter().enumerate() {
+            let mut conflicts = false;
+            for other in group {
+                if branches_conflict(repo, &branch, other)? {
+                    conflicts = true;
+                    break;
+                }
+            }
+            if !conflicts {
+                group_index = Some(index);
+                break;
+            }
+        }
+        match group_index {
+            Some(index) => groups[index].push(branch),
+            None => groups.push(vec![branch]),
+        }
+    }
+    Ok(groups
+        .into_iter()
+        .map(|group| group.into_iter().map(|branch| branch.id).collect())
+        .collect())
+}
+
+/// Return `true` if the changes of the heads of branches `a` and `b` can't be merged without
+/// conflicts.
+fn branches_conflict(
+    repo: &git2::Repository,
+    a: &branch::Branch,
+    b: &branch::Branch,
+) -> Result<bool> {
+    let merge_base = repo
+        .merge_base(a.head, b.head)
+        .context("failed to find merge base")?;
+    let base_tree = repo.find_commit(merge_base)?.tree()?;
+    let a_tree = repo.find_commit(a.head)?.tree()?;
+    let b_tree = repo.find_commit(b.head)?.tree()?;
+    Ok(repo
+        .merge_trees(&base_tree, &a_tree, &b_tree, None)
+        .context("failed to merge trees")?
+        .has_conflicts())
+}
 
-    let mut vbranch = vb_state.get_branch(branch_id)?;
-    let remote_branch = if let Some(upstream_branch) = &vbranch.upstream {
-        upstream_branch.clone()
-    } else {
-        let default_target = vb_state.get_default_target()?;
-        let upstream_remote = match default_target.push_remote_name {
-            Some(remote) => remote.clone(),
-            None => default_target.branch.remote().to_owned(),
-        };
+/// A branch to integrate as part of a [`IntegrationOrder`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationStep {
+    pub branch_id: BranchId,
+    /// The branches of the previous steps that the branch is expected to conflict with when it's
+    /// integrated after them.
+    pub conflicting_branches: Vec<BranchId>,
+}
 
-        let remote_branch = format!(
-            "refs/remotes/{}/{}",
-            upstream_remote,
-            normalize_branch_name(&vbranch.name)
-        )
-        .parse::<git::RemoteRefname>()
-        .context("failed to parse remote branch name")?;
+/// An order to integrate branches in, see [`suggested_integration_order()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationOrder {
+    pub steps: Vec<IntegrationStep>,
+    /// The amount of conflicting branches of all steps.
+    pub expected_conflicts: usize,
+}
 
-        let remote_branches = project_repository.git_remote_branches()?;
-        let existing_branches = remote_branches
+/// Suggest an order to integrate the virtual branches with commits in, one after another, which
+/// keeps the conflicts to resolve on the way low.
+///
+/// At each step, the branch that conflicts with the fewest branches of the previous steps is
+/// chosen, among those whose bases are integrated already, see [`branch_topological_order()`].
+/// Ties are broken by that order. Conflicts are those of each pair of branches, as used by
+/// [`parallel_integration_groups()`], so the result is an estimate, not a plan that is guaranteed
+/// to be optimal.
+pub fn suggested_integration_order(
+    project_repository: &project_repository::Repository,
+) -> Result<IntegrationOrder> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+
+    let mut branches = Vec::new();
+    for branch in vb_state.list_branches()? {
+        if !project_repository
+            .l(branch.head, LogUntil::Commit(default_target.sha))?
+            .is_empty()
+        {
+            branches.push(branch);
+        }
+    }
+    let topological_order = branch_topological_order(project_repository)?;
+    branches.sort_by_key(|branch| {
+        topological_order
             .iter()
-            .map(RemoteRefname::branch)
-            .map(str::to_lowercase) // git is weird about case sensitivity here, assume not case sensitive
-            .collect::<Vec<_>>();
+            .position(|branch_id| *branch_id == branch.id)
+    });
+    let bases = branch_bases(repo, default_target.sha, &branches)?;
+
+    let mut conflicts = vec![vec![false; branches.len()]; branches.len()];
+    for a in 0..branches.len() {
+        for b in a + 1..branches.len() {
+            let conflict = branches_conflict(repo, &branches[a], &branches[b])?;
+            conflicts[a][b] = conflict;
+            conflicts[b][a] = conflict;
+        }
+    }
 
-        remote_branch.with_branch(&dedup_fmt(
-            &existing_branches
-                .iter()
-                .map(String::as_str)
-                .collect::<Vec<_>>(),
-            remote_branch.branch(),
-            "-",
-        ))
+    let mut integrated = Vec::with_capacity(branches.len());
+    let mut order = IntegrationOrder {
+        steps: Vec::with_capacity(branches.len()),
+        expected_conflicts: 0,
     };
+    while integrated.len() < branches.len() {
+        let mut best: Option<(usize, Vec<BranchId>)> = None;
+        for index in 0..branches.len() {
+            if integrated.contains(&index)
+                || !bases[index].iter().all(|base| integrated.contains(base))
+            {
+                continue;
+            }
+            let conflicting_branches: Vec<_> = integrated
+                .iter()
+                .filter(|&&other| conflicts[index][other])
+                .map(|&other| branches[other].id)
+                .collect();
+            if best.as_ref().map_or(true, |(_, best_branches)| {
+                conflicting_branches.len() < best_branches.len()
+            }) {
+                best = Some((index, conflicting_branches));
+            }
+        }
+        let (index, conflicting_branches) =
+            best.context("branches are based on each other and can't be ordered")?;
+
+        integrated.push(index);
+        order.expected_conflicts += conflicting_branches.len();
+        order.steps.push(IntegrationStep {
+            branch_id: branches[index].id,
+            conflicting_branches,
+        });
+    }
+    Ok(order)
+}
 
-    project_repository.push(
-        &vbranch.head,
-        &remote_branch,
-        with_force,
-        credentials,
-        None,
-        askpass,
-    )?;
+/// A virtual branch that wasn't worked on for a while, see [`stale_branches()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleBranch {
+    pub branch_id: BranchId,
+    /// How long ago the head of the branch was committed, in seconds.
+    pub tip_age_seconds: u64,
+    /// The amount of commits of the branch that aren't part of the default target.
+    pub commit_count: usize,
+}
 
-    vbranch.upstream = Some(remote_branch.clone());
-    vbranch.upstream_head = Some(vbranch.head);
-    vb_state
-        .set_branch(vbranch.clone())
-        .context("failed to write target branch after push")?;
-    project_repository.fetch(
-        remote_branch.remote(),
-        credentials,
-        askpass.map(|_| "modal".to_string()),
-    )?;
+/// Return the virtual branches with commits whose head was committed more than `older_than` ago,
+/// oldest first, as they are likely abandoned.
+pub fn stale_branches(
+    project_repository: &project_repository::Repository,
+    older_than: std::time::Duration,
+) -> Result<Vec<StaleBranch>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let repo = project_repository.repo();
+    let now_seconds = now_since_unix_epoch_ms() / 1000;
 
-    Ok(())
+    let mut stale = Vec::new();
+    for branch in vb_state.list_branches()? {
+        let commits = project_repository.l(branch.head, LogUntil::Commit(default_target.sha))?;
+        if commits.is_empty() {
+            continue;
+        }
+        let head = repo
+            .find_commit(branch.head)
+            .context("failed to find branch head")?;
+        // commits from the future, e.g. due to clock skew, are as fresh as they get
+        let tip_age_seconds = u64::try_from(now_seconds - head.time().seconds()).unwrap_or(0);
+        if tip_age_seconds > older_than.as_secs() {
+            stale.push(StaleBranch {
+                branch_id: branch.id,
+                tip_age_seconds,
+                commit_count: commits.len(),
+            });
+        }
+    }
+    stale.sort_by(|a, b| b.tip_age_seconds.cmp(&a.tip_age_seconds));
+    Ok(stale)
+}
+
+/// A virtual branch whose upstream no longer exists on the remote, see
+/// [`stale_remote_branches()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleRemoteBranch {
+    pub branch_id: BranchId,
+    pub branch_name: String,
+    /// The remote-tracking ref the branch is still associated with.
+    pub missing_ref: git::RemoteRefname,
+}
+
+/// Return the virtual branches with an upstream whose remote-tracking ref doesn't exist anymore,
+/// e.g. because the branch was deleted on the remote, in the order they appear in the workspace.
+///
+/// Only local refs are checked, so a fetch that prunes deleted branches must have happened before
+/// for this to be accurate.
+pub fn stale_remote_branches(
+    project_repository: &project_repository::Repository,
+) -> Result<Vec<StaleRemoteBranch>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let repo = project_repository.repo();
+
+    let mut branches = vb_state.list_branches()?;
+    branches.sort_by_key(|branch| branch.order);
+
+    let mut stale = Vec::new();
+    for branch in branches {
+        let Some(upstream) = branch.upstream else {
+            continue;
+        };
+        match repo.find_reference(&upstream.to_string()) {
+            Ok(_) => {}
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                stale.push(StaleRemoteBranch {
+                    branch_id: branch.id,
+                    branch_name: branch.name,
+                    missing_ref: upstream,
+                });
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to find reference {upstream}"))
+            }
+        }
+    }
+    Ok(stale)
+}
+
+/// How sure [`emptied_by_integration()`] is that a commit would become empty.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EmptyCommitConfidence {
+    /// The commit is part of the target, or its changes are a no-op on top of it.
+    Certain,
+    /// The branch looks squash-merged, so the commit's changes are likely in the target, but
+    /// they don't apply cleanly on their own, e.g. because later commits of the branch changed
+    /// the same lines.
+    SquashMerged,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmptiedCommit {
+    #[serde(with = "crate::serde::oid")]
+    pub commit_id: git2::Oid,
+    pub confidence: EmptyCommitConfidence,
+}
+
+/// Return the commits of virtual branch `branch_id`, from its head down, whose changes are already
+/// fully present in the remote target branch, so they would become empty when the branch is
+/// rebased onto it and could be dropped instead.
+///
+/// Each commit is checked by merging its changes into the tree of the target in memory, nothing
+/// is written to the object database.
+pub fn emptied_by_integration(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<Vec<EmptiedCommit>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+    let repo = project_repository.repo();
+    let remote_head = repo
+        .find_branch_by_refname(&default_target.branch.clone().into())?
+        .ok_or(anyhow!("failed to get branch"))?
+        .get()
+        .peel_to_commit()?;
+    let upstream_commits =
+        project_repository.l(remote_head.id(), LogUntil::Commit(default_target.sha))?;
+    if upstream_commits.is_empty() {
+        // nothing new upstream, so nothing can have been integrated.
+        return Ok(Vec::new());
+    }
+    let upstream_tree = remote_head.tree()?;
+    let squash_merged = is_squash_merged(repo, &default_target, &branch, &upstream_commits)?;
+
+    let mut emptied = Vec::new();
+    for commit in project_repository.log(branch.head, LogUntil::Commit(default_target.sha))? {
+        let is_noop = upstream_commits.contains(&commit.id())
+            || merges_without_changes(
+                repo,
+                &commit.parent(0)?.tree()?,
+                &commit.tree()?,
+                &upstream_tree,
+            )?;
+        let confidence = if is_noop {
+            EmptyCommitConfidence::Certain
+        } else if squash_merged {
+            EmptyCommitConfidence::SquashMerged
+        } else {
+            continue;
+        };
+        emptied.push(EmptiedCommit {
+            commit_id: commit.id(),
+            confidence,
+        });
+    }
+    Ok(emptied)
+}
+
+/// Return `true` if merging the changes from `base_tree` to `tree` into `onto_tree` is clean and
+/// leaves `onto_tree` as it is, as it has these changes already.
+fn merges_without_changes(
+    repo: &git2::Repository,
+    base_tree: &git2::Tree,
+    tree: &git2::Tree,
+    onto_tree: &git2::Tree,
+) -> Result<bool> {
+    let merge_index = repo
+        .merge_trees(base_tree, tree, onto_tree, None)
+        .context("failed to merge trees")?;
+    Ok(!merge_index.has_conflicts() && index_matches_tree(repo, &merge_index, onto_tree)?)
+}
+
+/// Return `true` if `index` has exactly the entries of `tree`. Comparing the index directly
+/// avoids writing it as tree, which is expensive when checking many commits.
+fn index_matches_tree(
+    repo: &git2::Repository,
+    index: &git2::Index,
+    tree: &git2::Tree,
+) -> Result<bool> {
+    let changes = repo
+        .diff_tree_to_index(Some(tree), Some(index), None)
+        .context("failed to compare merged tree")?;
+    Ok(changes.deltas().len() == 0)
+}
+
+/// Return `true` if one of the `upstream_commits` changes the same lines of the same files as all
+/// commits of `branch` combined.
+fn is_squash_merged(
+    repo: &git2::Repository,
+    target: &target::Target,
+    branch: &branch::Branch,
+    upstream_commits: &[git2::Oid],
+) -> Result<bool> {
+    let merge_base = repo
+        .merge_base(target.sha, branch.head)
+        .context("failed to find merge base")?;
+    let base_tree = repo.find_commit(merge_base)?.tree()?;
+    let head_tree = repo.find_commit(branch.head)?.tree()?;
+    let branch_changes = diff::changed_lines_by_path(trees(repo, &base_tree, &head_tree)?);
+    if branch_changes.is_empty() {
+        return Ok(false);
+    }
+
+    for commit_id in upstream_commits {
+        let commit = repo.find_commit(*commit_id)?;
+        let Ok(parent) = commit.parent(0) else {
+            continue;
+        };
+        let commit_changes =
+            diff::changed_lines_by_path(trees(repo, &parent.tree()?, &commit.tree()?)?);
+        if commit_changes == branch_changes {
+            return Ok(true);
+        }
+    }
+    Ok(false)
 }
 
 fn is_commit_integrated(
@@ -2502,23 +4996,25 @@ fn is_commit_integrated(
         return Ok(true);
     }
 
-    // try to merge our tree into the upstream tree
-    let mut merge_index = project_repository
-        .repo()
-        .merge_trees(&merge_base_tree, &commit.tree()?, &upstream_tree, None)
+    // try to merge our tree into the upstream tree, keeping the blobs of merged files in memory
+    let repo = in_memory_repository(project_repository.repo())?;
+    let upstream_tree = repo.find_tree(upstream_tree.id())?;
+    let merge_index = repo
+        .merge_trees(
+            &repo.find_tree(merge_base_tree.id())?,
+            &repo.find_tree(commit.tree_id())?,
+            &upstream_tree,
+            None,
+        )
         .context("failed to merge trees")?;
 
     if merge_index.has_conflicts() {
         return Ok(false);
     }
 
-    let merge_tree_oid = merge_index
-        .write_tree_to(project_repository.repo())
-        .context("failed to write tree")?;
-
     // if the merge_tree is the same as the new_target_tree and there are no files (uncommitted changes)
-    // then the vbranch is fully merged
-    Ok(merge_tree_oid == upstream_tree.id())
+    // then the vbranch is fully merged.
+    index_matches_tree(&repo, &merge_index, &upstream_tree)
 }
 
 pub fn is_remote_branch_mergeable(
@@ -2621,6 +5117,7 @@ pub fn move_commit_file(
     to_commit_id: git2::Oid,
     target_ownership: &BranchOwnershipClaims,
 ) -> Result<git2::Oid> {
+    project_repository.assure_worktree()?;
     let vb_state = project_repository.project().virtual_branches();
 
     let Some(mut target_branch) = vb_state.try_branch(branch_id)? else {
@@ -2920,12 +5417,6 @@ pub fn amend(
         bail!("branch has no commits - there is nothing to amend to");
     }
 
-    // find commit oid
-    let amend_commit = project_repository
-        .repo()
-        .find_commit(commit_oid)
-        .context("failed to find commit")?;
-
     let diffs_to_amend = target_ownership
         .claims
         .iter()
@@ -2957,8 +5448,55 @@ pub fn amend(
         bail!("target ownership not found");
     }
 
+    amend_with_hunks(
+        project_repository,
+        &vb_state,
+        target_branch,
+        commit_oid,
+        &diffs_to_amend,
+    )
+}
+
+/// Add `diffs_to_amend` to the tree of `commit_oid` of `target_branch`, rebase the commits above
+/// it and return the id of the amended commit.
+fn amend_with_hunks(
+    project_repository: &project_repository::Repository,
+    vb_state: &VirtualBranchesHandle,
+    target_branch: &mut Branch,
+    commit_oid: git2::Oid,
+    diffs_to_amend: &HashMap<PathBuf, Vec<diff::GitHunk>>,
+) -> Result<git2::Oid> {
+    let (commit_oid, new_head) = amend_commit_with_hunks(
+        project_repository,
+        target_branch.head,
+        commit_oid,
+        diffs_to_amend,
+    )?;
+    target_branch.head = new_head;
+    vb_state.set_branch(target_branch.clone())?;
+    super::integration::update_gitbutler_integration(vb_state, project_repository)?;
+    invalidate_lock_ranges(project_repository);
+    Ok(commit_oid)
+}
+
+/// Add `diffs_to_amend` to the tree of `commit_oid`, and rebase the commits between it and `head`
+/// onto the amended commit, without updating any branch.
+///
+/// Return the ids of the amended commit and of the commit that takes the place of `head`.
+fn amend_commit_with_hunks(
+    project_repository: &project_repository::Repository,
+    head: git2::Oid,
+    commit_oid: git2::Oid,
+    diffs_to_amend: &HashMap<PathBuf, Vec<diff::GitHunk>>,
+) -> Result<(git2::Oid, git2::Oid)> {
+    // find commit oid
+    let amend_commit = project_repository
+        .repo()
+        .find_commit(commit_oid)
+        .context("failed to find commit")?;
+
     // apply diffs_to_amend to the commit tree
-    let new_tree_oid = write_tree_onto_commit(project_repository, commit_oid, &diffs_to_amend)?;
+    let new_tree_oid = write_tree_onto_commit(project_repository, commit_oid, diffs_to_amend)?;
     let new_tree = project_repository
         .repo()
         .find_tree(new_tree_oid)
@@ -2978,36 +5516,322 @@ pub fn amend(
         )
         .context("failed to create commit")?;
 
-    // now rebase upstream commits, if needed
-    let upstream_commits = project_repository.l(
+    // now rebase upstream commits, if needed
+    let upstream_commits = project_repository.l(
+        head,
+        project_repository::LogUntil::Commit(amend_commit.id()),
+    )?;
+    // if there are no upstream commits, we're done
+    let Some(last_commit) = upstream_commits.first().copied() else {
+        return Ok((commit_oid, commit_oid));
+    };
+
+    let new_head = cherry_rebase(
+        project_repository,
+        commit_oid,
+        amend_commit.id(),
+        last_commit,
+    )?
+    .ok_or_else(|| anyhow!("rebase failed"))?;
+    Ok((commit_oid, new_head))
+}
+
+/// One part of a hunk split by [`split_hunk()`], along with the commit it was amended to.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitHunk {
+    /// The identity of the part as it was applied to its commit.
+    pub hunk: HunkIdentity,
+    pub locked_to: diff::HunkLock,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitHunks {
+    /// The selected lines, amended to the first commit.
+    pub first: SplitHunk,
+    /// The remaining changes of the hunk, amended to the second commit.
+    pub second: SplitHunk,
+}
+
+/// Amend the changed lines of the uncommitted `hunk` of `branch_id` which are selected by
+/// `old_lines`, the numbers of removed lines, and `new_lines`, the numbers of added lines, to
+/// `first_commit`, and the remaining changes of the hunk to `second_commit`.
+///
+/// The part for the older of both commits is turned into a patch against the hunk's original
+/// content, and the other part into a patch on top of it. It's an error if a line number doesn't
+/// belong to a changed line of the hunk, or if the selection leaves one of the commits without
+/// changes.
+pub fn split_hunk(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    hunk: &HunkIdentity,
+    old_lines: &[u32],
+    new_lines: &[u32],
+    first_commit: git2::Oid,
+    second_commit: git2::Oid,
+) -> Result<SplitHunks> {
+    project_repository.assure_resolved()?;
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+
+    let applied_branches = vb_state
+        .list_branches()
+        .context("failed to read virtual branches")?
+        .into_iter()
+        .filter(|b| b.applied)
+        .collect::<Vec<_>>();
+    let integration_commit_id = get_workspace_head(&vb_state, project_repository)?;
+    let (mut applied_statuses, _) = get_applied_status(
+        project_repository,
+        &integration_commit_id,
+        &default_target.sha,
+        applied_branches,
+    )?;
+
+    let (ref mut target_branch, target_status) = applied_statuses
+        .iter_mut()
+        .find(|(b, _)| b.id == branch_id)
+        .ok_or_else(|| anyhow!("could not find applied branch with id {branch_id}"))?;
+
+    if target_branch.upstream.is_some() && !project_repository.project().ok_with_force_push {
+        // amending to a pushed commit will cause a force push that is not allowed
+        bail!("force-push is not allowed");
+    }
+
+    if first_commit == second_commit {
+        return Err(anyhow!(
+            "the parts of a hunk must go to two different commits"
+        ))
+        .context(Code::Validation);
+    }
+    let commit_ids =
+        project_repository.l(target_branch.head, LogUntil::Commit(default_target.sha))?;
+    let position = |commit_id: git2::Oid| {
+        commit_ids
+            .iter()
+            .position(|id| *id == commit_id)
+            .ok_or_else(|| anyhow!("commit {commit_id} is not part of branch {branch_id}"))
+            .context(Code::Validation)
+    };
+    let first_index = position(first_commit)?;
+    let second_index = position(second_commit)?;
+
+    let git_hunk = target_status
+        .get(&hunk.path)
+        .into_iter()
+        .flatten()
+        .find(|git_hunk| HunkIdentity::new(&hunk.path, git_hunk) == *hunk)
+        .ok_or_else(|| {
+            anyhow!(
+                "hunk at {}:{} not found in branch {branch_id}",
+                hunk.path.display(),
+                hunk.new_start
+            )
+        })
+        .context(Code::Validation)?;
+    if git_hunk.binary || git_hunk.change_type != diff::ChangeType::Modified {
+        return Err(anyhow!("only hunks of modified text files can be split"))
+            .context(Code::Validation);
+    }
+
+    // commits are listed head first, so the older one has the higher index
+    let first_is_lower = first_index > second_index;
+    let (lower_index, upper_index) = if first_is_lower {
+        (first_index, second_index)
+    } else {
+        (second_index, first_index)
+    };
+    let lower_commit = commit_ids[lower_index];
+    if project_repository
+        .repo()
+        .find_commit(lower_commit)?
+        .tree()?
+        .get_path(&hunk.path)
+        .is_err()
+    {
+        return Err(anyhow!(
+            "{} doesn't exist in commit {lower_commit}",
+            hunk.path.display()
+        ))
+        .context(Code::Validation);
+    }
+
+    let (lower_patch, upper_patch) =
+        split_hunk_lines(git_hunk, old_lines, new_lines, first_is_lower)?;
+
+    // both commits are amended before the branch is updated, so it's never left half-split
+    let (lower_commit, head) = amend_commit_with_hunks(
+        project_repository,
         target_branch.head,
-        project_repository::LogUntil::Commit(amend_commit.id()),
+        lower_commit,
+        &HashMap::from([(hunk.path.clone(), vec![lower_patch.clone()])]),
     )?;
-    // if there are no upstream commits, we're done
-    if upstream_commits.is_empty() {
-        target_branch.head = commit_oid;
-        vb_state.set_branch(target_branch.clone())?;
-        super::integration::update_gitbutler_integration(&vb_state, project_repository)?;
-        return Ok(commit_oid);
-    }
-
-    let last_commit = upstream_commits.first().cloned().unwrap();
+    // the upper commit was rebased onto the amended one, but kept its position in the branch
+    let upper_commit = project_repository
+        .l(head, LogUntil::Commit(default_target.sha))?
+        .get(upper_index)
+        .copied()
+        .context("failed to find rebased commit")?;
+    let (upper_commit, head) = amend_commit_with_hunks(
+        project_repository,
+        head,
+        upper_commit,
+        &HashMap::from([(hunk.path.clone(), vec![upper_patch.clone()])]),
+    )?;
+    target_branch.head = head;
+    vb_state.set_branch(target_branch.clone())?;
+    super::integration::update_gitbutler_integration(&vb_state, project_repository)?;
+    invalidate_lock_ranges(project_repository);
 
-    let new_head = cherry_rebase(
+    let part = |patch: &GitHunk, commit_id: git2::Oid| SplitHunk {
+        hunk: HunkIdentity::new(&hunk.path, patch),
+        locked_to: diff::HunkLock::new(branch_id, commit_id),
+    };
+    let mut lower = part(&lower_patch, lower_commit);
+    let mut upper = part(&upper_patch, upper_commit);
+    describe_locked_commits(
         project_repository,
-        commit_oid,
-        amend_commit.id(),
-        last_commit,
+        [&mut lower.locked_to, &mut upper.locked_to],
     )?;
+    let (first, second) = if first_is_lower {
+        (lower, upper)
+    } else {
+        (upper, lower)
+    };
+    Ok(SplitHunks { first, second })
+}
 
-    if let Some(new_head) = new_head {
-        target_branch.head = new_head;
-        vb_state.set_branch(target_branch.clone())?;
-        super::integration::update_gitbutler_integration(&vb_state, project_repository)?;
-        Ok(commit_oid)
+/// Split the changes of `hunk` into two patches, the first of which applies to the content before
+/// the hunk and the second on top of the first.
+///
+/// The first patch has the removed lines at `old_lines` and the added lines at `new_lines` if
+/// `selected_first` is set, or all other changed lines if not. Both patches start where the hunk
+/// starts in the original content, as that's what they are applied to.
+fn split_hunk_lines(
+    hunk: &GitHunk,
+    old_lines: &[u32],
+    new_lines: &[u32],
+    selected_first: bool,
+) -> Result<(GitHunk, GitHunk)> {
+    // a side without lines starts at the line before the hunk
+    let first_old_line = if hunk.old_lines == 0 {
+        hunk.old_start + 1
     } else {
-        Err(anyhow!("rebase failed"))
+        hunk.old_start
+    };
+    let first_new_line = if hunk.new_lines == 0 {
+        hunk.new_start + 1
+    } else {
+        hunk.new_start
+    };
+
+    let (mut old_line, mut new_line) = (first_old_line, first_new_line);
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut first, mut second) = (BString::default(), BString::default());
+    // whether the previous line is part of each patch, which a `\ No newline` marker belongs to
+    let mut previous = (false, false);
+    for line in hunk.diff_lines.lines_with_terminator().skip(1) {
+        let (first_prefix, second_prefix) = match line.first() {
+            Some(b'-') => {
+                let in_first = old_lines.contains(&old_line) == selected_first;
+                removed.push(old_line);
+                old_line += 1;
+                if in_first {
+                    (Some(b'-'), None)
+                } else {
+                    (Some(b' '), Some(b'-'))
+                }
+            }
+            Some(b'+') => {
+                let in_first = new_lines.contains(&new_line) == selected_first;
+                added.push(new_line);
+                new_line += 1;
+                if in_first {
+                    (Some(b'+'), Some(b' '))
+                } else {
+                    (None, Some(b'+'))
+                }
+            }
+            Some(b'\\') => (previous.0.then_some(b'\\'), previous.1.then_some(b'\\')),
+            _ => {
+                old_line += 1;
+                new_line += 1;
+                (Some(b' '), Some(b' '))
+            }
+        };
+        let content = line.get(1..).unwrap_or_default();
+        for (patch, prefix, is_part) in [
+            (&mut first, first_prefix, &mut previous.0),
+            (&mut second, second_prefix, &mut previous.1),
+        ] {
+            *is_part = prefix.is_some();
+            if let Some(prefix) = prefix {
+                patch.push(prefix);
+                patch.push_str(content);
+            }
+        }
+    }
+
+    if let Some(line) = old_lines.iter().find(|line| !removed.contains(line)) {
+        return Err(anyhow!("line {line} isn't removed by the hunk")).context(Code::Validation);
+    }
+    if let Some(line) = new_lines.iter().find(|line| !added.contains(line)) {
+        return Err(anyhow!("line {line} isn't added by the hunk")).context(Code::Validation);
+    }
+
+    let count = |patch: &BString, side: u8| {
+        patch
+            .lines_with_terminator()
+            .filter(
+                |line| matches!(line.first(), Some(&prefix) if prefix == side || prefix == b' '),
+            )
+            .count() as u32
+    };
+    let has_changes = |patch: &BString| {
+        patch
+            .lines_with_terminator()
+            .any(|line| matches!(line.first(), Some(b'+' | b'-')))
+    };
+    if !has_changes(&first) || !has_changes(&second) {
+        return Err(anyhow!(
+            "the selected lines must leave changes of the hunk for both commits"
+        ))
+        .context(Code::Validation);
     }
+
+    let start = |lines: u32| {
+        if lines == 0 {
+            first_old_line - 1
+        } else {
+            first_old_line
+        }
+    };
+    let to_hunk = |old_start: u32, old_lines: u32, new_lines: u32, body: BString| {
+        let new_start = start(new_lines);
+        let mut diff_lines: BString =
+            format!("@@ -{old_start},{old_lines} +{new_start},{new_lines} @@\n").into();
+        diff_lines.push_str(body);
+        GitHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            diff_lines,
+            binary: false,
+            locked_to: Box::new([]),
+            change_type: hunk.change_type,
+        }
+    };
+    let middle_lines = count(&first, b'+');
+    let first = to_hunk(hunk.old_start, count(&first, b'-'), middle_lines, first);
+    let second = to_hunk(
+        start(middle_lines),
+        middle_lines,
+        count(&second, b'+'),
+        second,
+    );
+    Ok((first, second))
 }
 
 // move a given commit in a branch up one or down one
@@ -3306,10 +6130,13 @@ fn cherry_rebase_group(
     Ok(new_head_id)
 }
 
+/// Cherry-pick `target_commit_id` onto `branch_id`. Conflicts are written with markers in
+/// `conflict_style`, or in the style configured in `merge.conflictStyle` if it's `None`.
 pub fn cherry_pick(
     project_repository: &project_repository::Repository,
     branch_id: BranchId,
     target_commit_id: git2::Oid,
+    conflict_style: Option<conflicts::ConflictStyle>,
 ) -> Result<Option<git2::Oid>> {
     project_repository.assure_unconflicted()?;
 
@@ -3412,7 +6239,10 @@ pub fn cherry_pick(
             .repo()
             .checkout_index_builder(&mut cherrypick_index)
             .allow_conflicts()
-            .conflict_style_merge()
+            .conflict_style(conflicts::ConflictStyle::or_from_config(
+                conflict_style,
+                project_repository.repo(),
+            ))
             .force()
             .checkout()
             .context("failed to checkout conflicts")?;
@@ -3584,6 +6414,231 @@ pub fn squash(
     }
 }
 
+/// What squashing a commit into its parent would result in, see [`preview_squash()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SquashPreview {
+    /// The message of the squashed commit, combined from the messages of both commits.
+    pub message: String,
+    /// The paths that would conflict when rebasing the commits above onto the squashed commit.
+    pub conflicting_paths: Vec<PathBuf>,
+}
+
+/// Preview what [`squash()`] would do to `commit_id` of `branch_id` without changing anything.
+///
+/// The squashed commit has the tree of `commit_id`, so it can't conflict, but the commits above it
+/// are rebased onto it in memory to be sure. It's an error if the commit can't be squashed at all,
+/// like the first commit of the branch.
+pub fn preview_squash(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    commit_id: git2::Oid,
+) -> Result<SquashPreview> {
+    let vb_state = project_repository.project().virtual_branches();
+    let branch = vb_state.get_branch(branch_id)?;
+    let default_target = vb_state.get_default_target()?;
+    let branch_commit_oids =
+        project_repository.l(branch.head, LogUntil::Commit(default_target.sha))?;
+
+    if !branch_commit_oids.contains(&commit_id) {
+        bail!("commit {commit_id} not in the branch")
+    }
+
+    let repo = project_repository.repo();
+    let commit_to_squash = repo
+        .find_commit(commit_id)
+        .context("failed to find commit")?;
+    let parent_commit = commit_to_squash
+        .parent(0)
+        .context("failed to find parent commit")?;
+    if !branch_commit_oids.contains(&parent_commit.id()) {
+        bail!("can not squash root commit");
+    }
+
+    let message = format!(
+        "{}\n{}",
+        parent_commit.message_bstr(),
+        commit_to_squash.message_bstr(),
+    );
+
+    let ids_to_rebase: Vec<_> = branch_commit_oids
+        .iter()
+        .take_while(|id| **id != commit_id)
+        .copied()
+        .collect();
+    let mut conflicting_paths = Vec::new();
+    add_rebase_conflicts(
+        repo,
+        commit_to_squash.tree_id(),
+        &ids_to_rebase,
+        &mut conflicting_paths,
+    )?;
+    conflicting_paths.sort();
+
+    Ok(SquashPreview {
+        message,
+        conflicting_paths,
+    })
+}
+
+/// What moving a commit to another position within its branch would result in, see
+/// [`preview_reorder()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderPreview {
+    /// The paths that would conflict when rebasing the commits in their new order, which is empty
+    /// if the move is clean.
+    pub conflicting_paths: Vec<PathBuf>,
+    /// Whether a commit that changes something would end up changing nothing, as its changes are
+    /// already contained in the commits now below it.
+    pub leaves_empty_commit: bool,
+}
+
+/// Preview moving the commit at `from_index` of `branch_id` to `to_index` without changing
+/// anything, so it can be checked before the commits are actually reordered.
+///
+/// Indices refer to the commits of the branch that aren't part of the default target, with the
+/// head at index 0. All commits from the lowest moved position upward are rebased in memory in
+/// their new order.
+pub fn preview_reorder(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+    from_index: usize,
+    to_index: usize,
+) -> Result<ReorderPreview> {
+    let vb_state = project_repository.project().virtual_branches();
+    let branch = vb_state.get_branch(branch_id)?;
+    let default_target = vb_state.get_default_target()?;
+    let mut commit_ids = project_repository.l(branch.head, LogUntil::Commit(default_target.sha))?;
+
+    if from_index >= commit_ids.len() || to_index >= commit_ids.len() {
+        return Err(anyhow!(
+            "can't move commit {from_index} to {to_index} in a branch of {} commits",
+            commit_ids.len()
+        ))
+        .context(Code::Validation);
+    }
+
+    let repo = project_repository.repo();
+    let lowest_index = from_index.max(to_index);
+    let onto_tree = repo
+        .find_commit(commit_ids[lowest_index])?
+        .parent(0)
+        .context("failed to find parent of moved commits")?
+        .tree_id();
+    let moved_id = commit_ids.remove(from_index);
+    commit_ids.insert(to_index, moved_id);
+
+    let mut conflicting_paths = Vec::new();
+    let leaves_empty_commit = add_rebase_conflicts(
+        repo,
+        onto_tree,
+        &commit_ids[..=lowest_index],
+        &mut conflicting_paths,
+    )?;
+    conflicting_paths.sort();
+
+    Ok(ReorderPreview {
+        conflicting_paths,
+        leaves_empty_commit,
+    })
+}
+
+/// Add the path of each conflict in `merge_index` to `conflicting_paths`, unless it's listed
+/// already.
+fn add_conflicting_paths(
+    merge_index: &git2::Index,
+    conflicting_paths: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for conflict in merge_index.conflicts()?.flatten() {
+        let path = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| entry.path.to_path_lossy().into_owned())
+            .next();
+        if let Some(path) = path.filter(|path| !conflicting_paths.contains(path)) {
+            conflicting_paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// A reason why squashing all commits of a branch into one is unsafe, see [`can_squash_branch()`].
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SquashBlocker {
+    /// The branch has less than two commits, so there is nothing to squash.
+    NothingToSquash,
+    /// The project has unresolved conflicts.
+    Conflicted,
+    /// Commits of the branch were pushed, and force-pushing is not allowed.
+    ForcePushRequired,
+    /// These branches are built on top of the branch, and would lose the commits they are based on.
+    #[serde(rename_all = "camelCase")]
+    StackedBranches { branch_ids: Vec<BranchId> },
+    /// Commits of the branch are signed, but the squashed commit wouldn't be.
+    SignaturesLost,
+}
+
+/// Check if all commits of `branch_id` can be squashed into a single commit, and return why it's
+/// unsafe if not, or nothing if it's safe. Nothing is changed.
+///
+/// The squashed commit has the tree of the head of the branch on top of its base, so it can't
+/// conflict by itself, but it replaces commits other branches or the remote may depend on.
+pub fn can_squash_branch(
+    project_repository: &project_repository::Repository,
+    branch_id: BranchId,
+) -> Result<Vec<SquashBlocker>> {
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let branch = vb_state.get_branch(branch_id)?;
+    let repo = project_repository.repo();
+
+    let mut blockers = Vec::new();
+    if project_repository.is_resolving() {
+        blockers.push(SquashBlocker::Conflicted);
+    }
+
+    let commits = project_repository.log(branch.head, LogUntil::Commit(default_target.sha))?;
+    if commits.len() < 2 {
+        blockers.push(SquashBlocker::NothingToSquash);
+    }
+
+    if branch.upstream_head.is_some() && !project_repository.project().ok_with_force_push {
+        blockers.push(SquashBlocker::ForcePushRequired);
+    }
+
+    let mut stacked = Vec::new();
+    if branch.head != default_target.sha {
+        let mut others = vb_state.list_branches()?;
+        others.sort_by_key(|other| other.order);
+        for other in others.into_iter().filter(|other| other.id != branch.id) {
+            if other.head == branch.head
+                || repo
+                    .graph_descendant_of(other.head, branch.head)
+                    .context("failed to check ancestry of branches")?
+            {
+                stacked.push(other.id);
+            }
+        }
+    }
+    if !stacked.is_empty() {
+        blockers.push(SquashBlocker::StackedBranches {
+            branch_ids: stacked,
+        });
+    }
+
+    let signs_commits = repo.gb_config()?.sign_commits.unwrap_or(false);
+    if !signs_commits
+        && commits
+            .iter()
+            .any(|commit| commit.header_field_bytes("gpgsig").is_ok())
+    {
+        blockers.push(SquashBlocker::SignaturesLost);
+    }
+    Ok(blockers)
+}
+
 // changes a commit message for commit_oid, rebases everything above it, updates branch head if successful
 pub fn update_commit_message(
     project_repository: &project_repository::Repository,
@@ -3811,6 +6866,201 @@ pub fn move_commit(
     Ok(())
 }
 
+/// Move `commit_id` from `source_branch_id` to `target_branch_id`, so that `position` commits
+/// of the target branch end up above it, with 0 making it the new head.
+///
+/// The commits of the source branch above the moved commit are rebased onto its parent, and
+/// those of the target branch below `position` are rebased onto it. Both rebases are checked in
+/// memory first, and if any commit would conflict nothing is changed and the error lists the
+/// conflicting paths. The hunks of the commit owned by the source branch move along with it.
+pub fn move_commit_between_branches(
+    project_repository: &project_repository::Repository,
+    source_branch_id: BranchId,
+    commit_id: git2::Oid,
+    target_branch_id: BranchId,
+    position: usize,
+) -> Result<()> {
+    project_repository.assure_resolved()?;
+    if source_branch_id == target_branch_id {
+        return Err(anyhow!(
+            "commit {commit_id} is in branch {target_branch_id} already, reorder it instead"
+        ))
+        .context(Code::Validation);
+    }
+    let vb_state = project_repository.project().virtual_branches();
+    let default_target = vb_state.get_default_target()?;
+    let mut source_branch = vb_state.get_branch(source_branch_id)?;
+    let mut target_branch = vb_state.get_branch(target_branch_id)?;
+    if !source_branch.applied || !target_branch.applied {
+        return Err(anyhow!(
+            "both branches must be applied to move commits between them"
+        ))
+        .context(Code::Validation);
+    }
+    let repo = project_repository.repo();
+
+    let source_ids =
+        project_repository.l(source_branch.head, LogUntil::Commit(default_target.sha))?;
+    let Some(commit_index) = source_ids.iter().position(|id| *id == commit_id) else {
+        return Err(anyhow!(
+            "commit {commit_id} not found in branch {source_branch_id}"
+        ))
+        .context(Code::Validation);
+    };
+    let target_ids =
+        project_repository.l(target_branch.head, LogUntil::Commit(default_target.sha))?;
+    if position > target_ids.len() {
+        return Err(anyhow!(
+            "can't move commit to position {position} in a branch of {} commits",
+            target_ids.len()
+        ))
+        .context(Code::Validation);
+    }
+
+    let commit = repo
+        .find_commit(commit_id)
+        .context("failed to find commit")?;
+    let commit_parent = commit.parent(0).context("failed to find parent commit")?;
+    let target_base = match (target_ids.get(position), target_ids.last()) {
+        (Some(id), _) => *id,
+        (None, Some(bottom_id)) => repo
+            .find_commit(*bottom_id)?
+            .parent(0)
+            .context("failed to find base of target branch")?
+            .id(),
+        (None, None) => target_branch.head,
+    };
+
+    // the commits to rebase, head first, as expected by `cherry_rebase_group()`
+    let mut source_ids_to_rebase = source_ids[..commit_index].to_vec();
+    let mut target_ids_to_rebase = target_ids[..position].to_vec();
+    target_ids_to_rebase.push(commit_id);
+
+    let mut conflicting_paths = Vec::new();
+    add_rebase_conflicts(
+        repo,
+        commit_parent.tree_id(),
+        &source_ids_to_rebase,
+        &mut conflicting_paths,
+    )?;
+    add_rebase_conflicts(
+        repo,
+        repo.find_commit(target_base)?.tree_id(),
+        &target_ids_to_rebase,
+        &mut conflicting_paths,
+    )?;
+    if !conflicting_paths.is_empty() {
+        conflicting_paths.sort();
+        let paths = conflicting_paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(anyhow!(
+            "moving commit {commit_id} would conflict in {paths}, nothing was changed"
+        ))
+        .context(Marker::BranchConflict);
+    }
+
+    let new_source_head = if source_ids_to_rebase.is_empty() {
+        commit_parent.id()
+    } else {
+        cherry_rebase_group(
+            project_repository,
+            commit_parent.id(),
+            &mut source_ids_to_rebase,
+        )
+        .context("failed to rebase source branch")?
+    };
+    let new_target_head =
+        cherry_rebase_group(project_repository, target_base, &mut target_ids_to_rebase)
+            .context("failed to rebase target branch")?;
+
+    let commit_diff = diff::trees(
+        repo,
+        &commit_parent.tree().context("failed to get parent tree")?,
+        &commit.tree().context("failed to get commit tree")?,
+    )?;
+    let ownerships_to_transfer = diff::diff_files_into_hunks(commit_diff)
+        .map(|(file_path, hunks)| OwnershipClaim {
+            file_path,
+            hunks: hunks.iter().map(Into::into).collect(),
+        })
+        .flat_map(|claim| source_branch.ownership.take(&claim))
+        .collect::<Vec<_>>();
+    for ownership in ownerships_to_transfer {
+        target_branch.ownership.put(ownership);
+    }
+
+    let (old_source_branch, old_target_branch) = (source_branch.clone(), target_branch.clone());
+    let now = crate::time::now_ms();
+    source_branch.head = new_source_head;
+    source_branch.updated_timestamp_ms = now;
+    target_branch.head = new_target_head;
+    target_branch.updated_timestamp_ms = now;
+    vb_state.set_branch(source_branch)?;
+    vb_state.set_branch(target_branch)?;
+
+    if let Err(err) =
+        super::integration::update_gitbutler_integration(&vb_state, project_repository)
+    {
+        vb_state.set_branch(old_source_branch)?;
+        vb_state.set_branch(old_target_branch)?;
+        return Err(err.context("failed to update gitbutler integration"));
+    }
+    Ok(())
+}
+
+/// Rebase `ids_to_rebase`, head first, onto the tree `onto_tree` in memory, and add the paths of
+/// each commit that would conflict to `conflicting_paths`.
+///
+/// A conflicting commit is assumed to be resolved with its own tree, so the commits above it
+/// are checked against that. The merged trees are [only kept in memory](in_memory_repository()),
+/// so nothing is written to the object database of `repo`.
+///
+/// Return `true` if a commit that changes something would end up changing nothing, as its changes
+/// are already contained in what it's rebased onto.
+fn add_rebase_conflicts(
+    repo: &git2::Repository,
+    onto_tree: git2::Oid,
+    ids_to_rebase: &[git2::Oid],
+    conflicting_paths: &mut Vec<PathBuf>,
+) -> Result<bool> {
+    let repo = in_memory_repository(repo)?;
+    let mut tree = repo.find_tree(onto_tree)?;
+    let mut leaves_empty_commit = false;
+    for id in ids_to_rebase.iter().rev() {
+        let commit = repo.find_commit(*id)?;
+        let base_tree = commit.parent(0)?.tree()?;
+        let commit_tree = commit.tree()?;
+        let mut merge_index = repo
+            .merge_trees(&base_tree, &tree, &commit_tree, None)
+            .context("failed to merge trees")?;
+        if merge_index.has_conflicts() {
+            add_conflicting_paths(&merge_index, conflicting_paths)?;
+            tree = commit_tree;
+        } else {
+            let tree_id = merge_index
+                .write_tree_to(&repo)
+                .context("failed to write tree")?;
+            leaves_empty_commit |= tree_id == tree.id() && commit_tree.id() != base_tree.id();
+            tree = repo.find_tree(tree_id)?;
+        }
+    }
+    Ok(leaves_empty_commit)
+}
+
+/// Open `repo` once more, with all objects written to it kept in memory instead of its object
+/// database, so they are gone once it's dropped.
+fn in_memory_repository(repo: &git2::Repository) -> Result<git2::Repository> {
+    let repo = git2::Repository::open(repo.path()).context("failed to open repository")?;
+    // the backends of the object database on disk have a lower priority, so they aren't written to
+    repo.odb()?
+        .add_new_mempack_backend(1000)
+        .context("failed to keep objects in memory")?;
+    Ok(repo)
+}
+
 pub fn create_virtual_branch_from_branch(
     project_repository: &project_repository::Repository,
     upstream: &git::Refname,
@@ -3911,6 +7161,7 @@ pub fn create_virtual_branch_from_branch(
         notes: String::new(),
         applied: false,
         upstream_head: upstream_branch.is_some().then_some(head_commit.id()),
+        push_remote: None,
         upstream: upstream_branch,
         tree: head_commit_tree.id(),
         head: head_commit.id(),
@@ -3924,7 +7175,7 @@ pub fn create_virtual_branch_from_branch(
     vb_state.set_branch(branch.clone())?;
     project_repository.add_branch_reference(&branch)?;
 
-    match apply_branch(project_repository, branch.id, user) {
+    match apply_branch(project_repository, branch.id, user, None) {
         Ok(_) => Ok(branch.id),
         Err(err)
             if err
@@ -4030,9 +7281,103 @@ fn update_conflict_markers(
     Ok(())
 }
 
+/// Return the path and 1-based line number of every added line in `files` which looks like a
+/// conflict marker, i.e. `<<<<<<<`, `=======` or `>>>>>>>`.
+fn find_conflict_markers(files: &[(PathBuf, Vec<GitHunk>)]) -> Vec<(PathBuf, u32)> {
+    let mut markers = Vec::new();
+    for (path, hunks) in files {
+        for hunk in hunks.iter().filter(|hunk| !hunk.binary) {
+            let mut line_number = hunk.new_start;
+            for line in hunk.diff_lines.lines() {
+                match line.first() {
+                    Some(b'+') => {
+                        if is_conflict_marker(&line[1..]) {
+                            markers.push((path.clone(), line_number));
+                        }
+                        line_number += 1;
+                    }
+                    Some(b' ') => line_number += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+    markers
+}
+
+fn is_conflict_marker(line: &[u8]) -> bool {
+    line.starts_with(b"<<<<<<<") || line.starts_with(b">>>>>>>") || line.trim_end() == b"======="
+}
+
+/// Return `true` if the removed and added lines of the hunk `diff` are the same once all
+/// whitespace is removed, as is the case for re-indented code.
+fn is_whitespace_only_change(diff: &BStr) -> bool {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut changed = false;
+    for line in diff.lines() {
+        let side = match line.first() {
+            Some(b'-') => &mut removed,
+            Some(b'+') => &mut added,
+            _ => continue,
+        };
+        changed = true;
+        side.extend(line[1..].iter().filter(|b| !b.is_ascii_whitespace()));
+    }
+    changed && removed == added
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn is_whitespace_only_change_test() {
+        assert!(is_whitespace_only_change(
+            b"@@ -1,2 +1,2 @@\n-fn a() {\n-  b();\n+fn a() {\n+    b();\n".as_bstr()
+        ));
+        assert!(is_whitespace_only_change(
+            b"@@ -1 +1,2 @@\n a\n+\n".as_bstr()
+        ));
+        assert!(!is_whitespace_only_change(
+            b"@@ -1 +1 @@\n-  b();\n+  c();\n".as_bstr()
+        ));
+        assert!(
+            !is_whitespace_only_change(b"@@ -1 +1 @@\n unchanged\n".as_bstr()),
+            "without changes there is nothing to ignore"
+        );
+    }
+
+    #[test]
+    fn is_conflict_marker_test() {
+        assert!(is_conflict_marker(b"<<<<<<< ours"));
+        assert!(is_conflict_marker(b"======="));
+        assert!(is_conflict_marker(b">>>>>>> theirs"));
+        assert!(!is_conflict_marker(b"== heading =="));
+        assert!(!is_conflict_marker(b"<<< not a marker"));
+    }
+
+    #[test]
+    fn group_joined_hunks_test() {
+        let hunk = |new_start, new_lines| GitHunk {
+            old_start: new_start,
+            old_lines: new_lines,
+            new_start,
+            new_lines,
+            diff_lines: BString::default(),
+            binary: false,
+            locked_to: Box::new([]),
+            change_type: diff::ChangeType::Modified,
+        };
+        let hunks = [hunk(20, 3), hunk(1, 4), hunk(5, 2), hunk(10, 2)];
+        let starts: Vec<Vec<_>> = group_joined_hunks(&hunks)
+            .into_iter()
+            .map(|group| group.into_iter().map(|hunk| hunk.new_start).collect())
+            .collect();
+        assert_eq!(starts, [vec![1, 5], vec![10], vec![20]]);
+        assert!(group_joined_hunks(&[]).is_empty());
+    }
+
     #[test]
     fn joined_test() {
         assert!(!joined(1, 2, 3, 4));
@@ -0,0 +1,307 @@
+use gitbutler_core::git::diff::{self, ChangeType, DiffLine, DiffLineKind, GitHunk};
+
+fn hunk(old_start: u32, old_lines: u32, new_start: u32, new_lines: u32, diff: &str) -> GitHunk {
+    GitHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        diff_lines: diff.into(),
+        binary: false,
+        locked_to: Box::new([]),
+        change_type: ChangeType::Modified,
+    }
+}
+
+fn line(
+    kind: DiffLineKind,
+    old_line: Option<u32>,
+    new_line: Option<u32>,
+    content: &str,
+) -> DiffLine {
+    DiffLine {
+        kind,
+        old_line,
+        new_line,
+        content: content.into(),
+    }
+}
+
+#[test]
+fn structured_hunk_numbers_lines() {
+    let hunk = hunk(
+        3,
+        3,
+        3,
+        3,
+        "@@ -3,3 +3,3 @@ line 2\n line 3\n-line 4\n+change 4\n line 5\n",
+    );
+    let structured = hunk.to_structured().unwrap();
+    assert_eq!(
+        (
+            structured.old_start,
+            structured.old_lines,
+            structured.new_start,
+            structured.new_lines
+        ),
+        (3, 3, 3, 3)
+    );
+    assert_eq!(
+        structured.lines,
+        vec![
+            line(DiffLineKind::Context, Some(3), Some(3), "line 3"),
+            line(DiffLineKind::Deletion, Some(4), None, "line 4"),
+            line(DiffLineKind::Addition, None, Some(4), "change 4"),
+            line(DiffLineKind::Context, Some(5), Some(5), "line 5"),
+        ]
+    );
+}
+
+#[test]
+fn structured_hunk_of_added_lines() {
+    let hunk = hunk(0, 0, 1, 2, "@@ -0,0 +1,2 @@\n+one\n+two");
+    assert_eq!(
+        hunk.to_structured().unwrap().lines,
+        vec![
+            line(DiffLineKind::Addition, None, Some(1), "one"),
+            line(DiffLineKind::Addition, None, Some(2), "two"),
+        ],
+        "the last line may lack a line separator"
+    );
+}
+
+#[test]
+fn structured_hunk_of_binary_hunk() {
+    let mut hunk = hunk(0, 0, 0, 0, "d3f2b1");
+    hunk.binary = true;
+    assert_eq!(hunk.to_structured(), None);
+}
+
+#[test]
+fn structured_hunk_serializes_to_json() {
+    let hunk = hunk(1, 1, 1, 1, "@@ -1 +1 @@\n-a\n+b\n");
+    assert_eq!(
+        serde_json::to_value(hunk.to_structured().unwrap()).unwrap(),
+        serde_json::json!({
+            "oldStart": 1,
+            "oldLines": 1,
+            "newStart": 1,
+            "newLines": 1,
+            "lines": [
+                { "kind": "deletion", "oldLine": 1, "newLine": null, "content": "a" },
+                { "kind": "addition", "oldLine": null, "newLine": 1, "content": "b" },
+            ],
+        })
+    );
+}
+
+#[test]
+fn word_diff_compares_removed_lines_with_the_added_ones_after_them() {
+    let hunk = hunk(
+        1,
+        4,
+        1,
+        5,
+        "@@ -1,4 +1,5 @@\n fn main() {\n-let a = 1;\n-let b = 2;\n+let a = 3;\n+let b = 2;\n }\n+// end\n",
+    );
+    assert_eq!(
+        hunk.word_diff(),
+        vec![
+            diff::WordDiffLine {
+                kind: DiffLineKind::Deletion,
+                line: 2,
+                spans: vec![diff::WordSpan { start: 8, end: 9 }],
+            },
+            diff::WordDiffLine {
+                kind: DiffLineKind::Addition,
+                line: 2,
+                spans: vec![diff::WordSpan { start: 8, end: 9 }],
+            },
+        ],
+        "unchanged lines within the changed ones and lines that are only added aren't listed"
+    );
+}
+
+#[test]
+fn tree_diffs_reuse_blob_diffs() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let tree_with = |content: &[u8]| {
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder
+            .insert("file.txt", repo.blob(content).unwrap(), 0o100644)
+            .unwrap();
+        repo.find_tree(builder.write().unwrap()).unwrap()
+    };
+    // unique content so no other test could have diffed these blobs before
+    let old_tree = tree_with(b"tree_diffs_reuse_blob_diffs\nold\n");
+    let new_tree = tree_with(b"tree_diffs_reuse_blob_diffs\nnew\n");
+
+    let first = diff::trees(&repo, &old_tree, &new_tree).unwrap();
+    let hits_before = diff::blob_diff_cache_stats().hits;
+    let second = diff::trees(&repo, &old_tree, &new_tree).unwrap();
+    let stats = diff::blob_diff_cache_stats();
+
+    assert_eq!(first, second);
+    assert_eq!(
+        first[std::path::Path::new("file.txt")].hunks[0].diff_lines,
+        "@@ -1,2 +1,2 @@\n tree_diffs_reuse_blob_diffs\n-old\n+new\n"
+    );
+    assert!(
+        stats.hits > hits_before,
+        "the second diff is answered from the cache"
+    );
+    assert!(stats.hit_rate() > 0.0);
+}
+
+#[test]
+fn blob_diffs_are_cached_per_repository() {
+    let diff_in_repo = |attributes: &str| {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(repo.path().join("info").join("attributes"), attributes).unwrap();
+        let tree_with = |content: &[u8]| {
+            let mut builder = repo.treebuilder(None).unwrap();
+            builder
+                .insert("file.txt", repo.blob(content).unwrap(), 0o100644)
+                .unwrap();
+            repo.find_tree(builder.write().unwrap()).unwrap()
+        };
+        let old_tree = tree_with(b"blob_diffs_are_cached_per_repository\nold\n");
+        let new_tree = tree_with(b"blob_diffs_are_cached_per_repository\nnew\n");
+        diff::trees(&repo, &old_tree, &new_tree).unwrap()
+    };
+
+    let text = diff_in_repo("");
+    let binary = diff_in_repo("file.txt -diff\n");
+    assert!(!text[std::path::Path::new("file.txt")].hunks[0].binary);
+    assert_ne!(
+        text, binary,
+        "the same blobs are diffed as binary in the repository that says so"
+    );
+}
+
+#[test]
+fn worktree_changes_stream_yields_the_hunks_of_workdir() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    std::fs::write(dir.path().join("changed.txt"), "one\ntwo\n").unwrap();
+    std::fs::write(dir.path().join("large.txt"), "a\n".repeat(100)).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let commit_id = repo
+        .commit(None, &signature, &signature, "initial", &tree, &[])
+        .unwrap();
+
+    std::fs::write(dir.path().join("changed.txt"), "one\n2\n").unwrap();
+    std::fs::write(dir.path().join("added.txt"), "new\n").unwrap();
+    std::fs::write(dir.path().join("large.txt"), "b\n".repeat(100)).unwrap();
+
+    let mut expected = diff::workdir(&repo, &commit_id).unwrap();
+    let changes = diff::worktree_changes_stream(&repo, &commit_id, &[], false, None, None)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(changes.len(), expected.len());
+    for (path, change) in changes {
+        let diff::StreamedDiff::Full(file) = change else {
+            panic!("{path:?} has no size limit")
+        };
+        assert_eq!(file.hunks, expected.remove(&path).unwrap().hunks);
+    }
+
+    let mut changes = diff::worktree_changes_stream(&repo, &commit_id, &[], false, None, Some(200))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    assert!(matches!(changes[0].1, diff::StreamedDiff::Full(_)));
+    assert!(matches!(changes[1].1, diff::StreamedDiff::Full(_)));
+    assert!(matches!(
+        changes[2].1,
+        diff::StreamedDiff::TooLarge {
+            lines_added: 100,
+            lines_removed: 100,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn renames_that_only_change_the_mode_record_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let blob = repo.blob(b"#!/bin/sh\necho hi\n").unwrap();
+    let tree_with = |path: &str, mode: i32| {
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert(path, blob, mode).unwrap();
+        repo.find_tree(builder.write().unwrap()).unwrap()
+    };
+    let old_tree = tree_with("old.sh", 0o100644);
+    let new_tree = tree_with("new.sh", 0o100755);
+
+    let files =
+        diff::trees_with_renames(&repo, &old_tree, &new_tree, diff::DEFAULT_RENAME_THRESHOLD)
+            .unwrap();
+    assert_eq!(files.len(), 1, "it's a single rename");
+    let file = &files[std::path::Path::new("new.sh")];
+    assert_eq!(
+        file.old_path.as_deref(),
+        Some(std::path::Path::new("old.sh"))
+    );
+    assert_eq!(
+        file.mode_change,
+        Some(diff::ModeChange {
+            old_mode: 0o100644,
+            new_mode: 0o100755
+        })
+    );
+    assert!(
+        file.hunks
+            .iter()
+            .all(|hunk| !hunk.diff_lines.contains(&b'+') && !hunk.diff_lines.contains(&b'-')),
+        "the content didn't change"
+    );
+}
+
+#[test]
+fn large_files_matching_pathspecs_stay_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    std::fs::write(dir.path().join("small.txt"), "one\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let commit_id = repo
+        .commit(None, &signature, &signature, "initial", &tree, &[])
+        .unwrap();
+
+    std::fs::write(dir.path().join("small.txt"), "two\n").unwrap();
+    std::fs::File::create(dir.path().join("large.txt"))
+        .unwrap()
+        .set_len(50_000_001)
+        .unwrap();
+
+    let files = diff::workdir_with_pathspecs(&repo, &commit_id, &["*.txt".into()]).unwrap();
+    assert_eq!(files.len(), 2);
+    let large = &files[std::path::Path::new("large.txt")];
+    assert!(large.skipped);
+    assert!(large.hunks.is_empty());
+    let small = &files[std::path::Path::new("small.txt")];
+    assert!(!small.skipped);
+    assert_eq!(small.hunks.len(), 1);
+
+    let files = diff::workdir_with_pathspecs(&repo, &commit_id, &["large.txt".into()]).unwrap();
+    assert_eq!(
+        files.keys().collect::<Vec<_>>(),
+        [std::path::Path::new("large.txt")],
+        "nothing is diffed if all matching files are too large"
+    );
+}
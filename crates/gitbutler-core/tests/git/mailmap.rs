@@ -0,0 +1,84 @@
+use gitbutler_core::git::Mailmap;
+
+fn resolve(repo: &git2::Repository, name: &str, email: &str) -> (String, String) {
+    Mailmap::from_repository(repo)
+        .unwrap()
+        .resolve(&git2::Signature::now(name, email).unwrap())
+}
+
+fn identity(name: &str, email: &str) -> (String, String) {
+    (name.to_string(), email.to_string())
+}
+
+#[test]
+fn unknown_identities_are_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    assert_eq!(
+        resolve(&repo, "A", "a@example.com"),
+        identity("A", "a@example.com")
+    );
+
+    std::fs::write(
+        dir.path().join(".mailmap"),
+        "Proper <proper@example.com> <old@example.com>\n",
+    )
+    .unwrap();
+    assert_eq!(
+        resolve(&repo, "A", "a@example.com"),
+        identity("A", "a@example.com")
+    );
+}
+
+#[test]
+fn mailmap_of_worktree() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    std::fs::write(
+        dir.path().join(".mailmap"),
+        "# a comment\nProper Name <proper@example.com> <OLD@example.com>\n",
+    )
+    .unwrap();
+    assert_eq!(
+        resolve(&repo, "A", "old@example.com"),
+        identity("Proper Name", "proper@example.com"),
+        "emails are compared case-insensitively"
+    );
+}
+
+#[test]
+fn mailmap_file_of_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path().join("repo")).unwrap();
+    let mailmap = dir.path().join("mailmap");
+    std::fs::write(
+        &mailmap,
+        "Proper Name <proper@example.com> <old@example.com>\n",
+    )
+    .unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("mailmap.file", mailmap.to_str().unwrap())
+        .unwrap();
+    assert_eq!(
+        resolve(&repo, "A", "old@example.com"),
+        identity("Proper Name", "proper@example.com")
+    );
+}
+
+#[test]
+fn mailmap_blob_of_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let blob = repo
+        .blob(b"<proper@example.com> <old@example.com>\n")
+        .unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("mailmap.blob", &blob.to_string())
+        .unwrap();
+    assert_eq!(
+        resolve(&repo, "A", "old@example.com"),
+        identity("A", "proper@example.com")
+    );
+}
@@ -1 +1,4 @@
 mod credentials;
+mod diff;
+mod mailmap;
+mod trailers;
@@ -0,0 +1,59 @@
+use gitbutler_core::git::append_trailers;
+
+fn trailers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn message_without_trailer_block() {
+    let message = append_trailers(
+        "subject\n\nsome body",
+        trailers(&[("Signed-off-by", "A <a@example.com>")]),
+    );
+    assert_eq!(
+        message,
+        "subject\n\nsome body\n\nSigned-off-by: A <a@example.com>"
+    );
+}
+
+#[test]
+fn subject_is_never_a_trailer_block() {
+    let message = append_trailers("fix: the thing\n", trailers(&[("Change-Id", "I123")]));
+    assert_eq!(message, "fix: the thing\n\nChange-Id: I123\n");
+}
+
+#[test]
+fn message_with_trailer_block() {
+    let message = append_trailers(
+        "subject\n\nbody\n\nSigned-off-by: A <a@example.com>\n",
+        trailers(&[("Co-authored-by", "B <b@example.com>")]),
+    );
+    assert_eq!(
+        message,
+        "subject\n\nbody\n\nSigned-off-by: A <a@example.com>\nCo-authored-by: B <b@example.com>\n"
+    );
+}
+
+#[test]
+fn duplicates_are_added_once() {
+    let message = append_trailers(
+        "subject\n\nSigned-off-by: A <a@example.com>",
+        trailers(&[
+            ("signed-off-by", "A <a@example.com>"),
+            ("Change-Id", "I123"),
+            ("Change-Id", "I123"),
+        ]),
+    );
+    assert_eq!(
+        message,
+        "subject\n\nSigned-off-by: A <a@example.com>\nChange-Id: I123"
+    );
+}
+
+#[test]
+fn nothing_to_add() {
+    assert_eq!(append_trailers("subject\n", Vec::new()), "subject\n");
+}
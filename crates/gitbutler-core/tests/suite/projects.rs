@@ -22,6 +22,20 @@ mod add {
         assert_eq!(project.title, path.iter().last().unwrap().to_str().unwrap());
     }
 
+    #[test]
+    fn sibling_of_existing() {
+        let (controller, _tmp) = new();
+        let tmp = tempfile::tempdir().unwrap();
+        let first_dir = tmp.path().join("first");
+        let second_dir = tmp.path().join("second");
+        git2::Repository::init(&first_dir).unwrap();
+        git2::Repository::init(&second_dir).unwrap();
+
+        controller.add(&first_dir).unwrap();
+        let project = controller.add(&second_dir).unwrap();
+        assert_eq!(project.path, second_dir);
+    }
+
     mod error {
         use super::*;
 
@@ -98,6 +112,81 @@ mod add {
             assert_eq!(err.to_string(), "can only work in main worktrees");
         }
 
+        #[test]
+        fn parent_of_existing() {
+            let (controller, _tmp) = new();
+            let tmp = tempfile::tempdir().unwrap();
+            let parent_dir = tmp.path().join("parent");
+            let child_dir = parent_dir.join("child");
+            git2::Repository::init(&parent_dir).unwrap();
+            git2::Repository::init(&child_dir).unwrap();
+
+            let child = controller.add(&child_dir).unwrap();
+            let err = controller.add(&parent_dir).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "path is nested in or contains project 'child' at {}",
+                    child.path.display()
+                )
+            );
+            assert_eq!(
+                err.downcast_ref(),
+                Some(&gitbutler_core::projects::NestedProject {
+                    existing: child.id,
+                    path: child.path.clone(),
+                    title: "child".into(),
+                }),
+                "the UI can tell which project is in the way"
+            );
+        }
+
+        #[test]
+        fn child_of_existing() {
+            let (controller, _tmp) = new();
+            let tmp = tempfile::tempdir().unwrap();
+            let parent_dir = tmp.path().join("parent");
+            let child_dir = parent_dir.join("child");
+            git2::Repository::init(&parent_dir).unwrap();
+            git2::Repository::init(&child_dir).unwrap();
+
+            let parent = controller.add(&parent_dir).unwrap();
+            let err = controller.add(&child_dir).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "path is nested in or contains project 'parent' at {}",
+                    parent.path.display()
+                )
+            );
+            assert_eq!(
+                err.downcast_ref(),
+                Some(&gitbutler_core::projects::NestedProject {
+                    existing: parent.id,
+                    path: parent.path.clone(),
+                    title: "parent".into(),
+                }),
+                "the UI can tell which project is in the way"
+            );
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn symlink_to_existing() {
+            let (controller, _tmp) = new();
+            let tmp = tempfile::tempdir().unwrap();
+            let repo_dir = tmp.path().join("repo");
+            let link = tmp.path().join("link");
+            git2::Repository::init(&repo_dir).unwrap();
+            std::os::unix::fs::symlink(&repo_dir, &link).unwrap();
+
+            controller.add(&repo_dir).unwrap();
+            assert_eq!(
+                controller.add(&link).unwrap_err().to_string(),
+                "project already exists"
+            );
+        }
+
         fn create_initial_commit(repo: &git2::Repository) -> git2::Oid {
             let signature = git2::Signature::now("test", "test@email.com").unwrap();
 
@@ -132,3 +221,200 @@ mod delete {
         assert!(!project.path.join(".gitbutler.json").exists());
     }
 }
+
+mod remove {
+    use super::*;
+
+    #[tokio::test]
+    async fn without_purge_keeps_data() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+        std::fs::create_dir_all(project.gb_dir()).unwrap();
+        std::fs::write(project.gb_dir().join("data"), "0123456789").unwrap();
+
+        assert_eq!(controller.remove(project.id, false).await.unwrap(), 0);
+        assert!(controller.get(project.id).is_err());
+        assert!(project.gb_dir().join("data").exists());
+    }
+
+    #[tokio::test]
+    async fn with_purge_deletes_data() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+        std::fs::create_dir_all(project.gb_dir()).unwrap();
+        std::fs::write(project.gb_dir().join("data"), "0123456789").unwrap();
+
+        let freed_bytes = controller.remove(project.id, true).await.unwrap();
+        assert!(freed_bytes >= 10, "at least the data file was freed");
+        assert!(controller.get(project.id).is_err());
+        assert!(!project.gb_dir().exists());
+        assert!(project.path.join(".git").exists(), "the repository remains");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn with_purge_refuses_data_outside_of_the_repository() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        std::fs::write(elsewhere.path().join("precious"), "data").unwrap();
+        if project.gb_dir().exists() {
+            std::fs::remove_dir_all(project.gb_dir()).unwrap();
+        }
+        std::os::unix::fs::symlink(elsewhere.path(), project.gb_dir()).unwrap();
+
+        let err = controller.remove(project.id, true).await.unwrap_err();
+        assert!(err.to_string().starts_with("refusing to delete"));
+        assert!(
+            controller.get(project.id).is_ok(),
+            "nothing was unregistered"
+        );
+        assert!(elsewhere.path().join("precious").exists());
+    }
+}
+
+mod ui_state {
+    use gitbutler_core::projects::{DiffViewMode, UiState, UI_STATE_VERSION};
+
+    use super::*;
+
+    #[test]
+    fn defaults_without_file() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+
+        let state = controller.get_ui_state(project.id).unwrap();
+        assert_eq!(
+            state,
+            UiState {
+                version: UI_STATE_VERSION,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+
+        let branch_id = "6ebd4cd5-ea42-4cba-9ab6-d4add5bb6d2e".parse().unwrap();
+        let state = UiState {
+            version: 0,
+            selected_branch: Some(branch_id),
+            diff_view_mode: DiffViewMode::Split,
+            collapsed_branches: vec![branch_id],
+        };
+        controller.set_ui_state(project.id, state.clone()).unwrap();
+
+        assert_eq!(
+            controller.get_ui_state(project.id).unwrap(),
+            UiState {
+                version: UI_STATE_VERSION,
+                ..state
+            }
+        );
+    }
+
+    #[test]
+    fn migrates_unversioned_file() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+
+        std::fs::create_dir_all(project.gb_dir()).unwrap();
+        std::fs::write(
+            project.gb_dir().join("ui_state.toml"),
+            "diffViewMode = \"split\"\nunknownField = 42\n",
+        )
+        .unwrap();
+
+        let state = controller.get_ui_state(project.id).unwrap();
+        assert_eq!(state.version, UI_STATE_VERSION);
+        assert_eq!(state.diff_view_mode, DiffViewMode::Split);
+        assert_eq!(state.selected_branch, None);
+    }
+
+    #[test]
+    fn keeps_file_of_newer_version() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+
+        let path = project.gb_dir().join("ui_state.toml");
+        let contents = format!(
+            "version = {}\ndiffViewMode = \"stacked\"\n",
+            UI_STATE_VERSION + 1
+        );
+        std::fs::create_dir_all(project.gb_dir()).unwrap();
+        std::fs::write(&path, &contents).unwrap();
+
+        assert!(controller
+            .set_ui_state(project.id, UiState::default())
+            .is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+    }
+}
+
+mod settings {
+    use gitbutler_core::projects::{AuthKey, Setting, UpdateRequest};
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn non_default_and_reset() {
+        let (controller, _tmp) = new();
+        let repository = gitbutler_testsupport::TestProject::default();
+        let project = controller.add(repository.path()).unwrap();
+
+        assert!(controller
+            .non_default_settings(project.id)
+            .unwrap()
+            .is_empty());
+
+        let private_key = tempfile::NamedTempFile::new().unwrap();
+        controller
+            .update(&UpdateRequest {
+                id: project.id,
+                ok_with_force_push: Some(false),
+                preferred_key: Some(AuthKey::Local {
+                    private_key_path: private_key.path().to_owned(),
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let settings = controller.non_default_settings(project.id).unwrap();
+        assert_eq!(settings.len(), 2);
+        assert_eq!(settings[0].key, Setting::PreferredKey);
+        assert_eq!(
+            settings[0].value,
+            json!({ "local": "<redacted>" }),
+            "key paths are redacted"
+        );
+        assert_eq!(settings[1].key, Setting::OkWithForcePush);
+        assert_eq!(settings[1].value, json!(false));
+        assert_eq!(settings[1].default, json!(true));
+
+        let project = controller
+            .reset_setting(project.id, Setting::OkWithForcePush)
+            .unwrap();
+        assert!(bool::from(project.ok_with_force_push));
+        assert_eq!(
+            controller
+                .non_default_settings(project.id)
+                .unwrap()
+                .into_iter()
+                .map(|setting| setting.key)
+                .collect::<Vec<_>>(),
+            [Setting::PreferredKey]
+        );
+    }
+}
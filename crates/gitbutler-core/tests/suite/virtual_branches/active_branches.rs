@@ -0,0 +1,41 @@
+use super::*;
+
+#[tokio::test]
+async fn applied_and_unapplied_branches_are_separated() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_1_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_2_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_3_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let branches = controller.active_branches(*project_id).await.unwrap();
+    assert_eq!(branches.active, [branch_1_id, branch_2_id, branch_3_id]);
+    assert!(branches.inactive.is_empty());
+
+    controller
+        .unapply_virtual_branch(*project_id, branch_2_id)
+        .await
+        .unwrap();
+
+    let branches = controller.active_branches(*project_id).await.unwrap();
+    assert_eq!(branches.active, [branch_1_id, branch_3_id]);
+    assert_eq!(branches.inactive, [branch_2_id]);
+}
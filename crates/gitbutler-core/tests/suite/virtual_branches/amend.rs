@@ -41,9 +41,16 @@ async fn forcepush_allowed() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     controller
         .push_virtual_branch(*project_id, branch_id, false, None)
@@ -106,9 +113,16 @@ async fn forcepush_forbidden() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit_oid = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     controller
         .push_virtual_branch(*project_id, branch_id, false, None)
@@ -151,9 +165,16 @@ async fn non_locked_hunk() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit_oid = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let branch = controller
         .list_virtual_branches(*project_id)
@@ -212,9 +233,16 @@ async fn locked_hunk() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit_oid = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let branch = controller
         .list_virtual_branches(*project_id)
@@ -282,9 +310,16 @@ async fn non_existing_ownership() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit_oid = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let branch = controller
         .list_virtual_branches(*project_id)
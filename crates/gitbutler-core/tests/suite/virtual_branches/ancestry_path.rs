@@ -0,0 +1,44 @@
+use super::*;
+
+#[tokio::test]
+async fn lists_commits_tip_to_base() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one").unwrap();
+    let base = repository.commit_all("one");
+    fs::write(repository.path().join("file.txt"), "two").unwrap();
+    let middle = repository.commit_all("two");
+    fs::write(repository.path().join("file.txt"), "three").unwrap();
+    let tip = repository.commit_all("three");
+
+    for first_parent in [true, false] {
+        assert_eq!(
+            controller
+                .ancestry_path(*project_id, tip, base, first_parent)
+                .await
+                .unwrap(),
+            Some(vec![tip, middle, base])
+        );
+        assert_eq!(
+            controller
+                .ancestry_path(*project_id, base, tip, first_parent)
+                .await
+                .unwrap(),
+            None,
+            "the tip isn't an ancestor of the base"
+        );
+    }
+
+    assert_eq!(
+        controller
+            .ancestry_path(*project_id, tip, tip, false)
+            .await
+            .unwrap(),
+        Some(vec![tip])
+    );
+}
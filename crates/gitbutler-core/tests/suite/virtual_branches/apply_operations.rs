@@ -0,0 +1,134 @@
+use git::CommitExt;
+use gitbutler_core::virtual_branches::{CommitRef, WorkspaceOp};
+
+use super::*;
+
+#[tokio::test]
+async fn all_operations_are_applied() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file one.txt"), "one").unwrap();
+    let commit_oid = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    let change_id = repository.find_commit(commit_oid).unwrap().change_id();
+
+    fs::write(repository.path().join("file two.txt"), "two").unwrap();
+    controller
+        .apply_operations(
+            *project_id,
+            &[
+                WorkspaceOp::Commit {
+                    branch_id,
+                    message: "commit two".into(),
+                    ownership: None,
+                },
+                WorkspaceOp::Reword {
+                    branch_id,
+                    commit: CommitRef::ChangeId(change_id.unwrap()),
+                    message: "commit one, reworded".into(),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+
+    let branch = get_branch(controller, *project_id, branch_id).await;
+    let descriptions = branch
+        .commits
+        .iter()
+        .map(|c| c.description.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(descriptions, ["commit two", "commit one, reworded"]);
+    assert!(branch.files.is_empty());
+}
+
+#[tokio::test]
+async fn nothing_is_applied_if_one_operation_fails() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let err = controller
+        .apply_operations(
+            *project_id,
+            &[
+                WorkspaceOp::Commit {
+                    branch_id,
+                    message: "commit".into(),
+                    ownership: None,
+                },
+                WorkspaceOp::Reword {
+                    branch_id,
+                    commit: CommitRef::ChangeId("does-not-exist".into()),
+                    message: "reworded".into(),
+                },
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().starts_with("Operation 1 (reword) failed"),
+        "the failed operation is named: {err}"
+    );
+
+    let branch = get_branch(controller, *project_id, branch_id).await;
+    assert!(branch.commits.is_empty(), "the commit was rolled back");
+    assert_eq!(branch.files.len(), 1);
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+        "content"
+    );
+}
+
+async fn get_branch(
+    controller: &Controller,
+    project_id: ProjectId,
+    branch_id: branch::BranchId,
+) -> gitbutler_core::virtual_branches::VirtualBranch {
+    controller
+        .list_virtual_branches(project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap()
+}
@@ -48,7 +48,7 @@ async fn deltect_conflict() {
 
         assert!(matches!(
             controller
-                .apply_virtual_branch(*project_id, branch1_id)
+                .apply_virtual_branch(*project_id, branch1_id, None)
                 .await
                 .unwrap_err()
                 .downcast_ref(),
@@ -91,7 +91,13 @@ async fn rebase_commit() {
         fs::write(repository.path().join("another_file.txt"), "virtual").unwrap();
 
         controller
-            .create_commit(*project_id, branch1_id, "virtual commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "virtual commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
 
@@ -155,7 +161,7 @@ async fn rebase_commit() {
     {
         // apply first vbranch again
         controller
-            .apply_virtual_branch(*project_id, branch1_id)
+            .apply_virtual_branch(*project_id, branch1_id, None)
             .await
             .unwrap();
 
@@ -259,7 +265,7 @@ async fn rebase_work() {
     {
         // apply first vbranch again
         controller
-            .apply_virtual_branch(*project_id, branch1_id)
+            .apply_virtual_branch(*project_id, branch1_id, None)
             .await
             .unwrap();
 
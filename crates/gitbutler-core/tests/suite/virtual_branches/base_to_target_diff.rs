@@ -0,0 +1,86 @@
+use super::*;
+
+#[tokio::test]
+async fn shows_what_landed_on_the_target() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\n").unwrap();
+    let base = repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("branch.txt"), "branch\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "branch commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let diff = controller
+        .base_to_target_diff(*project_id, branch_id, 3)
+        .await
+        .unwrap();
+    assert!(diff.files.is_empty(), "nothing landed on the target yet");
+
+    // simulate fetching a commit that changes `file.txt` upstream
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let base_commit = repo.find_commit(base).unwrap();
+    let mut builder = repo
+        .treebuilder(Some(&base_commit.tree().unwrap()))
+        .unwrap();
+    builder
+        .insert(
+            "file.txt",
+            repo.blob(b"one\nupstream\n").unwrap(),
+            git2::FileMode::Blob.into(),
+        )
+        .unwrap();
+    let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    repo.commit(
+        Some("refs/remotes/origin/master"),
+        &signature,
+        &signature,
+        "upstream",
+        &tree,
+        &[&base_commit],
+    )
+    .unwrap();
+
+    let diff = controller
+        .base_to_target_diff(*project_id, branch_id, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        diff.files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect::<Vec<_>>(),
+        ["file.txt"],
+        "the changes of the branch itself aren't included"
+    );
+    assert_eq!(
+        diff.files[0].hunks[0].diff_lines,
+        "@@ -2 +2 @@ one\n-two\n+upstream\n"
+    );
+    assert_eq!(diff.stat.insertions, 1);
+    assert_eq!(diff.stat.deletions, 1);
+}
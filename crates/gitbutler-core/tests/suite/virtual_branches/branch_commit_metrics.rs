@@ -0,0 +1,93 @@
+use super::*;
+
+#[tokio::test]
+async fn counts_changes_of_each_commit_and_the_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("one.txt"), "one\ntwo\n").unwrap();
+    fs::write(repository.path().join("two.txt"), "two\n").unwrap();
+    let first = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "first",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("one.txt"), "one\nchanged\n").unwrap();
+    let second = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "second",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let metrics = controller
+        .branch_commit_metrics(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        metrics
+            .commits
+            .iter()
+            .map(|commit| (
+                commit.commit_id,
+                commit.stat.files_changed,
+                commit.stat.insertions,
+                commit.stat.deletions,
+                commit.is_integrated
+            ))
+            .collect::<Vec<_>>(),
+        [(second, 1, 1, 1, false), (first, 2, 3, 0, false)]
+    );
+    assert_eq!(
+        (
+            metrics.total.files_changed,
+            metrics.total.insertions,
+            metrics.total.deletions
+        ),
+        (2, 4, 1),
+        "files changed by more than one commit are counted once"
+    );
+
+    // the first commit lands in the remote target branch
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.reference("refs/remotes/origin/master", first, true, "merged")
+        .unwrap();
+
+    let metrics = controller
+        .branch_commit_metrics(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        metrics
+            .commits
+            .iter()
+            .map(|commit| commit.is_integrated)
+            .collect::<Vec<_>>(),
+        [false, true]
+    );
+}
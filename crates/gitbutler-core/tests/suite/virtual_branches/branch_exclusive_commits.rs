@@ -0,0 +1,134 @@
+use gitbutler_core::virtual_branches::BranchSegment;
+
+use super::*;
+
+#[tokio::test]
+async fn each_branch_has_the_commits_above_the_one_below() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("one.txt"), "one").unwrap();
+    let commit_a1 = controller
+        .create_commit(*project_id, branch_a, "a1", None, &CommitOptions::default())
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("two.txt"), "two").unwrap();
+    let commit_a2 = controller
+        .create_commit(*project_id, branch_a, "a2", None, &CommitOptions::default())
+        .await
+        .unwrap()
+        .commit_id;
+
+    // let `b` build on top of `a` with two more commits
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let mut head = repo.find_commit(commit_a2).unwrap();
+    let mut commits_b = Vec::new();
+    for message in ["b1", "b2"] {
+        let commit_id = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                message,
+                &head.tree().unwrap(),
+                &[&head],
+            )
+            .unwrap();
+        commits_b.push(commit_id);
+        head = repo.find_commit(commit_id).unwrap();
+    }
+    let vb_state = project.virtual_branches();
+    let mut b = vb_state.get_branch(branch_b).unwrap();
+    b.head = head.id();
+    vb_state.set_branch(b).unwrap();
+
+    let expected = [
+        BranchSegment {
+            branch_id: branch_a,
+            commits: vec![commit_a2, commit_a1],
+        },
+        BranchSegment {
+            branch_id: branch_b,
+            commits: vec![commits_b[1], commits_b[0]],
+        },
+    ];
+    assert_eq!(
+        controller
+            .branch_exclusive_commits(*project_id, branch_a)
+            .await
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        controller
+            .branch_exclusive_commits(*project_id, branch_b)
+            .await
+            .unwrap(),
+        expected,
+        "the whole stack is returned for any of its branches"
+    );
+}
+
+#[tokio::test]
+async fn unstacked_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    assert_eq!(
+        controller
+            .branch_exclusive_commits(*project_id, branch_id)
+            .await
+            .unwrap(),
+        [BranchSegment {
+            branch_id,
+            commits: vec![commit_id],
+        }]
+    );
+}
@@ -0,0 +1,165 @@
+use gitbutler_core::virtual_branches::{BranchMergeStatus, MergeStatus};
+
+use super::*;
+
+/// Commit `content` to `new.txt` on top of the remote target branch and return the new commit.
+fn commit_upstream(repo: &git2::Repository, content: &str) -> git2::Oid {
+    let parent = repo
+        .find_reference("refs/remotes/origin/master")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let blob = repo.blob(content.as_bytes()).unwrap();
+    let mut tree = repo.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+    tree.insert("new.txt", blob, 0o100644).unwrap();
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@email.com").unwrap();
+    repo.commit(
+        Some("refs/remotes/origin/master"),
+        &signature,
+        &signature,
+        "upstream",
+        &tree,
+        &[&parent],
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn branches_without_integrated_commits_are_open() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller.branch_merge_statuses(*project_id).await.unwrap(),
+        [BranchMergeStatus {
+            branch_id,
+            status: MergeStatus::Open
+        }]
+    );
+}
+
+#[tokio::test]
+async fn branches_with_some_integrated_commits_are_partially_merged() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("one.txt"), "one\n").unwrap();
+    let first_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("two.txt"), "two\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // only the first commit is merged upstream
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.reference("refs/remotes/origin/master", first_commit_id, true, "")
+        .unwrap();
+
+    assert_eq!(
+        controller.branch_merge_statuses(*project_id).await.unwrap(),
+        [BranchMergeStatus {
+            branch_id,
+            status: MergeStatus::PartiallyMerged
+        }]
+    );
+}
+
+#[tokio::test]
+async fn squash_merged_branches_are_merged() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("new.txt"), "new\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // the squash-merge of the branch is changed again upstream, so its commit doesn't merge cleanly
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    commit_upstream(&repo, "new\n");
+    commit_upstream(&repo, "changed\n");
+
+    assert_eq!(
+        controller.branch_merge_statuses(*project_id).await.unwrap(),
+        [BranchMergeStatus {
+            branch_id,
+            status: MergeStatus::Merged
+        }]
+    );
+}
@@ -0,0 +1,158 @@
+use gitbutler_core::virtual_branches::DiffStat;
+
+use super::*;
+
+#[tokio::test]
+async fn combines_all_commits() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\nthree\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\nfour\n").unwrap();
+    fs::write(repository.path().join("new.txt"), "new\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // uncommitted changes are not part of it
+    fs::write(repository.path().join("uncommitted.txt"), "new\n").unwrap();
+
+    let diff = controller
+        .branch_squashed_diff(*project_id, branch_id, 0)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        diff.files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect::<Vec<_>>(),
+        ["file.txt", "new.txt"]
+    );
+    assert_eq!(
+        diff.files[0].hunks[0].diff_lines, "@@ -2 +2 @@ one\n-two\n+four\n",
+        "the intermediate state of `file.txt` is squashed away"
+    );
+    assert_eq!(
+        diff.stat,
+        DiffStat {
+            files_changed: 2,
+            insertions: 2,
+            deletions: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn hunks_within_coalesce_distance_are_merged() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        projects,
+        ..
+    } = &Test::default();
+
+    let lines: Vec<_> = (1..=20).map(|n| n.to_string()).collect();
+    fs::write(repository.path().join("file.txt"), lines.join("\n") + "\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let mut changed = lines.clone();
+    changed[1] = "two".into();
+    changed[11] = "twelve".into();
+    fs::write(
+        repository.path().join("file.txt"),
+        changed.join("\n") + "\n",
+    )
+    .unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let diff = controller
+        .branch_squashed_diff(*project_id, branch_id, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        diff.files[0].hunks.len(),
+        2,
+        "by default, only hunks within the context lines are merged"
+    );
+
+    projects
+        .update(&projects::UpdateRequest {
+            id: *project_id,
+            hunk_coalesce_distance: Some(9),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let diff = controller
+        .branch_squashed_diff(*project_id, branch_id, 0)
+        .await
+        .unwrap();
+    assert_eq!(diff.files[0].hunks.len(), 1);
+    assert_eq!(
+        diff.stat,
+        DiffStat {
+            files_changed: 1,
+            insertions: 2,
+            deletions: 2,
+        },
+        "the unchanged lines in between don't count as changes"
+    );
+}
@@ -0,0 +1,92 @@
+use super::*;
+
+#[tokio::test]
+async fn bases_come_before_dependents() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .branch_topological_order(*project_id)
+            .await
+            .unwrap(),
+        [branch_a, branch_b],
+        "independent branches keep their order"
+    );
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_b = controller
+        .create_commit(
+            *project_id,
+            branch_b,
+            "commit b",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    // let `a` build on top of `b`
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let parent = repo.find_commit(commit_b).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let commit_a = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "commit a",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut a = vb_state.get_branch(branch_a).unwrap();
+    a.head = commit_a;
+    vb_state.set_branch(a.clone()).unwrap();
+
+    assert_eq!(
+        controller
+            .branch_topological_order(*project_id)
+            .await
+            .unwrap(),
+        [branch_b, branch_a]
+    );
+
+    a.head = commit_b;
+    vb_state.set_branch(a).unwrap();
+    let err = controller
+        .branch_topological_order(*project_id)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("based on each other"),
+        "branches with the same head form a cycle: {err}"
+    );
+}
@@ -0,0 +1,103 @@
+use super::*;
+
+#[tokio::test]
+async fn is_where_branches_diverge() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let target_sha = project.virtual_branches().get_default_target().unwrap().sha;
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("a.txt"), "a").unwrap();
+    controller
+        .create_commit(*project_id, branch_a, "a", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("b.txt"), "b").unwrap();
+    controller
+        .create_commit(*project_id, branch_b, "b", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .branches_merge_base(*project_id, branch_a, branch_b)
+            .await
+            .unwrap(),
+        target_sha
+    );
+    assert_eq!(
+        controller
+            .branches_merge_base(*project_id, branch_b, branch_a)
+            .await
+            .unwrap(),
+        target_sha
+    );
+}
+
+#[tokio::test]
+async fn unrelated_histories_are_marked() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let signature = git2::Signature::now("test", "test@email.com").unwrap();
+    let empty_tree = repo
+        .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+        .unwrap();
+    let orphan = repo
+        .commit(None, &signature, &signature, "orphan", &empty_tree, &[])
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut branch = vb_state.get_branch(branch_b).unwrap();
+    branch.head = orphan;
+    vb_state.set_branch(branch).unwrap();
+
+    let err = controller
+        .branches_merge_base(*project_id, branch_a, branch_b)
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Marker::UnrelatedHistories));
+}
@@ -0,0 +1,161 @@
+use gitbutler_core::virtual_branches::BranchWithStatus;
+
+use super::*;
+
+#[tokio::test]
+async fn branches_with_integrated_heads_are_marked() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let merged_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("merged.txt"), "merged\n").unwrap();
+    let merged_head = controller
+        .create_commit(
+            *project_id,
+            merged_id,
+            "merged",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let open_id = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("open.txt"), "open\n").unwrap();
+    let open_head = controller
+        .create_commit(
+            *project_id,
+            open_id,
+            "open",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let statuses = |branches: Vec<BranchWithStatus>| {
+        branches
+            .into_iter()
+            .map(|branch| (branch.id, branch.head, branch.is_integrated))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        statuses(controller.branches_with_status(*project_id).await.unwrap()),
+        [(merged_id, merged_head, false), (open_id, open_head, false)]
+    );
+
+    // the remote target fast-forwards to the head of the first branch
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.reference("refs/remotes/origin/master", merged_head, true, "merged")
+        .unwrap();
+
+    assert_eq!(
+        statuses(controller.branches_with_status(*project_id).await.unwrap()),
+        [(merged_id, merged_head, true), (open_id, open_head, false)]
+    );
+}
+
+#[tokio::test]
+async fn checking_integration_writes_no_objects() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let shared = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+    fs::write(repository.path().join("shared.txt"), shared).unwrap();
+    repository.commit_all("shared");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("open.txt"), "open\n").unwrap();
+    fs::write(
+        repository.path().join("shared.txt"),
+        shared.replace("one", "branch"),
+    )
+    .unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "open",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // an upstream commit that also changes the shared file, so the branch has to be merged with it,
+    // with a new blob for the merged file, to check it
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let target = repo
+        .find_reference("refs/remotes/origin/master")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let blob = repo.blob(b"upstream\n").unwrap();
+    let mut tree = repo.treebuilder(Some(&target.tree().unwrap())).unwrap();
+    tree.insert("upstream.txt", blob, 0o100644).unwrap();
+    let shared_blob = repo
+        .blob(shared.replace("seven", "upstream").as_bytes())
+        .unwrap();
+    tree.insert("shared.txt", shared_blob, 0o100644).unwrap();
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@email.com").unwrap();
+    repo.commit(
+        Some("refs/remotes/origin/master"),
+        &signature,
+        &signature,
+        "upstream",
+        &tree,
+        &[&target],
+    )
+    .unwrap();
+
+    let loose_objects = || {
+        walkdir::WalkDir::new(repo.path().join("objects"))
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .count()
+    };
+    let before = loose_objects();
+    for _ in 0..10 {
+        let branches = controller.branches_with_status(*project_id).await.unwrap();
+        assert!(!branches[0].is_integrated);
+    }
+    assert_eq!(loose_objects(), before);
+}
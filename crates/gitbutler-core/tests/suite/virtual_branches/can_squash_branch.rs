@@ -0,0 +1,80 @@
+use gitbutler_core::virtual_branches::SquashBlocker;
+
+use super::*;
+
+#[tokio::test]
+async fn reasons_against_squashing() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("one.txt"), "").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        controller
+            .can_squash_branch(*project_id, branch_id)
+            .await
+            .unwrap(),
+        [SquashBlocker::NothingToSquash]
+    );
+
+    fs::write(repository.path().join("two.txt"), "").unwrap();
+    let head = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    assert!(controller
+        .can_squash_branch(*project_id, branch_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // another branch builds on top of it
+    let stacked_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut stacked = vb_state.get_branch(stacked_id).unwrap();
+    stacked.head = head;
+    vb_state.set_branch(stacked).unwrap();
+    assert_eq!(
+        controller
+            .can_squash_branch(*project_id, branch_id)
+            .await
+            .unwrap(),
+        [SquashBlocker::StackedBranches {
+            branch_ids: vec![stacked_id]
+        }]
+    );
+}
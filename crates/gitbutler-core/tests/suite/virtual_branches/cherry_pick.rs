@@ -26,17 +26,31 @@ mod cleanly {
         let commit_one = {
             fs::write(repository.path().join("file.txt"), "content").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         let commit_two = {
             fs::write(repository.path().join("file.txt"), "content two").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         controller
@@ -57,7 +71,7 @@ mod cleanly {
         );
 
         let cherry_picked_commit_oid = controller
-            .cherry_pick(*project_id, branch_id, commit_two)
+            .cherry_pick(*project_id, branch_id, commit_two, None)
             .await
             .unwrap();
         assert!(cherry_picked_commit_oid.is_some());
@@ -98,17 +112,31 @@ mod cleanly {
         let commit_one = {
             fs::write(repository.path().join("file.txt"), "content").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         let commit_two = {
             fs::write(repository.path().join("file_two.txt"), "content two").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         controller
@@ -135,7 +163,7 @@ mod cleanly {
             .unwrap();
 
         let cherry_picked_commit_oid = controller
-            .cherry_pick(*project_id, branch_two_id, commit_two)
+            .cherry_pick(*project_id, branch_two_id, commit_two, None)
             .await
             .unwrap();
         assert!(cherry_picked_commit_oid.is_some());
@@ -181,25 +209,46 @@ mod cleanly {
         let commit_one_oid = {
             fs::write(repository.path().join("file.txt"), "content").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         {
             fs::write(repository.path().join("file_two.txt"), "content two").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         let commit_three_oid = {
             fs::write(repository.path().join("file_three.txt"), "content three").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         controller
@@ -214,7 +263,7 @@ mod cleanly {
 
         assert_eq!(
             controller
-                .cherry_pick(*project_id, branch_id, commit_three_oid)
+                .cherry_pick(*project_id, branch_id, commit_three_oid, None)
                 .await
                 .unwrap_err()
                 .to_string(),
@@ -249,25 +298,46 @@ mod with_conflicts {
         let commit_one = {
             fs::write(repository.path().join("file.txt"), "content").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit one", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit one",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         {
             fs::write(repository.path().join("file_two.txt"), "content two").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit two", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit two",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         let commit_three = {
             fs::write(repository.path().join("file_three.txt"), "content three").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "commit three", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "commit three",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap()
+                .commit_id
         };
 
         controller
@@ -294,7 +364,7 @@ mod with_conflicts {
         {
             // cherry picking leads to conflict
             let cherry_picked_commit_oid = controller
-                .cherry_pick(*project_id, branch_id, commit_three)
+                .cherry_pick(*project_id, branch_id, commit_three, None)
                 .await
                 .unwrap();
             assert!(cherry_picked_commit_oid.is_none());
@@ -318,9 +388,16 @@ mod with_conflicts {
             // conflict can be resolved
             fs::write(repository.path().join("file_three.txt"), "resolved").unwrap();
             let commited_oid = controller
-                .create_commit(*project_id, branch_id, "resolution", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "resolution",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
-                .unwrap();
+                .unwrap()
+                .commit_id;
 
             let commit = repository.find_commit(commited_oid).unwrap();
             assert_eq!(commit.parent_count(), 2);
@@ -376,7 +453,7 @@ mod with_conflicts {
 
         assert_eq!(
             controller
-                .cherry_pick(*project_id, branch_id, commit_oid)
+                .cherry_pick(*project_id, branch_id, commit_oid, None)
                 .await
                 .unwrap_err()
                 .to_string(),
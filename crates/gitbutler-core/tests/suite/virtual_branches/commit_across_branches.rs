@@ -0,0 +1,125 @@
+use gitbutler_core::{error::Code, virtual_branches::CommitAssignment};
+
+use super::*;
+
+#[tokio::test]
+async fn creates_one_commit_per_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let (branch_a, branch_b) = setup_two_branches(repository, *project_id, controller).await;
+
+    let heads = controller
+        .commit_across_branches(
+            *project_id,
+            &[
+                assignment(branch_a, "commit a", "a.txt:1-2"),
+                assignment(branch_b, "commit b", "b.txt:1-2"),
+            ],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(heads.len(), 2);
+    for (head, (branch_id, message)) in heads
+        .iter()
+        .zip([(branch_a, "commit a"), (branch_b, "commit b")])
+    {
+        assert_eq!(head.branch_id, branch_id);
+        let branch = get_branch(controller, *project_id, branch_id).await;
+        assert_eq!(branch.commits.len(), 1);
+        assert_eq!(branch.commits[0].id, head.commit_id);
+        assert_eq!(branch.commits[0].description, message);
+        assert!(branch.files.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn rejects_hunk_assigned_twice() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let (branch_a, branch_b) = setup_two_branches(repository, *project_id, controller).await;
+
+    let err = controller
+        .commit_across_branches(
+            *project_id,
+            &[
+                assignment(branch_a, "commit a", "a.txt:1-2"),
+                assignment(branch_b, "commit b", "a.txt:1-2\nb.txt:1-2"),
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+
+    for branch_id in [branch_a, branch_b] {
+        let branch = get_branch(controller, *project_id, branch_id).await;
+        assert!(branch.commits.is_empty(), "nothing was committed");
+    }
+}
+
+/// Create two branches with a new file each.
+async fn setup_two_branches(
+    repository: &TestProject,
+    project_id: ProjectId,
+    controller: &Controller,
+) -> (branch::BranchId, branch::BranchId) {
+    controller
+        .set_base_branch(project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("a.txt"), "a\n").unwrap();
+    controller.list_virtual_branches(project_id).await.unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("b.txt"), "b\n").unwrap();
+    controller.list_virtual_branches(project_id).await.unwrap();
+
+    (branch_a, branch_b)
+}
+
+fn assignment(branch_id: branch::BranchId, message: &str, ownership: &str) -> CommitAssignment {
+    CommitAssignment {
+        branch_id,
+        message: message.to_owned(),
+        ownership: ownership.parse().unwrap(),
+    }
+}
+
+async fn get_branch(
+    controller: &Controller,
+    project_id: ProjectId,
+    branch_id: branch::BranchId,
+) -> gitbutler_core::virtual_branches::VirtualBranch {
+    controller
+        .list_virtual_branches(project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap()
+}
@@ -0,0 +1,59 @@
+use gitbutler_core::git::diff;
+
+use super::*;
+
+#[tokio::test]
+async fn is_smaller_than_the_file() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let mut content: Vec<u8> = (0..64 * 1024).map(|i| (i * 7 % 251) as u8).collect();
+    fs::write(repository.path().join("image.bin"), &content).unwrap();
+    let added = repository.commit_all("add binary");
+
+    content[1000..1010].fill(0xff);
+    fs::write(repository.path().join("image.bin"), &content).unwrap();
+    let modified = repository.commit_all("modify binary");
+
+    let delta_size = controller
+        .commit_binary_delta_size(*project_id, modified, path::Path::new("image.bin"))
+        .await
+        .unwrap()
+        .expect("the file is below the size limit");
+    assert!(delta_size > 0);
+    assert!(
+        delta_size < content.len() as u64 / 10,
+        "only a few bytes changed, and the delta is small: {delta_size}"
+    );
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let old = repo
+        .find_commit(added)
+        .unwrap()
+        .tree()
+        .unwrap()
+        .get_name("image.bin")
+        .unwrap()
+        .id();
+    let new = repo
+        .find_commit(modified)
+        .unwrap()
+        .tree()
+        .unwrap()
+        .get_name("image.bin")
+        .unwrap()
+        .id();
+    assert_eq!(
+        diff::binary_delta_size(&repo, Some(old), Some(new), 1024).unwrap(),
+        None,
+        "the delta isn't computed for blobs over the limit"
+    );
+    assert_eq!(
+        diff::binary_delta_size(&repo, Some(new), Some(new), 1024 * 1024).unwrap(),
+        Some(0)
+    );
+}
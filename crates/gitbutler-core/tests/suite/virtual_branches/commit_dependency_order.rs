@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use gitbutler_core::virtual_branches::{CommitDependencies, CycleError};
+
+use super::*;
+
+#[tokio::test]
+async fn commits_come_after_the_commits_they_change() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "line 1\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "line 1\nline 2\n").unwrap();
+    let first_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "add line 2",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    let second_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "add other",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("file.txt"), "line 1\nchanged 2\n").unwrap();
+    let third_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "change line 2",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let project_repository = gitbutler_core::project_repository::Repository::open(project).unwrap();
+    let dependencies =
+        gitbutler_core::virtual_branches::commit_dependencies(&project_repository, branch_id)
+            .unwrap();
+    assert_eq!(dependencies.commits, vec![first_id, second_id, third_id]);
+    assert_eq!(
+        dependencies.dependencies,
+        HashMap::from([(third_id, vec![first_id])]),
+        "only the last commit changes lines of another commit of the branch"
+    );
+
+    let order = controller
+        .commit_dependency_order(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert_eq!(order, vec![first_id, second_id, third_id]);
+}
+
+#[test]
+fn cycles_are_reported() {
+    let [a, b, c] = [1, 2, 3].map(|n| git2::Oid::from_str(&n.to_string().repeat(40)).unwrap());
+    let dependencies = CommitDependencies {
+        commits: vec![a, b, c],
+        dependencies: HashMap::from([(b, vec![c]), (c, vec![b])]),
+    };
+    assert_eq!(
+        dependencies.dependency_order(),
+        Err(CycleError {
+            commits: vec![b, c]
+        })
+    );
+
+    let dependencies = CommitDependencies {
+        commits: vec![a, b, c],
+        dependencies: HashMap::from([(a, vec![c])]),
+    };
+    assert_eq!(dependencies.dependency_order(), Ok(vec![b, c, a]));
+}
@@ -0,0 +1,109 @@
+use super::*;
+
+fn committed_content(repository: &TestProject, commit_id: git2::Oid, path: &str) -> String {
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let tree = repo.find_commit(commit_id).unwrap().tree().unwrap();
+    let blob = repo
+        .find_blob(tree.get_path(path::Path::new(path)).unwrap().id())
+        .unwrap();
+    String::from_utf8(blob.content().to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn fixes_committed_content_only() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let content = "one  \ntwo\t\nthree\n\n \n";
+    fs::write(repository.path().join("file.txt"), content).unwrap();
+
+    let commit = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions {
+                fix_whitespace: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(commit.adjusted_lines, Some(4));
+    assert_eq!(
+        committed_content(repository, commit.commit_id, "file.txt"),
+        "one\ntwo\nthree\n"
+    );
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+        content
+    );
+
+    let branch = get_virtual_branch(controller, *project_id, branch_id).await;
+    assert_eq!(branch.head, commit.commit_id);
+    assert_eq!(branch.files.len(), 1);
+    assert!(
+        branch.files[0].hunks.iter().all(|hunk| hunk.locked),
+        "the remaining whitespace changes depend on the fixed commit"
+    );
+}
+
+#[tokio::test]
+async fn leaves_existing_lines_alone() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "existing  \n").unwrap();
+    repository.commit_all("existing");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "existing  \nadded  \n").unwrap();
+
+    let commit = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions {
+                fix_whitespace: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(commit.adjusted_lines, Some(1));
+    assert_eq!(
+        committed_content(repository, commit.commit_id, "file.txt"),
+        "existing  \nadded\n"
+    );
+}
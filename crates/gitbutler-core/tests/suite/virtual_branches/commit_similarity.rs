@@ -0,0 +1,104 @@
+use super::*;
+
+#[tokio::test]
+async fn scores() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let commit_one_oid = {
+        fs::write(
+            repository.path().join("file.txt"),
+            "one\ntwo\nthree\nfour\n",
+        )
+        .unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    let commit_two_oid = {
+        fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    let commit_three_oid = {
+        fs::write(
+            repository.path().join("file.txt"),
+            "one\ntwo\nthree\nfive\n",
+        )
+        .unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    assert_eq!(
+        controller
+            .commit_similarity(*project_id, commit_one_oid, commit_one_oid)
+            .await
+            .unwrap(),
+        1.0
+    );
+    assert_eq!(
+        controller
+            .commit_similarity(*project_id, commit_one_oid, commit_two_oid)
+            .await
+            .unwrap(),
+        0.0
+    );
+
+    // commit three removes `four`, which commit one added
+    let score = controller
+        .commit_similarity(*project_id, commit_one_oid, commit_three_oid)
+        .await
+        .unwrap();
+    assert_eq!(score, 0.0, "the kind of change is part of the comparison");
+    assert_eq!(
+        controller
+            .commit_similarity(*project_id, commit_three_oid, commit_one_oid)
+            .await
+            .unwrap(),
+        score,
+        "the score doesn't depend on the order"
+    );
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use super::*;
+
+#[tokio::test]
+async fn lists_commits_from_head_to_base() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_one_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    let commit_one_oid = controller
+        .create_commit(
+            *project_id,
+            branch_one_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_one_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\nthree\n").unwrap();
+    let commit_three_oid = controller
+        .create_commit(
+            *project_id,
+            branch_one_id,
+            "commit three",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let commits = controller
+        .commits_touching_path(*project_id, Path::new("file.txt"))
+        .await
+        .unwrap();
+    assert_eq!(
+        commits
+            .iter()
+            .map(|c| (c.branch_id, c.commit_id))
+            .collect::<Vec<_>>(),
+        [
+            (branch_one_id, commit_three_oid),
+            (branch_one_id, commit_one_oid)
+        ],
+        "commits are listed from head to base"
+    );
+    assert_eq!(commits[0].hunks.len(), 1);
+    assert_eq!(
+        commits[0].hunks[0].diff_lines,
+        "@@ -1 +1,2 @@\n one\n+three\n"
+    );
+
+    assert!(controller
+        .commits_touching_path(*project_id, Path::new("untouched.txt"))
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,113 @@
+use gitbutler_core::project_repository::{
+    self, conflicts,
+    conflicts::{ConflictSide, ConflictStyle},
+};
+
+use super::*;
+
+/// Make `file.txt` conflict between the target and a virtual branch while applying it with
+/// `explicit_style`, and return its content.
+async fn conflicting_file(
+    conflict_style: Option<&str>,
+    explicit_style: Option<ConflictStyle>,
+) -> String {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    if let Some(style) = conflict_style {
+        git2::Repository::open(repository.path())
+            .unwrap()
+            .config()
+            .unwrap()
+            .set_str("merge.conflictStyle", style)
+            .unwrap();
+    }
+
+    {
+        fs::write(repository.path().join("file.txt"), "first").unwrap();
+        let first_commit_oid = repository.commit_all("first");
+        fs::write(repository.path().join("file.txt"), "second").unwrap();
+        repository.commit_all("second");
+        repository.push();
+        repository.reset_hard(Some(first_commit_oid));
+    }
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "conflict").unwrap();
+    controller.list_virtual_branches(*project_id).await.unwrap();
+
+    controller.update_base_branch(*project_id).await.unwrap();
+    controller
+        .apply_virtual_branch(*project_id, branch_id, explicit_style)
+        .await
+        .unwrap();
+
+    let content = fs::read_to_string(repository.path().join("file.txt")).unwrap();
+    let shows_base = match explicit_style {
+        Some(style) => style != ConflictStyle::Merge,
+        None => matches!(conflict_style, Some("diff3" | "zdiff3")),
+    };
+    if shows_base {
+        let project_repository = project_repository::Repository::open(project).unwrap();
+        conflicts::resolve_side(&project_repository, "file.txt", ConflictSide::Base)
+            .expect("the markers contain the base");
+        assert_eq!(
+            fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+            "first\n"
+        );
+    }
+    content
+}
+
+#[tokio::test]
+async fn merge_by_default() {
+    assert_eq!(
+        conflicting_file(None, None).await,
+        "<<<<<<< ours\nconflict\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+    assert_eq!(
+        conflicting_file(Some("merge"), None).await,
+        "<<<<<<< ours\nconflict\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+}
+
+#[tokio::test]
+async fn diff3_includes_the_base() {
+    assert_eq!(
+        conflicting_file(Some("diff3"), None).await,
+        "<<<<<<< ours\nconflict\n||||||| ancestor\nfirst\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+}
+
+#[tokio::test]
+async fn zdiff3_is_written_like_diff3() {
+    assert_eq!(
+        conflicting_file(Some("zdiff3"), None).await,
+        "<<<<<<< ours\nconflict\n||||||| ancestor\nfirst\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+}
+
+#[tokio::test]
+async fn explicit_style_overrides_config() {
+    assert_eq!(
+        conflicting_file(Some("diff3"), Some(ConflictStyle::Merge)).await,
+        "<<<<<<< ours\nconflict\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+    assert_eq!(
+        conflicting_file(None, Some(ConflictStyle::Diff3)).await,
+        "<<<<<<< ours\nconflict\n||||||| ancestor\nfirst\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+}
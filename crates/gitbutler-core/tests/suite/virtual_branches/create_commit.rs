@@ -1,4 +1,5 @@
 use gitbutler_core::{
+    error::Code,
     id::Id,
     virtual_branches::{Branch, VirtualBranch},
 };
@@ -36,7 +37,13 @@ async fn should_lock_updated_hunks() {
     }
 
     controller
-        .create_commit(*project_id, branch_id, "test", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "test",
+            None,
+            &CommitOptions::default(),
+        )
         .await
         .unwrap();
 
@@ -95,7 +102,13 @@ async fn should_not_lock_disjointed_hunks() {
     }
 
     controller
-        .create_commit(*project_id, branch_id, "test commit", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "test commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
         .unwrap();
     controller
@@ -196,7 +209,13 @@ async fn should_reset_into_same_branch() {
     write_file(repository, "file.txt", &lines);
 
     controller
-        .create_commit(*project_id, branch_2_id, "commit to branch 2", None, false)
+        .create_commit(
+            *project_id,
+            branch_2_id,
+            "commit to branch 2",
+            None,
+            &CommitOptions::default(),
+        )
         .await
         .unwrap();
 
@@ -256,17 +275,31 @@ async fn should_double_lock() {
     write_file(repository, "file.txt", &lines);
 
     let commit_1 = controller
-        .create_commit(*project_id, branch_id, "commit 1", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit 1",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     lines[6] = "change 2".to_string();
     write_file(repository, "file.txt", &lines);
 
     let commit_2 = controller
-        .create_commit(*project_id, branch_id, "commit 2", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit 2",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     lines[3] = "change3".to_string();
     write_file(repository, "file.txt", &lines);
@@ -279,6 +312,180 @@ async fn should_double_lock() {
     assert_eq!(locks[1].commit_id, commit_2);
 }
 
+#[tokio::test]
+async fn should_not_lock_reindented_hunks_if_configured() {
+    let Test {
+        project_id,
+        controller,
+        projects,
+        repository,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.rs"), "fn a() {\n  b();\n}\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.rs"), "fn a() {\n    b();\n}\n").unwrap();
+
+    let branch = get_virtual_branch(controller, *project_id, branch_id).await;
+    assert!(
+        branch.files[0].hunks[0].locked,
+        "by default, whitespace changes lock like any other change"
+    );
+
+    projects
+        .update(&projects::UpdateRequest {
+            id: *project_id,
+            ignore_whitespace_locks: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let branch = get_virtual_branch(controller, *project_id, branch_id).await;
+    assert_eq!(branch.files.len(), 1, "it's still a change");
+    assert_eq!(branch.files[0].hunks.len(), 1);
+    assert!(!branch.files[0].hunks[0].locked);
+}
+
+#[tokio::test]
+async fn should_reject_conflict_markers() {
+    let Test {
+        project_id,
+        controller,
+        repository,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    write_file(
+        repository,
+        "file.txt",
+        &[
+            "first".to_string(),
+            "<<<<<<< ours".to_string(),
+            "one".to_string(),
+            "=======".to_string(),
+            "two".to_string(),
+            ">>>>>>> theirs".to_string(),
+        ],
+    );
+
+    let err = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "markers",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+    assert_eq!(
+        err.root_cause().to_string(),
+        "refusing to commit unresolved conflict markers at file.txt:2, file.txt:4, file.txt:6"
+    );
+
+    let branch = get_virtual_branch(controller, *project_id, branch_id).await;
+    assert_eq!(branch.commits.len(), 0);
+    assert_eq!(branch.files.len(), 1);
+
+    // markers can be committed on purpose
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "markers",
+            None,
+            &CommitOptions {
+                allow_conflict_markers: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let branch = get_virtual_branch(controller, *project_id, branch_id).await;
+    assert_eq!(branch.commits.len(), 1);
+    assert_eq!(branch.files.len(), 0);
+}
+
+#[tokio::test]
+async fn should_append_trailers_to_message() {
+    let Test {
+        project_id,
+        controller,
+        repository,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    write_file(repository, "file.txt", &["content".to_string()]);
+
+    let trailers = [
+        ("Signed-off-by".to_string(), "A <a@example.com>".to_string()),
+        ("Change-Id".to_string(), "I123".to_string()),
+    ];
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "subject\n\nbody",
+            None,
+            &CommitOptions {
+                trailers: trailers.to_vec(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    assert_eq!(
+        repo.find_commit(commit_id).unwrap().message(),
+        Some("subject\n\nbody\n\nSigned-off-by: A <a@example.com>\nChange-Id: I123")
+    );
+}
+
 fn write_file(repository: &TestProject, path: &str, lines: &[String]) {
     fs::write(repository.path().join(path), lines.join("\n")).unwrap()
 }
@@ -24,7 +24,13 @@ async fn integration() {
 
         std::fs::write(repository.path().join("file.txt"), "first\n").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "first", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "first",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
         controller
@@ -62,7 +68,13 @@ async fn integration() {
         std::fs::write(repository.path().join("file.txt"), "first\nsecond").unwrap();
 
         controller
-            .create_commit(*project_id, branch_id, "second", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "second",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
     }
@@ -103,7 +115,7 @@ async fn integration() {
     {
         // should mark commits as integrated
         controller
-            .fetch_from_remotes(*project_id, None)
+            .fetch_from_remotes(*project_id, None, false)
             .await
             .unwrap();
 
@@ -251,7 +263,13 @@ async fn conflicts_with_commited() {
         assert_eq!(branches.len(), 1);
 
         controller
-            .create_commit(*project_id, branches[0].id, "hej", None, false)
+            .create_commit(
+                *project_id,
+                branches[0].id,
+                "hej",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
     };
@@ -0,0 +1,85 @@
+use super::*;
+
+#[tokio::test]
+async fn hunks_depending_on_two_branches() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        projects,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    // blame based locking tracks every commit that changed the lines of a hunk
+    projects
+        .update(&projects::UpdateRequest {
+            id: *project_id,
+            use_new_locking: Some(false),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    controller
+        .create_commit(*project_id, branch_a, "a", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let content = content.replace("line 7\n", "change 7\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    controller
+        .create_commit(*project_id, branch_b, "b", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    assert!(
+        controller
+            .cross_branch_locked_hunks(*project_id)
+            .await
+            .unwrap()
+            .is_empty(),
+        "without worktree changes nothing is locked"
+    );
+
+    // its context reaches the lines of both branches
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 4\n", "change 4\n"),
+    )
+    .unwrap();
+
+    let files = controller
+        .cross_branch_locked_hunks(*project_id)
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, path::Path::new("file.txt"));
+    assert_eq!(files[0].hunks.len(), 1);
+    assert_eq!(files[0].hunks[0].hunk_id, "1-8");
+    assert_eq!(files[0].hunks[0].locked_to, [branch_a, branch_b]);
+}
@@ -0,0 +1,157 @@
+use gitbutler_core::error::Code;
+
+use super::*;
+
+/// Create `refs/remotes/origin/develop` with a commit on top of the current head which adds
+/// `develop.txt`, without touching the worktree.
+fn create_remote_develop(repository: &TestProject) {
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let blob = repo.blob(b"develop\n").unwrap();
+    let mut tree = repo.treebuilder(Some(&head.tree().unwrap())).unwrap();
+    tree.insert("develop.txt", blob, 0o100644).unwrap();
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    repo.commit(
+        Some("refs/remotes/origin/develop"),
+        &signature,
+        &signature,
+        "develop",
+        &tree,
+        &[&head],
+    )
+    .unwrap();
+}
+
+fn paths(files: &[gitbutler_core::virtual_branches::RemoteBranchFile]) -> Vec<String> {
+    files
+        .iter()
+        .map(|file| file.path.display().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn three_dot_by_default() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    create_remote_develop(repository);
+
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("mine.txt"), "mine\n").unwrap();
+
+    let files = controller
+        .diff_against_remote(*project_id, "origin", "develop", None, 0, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        paths(&files),
+        ["mine.txt"],
+        "changes of the remote branch aren't shown"
+    );
+
+    let files = controller
+        .diff_against_remote(*project_id, "origin", "develop", None, 0, true)
+        .await
+        .unwrap();
+    assert_eq!(
+        paths(&files),
+        ["develop.txt", "mine.txt"],
+        "compared to its tip, the file of the remote branch is missing"
+    );
+}
+
+#[tokio::test]
+async fn single_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    create_remote_develop(repository);
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("a.txt"), "a\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_a,
+            "commit in a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("b.txt"), "b\n").unwrap();
+
+    let files = controller
+        .diff_against_remote(*project_id, "origin", "develop", Some(branch_a), 0, false)
+        .await
+        .unwrap();
+    assert_eq!(paths(&files), ["a.txt"]);
+
+    let files = controller
+        .diff_against_remote(*project_id, "origin", "develop", Some(branch_b), 0, false)
+        .await
+        .unwrap();
+    assert_eq!(paths(&files), ["b.txt"]);
+}
+
+#[tokio::test]
+async fn missing_remote_branch() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let err = controller
+        .diff_against_remote(*project_id, "origin", "develop", None, 0, false)
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+    assert_eq!(
+        err.root_cause().to_string(),
+        "remote branch 'origin/develop' not found, it may have to be fetched first"
+    );
+}
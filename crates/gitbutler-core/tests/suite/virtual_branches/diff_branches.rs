@@ -0,0 +1,99 @@
+use super::*;
+
+#[tokio::test]
+async fn includes_committed_and_uncommitted_changes() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_a,
+            "commit in a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+
+    let files = controller
+        .diff_branches(*project_id, branch_a, branch_b, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| file.path.display().to_string())
+            .collect::<Vec<_>>(),
+        ["file.txt", "other.txt"]
+    );
+    assert_eq!(
+        files[0].hunks[0].diff_lines, "@@ -2 +1,0 @@ one\n-two\n",
+        "the commit of a isn't in b"
+    );
+    assert_eq!(
+        files[1].hunks[0].diff_lines, "@@ -0,0 +1 @@\n+other\n",
+        "the uncommitted file of b isn't in a"
+    );
+}
+
+#[tokio::test]
+async fn identical_branches_have_no_changes() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    assert!(controller
+        .diff_branches(*project_id, branch_a, branch_b, 3)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,66 @@
+use gitbutler_core::virtual_branches::{DiffStat, DirStat};
+
+use super::*;
+
+#[tokio::test]
+async fn committed_and_uncommitted_changes_roll_up_to_parents() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::create_dir_all(repository.path().join("src")).unwrap();
+    fs::write(repository.path().join("src/a.rs"), "one\ntwo\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    fs::create_dir_all(repository.path().join("docs")).unwrap();
+    fs::write(repository.path().join("src/b.rs"), "one\n").unwrap();
+    fs::write(repository.path().join("docs/c.md"), "one\n").unwrap();
+
+    let stat = |files_changed, insertions| DiffStat {
+        files_changed,
+        insertions,
+        deletions: 0,
+    };
+    assert_eq!(
+        controller.dirstat(*project_id).await.unwrap(),
+        [
+            DirStat {
+                path: "".into(),
+                committed: stat(1, 2),
+                uncommitted: stat(2, 2),
+            },
+            DirStat {
+                path: "docs".into(),
+                committed: stat(0, 0),
+                uncommitted: stat(1, 1),
+            },
+            DirStat {
+                path: "src".into(),
+                committed: stat(1, 2),
+                uncommitted: stat(1, 1),
+            },
+        ]
+    );
+}
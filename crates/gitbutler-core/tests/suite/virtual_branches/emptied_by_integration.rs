@@ -0,0 +1,157 @@
+use gitbutler_core::virtual_branches::{EmptiedCommit, EmptyCommitConfidence};
+
+use super::*;
+
+/// Commit `content` to `path` on top of the remote target branch.
+fn commit_upstream(repo: &git2::Repository, path: &str, content: &str) {
+    let parent = repo
+        .find_reference("refs/remotes/origin/master")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    let blob = repo.blob(content.as_bytes()).unwrap();
+    let mut tree = repo.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+    tree.insert(path, blob, 0o100644).unwrap();
+    let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@email.com").unwrap();
+    repo.commit(
+        Some("refs/remotes/origin/master"),
+        &signature,
+        &signature,
+        "upstream",
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn commits_with_changes_already_upstream() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("new.txt"), "content\n").unwrap();
+    let emptied_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "first",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "second",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert!(
+        controller
+            .emptied_by_integration(*project_id, branch_id)
+            .await
+            .unwrap()
+            .is_empty(),
+        "nothing is upstream yet"
+    );
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    commit_upstream(&repo, "new.txt", "content\n");
+
+    assert_eq!(
+        controller
+            .emptied_by_integration(*project_id, branch_id)
+            .await
+            .unwrap(),
+        [EmptiedCommit {
+            commit_id: emptied_commit_id,
+            confidence: EmptyCommitConfidence::Certain,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn squash_merged_commits() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("new.txt"), "one\n").unwrap();
+    let first_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "first",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("new.txt"), "one\ntwo\n").unwrap();
+    let second_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "second",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    commit_upstream(&repo, "new.txt", "one\ntwo\n");
+
+    assert_eq!(
+        controller
+            .emptied_by_integration(*project_id, branch_id)
+            .await
+            .unwrap(),
+        [
+            EmptiedCommit {
+                commit_id: second_commit_id,
+                confidence: EmptyCommitConfidence::Certain,
+            },
+            EmptiedCommit {
+                commit_id: first_commit_id,
+                confidence: EmptyCommitConfidence::SquashMerged,
+            }
+        ],
+        "the first commit conflicts with the squashed change on its own"
+    );
+}
@@ -17,7 +17,7 @@ async fn should_update_last_fetched() {
     assert!(before_fetch.last_fetched_ms.is_none());
 
     let fetch = controller
-        .fetch_from_remotes(*project_id, None)
+        .fetch_from_remotes(*project_id, None, false)
         .await
         .unwrap();
     assert!(fetch.last_fetched_ms.is_some());
@@ -27,7 +27,7 @@ async fn should_update_last_fetched() {
     assert_eq!(fetch.last_fetched_ms, after_fetch.last_fetched_ms);
 
     let second_fetch = controller
-        .fetch_from_remotes(*project_id, None)
+        .fetch_from_remotes(*project_id, None, true)
         .await
         .unwrap();
     assert!(second_fetch.last_fetched_ms.is_some());
@@ -40,3 +40,73 @@ async fn should_update_last_fetched() {
         after_second_fetch.last_fetched_ms
     );
 }
+
+#[tokio::test]
+async fn upstreams_deleted_on_the_remote_are_pruned() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        projects,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    controller
+        .push_virtual_branch(*project_id, branch_id, false, None)
+        .await
+        .unwrap();
+    let upstream = project
+        .virtual_branches()
+        .get_branch(branch_id)
+        .unwrap()
+        .upstream
+        .unwrap();
+
+    // the branch was merged and deleted on the remote
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let remote_repo =
+        git2::Repository::open(repo.find_remote("origin").unwrap().url().unwrap()).unwrap();
+    remote_repo
+        .find_reference(&format!("refs/heads/{}", upstream.branch()))
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    controller
+        .fetch_from_remotes(*project_id, None, false)
+        .await
+        .unwrap();
+    assert!(
+        matches!(
+            projects.get(*project_id).unwrap().project_data_last_fetched,
+            Some(projects::FetchResult::Fetched { .. })
+        ),
+        "the target is still fetched"
+    );
+    assert!(repo.find_reference(&upstream.to_string()).is_err());
+
+    let stale = controller.stale_remote_branches(*project_id).await.unwrap();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].missing_ref, upstream);
+}
@@ -0,0 +1,159 @@
+use gitbutler_core::{
+    git::diff::{DiffLineKind, WordDiff, WordSpan},
+    virtual_branches::WorktreeFileDiff,
+};
+
+use super::*;
+
+#[tokio::test]
+async fn only_the_requested_file_is_diffed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("a.txt"), "a\n").unwrap();
+    fs::write(repository.path().join("b.txt"), "b\n").unwrap();
+
+    let WorktreeFileDiff::Patch { hunks, word_diffs } = controller
+        .file_diff(*project_id, path::Path::new("a.txt"), 0, WordDiff::Disabled)
+        .await
+        .unwrap()
+    else {
+        panic!("expected a patch");
+    };
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].diff_lines, "@@ -0,0 +1 @@\n+a\n");
+    assert_eq!(word_diffs, None, "words are only diffed if requested");
+}
+
+#[tokio::test]
+async fn unchanged_and_binary_files() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    repository.commit_all("file");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("image.bin"), [0, 1, 2, 3]).unwrap();
+
+    assert_eq!(
+        controller
+            .file_diff(
+                *project_id,
+                path::Path::new("file.txt"),
+                3,
+                WordDiff::Disabled
+            )
+            .await
+            .unwrap(),
+        WorktreeFileDiff::Unchanged
+    );
+    assert!(matches!(
+        controller
+            .file_diff(
+                *project_id,
+                path::Path::new("image.bin"),
+                3,
+                WordDiff::Disabled
+            )
+            .await
+            .unwrap(),
+        WorktreeFileDiff::Binary {
+            new_size_bytes: 4,
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn changed_words_are_listed_if_requested() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "the quick fox\n").unwrap();
+    repository.commit_all("file");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "the slow fox\n").unwrap();
+
+    let without_words = controller
+        .file_diff(
+            *project_id,
+            path::Path::new("file.txt"),
+            3,
+            WordDiff::Disabled,
+        )
+        .await
+        .unwrap();
+    let WorktreeFileDiff::Patch { hunks, word_diffs } = controller
+        .file_diff(
+            *project_id,
+            path::Path::new("file.txt"),
+            3,
+            WordDiff::Enabled,
+        )
+        .await
+        .unwrap()
+    else {
+        panic!("expected a patch");
+    };
+    let WorktreeFileDiff::Patch {
+        hunks: hunks_without_words,
+        ..
+    } = without_words
+    else {
+        panic!("expected a patch");
+    };
+    assert_eq!(
+        hunks, hunks_without_words,
+        "the hunks are the same either way"
+    );
+
+    let word_diffs = word_diffs.unwrap();
+    assert_eq!(word_diffs.len(), 1);
+    assert_eq!(
+        word_diffs[0]
+            .iter()
+            .map(|line| (line.kind, line.line, line.spans.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                DiffLineKind::Deletion,
+                1,
+                vec![WordSpan { start: 4, end: 9 }]
+            ),
+            (
+                DiffLineKind::Addition,
+                1,
+                vec![WordSpan { start: 4, end: 8 }]
+            ),
+        ]
+    );
+}
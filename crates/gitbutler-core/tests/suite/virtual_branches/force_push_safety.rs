@@ -0,0 +1,84 @@
+use gitbutler_core::virtual_branches::ForcePushSafety;
+
+use super::*;
+
+#[tokio::test]
+async fn detects_commits_only_on_upstream() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .force_push_safety(*project_id, branch_id)
+            .await
+            .unwrap(),
+        ForcePushSafety::Safe,
+        "nothing was pushed yet"
+    );
+
+    fs::write(repository.path().join("file.txt"), "one").unwrap();
+    let commit_one_oid = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(repository.path().join("file.txt"), "two").unwrap();
+    let commit_two_oid = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    controller
+        .push_virtual_branch(*project_id, branch_id, false, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .force_push_safety(*project_id, branch_id)
+            .await
+            .unwrap(),
+        ForcePushSafety::Safe
+    );
+
+    controller
+        .reset_virtual_branch(*project_id, branch_id, commit_one_oid)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .force_push_safety(*project_id, branch_id)
+            .await
+            .unwrap(),
+        ForcePushSafety::WouldOverwrite(vec![commit_two_oid])
+    );
+}
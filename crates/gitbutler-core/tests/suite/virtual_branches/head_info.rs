@@ -0,0 +1,41 @@
+use gitbutler_core::project_repository;
+
+use super::*;
+
+#[tokio::test]
+async fn two_commits_ahead_of_target() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.set_head("refs/heads/master").unwrap();
+    fs::write(repository.path().join("file.txt"), "first\n").unwrap();
+    repository.commit_all("first");
+    fs::write(repository.path().join("file.txt"), "second\n").unwrap();
+    let head_id = repository.commit_all("second");
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    let info = project_repository.head_info().unwrap();
+    assert_eq!(info.branch_name.as_deref(), Some("master"));
+    assert_eq!(info.head, head_id);
+    assert!(head_id.to_string().starts_with(&info.short_id));
+    assert_eq!((info.ahead, info.behind), (2, 0));
+    assert!(!info.is_dirty);
+
+    fs::write(repository.path().join("untracked.txt"), "new\n").unwrap();
+    assert!(project_repository.head_info().unwrap().is_dirty);
+}
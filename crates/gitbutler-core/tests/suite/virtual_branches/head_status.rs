@@ -0,0 +1,60 @@
+use gitbutler_core::project_repository::{self, HeadStatus};
+
+use super::*;
+
+#[tokio::test]
+async fn on_branch() {
+    let Test {
+        repository,
+        project,
+        ..
+    } = &Test::default();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let head = repo.head().unwrap();
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    assert_eq!(
+        project_repository.head_status().unwrap(),
+        HeadStatus::OnBranch {
+            name: head.name().unwrap().to_owned(),
+            head: head.target().unwrap(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn detached() {
+    let Test {
+        repository,
+        project,
+        ..
+    } = &Test::default();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let head_id = repo.head().unwrap().target().unwrap();
+    repo.set_head_detached(head_id).unwrap();
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    assert_eq!(
+        project_repository.head_status().unwrap(),
+        HeadStatus::Detached { head: head_id }
+    );
+}
+
+#[tokio::test]
+async fn unborn() {
+    let Test {
+        repository,
+        project,
+        ..
+    } = &Test::default();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.set_head("refs/heads/unborn").unwrap();
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    assert_eq!(
+        project_repository.head_status().unwrap(),
+        HeadStatus::Unborn
+    );
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+#[tokio::test]
+async fn groups_are_ordered_by_path_and_line() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=30).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("a.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(
+        repository.path().join("a.txt"),
+        content
+            .replace("line 2\n", "change 2\n")
+            .replace("line 28\n", "change 28\n"),
+    )
+    .unwrap();
+    fs::write(repository.path().join("b.txt"), "new\n").unwrap();
+
+    let branch_id = controller
+        .list_virtual_branches(*project_id)
+        .await
+        .unwrap()
+        .0[0]
+        .id;
+
+    let groups = controller.hunk_apply_order(*project_id).await.unwrap();
+    assert_eq!(
+        groups
+            .iter()
+            .map(|group| (
+                group.branch_id,
+                group.path.to_str().unwrap(),
+                group.hunk_ids.clone()
+            ))
+            .collect::<Vec<_>>(),
+        [
+            (branch_id, "a.txt", vec!["1-6".to_string()]),
+            (branch_id, "a.txt", vec!["25-31".to_string()]),
+            (branch_id, "b.txt", vec!["1-2".to_string()]),
+        ]
+    );
+}
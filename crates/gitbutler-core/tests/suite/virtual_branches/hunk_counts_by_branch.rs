@@ -0,0 +1,68 @@
+use super::*;
+
+#[tokio::test]
+async fn counts_locked_and_owned_hunks() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 1\n", "change 1\n"),
+    )
+    .unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_a,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // the change depends on the commit in `a`, so it's locked to it
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 1\n", "change 2\n"),
+    )
+    .unwrap();
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+
+    let counts = controller.hunk_counts_by_branch(*project_id).await.unwrap();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[0].branch_id, branch_a);
+    assert_eq!((counts[0].locked, counts[0].owned), (1, 1));
+    assert_eq!(counts[1].branch_id, branch_b);
+    assert_eq!((counts[1].locked, counts[1].owned), (0, 1));
+}
@@ -0,0 +1,474 @@
+use gitbutler_core::virtual_branches::BinaryChange;
+
+use super::*;
+
+#[tokio::test]
+async fn stale_locks_are_reused_until_branches_change() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 2\n", "change 2\n"),
+    )
+    .unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert!(!locks.stale, "there is nothing to reuse yet");
+    assert_eq!(locks.hunks.len(), 1);
+    assert_eq!(locks.hunks[0].path, path::Path::new("file.txt"));
+    assert_eq!(locks.hunks[0].locked_to[0].commit_id, commit_id);
+
+    // the change is gone, but the locks computed last are still returned
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let stale_locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert!(stale_locks.stale);
+    assert_eq!(stale_locks.hunks, locks.hunks);
+
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert!(!locks.stale);
+    assert!(locks.hunks.is_empty());
+
+    fs::write(repository.path().join("file.txt"), "other\n").unwrap();
+    controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "other",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert!(
+        !locks.stale,
+        "the branch got a new commit, so the previous locks are outdated"
+    );
+}
+
+#[tokio::test]
+async fn hunks_without_locks_are_unassigned() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("committed.txt"), &content).unwrap();
+    fs::write(repository.path().join("worktree.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let committed = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("committed.txt"), &committed).unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(
+        repository.path().join("committed.txt"),
+        committed.replace("line 2\n", "change 2\n"),
+    )
+    .unwrap();
+    fs::write(
+        repository.path().join("worktree.txt"),
+        content.replace("line 1\n", "change 1\n"),
+    )
+    .unwrap();
+
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert_eq!(locks.hunks.len(), 1);
+    assert_eq!(locks.hunks[0].path, path::Path::new("committed.txt"));
+    assert_eq!(locks.hunks[0].locked_to[0].commit_id, commit_id);
+    assert_eq!(locks.unassigned.len(), 1);
+    assert_eq!(locks.unassigned[0].path, path::Path::new("worktree.txt"));
+
+    let stale_locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert!(stale_locks.stale);
+    assert_eq!(stale_locks.unassigned, locks.unassigned);
+}
+
+#[tokio::test]
+async fn context_lines_only_affect_the_diff() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 4\n", "change 4\n"),
+    )
+    .unwrap();
+
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert_eq!(locks.hunks.len(), 1);
+    assert_eq!(locks.hunks[0].diff, None);
+
+    let without_context = controller
+        .hunk_locks(*project_id, false, Some(0), false)
+        .await
+        .unwrap();
+    assert_eq!(without_context.hunks.len(), 1);
+    assert_eq!(without_context.hunks[0].hunk_id, locks.hunks[0].hunk_id);
+    assert_eq!(without_context.hunks[0].locked_to[0].commit_id, commit_id);
+    assert_eq!(
+        without_context.hunks[0].diff.as_deref(),
+        Some("@@ -4 +4 @@ line 3\n-line 4\n+change 4\n")
+    );
+
+    let with_context = controller
+        .hunk_locks(*project_id, false, Some(1), false)
+        .await
+        .unwrap();
+    assert_eq!(with_context.hunks[0].locked_to, locks.hunks[0].locked_to);
+    assert_eq!(
+        with_context.hunks[0].diff.as_deref(),
+        Some("@@ -3,3 +3,3 @@ line 2\n line 3\n-line 4\n+change 4\n line 5\n")
+    );
+}
+
+#[tokio::test]
+async fn locks_follow_new_commits() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "line\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "first\n").unwrap();
+    let first_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "first",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(repository.path().join("file.txt"), "second\n").unwrap();
+    for _ in 0..2 {
+        // the second time around, the ranges of the unchanged branches are reused
+        let locks = controller
+            .hunk_locks(*project_id, false, None, false)
+            .await
+            .unwrap();
+        assert_eq!(locks.hunks.len(), 1);
+        assert_eq!(locks.hunks[0].locked_to[0].commit_id, first_commit_id);
+    }
+
+    let second_commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "second",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("file.txt"), "third\n").unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert_eq!(locks.hunks.len(), 1);
+    assert_eq!(locks.hunks[0].locked_to[0].commit_id, second_commit_id);
+}
+
+#[tokio::test]
+async fn binary_changes_are_listed_separately() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("image.png"), b"\x89PNG\0\x01\x02").unwrap();
+    fs::write(repository.path().join("text.txt"), "text\n").unwrap();
+
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        locks.binary_changes,
+        [BinaryChange {
+            path: "image.png".into(),
+            branch_id,
+        }]
+    );
+    assert_eq!(locks.unassigned.len(), 1);
+    assert_eq!(locks.unassigned[0].path, path::Path::new("text.txt"));
+
+    let stale_locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert_eq!(stale_locks.binary_changes, locks.binary_changes);
+}
+
+#[tokio::test]
+async fn locks_name_author_and_summary_of_locked_commit() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "change the first line\n\nwith a body",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 2\n", "change 2\n"),
+    )
+    .unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, true, None, false)
+        .await
+        .unwrap();
+    assert_eq!(locks.hunks.len(), 1);
+    let lock = &locks.hunks[0].locked_to[0];
+    assert_eq!(lock.commit_id, commit_id);
+    let commit = repository.find_commit(commit_id).unwrap();
+    assert_eq!(lock.author, commit.author().name().unwrap());
+    assert_eq!(lock.summary, "change the first line");
+}
+
+#[tokio::test]
+async fn renamed_and_edited_files_lock_against_their_old_path() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=20).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::remove_file(repository.path().join("file.txt")).unwrap();
+    fs::write(
+        repository.path().join("moved.txt"),
+        content.replace("line 2\n", "change 2\n"),
+    )
+    .unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    let locked_paths: Vec<_> = locks.hunks.iter().map(|hunk| hunk.path.clone()).collect();
+    assert_eq!(
+        locked_paths,
+        [
+            path::PathBuf::from("file.txt"),
+            path::PathBuf::from("moved.txt")
+        ],
+        "the edit next to the committed change locks both sides of the rename"
+    );
+    for hunk in &locks.hunks {
+        assert_eq!(hunk.locked_to.len(), 1);
+        assert_eq!(hunk.locked_to[0].commit_id, commit_id);
+    }
+
+    fs::write(
+        repository.path().join("moved.txt"),
+        content.replace("line 20\n", "change 20\n"),
+    )
+    .unwrap();
+    let locks = controller
+        .hunk_locks(*project_id, false, None, false)
+        .await
+        .unwrap();
+    assert!(
+        locks.hunks.is_empty(),
+        "the edit is far from the committed change, so neither side of the rename is locked"
+    );
+    assert_eq!(locks.unassigned.len(), 2);
+}
@@ -0,0 +1,49 @@
+use gitbutler_core::virtual_branches::HunkRef;
+
+use super::*;
+
+#[tokio::test]
+async fn compares_changed_lines_across_files() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("a.txt"), "x\ny\n").unwrap();
+    fs::write(repository.path().join("b.txt"), "x\ny\n").unwrap();
+    fs::write(repository.path().join("c.txt"), "x\nz\n").unwrap();
+    let hunk = |path: &str| HunkRef {
+        path: path.into(),
+        hunk_id: "1-3".into(),
+    };
+
+    let comparison = controller
+        .hunks_identical(*project_id, &hunk("a.txt"), &hunk("b.txt"))
+        .await
+        .unwrap();
+    assert!(comparison.identical);
+    assert_eq!(comparison.similarity, 1.0);
+
+    let comparison = controller
+        .hunks_identical(*project_id, &hunk("a.txt"), &hunk("c.txt"))
+        .await
+        .unwrap();
+    assert!(!comparison.identical);
+    assert_eq!(
+        comparison.similarity,
+        1.0 / 3.0,
+        "one of three lines is shared"
+    );
+
+    assert!(controller
+        .hunks_identical(*project_id, &hunk("a.txt"), &hunk("missing.txt"))
+        .await
+        .is_err());
+}
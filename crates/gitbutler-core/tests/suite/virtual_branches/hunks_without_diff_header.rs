@@ -0,0 +1,45 @@
+use super::*;
+
+#[tokio::test]
+async fn empty_new_files_and_mode_changes_are_listed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("script.sh"), "echo hi\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("empty.txt"), "").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            repository.path().join("script.sh"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+
+    let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
+    assert_eq!(branches.len(), 1);
+    let mut paths: Vec<_> = branches[0]
+        .files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect();
+    paths.sort();
+    let mut expected = vec![path::PathBuf::from("empty.txt")];
+    if cfg!(unix) {
+        expected.push(path::PathBuf::from("script.sh"));
+    }
+    assert_eq!(paths, expected);
+}
@@ -22,24 +22,45 @@ async fn insert_blank_commit_down() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let _commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // create commit
     fs::write(repository.path().join("file2.txt"), "content2").unwrap();
     fs::write(repository.path().join("file3.txt"), "content3").unwrap();
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // create commit
     fs::write(repository.path().join("file4.txt"), "content4").unwrap();
     let _commit3_id = controller
-        .create_commit(*project_id, branch_id, "commit three", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit three",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     controller
         .insert_blank_commit(*project_id, branch_id, commit2_id, 1)
@@ -94,24 +115,45 @@ async fn insert_blank_commit_up() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let _commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // create commit
     fs::write(repository.path().join("file2.txt"), "content2").unwrap();
     fs::write(repository.path().join("file3.txt"), "content3").unwrap();
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // create commit
     fs::write(repository.path().join("file4.txt"), "content4").unwrap();
     let _commit3_id = controller
-        .create_commit(*project_id, branch_id, "commit three", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit three",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     controller
         .insert_blank_commit(*project_id, branch_id, commit2_id, -1)
@@ -0,0 +1,41 @@
+use gitbutler_core::virtual_branches::BinaryReason;
+
+use super::*;
+
+#[tokio::test]
+async fn lists_new_binaries_above_the_threshold() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let binary = |len: usize| (0..len).map(|i| (i % 7) as u8).collect::<Vec<u8>>();
+    fs::write(repository.path().join("tracked.bin"), binary(2048)).unwrap();
+    repository.commit_all("add binary");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("tracked.bin"), binary(4096)).unwrap();
+    fs::write(repository.path().join("large.bin"), binary(2048)).unwrap();
+    fs::write(repository.path().join("small.bin"), binary(16)).unwrap();
+    fs::write(repository.path().join("large.txt"), "text\n".repeat(1000)).unwrap();
+
+    let binaries = controller
+        .large_new_binaries(*project_id, 1024)
+        .await
+        .unwrap();
+    assert_eq!(
+        binaries.len(),
+        1,
+        "only new binaries above the threshold are listed"
+    );
+    assert_eq!(binaries[0].path, path::Path::new("large.bin"));
+    assert_eq!(binaries[0].size_bytes, 2048);
+    assert_eq!(binaries[0].reason, BinaryReason::Content);
+}
@@ -0,0 +1,113 @@
+use bstr::ByteSlice;
+use gitbutler_core::git::diff;
+
+use super::*;
+
+#[tokio::test]
+async fn renamed_and_edited_file_is_listed_once_when_following_renames() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content = (1..=10).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("old.txt"), &content).unwrap();
+    repository.commit_all("add file");
+
+    fs::remove_file(repository.path().join("old.txt")).unwrap();
+    fs::write(
+        repository.path().join("new.txt"),
+        content.replace("line 5\n", "change 5\n"),
+    )
+    .unwrap();
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let mut index = repo.index().unwrap();
+    index.remove_path(path::Path::new("old.txt")).unwrap();
+    index.write().unwrap();
+    let renamed = repository.commit_all("rename file");
+
+    let files = controller
+        .list_remote_commit_files(*project_id, renamed, None, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        files.len(),
+        2,
+        "without rename detection it's a deletion and an addition"
+    );
+    assert!(files.iter().all(|file| file.old_path.is_none()));
+
+    let files = controller
+        .list_remote_commit_files(
+            *project_id,
+            renamed,
+            Some(diff::DEFAULT_RENAME_THRESHOLD),
+            false,
+        )
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    let file = &files[0];
+    assert_eq!(file.path, path::Path::new("new.txt"));
+    assert_eq!(file.old_path.as_deref(), Some(path::Path::new("old.txt")));
+    assert_eq!(file.hunks.len(), 1);
+    assert!(file.hunks[0].diff_lines.contains_str("+change 5"));
+    assert!(file.hunks[0].diff_lines.contains_str("-line 5"));
+
+    let files = controller
+        .list_remote_commit_files(*project_id, renamed, Some(100), false)
+        .await
+        .unwrap();
+    assert_eq!(
+        files.len(),
+        2,
+        "the content changed, so it's not an exact rename"
+    );
+}
+
+#[tokio::test]
+async fn hunks_are_attributed_to_the_commit_that_last_touched_their_lines() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\nthree\n").unwrap();
+    let first = repository.commit_all("add file");
+    fs::write(repository.path().join("file.txt"), "one\nchanged\nthree\n").unwrap();
+    fs::write(repository.path().join("new.txt"), "new\n").unwrap();
+    let second = repository.commit_all("change file");
+
+    let files = controller
+        .list_remote_commit_files(*project_id, second, None, false)
+        .await
+        .unwrap();
+    assert!(
+        files.iter().all(|file| file.hunk_attribution.is_none()),
+        "attribution has to be requested"
+    );
+
+    let mut files = controller
+        .list_remote_commit_files(*project_id, second, None, true)
+        .await
+        .unwrap();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let attribution = files[0].hunk_attribution.as_ref().unwrap();
+    assert_eq!(attribution.len(), 1);
+    let attribution = attribution[0]
+        .as_ref()
+        .expect("the changed lines exist in the parent");
+    assert_eq!(attribution.commit_id, first);
+    assert!(attribution.by_commit_author);
+
+    assert_eq!(files[1].path, path::Path::new("new.txt"));
+    assert_eq!(
+        files[1].hunk_attribution,
+        Some(vec![None]),
+        "new files have nobody who touched their lines before"
+    );
+}
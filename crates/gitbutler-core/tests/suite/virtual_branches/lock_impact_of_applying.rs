@@ -0,0 +1,97 @@
+use gitbutler_core::error::Code;
+
+use super::*;
+
+#[tokio::test]
+async fn hunks_touching_commits_of_the_branch_gain_locks() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\nthree\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "ONE\ntwo\nthree\n").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    controller
+        .unapply_virtual_branch(*project_id, branch_id)
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "uno\ntwo\nthree\n").unwrap();
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+
+    let impact = controller
+        .lock_impact_of_applying(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        impact.len(),
+        1,
+        "only the hunk touching the commit is affected"
+    );
+    assert_eq!(impact[0].path, path::Path::new("file.txt"));
+    assert!(impact[0].locked_to_before.is_empty());
+    assert_eq!(
+        impact[0]
+            .locked_to_after
+            .iter()
+            .map(|lock| (lock.branch_id, lock.commit_id))
+            .collect::<Vec<_>>(),
+        [(branch_id, commit_id)]
+    );
+
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+        "uno\ntwo\nthree\n",
+        "nothing is applied"
+    );
+}
+
+#[tokio::test]
+async fn applied_branches_are_rejected() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let err = controller
+        .lock_impact_of_applying(*project_id, branch_id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+}
@@ -0,0 +1,29 @@
+use super::*;
+
+#[tokio::test]
+async fn commit_authors_are_resolved() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(
+        repository.path().join(".mailmap"),
+        "Proper Name <proper@example.com> <test@email.com>\n",
+    )
+    .unwrap();
+    repository.commit_all("add mailmap");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let base = controller.get_base_branch_data(*project_id).await.unwrap();
+    let author = &base.recent_commits[0].author;
+    assert_eq!(author.name, "Proper Name");
+    assert_eq!(author.email, "proper@example.com");
+}
@@ -0,0 +1,70 @@
+use gitbutler_core::virtual_branches::FetchRefspecs;
+
+use super::*;
+
+#[tokio::test]
+async fn target_and_upstreams_are_fetched() {
+    let Test {
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .minimal_fetch_refspecs(*project_id)
+            .await
+            .unwrap(),
+        [FetchRefspecs {
+            remote: "origin".into(),
+            refspecs: vec!["+refs/heads/master:refs/remotes/origin/master".into()],
+        }]
+    );
+
+    let vb_state = project.virtual_branches();
+    for (upstream, name) in [
+        ("refs/remotes/origin/feature", "feature"),
+        ("refs/remotes/fork/feature", "fork"),
+        ("refs/remotes/origin/master", "same as target"),
+    ] {
+        let branch_id = controller
+            .create_virtual_branch(
+                *project_id,
+                &branch::BranchCreateRequest {
+                    name: Some(name.into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let mut branch = vb_state.get_branch(branch_id).unwrap();
+        branch.upstream = Some(upstream.parse().unwrap());
+        vb_state.set_branch(branch).unwrap();
+    }
+
+    assert_eq!(
+        controller
+            .minimal_fetch_refspecs(*project_id)
+            .await
+            .unwrap(),
+        [
+            FetchRefspecs {
+                remote: "origin".into(),
+                refspecs: vec![
+                    "+refs/heads/master:refs/remotes/origin/master".into(),
+                    "+refs/heads/feature:refs/remotes/origin/feature".into()
+                ],
+            },
+            FetchRefspecs {
+                remote: "fork".into(),
+                refspecs: vec!["+refs/heads/feature:refs/remotes/fork/feature".into()],
+            }
+        ]
+    );
+}
@@ -6,7 +6,7 @@ use gitbutler_core::{
     git,
     projects::{self, Project, ProjectId},
     users,
-    virtual_branches::{branch, Controller},
+    virtual_branches::{branch, CommitOptions, Controller},
 };
 use tempfile::TempDir;
 
@@ -62,31 +62,97 @@ impl Test {
     }
 }
 
+mod active_branches;
 mod amend;
+mod ancestry_path;
+mod apply_operations;
 mod apply_virtual_branch;
+mod base_to_target_diff;
+mod branch_commit_metrics;
+mod branch_exclusive_commits;
+mod branch_merge_statuses;
+mod branch_squashed_diff;
+mod branch_topological_order;
+mod branches_merge_base;
+mod branches_with_status;
+mod can_squash_branch;
 mod cherry_pick;
+mod commit_across_branches;
+mod commit_binary_delta_size;
+mod commit_dependency_order;
+mod commit_fixing_whitespace;
+mod commit_similarity;
+mod commits_touching_path;
+mod conflict_style;
 mod create_commit;
 mod create_virtual_branch_from_branch;
+mod cross_branch_locked_hunks;
 mod delete_virtual_branch;
+mod diff_against_remote;
+mod diff_branches;
+mod dirstat;
+mod emptied_by_integration;
 mod fetch_from_remotes;
+mod file_diff;
+mod force_push_safety;
+mod head_info;
+mod head_status;
+mod hunk_apply_order;
+mod hunk_counts_by_branch;
+mod hunk_locks;
+mod hunks_identical;
+mod hunks_without_diff_header;
 mod init;
 mod insert_blank_commit;
+mod large_new_binaries;
+mod list_remote_commit_files;
+mod lock_impact_of_applying;
+mod mailmap;
+mod minimal_fetch_refspecs;
+mod move_commit_between_branches;
 mod move_commit_file;
 mod move_commit_to_vbranch;
+mod newly_integrated_commits;
+mod open_bare;
 mod oplog;
+mod parallel_integration_groups;
+mod path_metadata;
+mod porcelain_status;
+mod pr_base_branch;
+mod preview_reorder;
+mod preview_squash;
+mod push_remote;
+mod rebase_correspondence;
+mod redundant_worktree_changes;
 mod references;
 mod reorder_commit;
+mod reorder_stack_branches;
+mod repo_object_stats;
 mod reset_virtual_branch;
+mod resolve_conflict_side;
+mod resolve_conflicts;
+mod safe_amend_targets;
 mod selected_for_changes;
 mod set_base_branch;
+mod set_branch_order;
+mod split_hunk;
 mod squash;
+mod stack_pr_descriptions;
+mod stale_branches;
+mod stale_remote_branches;
+mod suggested_integration_order;
+mod target_rewritten;
 mod unapply;
 mod unapply_ownership;
 mod undo_commit;
+mod unowned_files;
 mod update_base_branch;
 mod update_commit_message;
 mod upstream;
 mod verify_branch;
+mod workspace_change_ids;
+mod workspace_tree_without_branch;
+mod worktree_changes;
 
 #[tokio::test]
 async fn resolve_conflict_flow() {
@@ -144,7 +210,7 @@ async fn resolve_conflict_flow() {
     {
         // when we apply conflicted branch, it has conflict
         controller
-            .apply_virtual_branch(*project_id, branch1_id)
+            .apply_virtual_branch(*project_id, branch1_id, None)
             .await
             .unwrap();
 
@@ -166,7 +232,13 @@ async fn resolve_conflict_flow() {
         // can't commit conflicts
         assert!(matches!(
             controller
-                .create_commit(*project_id, branch1_id, "commit conflicts", None, false)
+                .create_commit(
+                    *project_id,
+                    branch1_id,
+                    "commit conflicts",
+                    None,
+                    &CommitOptions::default()
+                )
                 .await
                 .unwrap_err()
                 .downcast_ref(),
@@ -178,9 +250,16 @@ async fn resolve_conflict_flow() {
         // fixing the conflict removes conflicted mark
         fs::write(repository.path().join("file.txt"), "resolved").unwrap();
         let commit_oid = controller
-            .create_commit(*project_id, branch1_id, "resolution", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "resolution",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let commit = repository.find_commit(commit_oid).unwrap();
         assert_eq!(commit.parent_count(), 2);
@@ -0,0 +1,210 @@
+use gitbutler_core::virtual_branches::BranchId;
+
+use super::*;
+
+#[tokio::test]
+async fn commit_is_inserted_at_position() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let source_branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("one.txt"), "one\n").unwrap();
+    let one_id = controller
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("two.txt"), "two\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let target_branch_id = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("three.txt"), "three\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            target_branch_id,
+            "three",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    controller
+        .move_commit_between_branches(*project_id, source_branch_id, one_id, target_branch_id, 1)
+        .await
+        .unwrap();
+
+    let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
+    let descriptions = |branch_id: BranchId| {
+        branches
+            .iter()
+            .find(|branch| branch.id == branch_id)
+            .unwrap()
+            .commits
+            .iter()
+            .map(|commit| commit.description.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(descriptions(source_branch_id), vec!["two"]);
+    assert_eq!(descriptions(target_branch_id), vec!["three", "one"]);
+    for branch in &branches {
+        assert!(branch.files.is_empty(), "the worktree is unchanged");
+    }
+}
+
+#[tokio::test]
+async fn conflicts_leave_branches_unchanged() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let source_branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    let one_id = controller
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("file.txt"), "two\n").unwrap();
+    let two_id = controller
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let target_branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    // the commit above changes the file the moved commit adds
+    let err = controller
+        .move_commit_between_branches(*project_id, source_branch_id, one_id, target_branch_id, 0)
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Marker::BranchConflict));
+    assert!(err.to_string().contains("file.txt"), "{err}");
+
+    let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
+    let source_branch = branches
+        .iter()
+        .find(|branch| branch.id == source_branch_id)
+        .unwrap();
+    assert_eq!(source_branch.head, two_id);
+    let target_branch = branches
+        .iter()
+        .find(|branch| branch.id == target_branch_id)
+        .unwrap();
+    assert!(target_branch.commits.is_empty());
+}
+
+#[tokio::test]
+async fn position_must_be_within_target_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let source_branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    let target_branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let err = controller
+        .move_commit_between_branches(
+            *project_id,
+            source_branch_id,
+            commit_id,
+            target_branch_id,
+            1,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&gitbutler_core::error::Code::Validation)
+    );
+}
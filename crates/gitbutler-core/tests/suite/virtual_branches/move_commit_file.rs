@@ -24,18 +24,32 @@ async fn move_file_down() {
     // create commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
     let commit1 = repository.find_commit(commit1_id).unwrap();
 
     // create commit
     fs::write(repository.path().join("file2.txt"), "content2").unwrap();
     fs::write(repository.path().join("file3.txt"), "content3").unwrap();
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
     let commit2 = repository.find_commit(commit2_id).unwrap();
 
     // amend another hunk
@@ -89,16 +103,30 @@ async fn move_file_up() {
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     fs::write(repository.path().join("file2.txt"), "content2").unwrap();
     let commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // create commit
     fs::write(repository.path().join("file3.txt"), "content3").unwrap();
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     // amend another hunk
     let to_amend: branch::BranchOwnershipClaims = "file2.txt:1-2".parse().unwrap();
@@ -147,17 +175,17 @@ async fn move_file_up_overlapping_hunks() {
     // create bottom commit
     fs::write(repository.path().join("file.txt"), "content").unwrap();
     let _commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
+        .create_commit(*project_id, branch_id, "commit one", None, &CommitOptions::default())
         .await
-        .unwrap();
+        .unwrap().commit_id;
 
     // create middle commit one
     fs::write(repository.path().join("file2.txt"), "content2\ncontent2a\n").unwrap();
     fs::write(repository.path().join("file3.txt"), "content3").unwrap();
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
+        .create_commit(*project_id, branch_id, "commit two", None, &CommitOptions::default())
         .await
-        .unwrap();
+        .unwrap().commit_id;
 
     // create middle commit two
     fs::write(
@@ -167,16 +195,16 @@ async fn move_file_up_overlapping_hunks() {
     .unwrap();
     fs::write(repository.path().join("file4.txt"), "content4").unwrap();
     let commit3_id = controller
-        .create_commit(*project_id, branch_id, "commit three", None, false)
+        .create_commit(*project_id, branch_id, "commit three", None, &CommitOptions::default())
         .await
-        .unwrap();
+        .unwrap().commit_id;
 
     // create top commit
     fs::write(repository.path().join("file5.txt"), "content5").unwrap();
     let _commit4_id = controller
-        .create_commit(*project_id, branch_id, "commit four", None, false)
+        .create_commit(*project_id, branch_id, "commit four", None, &CommitOptions::default())
         .await
-        .unwrap();
+        .unwrap().commit_id;
 
     // move one line from middle commit two up to middle commit one
     let to_amend: branch::BranchOwnershipClaims = "file2.txt:1-6".parse().unwrap();
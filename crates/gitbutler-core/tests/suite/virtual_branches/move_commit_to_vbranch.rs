@@ -1,4 +1,4 @@
-use gitbutler_core::virtual_branches::{branch, BranchId};
+use gitbutler_core::virtual_branches::{branch, BranchId, CommitOptions};
 
 use crate::suite::virtual_branches::Test;
 
@@ -24,9 +24,16 @@ async fn no_diffs() {
     let source_branch_id = branches[0].id;
 
     let commit_oid = controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let target_branch_id = controller
         .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
@@ -84,9 +91,16 @@ async fn diffs_on_source_branch() {
     let source_branch_id = branches[0].id;
 
     let commit_oid = controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     std::fs::write(
         repository.path().join("another file.txt"),
@@ -150,9 +164,16 @@ async fn diffs_on_target_branch() {
     let source_branch_id = branches[0].id;
 
     let commit_oid = controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let target_branch_id = controller
         .create_virtual_branch(
@@ -222,9 +243,16 @@ async fn locked_hunks_on_source_branch() {
     let source_branch_id = branches[0].id;
 
     let commit_oid = controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     std::fs::write(repository.path().join("file.txt"), "locked content").unwrap();
 
@@ -265,7 +293,13 @@ async fn no_commit() {
     let source_branch_id = branches[0].id;
 
     controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
         .unwrap();
 
@@ -311,9 +345,16 @@ async fn no_branch() {
     let source_branch_id = branches[0].id;
 
     let commit_oid = controller
-        .create_commit(*project_id, source_branch_id, "commit", None, false)
+        .create_commit(
+            *project_id,
+            source_branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
         .await
-        .unwrap();
+        .unwrap()
+        .commit_id;
 
     let id = BranchId::generate();
     assert_eq!(
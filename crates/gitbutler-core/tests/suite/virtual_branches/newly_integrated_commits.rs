@@ -0,0 +1,161 @@
+use gitbutler_core::virtual_branches::BranchCommit;
+
+use super::*;
+
+#[tokio::test]
+async fn commits_that_landed_upstream() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let base = controller
+        .get_base_branch_data(*project_id)
+        .await
+        .unwrap()
+        .base_sha;
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    assert!(
+        controller
+            .newly_integrated_commits(*project_id, base)
+            .await
+            .unwrap()
+            .is_empty(),
+        "nothing landed upstream yet"
+    );
+
+    // the commit is merged upstream as is
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.reference("refs/remotes/origin/master", commit_id, true, "integrate")
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .newly_integrated_commits(*project_id, base)
+            .await
+            .unwrap(),
+        [BranchCommit {
+            branch_id,
+            commit_id
+        }]
+    );
+    assert!(
+        controller
+            .newly_integrated_commits(*project_id, commit_id)
+            .await
+            .unwrap()
+            .is_empty(),
+        "the commit was already integrated at that point"
+    );
+}
+
+#[tokio::test]
+async fn commits_squash_merged_before_since_are_not_new() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let base = controller
+        .get_base_branch_data(*project_id)
+        .await
+        .unwrap()
+        .base_sha;
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    // the commit is squash-merged upstream, and then something else lands on top
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let signature = git2::Signature::now("test", "test@email.com").unwrap();
+    let squashed_tree = repo.find_commit(commit_id).unwrap().tree().unwrap();
+    let squashed = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "squashed",
+            &squashed_tree,
+            &[&repo.find_commit(base).unwrap()],
+        )
+        .unwrap();
+    let mut builder = repo.treebuilder(Some(&squashed_tree)).unwrap();
+    builder
+        .insert("other.txt", repo.blob(b"other").unwrap(), 0o100644)
+        .unwrap();
+    let later = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "later",
+            &repo.find_tree(builder.write().unwrap()).unwrap(),
+            &[&repo.find_commit(squashed).unwrap()],
+        )
+        .unwrap();
+    repo.reference("refs/remotes/origin/master", later, true, "integrate")
+        .unwrap();
+
+    assert_eq!(
+        controller
+            .newly_integrated_commits(*project_id, base)
+            .await
+            .unwrap(),
+        [BranchCommit {
+            branch_id,
+            commit_id
+        }],
+        "the squash-merge landed after base"
+    );
+    assert!(
+        controller
+            .newly_integrated_commits(*project_id, squashed)
+            .await
+            .unwrap()
+            .is_empty(),
+        "the commit was squash-merged already at that point"
+    );
+}
@@ -0,0 +1,78 @@
+use gitbutler_core::{
+    error::Code,
+    project_repository,
+    virtual_branches::{dirstat, get_status_by_branch, worktree_changes},
+};
+
+use super::*;
+
+#[tokio::test]
+async fn mirrors_list_virtual_branch_refs() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let mirror_dir = tempfile::tempdir().unwrap();
+    let mirror = git2::Repository::init_bare(mirror_dir.path()).unwrap();
+    mirror
+        .remote_anonymous(repository.path().to_str().unwrap())
+        .unwrap()
+        .fetch(&["+refs/*:refs/*"], None, None)
+        .unwrap();
+
+    let bare = project_repository::Repository::open_bare(&Project {
+        path: mirror_dir.path().to_owned(),
+        ..Default::default()
+    })
+    .unwrap();
+    let refs = bare.virtual_branch_refs().unwrap();
+    assert_eq!(
+        refs,
+        project_repository::Repository::open(project)
+            .unwrap()
+            .virtual_branch_refs()
+            .unwrap(),
+        "the mirror has the same branches as the project"
+    );
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].head, commit_id);
+
+    let err = worktree_changes(&bare, &[], false, false, None, None).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+    for err in [
+        get_status_by_branch(&bare, None).map(|_| ()).unwrap_err(),
+        dirstat(&bare).map(|_| ()).unwrap_err(),
+        bare.head_info().map(|_| ()).unwrap_err(),
+    ] {
+        assert_eq!(
+            err.downcast_ref(),
+            Some(&Code::Validation),
+            "everything that needs a worktree fails early"
+        );
+    }
+}
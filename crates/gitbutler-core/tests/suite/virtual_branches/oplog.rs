@@ -1,4 +1,6 @@
 use super::*;
+use gitbutler_core::error::Code;
+use gitbutler_core::ops::entry::OperationKind;
 use itertools::Itertools;
 use std::io::Write;
 use std::path::Path;
@@ -42,7 +44,7 @@ async fn workdir_vbranch_restore() -> anyhow::Result<()> {
                 branch_id,
                 &format!("commit {round}"),
                 None,
-                false, /* run hook */
+                &CommitOptions::default(),
             )
             .await?;
         assert_eq!(
@@ -119,8 +121,15 @@ async fn basic_oplog() -> anyhow::Result<()> {
     // create commit
     fs::write(repository.path().join("file.txt"), "content")?;
     let _commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
-        .await?;
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?
+        .commit_id;
 
     // dont store large files
     let file_path = repository.path().join("large.txt");
@@ -135,8 +144,15 @@ async fn basic_oplog() -> anyhow::Result<()> {
     fs::write(repository.path().join("file2.txt"), "content2")?;
     fs::write(repository.path().join("file3.txt"), "content3")?;
     let commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
-        .await?;
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?
+        .commit_id;
 
     // Create conflict state
     let conflicts_path = repository.path().join(".git").join("conflicts");
@@ -154,8 +170,15 @@ async fn basic_oplog() -> anyhow::Result<()> {
 
     fs::write(repository.path().join("file4.txt"), "content4")?;
     let _commit3_id = controller
-        .create_commit(*project_id, branch_id, "commit three", None, false)
-        .await?;
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit three",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?
+        .commit_id;
 
     let branch = controller
         .list_virtual_branches(*project_id)
@@ -248,6 +271,162 @@ async fn basic_oplog() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn undo_last_commit() -> anyhow::Result<()> {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse()?)
+        .await?;
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await?;
+
+    fs::write(repository.path().join("file.txt"), "content")?;
+    let (before, _) = controller.list_virtual_branches(*project_id).await?;
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?;
+
+    fs::write(repository.path().join("other.txt"), "unsaved")?;
+    let err = project.undo_last_operation(false).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+    assert_eq!(
+        err.root_cause().to_string(),
+        "undoing would discard uncommitted changes to other.txt"
+    );
+    fs::remove_file(repository.path().join("other.txt"))?;
+
+    let undone = project.undo_last_operation(false)?;
+    assert_eq!(
+        undone.details.map(|details| details.operation),
+        Some(OperationKind::CreateCommit)
+    );
+
+    let (after, _) = controller.list_virtual_branches(*project_id).await?;
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].id, before[0].id);
+    assert_eq!(after[0].head, before[0].head, "the commit is undone");
+    assert_eq!(after[0].commits.len(), 0);
+    assert_eq!(after[0].files.len(), 1);
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt"))?,
+        "content",
+        "the committed change is uncommitted again"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_last_commit_with_force() -> anyhow::Result<()> {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse()?)
+        .await?;
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await?;
+
+    fs::write(repository.path().join("file.txt"), "content")?;
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?;
+
+    fs::remove_file(repository.path().join("file.txt"))?;
+    fs::write(repository.path().join("other.txt"), "unsaved")?;
+    let err = project.undo_last_operation(false).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "undoing would discard uncommitted changes to other.txt",
+        "deleted files aren't lost as restoring brings them back"
+    );
+
+    let undone = project.undo_last_operation(true)?;
+    assert_eq!(
+        undone.details.map(|details| details.operation),
+        Some(OperationKind::CreateCommit)
+    );
+    let (branches, _) = controller.list_virtual_branches(*project_id).await?;
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0].commits.len(), 0, "the commit is undone");
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt"))?,
+        "content"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_apply_without_force() -> anyhow::Result<()> {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse()?)
+        .await?;
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await?;
+
+    fs::write(repository.path().join("file.txt"), "content")?;
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?;
+    controller
+        .unapply_virtual_branch(*project_id, branch_id)
+        .await?;
+    assert!(!repository.path().join("file.txt").exists());
+    controller
+        .apply_virtual_branch(*project_id, branch_id, None)
+        .await?;
+    assert!(repository.path().join("file.txt").exists());
+
+    let undone = project.undo_last_operation(false)?;
+    assert_eq!(
+        undone.details.map(|details| details.operation),
+        Some(OperationKind::ApplyBranch),
+        "the files the operation brought back aren't uncommitted changes"
+    );
+    assert!(!repository.path().join("file.txt").exists());
+    Ok(())
+}
+
 #[tokio::test]
 async fn restores_gitbutler_integration() -> anyhow::Result<()> {
     let Test {
@@ -271,8 +450,15 @@ async fn restores_gitbutler_integration() -> anyhow::Result<()> {
     // create commit
     fs::write(repository.path().join("file.txt"), "content")?;
     let _commit1_id = controller
-        .create_commit(*project_id, branch_id, "commit one", None, false)
-        .await?;
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit one",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?
+        .commit_id;
 
     let repo = git2::Repository::open(&project.path)?;
 
@@ -286,8 +472,15 @@ async fn restores_gitbutler_integration() -> anyhow::Result<()> {
     // create second commit
     fs::write(repository.path().join("file.txt"), "changed content")?;
     let _commit2_id = controller
-        .create_commit(*project_id, branch_id, "commit two", None, false)
-        .await?;
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await?
+        .commit_id;
 
     // check the integration commit changed
     let head = repo.head().expect("never unborn");
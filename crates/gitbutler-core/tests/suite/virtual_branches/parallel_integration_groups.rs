@@ -0,0 +1,87 @@
+use super::*;
+
+#[tokio::test]
+async fn conflicting_branches_are_in_separate_groups() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "change a\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_a_id,
+            "a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    controller
+        .unapply_virtual_branch(*project_id, branch_a_id)
+        .await
+        .unwrap();
+
+    let branch_b_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "change b\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_b_id,
+            "b",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let branch_c_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_c_id,
+            "c",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // without commits, there is nothing to integrate
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let groups = controller
+        .parallel_integration_groups(*project_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        groups,
+        vec![vec![branch_a_id, branch_c_id], vec![branch_b_id]]
+    );
+}
@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use gitbutler_core::virtual_branches::PathKind;
+
+use super::*;
+
+#[tokio::test]
+async fn worktree_and_commit() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::create_dir_all(repository.path().join("dir")).unwrap();
+    fs::write(repository.path().join("dir/file.txt"), "content").unwrap();
+    let commit_id = repository.commit_all("initial");
+
+    fs::write(repository.path().join("dir/file.txt"), "changed content").unwrap();
+
+    let committed = controller
+        .path_metadata(*project_id, Path::new("dir/file.txt"), Some(commit_id))
+        .await
+        .unwrap()
+        .expect("present in commit");
+    assert_eq!(committed.kind, PathKind::Regular);
+    assert_eq!(committed.mode, 0o100644);
+    assert_eq!(committed.size, Some("content".len()));
+
+    let worktree = controller
+        .path_metadata(*project_id, Path::new("dir/file.txt"), None)
+        .await
+        .unwrap()
+        .expect("present in worktree");
+    assert_eq!(worktree.size, Some("changed content".len()));
+    assert_ne!(worktree.id, committed.id);
+
+    let dir = controller
+        .path_metadata(*project_id, Path::new("dir"), Some(commit_id))
+        .await
+        .unwrap()
+        .expect("directories are tree entries");
+    assert_eq!(dir.kind, PathKind::Directory);
+    assert_eq!(dir.size, None);
+
+    assert_eq!(
+        controller
+            .path_metadata(*project_id, Path::new("missing.txt"), Some(commit_id))
+            .await
+            .unwrap(),
+        None
+    );
+}
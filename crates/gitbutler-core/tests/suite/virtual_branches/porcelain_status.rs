@@ -0,0 +1,54 @@
+use super::*;
+
+fn blob_id(content: &str) -> git2::Oid {
+    git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn changes_are_listed_like_git_status() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("modified.txt"), "modified\n").unwrap();
+    fs::write(repository.path().join("deleted.txt"), "deleted\n").unwrap();
+    fs::write(repository.path().join("old.txt"), "renamed\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(controller.porcelain_status(*project_id).await.unwrap(), "");
+
+    fs::write(repository.path().join("modified.txt"), "changed\n").unwrap();
+    fs::remove_file(repository.path().join("deleted.txt")).unwrap();
+    fs::rename(
+        repository.path().join("old.txt"),
+        repository.path().join("new.txt"),
+    )
+    .unwrap();
+    fs::write(repository.path().join("untracked.txt"), "new\n").unwrap();
+    fs::write(repository.path().join("spaced \"name\".txt"), "new\n").unwrap();
+
+    let (deleted, modified, renamed) = (
+        blob_id("deleted\n"),
+        blob_id("modified\n"),
+        blob_id("renamed\n"),
+    );
+    assert_eq!(
+        controller.porcelain_status(*project_id).await.unwrap(),
+        format!(
+            "1 .D N... 100644 100644 000000 {deleted} {deleted} deleted.txt\n\
+             1 .M N... 100644 100644 100644 {modified} {modified} modified.txt\n\
+             2 .R N... 100644 100644 100644 {renamed} {renamed} R100 new.txt\told.txt\n\
+             ? \"spaced \\\"name\\\".txt\"\n\
+             ? untracked.txt\n"
+        )
+    );
+}
@@ -0,0 +1,89 @@
+use super::*;
+
+#[tokio::test]
+async fn targets_the_branch_below() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                name: Some("feature a".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                name: Some("b".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_a = controller
+        .create_commit(
+            *project_id,
+            branch_a,
+            "commit a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    // let `b` build on top of `a`
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let parent = repo.find_commit(commit_a).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let commit_b = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "commit b",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut b = vb_state.get_branch(branch_b).unwrap();
+    b.head = commit_b;
+    vb_state.set_branch(b).unwrap();
+
+    let base = controller
+        .pr_base_branch(*project_id, branch_b)
+        .await
+        .unwrap();
+    assert_eq!(base.branch_id, Some(branch_a));
+    assert_eq!(
+        base.branch_name, "feature-a",
+        "the branch wasn't pushed, so it's named like it would be by default"
+    );
+    assert_eq!(base.head, commit_a);
+
+    let base = controller
+        .pr_base_branch(*project_id, branch_a)
+        .await
+        .unwrap();
+    assert_eq!(base.branch_id, None);
+    assert_eq!(base.branch_name, "master");
+    assert_eq!(base.head, vb_state.get_default_target().unwrap().sha);
+}
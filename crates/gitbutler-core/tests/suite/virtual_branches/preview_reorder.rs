@@ -0,0 +1,134 @@
+use gitbutler_core::virtual_branches::VirtualBranch;
+
+use super::*;
+
+#[tokio::test]
+async fn reports_conflicts_without_changing_the_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    for (path, content, message) in [
+        ("file.txt", "one\n", "add file"),
+        ("file.txt", "two\n", "change file"),
+        ("other.txt", "other\n", "add other"),
+    ] {
+        fs::write(repository.path().join(path), content).unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                message,
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap();
+    }
+    let head = |branches: Vec<VirtualBranch>| {
+        branches
+            .into_iter()
+            .find(|branch| branch.id == branch_id)
+            .unwrap()
+            .head
+    };
+    let head_before = head(
+        controller
+            .list_virtual_branches(*project_id)
+            .await
+            .unwrap()
+            .0,
+    );
+
+    let preview = controller
+        .preview_reorder(*project_id, branch_id, 0, 2)
+        .await
+        .unwrap();
+    assert!(
+        preview.conflicting_paths.is_empty(),
+        "independent commits can be moved freely"
+    );
+    assert!(!preview.leaves_empty_commit);
+
+    let preview = controller
+        .preview_reorder(*project_id, branch_id, 1, 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        preview.conflicting_paths,
+        [path::PathBuf::from("file.txt")],
+        "the change can't come before the file is added"
+    );
+
+    assert!(controller
+        .preview_reorder(*project_id, branch_id, 0, 3)
+        .await
+        .is_err());
+
+    assert_eq!(
+        head(
+            controller
+                .list_virtual_branches(*project_id)
+                .await
+                .unwrap()
+                .0
+        ),
+        head_before,
+        "nothing is changed"
+    );
+}
+
+#[tokio::test]
+async fn reports_commits_that_would_be_empty() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    for (content, message) in [("a\n", "add"), ("b\n", "change"), ("a\n", "revert")] {
+        fs::write(repository.path().join("file.txt"), content).unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                message,
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap();
+    }
+
+    // right on top of "add", "revert" wouldn't change anything
+    let preview = controller
+        .preview_reorder(*project_id, branch_id, 0, 1)
+        .await
+        .unwrap();
+    assert!(preview.conflicting_paths.is_empty());
+    assert!(preview.leaves_empty_commit);
+}
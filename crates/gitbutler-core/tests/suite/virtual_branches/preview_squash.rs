@@ -0,0 +1,128 @@
+use super::*;
+
+#[tokio::test]
+async fn combines_messages_without_changing_the_branch() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    {
+        fs::write(repository.path().join("file one.txt"), "").unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    let commit_two_oid = {
+        fs::write(repository.path().join("file two.txt"), "").unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    let head = {
+        fs::write(repository.path().join("file three.txt"), "").unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    let preview = controller
+        .preview_squash(*project_id, branch_id, commit_two_oid)
+        .await
+        .unwrap();
+    assert_eq!(preview.message, "commit one\ncommit two");
+    assert!(preview.conflicting_paths.is_empty());
+
+    let branch = controller
+        .list_virtual_branches(*project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap();
+    assert_eq!(branch.head, head);
+    assert_eq!(branch.commits.len(), 3);
+}
+
+#[tokio::test]
+async fn root_commit() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let commit_one_oid = {
+        fs::write(repository.path().join("file one.txt"), "").unwrap();
+        controller
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
+            .await
+            .unwrap()
+            .commit_id
+    };
+
+    assert_eq!(
+        controller
+            .preview_squash(*project_id, branch_id, commit_one_oid)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "can not squash root commit"
+    );
+}
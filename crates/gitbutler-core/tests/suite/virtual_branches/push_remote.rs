@@ -0,0 +1,95 @@
+use gitbutler_core::error::Code;
+
+use super::*;
+
+#[tokio::test]
+async fn rejects_unknown_remote() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let err = controller
+        .set_branch_push_remote(*project_id, branch_id, Some("nonexistent"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+}
+
+#[tokio::test]
+async fn pushes_to_override_remote() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let url = repo
+        .find_remote("origin")
+        .unwrap()
+        .url()
+        .unwrap()
+        .to_owned();
+    repo.remote("fork", &url).unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    controller
+        .set_branch_push_remote(*project_id, branch_id, Some("fork"))
+        .await
+        .unwrap();
+
+    let result = controller
+        .push_virtual_branch(*project_id, branch_id, false, None)
+        .await
+        .unwrap();
+    assert_eq!(result.remote, "fork");
+    assert_eq!(result.refname.remote(), "fork");
+
+    controller
+        .set_branch_push_remote(*project_id, branch_id, None)
+        .await
+        .unwrap();
+
+    let result = controller
+        .push_virtual_branch(*project_id, branch_id, false, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        result.remote, "origin",
+        "without override the default target remote is used"
+    );
+}
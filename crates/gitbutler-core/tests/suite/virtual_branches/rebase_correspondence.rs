@@ -0,0 +1,124 @@
+use super::*;
+
+#[tokio::test]
+async fn matches_commits_to_the_rewritten_target() {
+    let Test {
+        repository,
+        project_id,
+        project,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    let merge_base = repository.commit_all("one");
+    fs::write(repository.path().join("a.txt"), "a\n").unwrap();
+    let local_tip = repository.commit_all("add a");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    // the target is rewritten to drop the last commit, which is rebased onto it within the branch,
+    // followed by a commit of its own
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let parent = repo.find_commit(merge_base).unwrap();
+    let remote_tip = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "rewritten",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    repo.reference("refs/remotes/origin/master", remote_tip, true, "force-push")
+        .unwrap();
+
+    let rebased_tree = repo.find_commit(local_tip).unwrap().tree().unwrap();
+    let rebased = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "add a",
+            &rebased_tree,
+            &[&repo.find_commit(remote_tip).unwrap()],
+        )
+        .unwrap();
+    let mut builder = repo.treebuilder(Some(&rebased_tree)).unwrap();
+    builder
+        .insert("b.txt", repo.blob(b"b\n").unwrap(), 0o100644)
+        .unwrap();
+    let head = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "add b",
+            &repo.find_tree(builder.write().unwrap()).unwrap(),
+            &[&repo.find_commit(rebased).unwrap()],
+        )
+        .unwrap();
+
+    let vb_state = project.virtual_branches();
+    let mut branch = vb_state.get_branch(branch_id).unwrap();
+    branch.head = head;
+    vb_state.set_branch(branch).unwrap();
+
+    let correspondence = controller
+        .rebase_correspondence(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert_eq!(correspondence.matched.len(), 1);
+    assert_eq!(correspondence.matched[0].commit_id, rebased);
+    assert_eq!(correspondence.matched[0].original_id, local_tip);
+    assert_eq!(correspondence.matched[0].similarity, Some(1.0));
+    assert_eq!(correspondence.unmatched, [head]);
+}
+
+#[tokio::test]
+async fn nothing_matches_without_a_rewritten_target() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    let correspondence = controller
+        .rebase_correspondence(*project_id, branch_id)
+        .await
+        .unwrap();
+    assert!(correspondence.matched.is_empty());
+    assert_eq!(correspondence.unmatched, [commit_id]);
+}
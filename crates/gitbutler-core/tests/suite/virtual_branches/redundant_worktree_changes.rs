@@ -0,0 +1,56 @@
+use super::*;
+
+#[tokio::test]
+async fn detects_change_applied_twice() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\n").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "add two",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    assert!(controller
+        .redundant_worktree_changes(*project_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    fs::write(repository.path().join("file.txt"), "one\ntwo\ntwo\n").unwrap();
+    fs::write(repository.path().join("other.txt"), "two\n").unwrap();
+
+    let redundant = controller
+        .redundant_worktree_changes(*project_id)
+        .await
+        .unwrap();
+    assert_eq!(redundant.len(), 1, "the new file doesn't match the commit");
+    assert_eq!(redundant[0].branch_id, branch_id);
+    assert_eq!(redundant[0].path, path::PathBuf::from("file.txt"));
+    assert_eq!(redundant[0].commit_id, commit_id);
+}
@@ -0,0 +1,134 @@
+use gitbutler_core::error::Code;
+
+use super::*;
+
+/// Create a stack of branch `base` with branches `a` and `b` on top of it, and return their ids
+/// and names.
+async fn forked_stack(
+    Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    }: &Test,
+) -> [(branch::BranchId, String); 3] {
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(
+            controller
+                .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+                .await
+                .unwrap(),
+        );
+    }
+    fs::write(repository.path().join("base.txt"), "base").unwrap();
+    let base_commit = controller
+        .create_commit(*project_id, ids[0], "base", None, &CommitOptions::default())
+        .await
+        .unwrap()
+        .commit_id;
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let base_commit = repo.find_commit(base_commit).unwrap();
+    let vb_state = project.virtual_branches();
+    for (id, message) in ids[1..].iter().zip(["a", "b"]) {
+        let commit_id = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                message,
+                &base_commit.tree().unwrap(),
+                &[&base_commit],
+            )
+            .unwrap();
+        let mut branch = vb_state.get_branch(*id).unwrap();
+        branch.head = commit_id;
+        vb_state.set_branch(branch).unwrap();
+    }
+
+    let names = ids
+        .iter()
+        .map(|id| (*id, vb_state.get_branch(*id).unwrap().name))
+        .collect::<Vec<_>>();
+    names.try_into().unwrap()
+}
+
+#[tokio::test]
+async fn branches_that_are_not_based_on_each_other_swap_places() {
+    let test = Test::default();
+    let [(base_id, base), (a_id, a), (b_id, b)] = forked_stack(&test).await;
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &test;
+
+    let ids = |branches: Vec<branch::Branch>| -> Vec<branch::BranchId> {
+        branches.into_iter().map(|branch| branch.id).collect()
+    };
+    let branches = controller
+        .reorder_stack_branches(*project_id, base_id, &[a.clone(), b.clone(), base.clone()])
+        .await
+        .unwrap();
+    assert_eq!(ids(branches), [a_id, b_id, base_id]);
+
+    let bottom_to_top: Vec<_> = controller
+        .branch_exclusive_commits(*project_id, base_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|segment| segment.branch_id)
+        .collect();
+    assert_eq!(bottom_to_top, [base_id, b_id, a_id]);
+
+    let branches = controller
+        .reorder_stack_branches(*project_id, base_id, &[b, a, base])
+        .await
+        .unwrap();
+    assert_eq!(ids(branches), [b_id, a_id, base_id]);
+}
+
+#[tokio::test]
+async fn invalid_orders_are_rejected() {
+    let test = Test::default();
+    let [(base_id, base), (_, a), (_, b)] = forked_stack(&test).await;
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &test;
+
+    for (names, message) in [
+        (
+            vec![a.clone(), b.clone()],
+            format!("branch '{base}' of the stack is missing"),
+        ),
+        (
+            vec![a.clone(), a.clone(), b.clone(), base.clone()],
+            format!("branch '{a}' is listed more than once"),
+        ),
+        (
+            vec![a.clone(), "unknown".into(), b.clone(), base.clone()],
+            "branch 'unknown' isn't part of the stack".into(),
+        ),
+        (
+            vec![a.clone(), base.clone(), b.clone()],
+            format!("branch '{b}' is based on branch '{base}' and has to be above it"),
+        ),
+    ] {
+        let err = controller
+            .reorder_stack_branches(*project_id, base_id, &names)
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+        assert_eq!(err.root_cause().to_string(), message);
+    }
+}
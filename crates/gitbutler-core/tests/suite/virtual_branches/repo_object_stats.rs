@@ -0,0 +1,52 @@
+use gitbutler_core::project_repository;
+
+use super::*;
+
+#[tokio::test]
+async fn counts_loose_objects() {
+    let Test {
+        repository,
+        project,
+        ..
+    } = &Test::default();
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    let before = project_repository.object_stats().unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    repository.commit_all("commit");
+
+    let after = project_repository.object_stats().unwrap();
+    // a blob, a tree and a commit
+    assert_eq!(after.loose_objects, before.loose_objects + 3);
+    assert_eq!(after.packed_objects, before.packed_objects);
+    assert!(after.size_bytes > before.size_bytes);
+}
+
+#[tokio::test]
+async fn counts_packed_objects() {
+    let Test {
+        repository,
+        project,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = repository.commit_all("commit");
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    let before = project_repository.object_stats().unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let mut packbuilder = repo.packbuilder().unwrap();
+    packbuilder.insert_commit(commit_id).unwrap();
+    let object_count = packbuilder.object_count();
+    packbuilder
+        .write(&repo.path().join("objects/pack"), 0o644)
+        .unwrap();
+
+    let after = project_repository.object_stats().unwrap();
+    assert_eq!(after.packed_objects, before.packed_objects + object_count);
+    assert_eq!(after.loose_objects, before.loose_objects);
+    assert!(after.size_bytes > before.size_bytes);
+}
@@ -1,6 +1,6 @@
 use std::fs;
 
-use gitbutler_core::virtual_branches::branch;
+use gitbutler_core::virtual_branches::{branch, CommitOptions};
 
 use crate::suite::virtual_branches::Test;
 
@@ -28,9 +28,16 @@ async fn to_head() {
 
         // commit changes
         let oid = controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
         assert_eq!(branches.len(), 1);
@@ -90,9 +97,16 @@ async fn to_target() {
 
         // commit changes
         let oid = controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
         assert_eq!(branches.len(), 1);
@@ -150,9 +164,16 @@ async fn to_commit() {
         fs::write(repository.path().join("file.txt"), "content").unwrap();
 
         let oid = controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
         assert_eq!(branches.len(), 1);
@@ -173,9 +194,16 @@ async fn to_commit() {
         fs::write(repository.path().join("file.txt"), "more content").unwrap();
 
         let second_commit_oid = controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
         assert_eq!(branches.len(), 1);
@@ -234,9 +262,16 @@ async fn to_non_existing() {
 
         // commit changes
         let oid = controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
-            .unwrap();
+            .unwrap()
+            .commit_id;
 
         let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
         assert_eq!(branches.len(), 1);
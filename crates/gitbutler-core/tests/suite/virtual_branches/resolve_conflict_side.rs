@@ -0,0 +1,60 @@
+use gitbutler_core::project_repository::{self, conflicts, conflicts::ConflictSide};
+
+use super::*;
+
+#[tokio::test]
+async fn takes_one_side_wholesale() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    {
+        fs::write(repository.path().join("file.txt"), "first").unwrap();
+        let first_commit_oid = repository.commit_all("first");
+        fs::write(repository.path().join("file.txt"), "second").unwrap();
+        repository.commit_all("second");
+        repository.push();
+        repository.reset_hard(Some(first_commit_oid));
+    }
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "conflict").unwrap();
+    controller.list_virtual_branches(*project_id).await.unwrap();
+
+    controller.update_base_branch(*project_id).await.unwrap();
+    controller
+        .apply_virtual_branch(*project_id, branch_id, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+        "<<<<<<< ours\nconflict\n=======\nsecond\n>>>>>>> theirs\n"
+    );
+
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    assert!(
+        conflicts::resolve_side(&project_repository, "file.txt", ConflictSide::Base).is_err(),
+        "the conflict markers don't contain the base"
+    );
+
+    let conflicts_remain =
+        conflicts::resolve_side(&project_repository, "file.txt", ConflictSide::Theirs).unwrap();
+    assert!(!conflicts_remain);
+    assert_eq!(
+        fs::read_to_string(repository.path().join("file.txt")).unwrap(),
+        "second\n"
+    );
+    assert!(!conflicts::is_conflicting(&project_repository, None).unwrap());
+}
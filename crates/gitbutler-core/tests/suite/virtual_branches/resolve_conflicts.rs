@@ -0,0 +1,29 @@
+use gitbutler_core::project_repository::{self, conflicts};
+
+use super::*;
+
+#[tokio::test]
+async fn resolving_with_content_reads_the_conflicts_once() {
+    let Test { project, .. } = &Test::default();
+    let project_repository = project_repository::Repository::open(project).unwrap();
+    conflicts::mark(&project_repository, ["a.txt", "b.txt", "c.txt"], None).unwrap();
+    let conflicts_path = project_repository.repo().path().join("conflicts");
+
+    let mut content = conflicts::read(&project_repository).unwrap();
+    for (path, remaining) in [
+        ("b.txt", &b"a.txt\nc.txt\n"[..]),
+        ("a.txt", b"c.txt\n"),
+        ("c.txt", b""),
+    ] {
+        // if the file was read again, this path would show up as conflicting
+        fs::write(&conflicts_path, "other.txt\n").unwrap();
+        content = conflicts::resolve_with_content(&project_repository, &content, path).unwrap();
+        assert_eq!(content, remaining);
+        assert!(!conflicts::is_conflicting_with_content(
+            &content,
+            Some(path::Path::new(path))
+        ));
+    }
+    assert!(!conflicts_path.exists());
+    assert!(!conflicts::is_conflicting(&project_repository, None).unwrap());
+}
@@ -0,0 +1,118 @@
+use gitbutler_core::error::Code;
+use gitbutler_core::virtual_branches::BranchCommit;
+
+use super::*;
+
+#[tokio::test]
+async fn only_the_latest_locked_commit_is_safe() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        projects,
+        ..
+    } = &Test::default();
+
+    let content = (1..=7).map(|n| format!("line {n}\n")).collect::<String>();
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    // blame based locking tracks every commit that changed the lines of a hunk
+    projects
+        .update(&projects::UpdateRequest {
+            id: *project_id,
+            use_new_locking: Some(false),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let content = content.replace("line 1\n", "change 1\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let first = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "first",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    let content = content.replace("line 7\n", "change 7\n");
+    fs::write(repository.path().join("file.txt"), &content).unwrap();
+    let second = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "second",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    // its context reaches the lines of both commits
+    fs::write(
+        repository.path().join("file.txt"),
+        content.replace("line 4\n", "change 4\n"),
+    )
+    .unwrap();
+
+    let amend_targets = controller
+        .safe_amend_targets(
+            *project_id,
+            path::Path::new("file.txt"),
+            &"1-8".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        amend_targets.targets,
+        [BranchCommit {
+            branch_id,
+            commit_id: second
+        }]
+    );
+    assert_eq!(amend_targets.excluded.len(), 1);
+    assert_eq!(amend_targets.excluded[0].commit_id, first);
+    assert!(amend_targets.excluded[0]
+        .reason
+        .contains(&second.to_string()));
+}
+
+#[tokio::test]
+async fn unknown_hunk() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let err = controller
+        .safe_amend_targets(
+            *project_id,
+            path::Path::new("file.txt"),
+            &"1-2".parse().unwrap(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+}
@@ -364,7 +364,7 @@ async fn applying_first_branch() {
         .await
         .unwrap();
     controller
-        .apply_virtual_branch(*project_id, branches[0].id)
+        .apply_virtual_branch(*project_id, branches[0].id, None)
         .await
         .unwrap();
 
@@ -0,0 +1,119 @@
+use super::*;
+use gitbutler_core::error::Code;
+
+async fn listed_ids(controller: &Controller, project_id: ProjectId) -> Vec<branch::BranchId> {
+    let (branches, _) = controller.list_virtual_branches(project_id).await.unwrap();
+    branches.into_iter().map(|branch| branch.id).collect()
+}
+
+#[tokio::test]
+async fn branches_sharing_an_order_are_listed_stably() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(
+            controller
+                .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+                .await
+                .unwrap(),
+        );
+    }
+    for id in &ids {
+        controller
+            .update_virtual_branch(
+                *project_id,
+                branch::BranchUpdateRequest {
+                    id: *id,
+                    order: Some(0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let first = listed_ids(controller, *project_id).await;
+    assert_eq!(first.len(), 3);
+    for _ in 0..5 {
+        assert_eq!(listed_ids(controller, *project_id).await, first);
+    }
+}
+
+#[tokio::test]
+async fn order_is_persisted() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(
+            controller
+                .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+                .await
+                .unwrap(),
+        );
+    }
+    assert_eq!(listed_ids(controller, *project_id).await, ids);
+
+    let new_order = vec![ids[2], ids[0], ids[1]];
+    controller
+        .set_branch_order(*project_id, &new_order)
+        .await
+        .unwrap();
+    assert_eq!(listed_ids(controller, *project_id).await, new_order);
+    assert_eq!(
+        listed_ids(controller, *project_id).await,
+        new_order,
+        "the order sticks"
+    );
+}
+
+#[tokio::test]
+async fn each_applied_branch_must_be_listed_once() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    let b = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    for branch_ids in [vec![a], vec![a, a], vec![a, b, a]] {
+        let err = controller
+            .set_branch_order(*project_id, &branch_ids)
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Code::Validation));
+    }
+    assert_eq!(listed_ids(controller, *project_id).await, vec![a, b]);
+}
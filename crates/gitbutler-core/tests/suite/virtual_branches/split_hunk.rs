@@ -0,0 +1,214 @@
+use std::path::Path;
+
+use gitbutler_core::{
+    error::Code,
+    git::diff::{ChangeType, GitHunk},
+    virtual_branches::{branch::HunkIdentity, BranchId},
+};
+
+use super::*;
+
+/// Commit `file.txt` and `other.txt` to two commits of a new branch, change two lines of
+/// `file.txt` in the worktree, and return the branch with its commits, head first, and the
+/// identity of the changed hunk.
+async fn setup(
+    repository: &TestProject,
+    project_id: ProjectId,
+    controller: &Controller,
+) -> (BranchId, Vec<git2::Oid>, HunkIdentity) {
+    controller
+        .set_base_branch(project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+    let branch_id = controller
+        .create_virtual_branch(project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "1\n2\n3\n4\n5\n").unwrap();
+    let lower = controller
+        .create_commit(
+            project_id,
+            branch_id,
+            "lower",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    let upper = controller
+        .create_commit(
+            project_id,
+            branch_id,
+            "upper",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    fs::write(repository.path().join("file.txt"), "1\nTWO\n3\nFOUR\n5\n").unwrap();
+    let branch = controller
+        .list_virtual_branches(project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap();
+    let hunk = &branch.files[0].hunks[0];
+    let hunk = HunkIdentity::new(
+        Path::new("file.txt"),
+        &GitHunk {
+            old_start: 1,
+            old_lines: 5,
+            new_start: 1,
+            new_lines: 5,
+            diff_lines: hunk.diff.clone(),
+            binary: false,
+            locked_to: Box::new([]),
+            change_type: ChangeType::Modified,
+        },
+    );
+    (branch_id, vec![upper, lower], hunk)
+}
+
+fn file_in_commit(repository: &TestProject, commit_id: git2::Oid) -> String {
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let blob = repo
+        .find_commit(commit_id)
+        .unwrap()
+        .tree()
+        .unwrap()
+        .get_path(Path::new("file.txt"))
+        .unwrap()
+        .to_object(&repo)
+        .unwrap()
+        .peel_to_blob()
+        .unwrap();
+    String::from_utf8(blob.content().to_owned()).unwrap()
+}
+
+#[tokio::test]
+async fn selected_lines_go_to_the_lower_commit() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+    let (branch_id, commits, hunk) = setup(repository, *project_id, controller).await;
+
+    let split = controller
+        .split_hunk(
+            *project_id,
+            branch_id,
+            &hunk,
+            &[2],
+            &[2],
+            commits[1],
+            commits[0],
+        )
+        .await
+        .unwrap();
+
+    let branch = controller
+        .list_virtual_branches(*project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap();
+    assert!(branch.files.is_empty(), "all changes were committed");
+    let (upper, lower) = (branch.commits[0].id, branch.commits[1].id);
+    assert_eq!(split.first.locked_to.commit_id, lower);
+    assert_eq!(split.second.locked_to.commit_id, upper);
+    assert_eq!(file_in_commit(repository, lower), "1\nTWO\n3\n4\n5\n");
+    assert_eq!(file_in_commit(repository, upper), "1\nTWO\n3\nFOUR\n5\n");
+}
+
+#[tokio::test]
+async fn selected_lines_go_to_the_upper_commit() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+    let (branch_id, commits, hunk) = setup(repository, *project_id, controller).await;
+
+    let split = controller
+        .split_hunk(
+            *project_id,
+            branch_id,
+            &hunk,
+            &[2],
+            &[2],
+            commits[0],
+            commits[1],
+        )
+        .await
+        .unwrap();
+
+    let branch = controller
+        .list_virtual_branches(*project_id)
+        .await
+        .unwrap()
+        .0
+        .into_iter()
+        .find(|b| b.id == branch_id)
+        .unwrap();
+    assert!(branch.files.is_empty(), "all changes were committed");
+    let (upper, lower) = (branch.commits[0].id, branch.commits[1].id);
+    assert_eq!(split.first.locked_to.commit_id, upper);
+    assert_eq!(split.second.locked_to.commit_id, lower);
+    assert_eq!(file_in_commit(repository, lower), "1\n2\n3\nFOUR\n5\n");
+    assert_eq!(file_in_commit(repository, upper), "1\nTWO\n3\nFOUR\n5\n");
+}
+
+#[tokio::test]
+async fn invalid_selections_are_rejected() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+    let (branch_id, commits, hunk) = setup(repository, *project_id, controller).await;
+
+    for (old_lines, new_lines) in [
+        (&[3][..], &[][..]),
+        (&[2, 4][..], &[2, 4][..]),
+        (&[][..], &[][..]),
+    ] {
+        let err = controller
+            .split_hunk(
+                *project_id,
+                branch_id,
+                &hunk,
+                old_lines,
+                new_lines,
+                commits[1],
+                commits[0],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Code::Validation), "{err:#}");
+    }
+    assert!(controller
+        .split_hunk(
+            *project_id,
+            branch_id,
+            &hunk,
+            &[2],
+            &[2],
+            commits[0],
+            commits[0]
+        )
+        .await
+        .is_err());
+}
@@ -22,33 +22,61 @@ async fn head() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let commit_four_oid = {
         fs::write(repository.path().join("file four.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit four", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit four",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -98,33 +126,61 @@ async fn middle() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let commit_two_oid = {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file four.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit four", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit four",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -184,9 +240,16 @@ async fn forcepush_allowed() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -197,25 +260,46 @@ async fn forcepush_allowed() {
     let commit_two_oid = {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file four.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit four", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit four",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -267,9 +351,16 @@ async fn forcepush_forbidden() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -289,25 +380,46 @@ async fn forcepush_forbidden() {
     let commit_two_oid = {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file four.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit four", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit four",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     assert_eq!(
@@ -342,9 +454,16 @@ async fn root_forbidden() {
     let commit_one_oid = {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     assert_eq!(
@@ -0,0 +1,110 @@
+use super::*;
+
+#[tokio::test]
+async fn describes_each_branch_of_the_stack() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                name: Some("a".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                name: Some("b".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    // not part of the stack as it doesn't have commits
+    controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                name: Some("c".into()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_a = controller
+        .create_commit(
+            *project_id,
+            branch_a,
+            "commit a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+
+    // let `b` build on top of `a`
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let parent = repo.find_commit(commit_a).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let commit_b = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "commit b\n\nwith a body",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut b = vb_state.get_branch(branch_b).unwrap();
+    b.head = commit_b;
+    vb_state.set_branch(b).unwrap();
+
+    let descriptions = controller
+        .stack_pr_descriptions(*project_id, branch_b)
+        .await
+        .unwrap();
+    assert_eq!(
+        descriptions
+            .iter()
+            .map(|description| description.branch_id)
+            .collect::<Vec<_>>(),
+        [branch_a, branch_b]
+    );
+    assert_eq!(
+        descriptions[0].body,
+        "Based on `master`.\n\n## Commits\n\n- commit a\n\n## Stack\n\n- [ ] `a` (this pull request)\n- [ ] `b`\n"
+    );
+    assert_eq!(
+        descriptions[1].body,
+        "Stacked on `a`.\n\n## Commits\n\n- commit b\n\n## Stack\n\n- [ ] `a`\n- [ ] `b` (this pull request)\n"
+    );
+
+    assert_eq!(
+        controller
+            .stack_pr_descriptions(*project_id, branch_a)
+            .await
+            .unwrap(),
+        descriptions,
+        "the stack is the same no matter which of its branches is passed"
+    );
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use super::*;
+
+#[tokio::test]
+async fn branches_with_old_heads_are_stale() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let fresh_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("fresh.txt"), "fresh").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            fresh_id,
+            "fresh",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // branches with commits from long ago
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let vb_state = project.virtual_branches();
+    let mut old_ids = Vec::new();
+    for (seconds, messages) in [
+        (1_000_000_000, &["old 1", "old 2"][..]),
+        (900_000_000, &["older"][..]),
+    ] {
+        let id = controller
+            .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+            .await
+            .unwrap();
+        let signature =
+            git2::Signature::new("test", "test@example.com", &git2::Time::new(seconds, 0)).unwrap();
+        let mut branch = vb_state.get_branch(id).unwrap();
+        let mut head = repo.find_commit(branch.head).unwrap();
+        for message in messages {
+            let commit_id = repo
+                .commit(
+                    None,
+                    &signature,
+                    &signature,
+                    message,
+                    &head.tree().unwrap(),
+                    &[&head],
+                )
+                .unwrap();
+            head = repo.find_commit(commit_id).unwrap();
+        }
+        branch.head = head.id();
+        vb_state.set_branch(branch).unwrap();
+        old_ids.push(id);
+    }
+
+    // without commits, there is nothing to abandon
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let stale = controller
+        .stale_branches(*project_id, Duration::from_secs(24 * 60 * 60))
+        .await
+        .unwrap();
+    let ids: Vec<_> = stale.iter().map(|branch| branch.branch_id).collect();
+    assert_eq!(ids, [old_ids[1], old_ids[0]], "oldest first");
+    assert_eq!(stale[0].commit_count, 1);
+    assert_eq!(stale[1].commit_count, 2);
+    assert!(stale[1].tip_age_seconds > 24 * 60 * 60);
+    assert!(stale[0].tip_age_seconds > stale[1].tip_age_seconds);
+}
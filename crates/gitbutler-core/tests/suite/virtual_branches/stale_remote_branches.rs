@@ -0,0 +1,59 @@
+use super::*;
+
+#[tokio::test]
+async fn pruned_upstreams_are_listed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    controller
+        .push_virtual_branch(*project_id, branch_id, false, None)
+        .await
+        .unwrap();
+
+    assert!(controller
+        .stale_remote_branches(*project_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let branch = project.virtual_branches().get_branch(branch_id).unwrap();
+    let upstream = branch.upstream.unwrap();
+
+    // a fetch with pruning removed the remote-tracking ref of the deleted branch
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    repo.find_reference(&upstream.to_string())
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    let stale = controller.stale_remote_branches(*project_id).await.unwrap();
+    assert_eq!(stale.len(), 1);
+    assert_eq!(stale[0].branch_id, branch_id);
+    assert_eq!(stale[0].branch_name, branch.name);
+    assert_eq!(stale[0].missing_ref, upstream);
+}
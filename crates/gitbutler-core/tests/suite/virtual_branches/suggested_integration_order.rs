@@ -0,0 +1,96 @@
+use super::*;
+
+#[tokio::test]
+async fn clean_branches_go_before_conflicting_ones() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "change a\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_a_id,
+            "a",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    controller
+        .unapply_virtual_branch(*project_id, branch_a_id)
+        .await
+        .unwrap();
+
+    let branch_b_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("file.txt"), "change b\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_b_id,
+            "b",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let branch_c_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("other.txt"), "other\n").unwrap();
+    controller
+        .create_commit(
+            *project_id,
+            branch_c_id,
+            "c",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // without commits, there is nothing to integrate
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    let order = controller
+        .suggested_integration_order(*project_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        order
+            .steps
+            .iter()
+            .map(|step| step.branch_id)
+            .collect::<Vec<_>>(),
+        vec![branch_a_id, branch_c_id, branch_b_id],
+        "b conflicts with a, so c is integrated first"
+    );
+    assert!(order.steps[0].conflicting_branches.is_empty());
+    assert!(order.steps[1].conflicting_branches.is_empty());
+    assert_eq!(order.steps[2].conflicting_branches, vec![branch_a_id]);
+    assert_eq!(order.expected_conflicts, 1);
+}
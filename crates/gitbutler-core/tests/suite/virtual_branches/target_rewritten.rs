@@ -0,0 +1,53 @@
+use super::*;
+
+#[tokio::test]
+async fn detects_force_pushed_target() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "one").unwrap();
+    let merge_base = repository.commit_all("one");
+    fs::write(repository.path().join("file.txt"), "two").unwrap();
+    let local_tip = repository.commit_all("two");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        controller.target_rewritten(*project_id).await.unwrap(),
+        None
+    );
+
+    // simulate fetching a target that was rewritten to replace the last commit
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let parent = repo.find_commit(merge_base).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let remote_tip = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "rewritten",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    repo.reference("refs/remotes/origin/master", remote_tip, true, "force-push")
+        .unwrap();
+
+    let rewrite = controller
+        .target_rewritten(*project_id)
+        .await
+        .unwrap()
+        .expect("the target doesn't contain the local tip anymore");
+    assert_eq!(rewrite.local_tip, local_tip);
+    assert_eq!(rewrite.remote_tip, remote_tip);
+    assert_eq!(rewrite.merge_base, Some(merge_base));
+}
@@ -101,7 +101,7 @@ async fn conflicting() {
     {
         // apply branch, it should conflict
         controller
-            .apply_virtual_branch(*project_id, branch_id)
+            .apply_virtual_branch(*project_id, branch_id, None)
             .await
             .unwrap();
 
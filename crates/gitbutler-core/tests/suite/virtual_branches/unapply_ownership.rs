@@ -1,6 +1,6 @@
 use std::fs;
 
-use gitbutler_core::virtual_branches::{branch, branch::BranchOwnershipClaims};
+use gitbutler_core::virtual_branches::{branch, branch::BranchOwnershipClaims, CommitOptions};
 
 use crate::suite::virtual_branches::Test;
 
@@ -29,7 +29,13 @@ async fn should_unapply_with_commits() {
     )
     .unwrap();
     controller
-        .create_commit(*project_id, branch_id, "test", None, false)
+        .create_commit(
+            *project_id,
+            branch_id,
+            "test",
+            None,
+            &CommitOptions::default(),
+        )
         .await
         .unwrap();
 
@@ -0,0 +1,77 @@
+use gitbutler_core::{git::diff::ChangeType, virtual_branches::UnownedFile};
+
+use super::*;
+
+#[tokio::test]
+async fn new_and_modified_files_without_claims() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join("file.txt"), "content\n").unwrap();
+    repository.commit_all("initial");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "changed content\n").unwrap();
+    fs::write(repository.path().join("new.txt"), "new\n").unwrap();
+
+    assert_eq!(
+        controller.unowned_files(*project_id).await.unwrap(),
+        vec![
+            UnownedFile {
+                path: "file.txt".into(),
+                change_type: ChangeType::Modified,
+            },
+            UnownedFile {
+                path: "new.txt".into(),
+                change_type: ChangeType::Added,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn claimed_files_are_not_listed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("claimed.txt"), "content\n").unwrap();
+    // listing the branches assigns the new file to the default branch
+    controller.list_virtual_branches(*project_id).await.unwrap();
+    fs::write(repository.path().join("unclaimed.txt"), "content\n").unwrap();
+
+    assert_eq!(
+        controller.unowned_files(*project_id).await.unwrap(),
+        vec![UnownedFile {
+            path: "unclaimed.txt".into(),
+            change_type: ChangeType::Added,
+        }]
+    );
+}
@@ -66,7 +66,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -118,7 +118,13 @@ mod unapplied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -152,7 +158,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -204,7 +210,13 @@ mod unapplied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -243,7 +255,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -295,7 +307,13 @@ mod unapplied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -331,7 +349,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -383,7 +401,13 @@ mod unapplied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -419,7 +443,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -476,7 +500,7 @@ mod unapplied_branch {
                     branch_id,
                     "non conflicting commit",
                     None,
-                    false,
+                    &CommitOptions::default(),
                 )
                 .await
                 .unwrap();
@@ -514,7 +538,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -561,7 +585,13 @@ mod unapplied_branch {
 
             fs::write(repository.path().join("file.txt"), "second").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -618,7 +648,7 @@ mod unapplied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -674,7 +704,13 @@ mod unapplied_branch {
             fs::write(repository.path().join("file.txt"), "second").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -728,7 +764,13 @@ mod unapplied_branch {
             // open pr
             fs::write(repository.path().join("file2.txt"), "new file").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
             controller
@@ -827,7 +869,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -879,7 +921,13 @@ mod applied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -908,7 +956,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -960,7 +1008,13 @@ mod applied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -994,7 +1048,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -1046,7 +1100,13 @@ mod applied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1077,7 +1137,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -1129,7 +1189,13 @@ mod applied_branch {
 
             fs::write(repository.path().join("file.txt"), "conflict").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "conflicting commit", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "conflicting commit",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1160,7 +1226,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -1224,7 +1290,13 @@ mod applied_branch {
                 fs::write(repository.path().join("file2.txt"), "no conflict").unwrap();
 
                 controller
-                    .create_commit(*project_id, branch_id, "no conflicts", None, false)
+                    .create_commit(
+                        *project_id,
+                        branch_id,
+                        "no conflicts",
+                        None,
+                        &CommitOptions::default(),
+                    )
                     .await
                     .unwrap();
                 controller
@@ -1295,7 +1367,13 @@ mod applied_branch {
                 fs::write(repository.path().join("file2.txt"), "no conflict").unwrap();
 
                 controller
-                    .create_commit(*project_id, branch_id, "no conflicts", None, false)
+                    .create_commit(
+                        *project_id,
+                        branch_id,
+                        "no conflicts",
+                        None,
+                        &CommitOptions::default(),
+                    )
                     .await
                     .unwrap();
                 controller
@@ -1376,7 +1454,13 @@ mod applied_branch {
             fs::write(repository.path().join("file2.txt"), "no conflict").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "no conflicts", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "no conflicts",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1406,7 +1490,7 @@ mod applied_branch {
 
         {
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -1458,7 +1542,13 @@ mod applied_branch {
             fs::write(repository.path().join("file.txt"), "second").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
             controller
@@ -1508,7 +1598,7 @@ mod applied_branch {
         {
             // applying the branch should produce conflict markers
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
             let (branches, _) = controller.list_virtual_branches(*project_id).await.unwrap();
@@ -1576,7 +1666,13 @@ mod applied_branch {
             .unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "fourth", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "fourth",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1624,7 +1720,7 @@ mod applied_branch {
 
         {
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
 
@@ -1676,7 +1772,13 @@ mod applied_branch {
             fs::write(repository.path().join("file.txt"), "first").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "first", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "first",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1720,7 +1822,7 @@ mod applied_branch {
 
         {
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
 
@@ -1758,7 +1860,13 @@ mod applied_branch {
             fs::write(repository.path().join("file.txt"), "first").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "first", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "first",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
 
@@ -1802,7 +1910,7 @@ mod applied_branch {
 
         {
             controller
-                .apply_virtual_branch(*project_id, branch_id)
+                .apply_virtual_branch(*project_id, branch_id, None)
                 .await
                 .unwrap();
 
@@ -1850,7 +1958,13 @@ mod applied_branch {
             fs::write(repository.path().join("file.txt"), "second").unwrap();
 
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
         };
@@ -1899,7 +2013,13 @@ mod applied_branch {
             // open pr
             fs::write(repository.path().join("file2.txt"), "new file").unwrap();
             controller
-                .create_commit(*project_id, branch_id, "second", None, false)
+                .create_commit(
+                    *project_id,
+                    branch_id,
+                    "second",
+                    None,
+                    &CommitOptions::default(),
+                )
                 .await
                 .unwrap();
             controller
@@ -1960,7 +2080,13 @@ mod applied_branch {
 
         fs::write(repository.path().join("file-3.txt"), "three").unwrap();
         controller
-            .create_commit(*project_id, branch_1_id, "third", None, false)
+            .create_commit(
+                *project_id,
+                branch_1_id,
+                "third",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
 
@@ -1978,7 +2104,13 @@ mod applied_branch {
         fs::write(repository.path().join("file-4.txt"), "four").unwrap();
 
         controller
-            .create_commit(*project_id, branch_2_id, "fourth", None, false)
+            .create_commit(
+                *project_id,
+                branch_2_id,
+                "fourth",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap();
 
@@ -24,25 +24,46 @@ async fn head() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let commit_three_oid = {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
     let commit_three = repository.find_commit(commit_three_oid).unwrap();
     let before_change_id = &commit_three.change_id();
@@ -107,25 +128,46 @@ async fn middle() {
     {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let commit_two_oid = {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -185,9 +227,16 @@ async fn forcepush_allowed() {
     let commit_one_oid = {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -250,9 +299,16 @@ async fn forcepush_forbidden() {
     let commit_one_oid = {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -292,25 +348,46 @@ async fn root() {
     let commit_one_oid = {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file two.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit two", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit two",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
         fs::write(repository.path().join("file three.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit three", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit three",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     controller
@@ -360,9 +437,16 @@ async fn empty() {
     let commit_one_oid = {
         fs::write(repository.path().join("file one.txt"), "").unwrap();
         controller
-            .create_commit(*project_id, branch_id, "commit one", None, false)
+            .create_commit(
+                *project_id,
+                branch_id,
+                "commit one",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     assert_eq!(
@@ -23,18 +23,32 @@ async fn detect_upstream_commits() {
         // create first commit
         fs::write(repository.path().join("file.txt"), "content").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let oid2 = {
         // create second commit
         fs::write(repository.path().join("file.txt"), "content2").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     // push
@@ -47,9 +61,16 @@ async fn detect_upstream_commits() {
         // create third commit
         fs::write(repository.path().join("file.txt"), "content3").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
@@ -90,18 +111,32 @@ async fn detect_integrated_commits() {
         // create first commit
         fs::write(repository.path().join("file.txt"), "content").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     let oid2 = {
         // create second commit
         fs::write(repository.path().join("file.txt"), "content2").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     // push
@@ -128,9 +163,16 @@ async fn detect_integrated_commits() {
         // create third commit
         fs::write(repository.path().join("file.txt"), "content3").unwrap();
         controller
-            .create_commit(*project_id, branch1_id, "commit", None, false)
+            .create_commit(
+                *project_id,
+                branch1_id,
+                "commit",
+                None,
+                &CommitOptions::default(),
+            )
             .await
             .unwrap()
+            .commit_id
     };
 
     {
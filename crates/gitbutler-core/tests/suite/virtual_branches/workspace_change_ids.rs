@@ -0,0 +1,78 @@
+use gitbutler_core::{git::CommitExt, virtual_branches::BranchCommit};
+
+use super::*;
+
+#[tokio::test]
+async fn maps_change_ids_to_commits() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        project,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_id = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "content").unwrap();
+    let commit_id = controller
+        .create_commit(
+            *project_id,
+            branch_id,
+            "commit",
+            None,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+        .commit_id;
+    let change_id = repository
+        .find_commit(commit_id)
+        .unwrap()
+        .change_id()
+        .expect("commits created by GitButler have a change-id");
+
+    // a commit created by another tool on top of the branch
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let parent = repo.find_commit(commit_id).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let foreign_commit_id = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "foreign",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let vb_state = project.virtual_branches();
+    let mut branch = vb_state.get_branch(branch_id).unwrap();
+    branch.head = foreign_commit_id;
+    vb_state.set_branch(branch).unwrap();
+
+    let change_ids = controller.workspace_change_ids(*project_id).await.unwrap();
+    assert_eq!(change_ids.by_change_id.len(), 1);
+    assert_eq!(
+        change_ids.by_change_id[&change_id],
+        BranchCommit {
+            branch_id,
+            commit_id
+        }
+    );
+    assert_eq!(
+        change_ids.without_change_id,
+        [BranchCommit {
+            branch_id,
+            commit_id: foreign_commit_id
+        }]
+    );
+}
@@ -0,0 +1,83 @@
+use gitbutler_core::virtual_branches::BranchId;
+
+use super::*;
+
+#[tokio::test]
+async fn diff_against_workspace_shows_the_branch_changes() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let branch_a = controller
+        .create_virtual_branch(*project_id, &branch::BranchCreateRequest::default())
+        .await
+        .unwrap();
+    fs::write(repository.path().join("a.txt"), "a\n").unwrap();
+    controller
+        .create_commit(*project_id, branch_a, "a", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    let branch_b = controller
+        .create_virtual_branch(
+            *project_id,
+            &branch::BranchCreateRequest {
+                selected_for_changes: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    fs::write(repository.path().join("b.txt"), "b\n").unwrap();
+    controller
+        .create_commit(*project_id, branch_b, "b", None, &CommitOptions::default())
+        .await
+        .unwrap();
+
+    let tree_id = controller
+        .workspace_tree_without_branch(*project_id, branch_a)
+        .await
+        .unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    assert!(tree.get_path(path::Path::new("a.txt")).is_err());
+    assert!(tree.get_path(path::Path::new("b.txt")).is_ok());
+
+    let workspace_tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let diff = repo
+        .diff_tree_to_tree(Some(&tree), Some(&workspace_tree), None)
+        .unwrap();
+    let paths = diff
+        .deltas()
+        .map(|delta| delta.new_file().path().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, [path::PathBuf::from("a.txt")]);
+}
+
+#[tokio::test]
+async fn unknown_branch() {
+    let Test {
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    assert!(controller
+        .workspace_tree_without_branch(*project_id, BranchId::generate())
+        .await
+        .is_err());
+}
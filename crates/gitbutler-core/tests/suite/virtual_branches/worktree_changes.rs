@@ -0,0 +1,444 @@
+use gitbutler_core::virtual_branches::{MovedSubmodule, SubmoduleChanges, WorktreeChanges};
+
+use super::*;
+
+async fn changed_paths(
+    controller: &Controller,
+    project_id: ProjectId,
+    pathspecs: &[&str],
+) -> Vec<String> {
+    let pathspecs: Vec<_> = pathspecs.iter().map(ToString::to_string).collect();
+    controller
+        .worktree_changes(project_id, &pathspecs, false, false, None, None)
+        .await
+        .unwrap()
+        .files
+        .into_iter()
+        .map(|file| file.path.to_str().unwrap().to_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn only_matching_paths_are_diffed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::create_dir_all(repository.path().join("src")).unwrap();
+    fs::create_dir_all(repository.path().join("docs")).unwrap();
+    for path in ["src/a.rs", "src/b.txt", "docs/c.md", "top.rs"] {
+        fs::write(repository.path().join(path), "content\n").unwrap();
+    }
+
+    assert_eq!(
+        changed_paths(controller, *project_id, &[]).await,
+        ["docs/c.md", "src/a.rs", "src/b.txt", "top.rs"],
+        "without pathspecs, everything is diffed"
+    );
+    assert_eq!(
+        changed_paths(controller, *project_id, &["docs/c.md"]).await,
+        ["docs/c.md"]
+    );
+    assert_eq!(
+        changed_paths(controller, *project_id, &["src", "*.rs"]).await,
+        ["src/a.rs", "src/b.txt", "top.rs"],
+        "paths matching more than one pathspec are listed once"
+    );
+    assert_eq!(
+        changed_paths(controller, *project_id, &["src", "!*.txt"]).await,
+        ["src/a.rs"],
+        "negated pathspecs exclude what they match"
+    );
+    assert!(changed_paths(controller, *project_id, &["missing"])
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn dirty_submodules_are_summarized_if_requested() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let submodule = repo
+        .submodule("https://example.com/lib.git", path::Path::new("lib"), true)
+        .unwrap();
+    let submodule_repo = submodule.open().unwrap();
+    fs::write(submodule_repo.workdir().unwrap().join("a.txt"), "a").unwrap();
+    fs::write(submodule_repo.workdir().unwrap().join("b.txt"), "b").unwrap();
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], false, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.submodules, None,
+        "submodules are only inspected on request"
+    );
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], true, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.submodules,
+        Some(vec![SubmoduleChanges::Dirty {
+            path: "lib".into(),
+            changed_files: 2
+        }])
+    );
+
+    let changes = controller
+        .worktree_changes(*project_id, &["other".to_owned()], true, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.submodules,
+        Some(vec![]),
+        "submodules not matching the pathspecs are ignored"
+    );
+}
+
+#[tokio::test]
+async fn submodules_with_other_commits_checked_out_are_listed() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let repo = git2::Repository::open(repository.path()).unwrap();
+    let mut submodule = repo
+        .submodule("https://example.com/lib.git", path::Path::new("lib"), true)
+        .unwrap();
+    let submodule_repo = submodule.open().unwrap();
+    let commit_in_submodule = |content: &str| {
+        fs::write(submodule_repo.workdir().unwrap().join("file.txt"), content).unwrap();
+        let mut index = submodule_repo.index().unwrap();
+        index.add_path(path::Path::new("file.txt")).unwrap();
+        let tree = submodule_repo
+            .find_tree(index.write_tree().unwrap())
+            .unwrap();
+        let signature = git2::Signature::now("test", "test@email.com").unwrap();
+        let parent = submodule_repo
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap());
+        submodule_repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                content,
+                &tree,
+                parent.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+            )
+            .unwrap()
+    };
+    let old_id = commit_in_submodule("old");
+    submodule.add_finalize().unwrap();
+    repository.commit_all("add submodule");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], false, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(changes.moved_submodules, vec![]);
+
+    let new_id = commit_in_submodule("new");
+    let changes = controller
+        .worktree_changes(*project_id, &[], false, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.moved_submodules,
+        vec![MovedSubmodule {
+            path: "lib".into(),
+            old_id: Some(old_id),
+            new_id,
+        }]
+    );
+    assert!(
+        changes.files.is_empty(),
+        "the submodule has no content to diff, so it isn't a changed file"
+    );
+}
+
+#[tokio::test]
+async fn uninitialized_submodules_are_skipped() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(
+        repository.path().join(".gitmodules"),
+        "[submodule \"lib\"]\n\tpath = lib\n\turl = https://example.com/lib.git\n",
+    )
+    .unwrap();
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], true, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.submodules,
+        Some(vec![SubmoduleChanges::Skipped {
+            path: "lib".into(),
+            note: "the submodule isn't initialized".into()
+        }])
+    );
+}
+
+#[tokio::test]
+async fn ignored_files_are_omitted_unless_requested() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::create_dir_all(repository.path().join("build")).unwrap();
+    fs::write(repository.path().join("build/tracked.txt"), "tracked\n").unwrap();
+    repository.commit_all("tracked");
+    fs::write(repository.path().join(".gitignore"), "build/\n").unwrap();
+    repository.commit_all("ignore build");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("build/tracked.txt"), "changed\n").unwrap();
+    fs::write(repository.path().join("build/output.o"), "output\n").unwrap();
+
+    let paths = |changes: WorktreeChanges| -> Vec<String> {
+        changes
+            .files
+            .into_iter()
+            .map(|file| file.path.to_str().unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(
+        paths(
+            controller
+                .worktree_changes(*project_id, &[], false, false, None, None)
+                .await
+                .unwrap()
+        ),
+        ["build/tracked.txt"],
+        "tracked files are listed even though they match an ignore pattern"
+    );
+    assert_eq!(
+        paths(
+            controller
+                .worktree_changes(*project_id, &[], false, true, None, None)
+                .await
+                .unwrap()
+        ),
+        ["build/output.o", "build/tracked.txt"]
+    );
+}
+
+#[tokio::test]
+async fn renames_are_detected_if_requested() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    let content: String = (1..=10).map(|line| format!("line {line}\n")).collect();
+    fs::write(repository.path().join("foo.rs"), &content).unwrap();
+    repository.commit_all("add foo");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::remove_file(repository.path().join("foo.rs")).unwrap();
+    fs::write(
+        repository.path().join("bar.rs"),
+        content.replace("line 5\n", "line five\n"),
+    )
+    .unwrap();
+
+    let changes = |rename_threshold| async move {
+        controller
+            .worktree_changes(*project_id, &[], false, false, rename_threshold, None)
+            .await
+            .unwrap()
+            .files
+    };
+
+    let files = changes(None).await;
+    assert_eq!(
+        files
+            .iter()
+            .map(|file| (file.path.to_str().unwrap(), file.old_path.is_some()))
+            .collect::<Vec<_>>(),
+        [("bar.rs", false), ("foo.rs", false)],
+        "without a threshold, the rename is an addition and a deletion"
+    );
+
+    let files = changes(Some(50)).await;
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, path::Path::new("bar.rs"));
+    assert_eq!(
+        files[0].old_path.as_deref(),
+        Some(path::Path::new("foo.rs"))
+    );
+    assert_eq!(files[0].hunks.len(), 1);
+    assert_eq!(
+        files[0].hunks[0]
+            .diff_lines
+            .to_string()
+            .lines()
+            .filter(|line| line.starts_with('-'))
+            .count(),
+        1,
+        "only the edited line shows up as removed"
+    );
+
+    assert_eq!(
+        changes(Some(100)).await.len(),
+        2,
+        "changes above the threshold are still an addition and a deletion"
+    );
+}
+
+#[tokio::test]
+async fn line_ending_normalization_is_not_a_change() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    fs::write(repository.path().join(".gitattributes"), "* text=auto\n").unwrap();
+    fs::write(repository.path().join("file.txt"), "first\nsecond\n").unwrap();
+    repository.commit_all("normalized");
+    repository.push();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("file.txt"), "first\r\nsecond\r\n").unwrap();
+    assert!(
+        changed_paths(controller, *project_id, &[]).await.is_empty(),
+        "the file is the same as committed once it's normalized"
+    );
+
+    fs::write(repository.path().join("file.txt"), "first\r\nchanged\r\n").unwrap();
+    assert_eq!(
+        changed_paths(controller, *project_id, &[]).await,
+        ["file.txt"]
+    );
+}
+
+#[tokio::test]
+async fn diffs_above_the_limit_only_have_line_counts() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("small.txt"), "small\n").unwrap();
+    fs::write(repository.path().join("lockfile"), "entry\n".repeat(1000)).unwrap();
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], false, false, None, Some(1024))
+        .await
+        .unwrap();
+    assert_eq!(changes.files.len(), 1);
+    assert_eq!(changes.files[0].path, path::Path::new("small.txt"));
+    assert_eq!(changes.too_large.len(), 1);
+    let too_large = &changes.too_large[0];
+    assert_eq!(too_large.path, path::Path::new("lockfile"));
+    assert_eq!((too_large.lines_added, too_large.lines_removed), (1000, 0));
+    assert!(too_large.diff_size_bytes > 1024);
+
+    let changes = controller
+        .worktree_changes(*project_id, &[], false, false, None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        changes.files.len(),
+        2,
+        "without a limit all diffs are returned"
+    );
+    assert!(changes.too_large.is_empty());
+}
+
+#[tokio::test]
+async fn files_with_nul_bytes_are_binary() {
+    let Test {
+        repository,
+        project_id,
+        controller,
+        ..
+    } = &Test::default();
+
+    controller
+        .set_base_branch(*project_id, &"refs/remotes/origin/master".parse().unwrap())
+        .await
+        .unwrap();
+
+    fs::write(repository.path().join("artifact.o"), b"\x7fELF\0\0\x01").unwrap();
+
+    let files = controller
+        .worktree_changes(*project_id, &[], false, false, None, None)
+        .await
+        .unwrap()
+        .files;
+    assert_eq!(files.len(), 1);
+    assert!(files[0].binary);
+    assert_eq!(files[0].hunks.len(), 1);
+    assert!(files[0].hunks[0].binary, "the content is only referred to");
+}
@@ -1,4 +1,9 @@
-use gitbutler_core::virtual_branches::branch::Hunk;
+use std::path::Path;
+
+use gitbutler_core::{
+    git::diff::{ChangeType, GitHunk},
+    virtual_branches::branch::{Hunk, HunkIdentity},
+};
 
 #[test]
 fn to_from_string() {
@@ -61,13 +66,38 @@ fn hash_diff_empty_is_fine() {
 
 #[test]
 fn hash_diff_content_hash() {
-    let a_hash = Hunk::hash_diff("@@x\na");
-    let b_hash = Hunk::hash_diff("@@y\na");
+    let a_hash = Hunk::hash_diff("@@x\n-a\n+b\n");
+    let b_hash = Hunk::hash_diff("@@y\n-a\n+b\n");
     assert_eq!(
         a_hash, b_hash,
         "it skips the first line which is assumed to be a diff-header.\
         That way, the content is hashed instead"
-    )
+    );
+    assert_ne!(a_hash, Hunk::hash_diff("@@x\n-a\n+c\n"));
+}
+
+#[test]
+fn hash_diff_ignores_context_lines() {
+    assert_eq!(
+        Hunk::hash_diff("@@ -2 +2 @@\n-x\n+y\n"),
+        Hunk::hash_diff("@@ -10,3 +10,3 @@\n above\n-x\n+y\n below\n"),
+        "moving the hunk changes its position and context, but not its changes"
+    );
+    assert_eq!(
+        Hunk::hash_diff("@@ -1,4 +1,4 @@\n a\n-x\n b\n+y\n"),
+        Hunk::hash_diff("@@ -1,4 +1,4 @@\n c\n-x\n d\n+y\n"),
+        "context lines between the changes are ignored as well"
+    );
+    assert_ne!(
+        Hunk::hash_diff("@@ -2 +2 @@\n-x\n+y\n"),
+        Hunk::hash_diff("@@ -2 +2 @@\n-y\n+x\n"),
+        "the order of the changed lines matters"
+    );
+    assert_eq!(
+        Hunk::hash_diff("@@ -2 +2 @@\n-x\n+y\n"),
+        Hunk::hash("x\ny\n"),
+        "the markers aren't part of the hash"
+    );
 }
 
 #[test]
@@ -120,3 +150,129 @@ fn eq() {
         assert_eq!(a == b, expected, "comparing {} and {}", a, b);
     }
 }
+
+#[test]
+fn stable_id_ignores_line_numbers_and_changed_lines() {
+    let path = std::path::Path::new("file.txt");
+    let id = Hunk::stable_id(path, "@@ -1,3 +1,3 @@\n above\n-old\n+new\n below\n");
+    assert_eq!(
+        id,
+        Hunk::stable_id(path, "@@ -10,3 +12,3 @@\n above\n-old\n+new\n below\n"),
+        "the hunk moved"
+    );
+    assert_eq!(
+        id,
+        Hunk::stable_id(
+            path,
+            "@@ -1,3 +1,4 @@\n above\n-old\n+new\n+newer\n below\n"
+        ),
+        "the hunk was edited"
+    );
+    assert_ne!(
+        id,
+        Hunk::stable_id(path, "@@ -1,3 +1,3 @@\n other\n-old\n+new\n below\n"),
+        "the anchors changed"
+    );
+    assert_ne!(
+        id,
+        Hunk::stable_id(
+            std::path::Path::new("other.txt"),
+            "@@ -1,3 +1,3 @@\n above\n-old\n+new\n below\n"
+        ),
+        "the path is part of the id"
+    );
+}
+
+fn git_hunk(old_start: u32, new_start: u32, diff: &str) -> GitHunk {
+    GitHunk {
+        old_start,
+        old_lines: 1,
+        new_start,
+        new_lines: 1,
+        diff_lines: diff.into(),
+        binary: false,
+        locked_to: Box::new([]),
+        change_type: ChangeType::Modified,
+    }
+}
+
+#[test]
+fn hunk_identity_with_section_tells_functions_apart() {
+    let path = Path::new("file.rs");
+    let digest = |diff| HunkIdentity::new(path, &git_hunk(2, 2, diff)).digest;
+    let section_digest = |diff| HunkIdentity::with_section(path, &git_hunk(2, 2, diff)).digest;
+    let in_a = "@@ -2 +2 @@ fn a() {\n-    x;\n+    y;\n";
+    let in_b = "@@ -12 +12 @@ fn b() {\n-    x;\n+    y;\n";
+    assert_eq!(
+        digest(in_a),
+        digest(in_b),
+        "the same change in different functions collides without the section"
+    );
+    assert_ne!(section_digest(in_a), section_digest(in_b));
+    assert_eq!(
+        section_digest(in_a),
+        section_digest("@@ -5 +5 @@ fn a() {\n-    x;\n+    y;\n"),
+        "the position within the file doesn't matter"
+    );
+    for diff in ["@@ -1 +1 @@\n-a\n+b\n", "a", ""] {
+        assert_eq!(
+            section_digest(diff),
+            digest(diff),
+            "without section it's the same as without sections"
+        );
+    }
+}
+
+#[test]
+fn hunk_identity_tells_hunks_with_the_same_change_apart() {
+    let diff = "@@ -2 +2 @@\n-x\n+y\n";
+    let path = Path::new("file.txt");
+    let first = HunkIdentity::new(path, &git_hunk(2, 2, diff));
+    assert_ne!(
+        first,
+        HunkIdentity::new(path, &git_hunk(12, 12, diff)),
+        "the position is part of the identity"
+    );
+    assert_ne!(
+        first,
+        HunkIdentity::new(Path::new("other.txt"), &git_hunk(2, 2, diff)),
+        "the path is part of the identity"
+    );
+    assert_eq!(first.digest, Hunk::hash("x\ny\n"));
+}
+
+#[test]
+fn hunk_identity_ignores_context_lines() {
+    let path = Path::new("file.txt");
+    assert_eq!(
+        HunkIdentity::new(path, &git_hunk(2, 2, "@@ -2 +2 @@\n-x\n+y\n")),
+        HunkIdentity::new(
+            path,
+            &git_hunk(2, 2, "@@ -1,3 +1,3 @@\n above\n-x\n+y\n below\n")
+        )
+    );
+}
+
+#[test]
+fn hunk_identity_without_diff_header_hashes_whole_diff() {
+    let path = Path::new("file.txt");
+    assert_eq!(
+        HunkIdentity::new(path, &git_hunk(2, 2, "-x\n+y\n")).digest,
+        Hunk::hash_diff("-x\n+y\n")
+    );
+    assert_eq!(
+        HunkIdentity::new(path, &git_hunk(0, 0, "")).digest,
+        Hunk::hash(""),
+        "empty new files and mode changes have no diff at all"
+    );
+
+    let binary = GitHunk {
+        binary: true,
+        ..git_hunk(0, 0, "0123456789abcdef")
+    };
+    assert_eq!(
+        HunkIdentity::new(path, &binary).digest,
+        Hunk::hash("0123456789abcdef"),
+        "binary hunks have no header and are hashed as a whole"
+    );
+}
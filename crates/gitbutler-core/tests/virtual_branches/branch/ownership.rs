@@ -37,6 +37,7 @@ fn reconcile_ownership_simple() {
         notes: String::default(),
         upstream: None,
         upstream_head: None,
+        push_remote: None,
         created_timestamp_ms: u128::default(),
         updated_timestamp_ms: u128::default(),
         order: usize::default(),
@@ -63,6 +64,7 @@ fn reconcile_ownership_simple() {
         notes: String::default(),
         upstream: None,
         upstream_head: None,
+        push_remote: None,
         created_timestamp_ms: u128::default(),
         updated_timestamp_ms: u128::default(),
         order: usize::default(),
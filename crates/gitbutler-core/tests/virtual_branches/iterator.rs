@@ -25,6 +25,7 @@ fn new_test_branch() -> virtual_branches::branch::Branch {
             .unwrap(),
         ),
         upstream_head: None,
+        push_remote: None,
         created_timestamp_ms: TEST_INDEX.load(Ordering::Relaxed) as u128,
         updated_timestamp_ms: (TEST_INDEX.load(Ordering::Relaxed) + 100) as u128,
         head: format!(
@@ -65,6 +65,7 @@ fn commit_on_branch_then_change_file_then_get_status() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     // status (no files)
@@ -163,6 +164,7 @@ fn track_binary_files() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     // status (no files)
@@ -196,6 +198,7 @@ fn track_binary_files() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository).unwrap();
@@ -960,6 +963,7 @@ async fn merge_vbranch_upstream_conflict() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1435,6 +1439,7 @@ fn upstream_integrated_vbranch() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
     commit(
         project_repository,
@@ -1443,6 +1448,7 @@ fn upstream_integrated_vbranch() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1503,6 +1509,7 @@ fn commit_same_hunk_twice() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1538,6 +1545,7 @@ fn commit_same_hunk_twice() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1596,6 +1604,7 @@ fn commit_same_file_twice() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1631,6 +1640,7 @@ fn commit_same_file_twice() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1689,6 +1699,7 @@ fn commit_partial_by_hunk() -> Result<()> {
         Some(&"test.txt:1-6".parse::<BranchOwnershipClaims>().unwrap()),
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1707,6 +1718,7 @@ fn commit_partial_by_hunk() -> Result<()> {
         Some(&"test.txt:16-22".parse::<BranchOwnershipClaims>().unwrap()),
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1757,6 +1769,7 @@ fn commit_partial_by_file() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1816,6 +1829,7 @@ fn commit_add_and_delete_files() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -1881,6 +1895,7 @@ fn commit_executable_and_symlinks() -> Result<()> {
         None,
         None,
         false,
+        false,
     )?;
 
     let (branches, _) = virtual_branches::list_virtual_branches(project_repository)?;
@@ -2109,6 +2124,7 @@ fn post_commit_hook() -> Result<()> {
         None,
         None,
         true,
+        false,
     )?;
 
     assert!(hook_ran_proof.exists());
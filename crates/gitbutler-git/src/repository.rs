@@ -272,16 +272,16 @@ where
     }
 }
 
-/// Fetches the given refspec from the given remote in the repository
-/// at the given path. Any prompts for the user are passed to the asynchronous
-/// callback `on_prompt` which should return the user's response or `None` if the
-/// operation should be aborted, in which case an `Err` value is returned from this
-/// function.
+/// Fetches the given refspecs from the given remote in the repository
+/// at the given path, all with a single invocation of git. Any prompts for the
+/// user are passed to the asynchronous callback `on_prompt` which should return
+/// the user's response or `None` if the operation should be aborted, in which
+/// case an `Err` value is returned from this function.
 pub async fn fetch<P, F, Fut, E, Extra>(
     repo_path: P,
     executor: E,
     remote: &str,
-    refspec: RefSpec,
+    refspecs: &[RefSpec],
     on_prompt: F,
     extra: Extra,
 ) -> Result<(), crate::Error<Error<E>>>
@@ -294,10 +294,10 @@ where
 {
     let mut args = vec!["fetch", "--quiet", "--prune"];
 
-    let refspec = refspec.to_string();
+    let refspecs = refspecs.iter().map(ToString::to_string).collect::<Vec<_>>();
 
     args.push(remote);
-    args.push(&refspec);
+    args.extend(refspecs.iter().map(String::as_str));
 
     let (status, stdout, stderr) =
         execute_with_auth_harness(repo_path, &executor, &args, None, on_prompt, extra).await?;
@@ -24,6 +24,17 @@ impl App {
         Ok(())
     }
 
+    pub fn resolve_conflict_side(
+        &self,
+        project_id: ProjectId,
+        path: &str,
+        side: conflicts::ConflictSide,
+    ) -> Result<bool> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        conflicts::resolve_side(&project_repository, path, side)
+    }
+
     pub fn git_remote_branches(&self, project_id: ProjectId) -> Result<Vec<git::RemoteRefname>> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
@@ -64,6 +75,31 @@ impl App {
         Ok(size)
     }
 
+    pub fn repo_object_stats(
+        &self,
+        project_id: ProjectId,
+    ) -> Result<project_repository::ObjectStats> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        project_repository
+            .object_stats()
+            .context("failed to compute object stats")
+    }
+
+    pub fn head_status(&self, project_id: ProjectId) -> Result<project_repository::HeadStatus> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        project_repository
+            .head_status()
+            .context("failed to get repository head")
+    }
+
+    pub fn head_info(&self, project_id: ProjectId) -> Result<project_repository::HeadInfo> {
+        let project = self.projects.get(project_id)?;
+        let project_repository = project_repository::Repository::open(&project)?;
+        project_repository.head_info()
+    }
+
     pub fn git_head(&self, project_id: ProjectId) -> Result<String> {
         let project = self.projects.get(project_id)?;
         let project_repository = project_repository::Repository::open(&project)?;
@@ -1,4 +1,8 @@
-use gitbutler_core::{git, projects::ProjectId};
+use gitbutler_core::{
+    git,
+    project_repository::{self, conflicts::ConflictSide},
+    projects::ProjectId,
+};
 use tauri::Manager;
 use tracing::instrument;
 
@@ -64,6 +68,16 @@ pub async fn git_index_size(
     Ok(app.git_index_size(project_id).expect("git index size"))
 }
 
+#[tauri::command(async)]
+#[instrument(skip(handle), err(Debug))]
+pub async fn repo_object_stats(
+    handle: tauri::AppHandle,
+    project_id: ProjectId,
+) -> Result<project_repository::ObjectStats, Error> {
+    let app = handle.state::<app::App>();
+    Ok(app.repo_object_stats(project_id)?)
+}
+
 #[tauri::command(async)]
 #[instrument(skip(handle), err(Debug))]
 pub async fn git_head(handle: tauri::AppHandle, project_id: ProjectId) -> Result<String, Error> {
@@ -72,6 +86,26 @@ pub async fn git_head(handle: tauri::AppHandle, project_id: ProjectId) -> Result
     Ok(head)
 }
 
+#[tauri::command(async)]
+#[instrument(skip(handle), err(Debug))]
+pub async fn head_status(
+    handle: tauri::AppHandle,
+    project_id: ProjectId,
+) -> Result<project_repository::HeadStatus, Error> {
+    let app = handle.state::<app::App>();
+    Ok(app.head_status(project_id)?)
+}
+
+#[tauri::command(async)]
+#[instrument(skip(handle), err(Debug))]
+pub async fn head_info(
+    handle: tauri::AppHandle,
+    project_id: ProjectId,
+) -> Result<project_repository::HeadInfo, Error> {
+    let app = handle.state::<app::App>();
+    Ok(app.head_info(project_id)?)
+}
+
 #[tauri::command(async)]
 #[instrument(skip(handle), err(Debug))]
 pub async fn delete_all_data(handle: tauri::AppHandle) -> Result<(), Error> {
@@ -92,6 +126,18 @@ pub async fn mark_resolved(
     Ok(())
 }
 
+#[tauri::command(async)]
+#[instrument(skip(handle), err(Debug))]
+pub async fn resolve_conflict_side(
+    handle: tauri::AppHandle,
+    project_id: ProjectId,
+    path: &str,
+    side: ConflictSide,
+) -> Result<bool, Error> {
+    let app = handle.state::<app::App>();
+    Ok(app.resolve_conflict_side(project_id, path, side)?)
+}
+
 #[tauri::command(async)]
 #[instrument(skip(_handle), err(Debug))]
 pub async fn git_set_global_config(
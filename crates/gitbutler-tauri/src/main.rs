@@ -154,13 +154,17 @@ fn main() {
                 .invoke_handler(tauri::generate_handler![
                     commands::git_remote_branches,
                     commands::git_head,
+                    commands::head_status,
+                    commands::head_info,
                     commands::delete_all_data,
                     commands::mark_resolved,
+                    commands::resolve_conflict_side,
                     commands::git_set_global_config,
                     commands::git_get_global_config,
                     commands::git_test_push,
                     commands::git_test_fetch,
                     commands::git_index_size,
+                    commands::repo_object_stats,
                     zip::commands::get_logs_archive_path,
                     zip::commands::get_project_archive_path,
                     zip::commands::get_project_data_archive_path,
@@ -171,32 +175,87 @@ fn main() {
                     projects::commands::get_project,
                     projects::commands::update_project,
                     projects::commands::delete_project,
+                    projects::commands::remove_project,
                     projects::commands::list_projects,
                     projects::commands::set_project_active,
                     projects::commands::git_get_local_config,
                     projects::commands::git_set_local_config,
+                    projects::commands::get_project_ui_state,
+                    projects::commands::set_project_ui_state,
+                    projects::commands::non_default_settings,
+                    projects::commands::reset_setting,
                     projects::commands::check_signing_settings,
                     virtual_branches::commands::list_virtual_branches,
                     virtual_branches::commands::create_virtual_branch,
                     virtual_branches::commands::commit_virtual_branch,
+                    virtual_branches::commands::apply_operations,
+                    virtual_branches::commands::commit_across_branches,
                     virtual_branches::commands::get_base_branch_data,
+                    virtual_branches::commands::target_rewritten,
+                    virtual_branches::commands::rebase_correspondence,
+                    virtual_branches::commands::newly_integrated_commits,
+                    virtual_branches::commands::branch_merge_statuses,
+                    virtual_branches::commands::branches_with_status,
+                    virtual_branches::commands::branch_commit_metrics,
+                    virtual_branches::commands::workspace_tree_without_branch,
+                    virtual_branches::commands::stale_branches,
+                    virtual_branches::commands::stale_remote_branches,
+                    virtual_branches::commands::parallel_integration_groups,
+                    virtual_branches::commands::suggested_integration_order,
+                    virtual_branches::commands::reorder_stack_branches,
+                    virtual_branches::commands::emptied_by_integration,
                     virtual_branches::commands::set_base_branch,
                     virtual_branches::commands::update_base_branch,
                     virtual_branches::commands::integrate_upstream_commits,
                     virtual_branches::commands::update_virtual_branch,
+                    virtual_branches::commands::set_branch_order,
                     virtual_branches::commands::delete_virtual_branch,
                     virtual_branches::commands::apply_branch,
                     virtual_branches::commands::unapply_branch,
                     virtual_branches::commands::unapply_ownership,
                     virtual_branches::commands::reset_files,
                     virtual_branches::commands::push_virtual_branch,
+                    virtual_branches::commands::force_push_safety,
+                    virtual_branches::commands::set_branch_push_remote,
                     virtual_branches::commands::create_virtual_branch_from_branch,
                     virtual_branches::commands::can_apply_virtual_branch,
                     virtual_branches::commands::can_apply_remote_branch,
                     virtual_branches::commands::list_remote_commit_files,
+                    virtual_branches::commands::commit_binary_delta_size,
+                    virtual_branches::commands::path_metadata,
+                    virtual_branches::commands::worktree_changes,
+                    virtual_branches::commands::file_diff,
+                    virtual_branches::commands::dirstat,
+                    virtual_branches::commands::porcelain_status,
+                    virtual_branches::commands::large_new_binaries,
+                    virtual_branches::commands::commits_touching_path,
+                    virtual_branches::commands::commit_similarity,
+                    virtual_branches::commands::hunks_identical,
+                    virtual_branches::commands::ancestry_path,
+                    virtual_branches::commands::base_to_target_diff,
+                    virtual_branches::commands::branch_squashed_diff,
+                    virtual_branches::commands::workspace_change_ids,
+                    virtual_branches::commands::branch_topological_order,
+                    virtual_branches::commands::active_branches,
+                    virtual_branches::commands::stack_pr_descriptions,
+                    virtual_branches::commands::pr_base_branch,
+                    virtual_branches::commands::branch_exclusive_commits,
+                    virtual_branches::commands::branches_merge_base,
+                    virtual_branches::commands::safe_amend_targets,
+                    virtual_branches::commands::hunk_locks,
+                    virtual_branches::commands::commit_dependency_order,
+                    virtual_branches::commands::hunk_apply_order,
+                    virtual_branches::commands::hunk_counts_by_branch,
+                    virtual_branches::commands::cross_branch_locked_hunks,
+                    virtual_branches::commands::lock_impact_of_applying,
+                    virtual_branches::commands::unowned_files,
+                    virtual_branches::commands::redundant_worktree_changes,
+                    virtual_branches::commands::diff_branches,
+                    virtual_branches::commands::diff_against_remote,
                     virtual_branches::commands::reset_virtual_branch,
                     virtual_branches::commands::cherry_pick_onto_virtual_branch,
                     virtual_branches::commands::amend_virtual_branch,
+                    virtual_branches::commands::split_hunk,
                     virtual_branches::commands::move_commit_file,
                     virtual_branches::commands::undo_commit,
                     virtual_branches::commands::insert_blank_commit,
@@ -204,12 +263,18 @@ fn main() {
                     virtual_branches::commands::update_commit_message,
                     virtual_branches::commands::list_remote_branches,
                     virtual_branches::commands::get_remote_branch_data,
+                    virtual_branches::commands::can_squash_branch,
                     virtual_branches::commands::squash_branch_commit,
+                    virtual_branches::commands::preview_squash,
+                    virtual_branches::commands::preview_reorder,
                     virtual_branches::commands::fetch_from_remotes,
+                    virtual_branches::commands::minimal_fetch_refspecs,
                     virtual_branches::commands::move_commit,
+                    virtual_branches::commands::move_commit_between_branches,
                     undo::list_snapshots,
                     undo::restore_snapshot,
                     undo::snapshot_diff,
+                    undo::undo_last_operation,
                     config::get_gb_config,
                     config::set_gb_config,
                     menu::menu_item_set_enabled,
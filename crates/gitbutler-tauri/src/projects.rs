@@ -65,6 +65,20 @@ pub mod commands {
             .map_err(Into::into)
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn remove_project(
+        handle: tauri::AppHandle,
+        id: ProjectId,
+        purge_data: bool,
+    ) -> Result<u64, Error> {
+        handle
+            .state::<Controller>()
+            .remove(id, purge_data)
+            .await
+            .map_err(Into::into)
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn git_get_local_config(
@@ -89,6 +103,47 @@ pub mod commands {
             .map_err(Into::into)
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn non_default_settings(
+        handle: tauri::AppHandle,
+        id: ProjectId,
+    ) -> Result<Vec<projects::NonDefaultSetting>, Error> {
+        Ok(handle.state::<Controller>().non_default_settings(id)?)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn reset_setting(
+        handle: tauri::AppHandle,
+        id: ProjectId,
+        key: projects::Setting,
+    ) -> Result<projects::Project, Error> {
+        Ok(handle.state::<Controller>().reset_setting(id, key)?)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn get_project_ui_state(
+        handle: tauri::AppHandle,
+        id: ProjectId,
+    ) -> Result<projects::UiState, Error> {
+        Ok(handle.state::<Controller>().get_ui_state(id)?)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn set_project_ui_state(
+        handle: tauri::AppHandle,
+        id: ProjectId,
+        state: projects::UiState,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .set_ui_state(id, state)
+            .map_err(Into::into)
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn check_signing_settings(
@@ -45,6 +45,21 @@ pub async fn restore_snapshot(
     Ok(())
 }
 
+#[tauri::command(async)]
+#[instrument(skip(handle), err(Debug))]
+pub async fn undo_last_operation(
+    handle: tauri::AppHandle,
+    project_id: ProjectId,
+    force: bool,
+) -> Result<Snapshot, Error> {
+    let project = handle
+        .state::<projects::Controller>()
+        .get(project_id)
+        .context("failed to get project")?;
+    let snapshot = project.undo_last_operation(force)?;
+    Ok(snapshot)
+}
+
 #[tauri::command(async)]
 #[instrument(skip(handle), err(Debug))]
 pub async fn snapshot_diff(
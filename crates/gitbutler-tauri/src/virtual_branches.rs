@@ -1,15 +1,28 @@
 pub mod commands {
+    use std::{path, time::Duration};
+
     use crate::error::Error;
     use anyhow::{anyhow, Context};
     use gitbutler_core::{
         assets,
         error::Code,
-        git, projects,
+        git,
+        project_repository::conflicts::ConflictStyle,
+        projects,
         projects::ProjectId,
         virtual_branches::{
-            branch::{self, BranchId, BranchOwnershipClaims},
+            branch::{self, BranchId, BranchOwnershipClaims, Hunk, HunkIdentity},
             controller::Controller,
-            BaseBranch, RemoteBranch, RemoteBranchData, RemoteBranchFile, VirtualBranches,
+            ActiveBranches, AmendTargets, BaseBranch, BranchCommit, BranchCommitMetrics,
+            BranchHunkCounts, BranchMergeStatus, BranchSegment, BranchWithStatus, CommitAssignment,
+            CommitOptions, CreatedCommit, CrossBranchLockedFile, DirStat, EmptiedCommit,
+            FetchRefspecs, ForcePushSafety, HunkComparison, HunkGroup, HunkLocks, HunkRef,
+            IntegrationOrder, LargeBinary, LockImpact, PathCommit, PathMetadata, PrBase,
+            PrDescription, PushResult, RebaseCorrespondence, RedundantHunk, RemoteBranch,
+            RemoteBranchData, RemoteBranchFile, ReorderPreview, SplitHunks, SquashBlocker,
+            SquashPreview, SquashedDiff, StaleBranch, StaleRemoteBranch, TargetRewrite,
+            UnownedFile, VirtualBranches, WorkspaceChangeIds, WorkspaceOp, WorktreeChanges,
+            WorktreeFileDiff,
         },
     };
     use tauri::{AppHandle, Manager};
@@ -25,298 +38,1060 @@ pub mod commands {
         branch: BranchId,
         message: &str,
         ownership: Option<BranchOwnershipClaims>,
-        run_hooks: bool,
+        options: Option<CommitOptions>,
+    ) -> Result<CreatedCommit, Error> {
+        let created = handle
+            .state::<Controller>()
+            .create_commit(
+                project_id,
+                branch,
+                message,
+                ownership.as_ref(),
+                &options.unwrap_or_default(),
+            )
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(created)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn apply_operations(
+        handle: AppHandle,
+        project_id: ProjectId,
+        ops: Vec<WorkspaceOp>,
+    ) -> Result<(), Error> {
+        let result = handle
+            .state::<Controller>()
+            .apply_operations(project_id, &ops)
+            .await;
+        emit_vbranches(&handle, project_id).await;
+        result.map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn commit_across_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+        assignments: Vec<CommitAssignment>,
+    ) -> Result<Vec<BranchCommit>, Error> {
+        let result = handle
+            .state::<Controller>()
+            .commit_across_branches(project_id, &assignments)
+            .await;
+        emit_vbranches(&handle, project_id).await;
+        result.map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn list_virtual_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<VirtualBranches, Error> {
+        let (branches, skipped_files) = handle
+            .state::<Controller>()
+            .list_virtual_branches(project_id)
+            .await?;
+
+        let proxy = handle.state::<assets::Proxy>();
+        let branches = proxy.proxy_virtual_branches(branches).await;
+        Ok(VirtualBranches {
+            branches,
+            skipped_files,
+        })
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn create_virtual_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: branch::BranchCreateRequest,
+    ) -> Result<BranchId, Error> {
+        let branch_id = handle
+            .state::<Controller>()
+            .create_virtual_branch(project_id, &branch)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(branch_id)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn create_virtual_branch_from_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: git::Refname,
+    ) -> Result<BranchId, Error> {
+        let branch_id = handle
+            .state::<Controller>()
+            .create_virtual_branch_from_branch(project_id, &branch)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(branch_id)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn integrate_upstream_commits(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: BranchId,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .integrate_upstream_commits(project_id, branch, conflict_style)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn get_base_branch_data(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Option<BaseBranch>, Error> {
+        if let Ok(base_branch) = handle
+            .state::<Controller>()
+            .get_base_branch_data(project_id)
+            .await
+        {
+            let proxy = handle.state::<assets::Proxy>();
+            let base_branch = proxy.proxy_base_branch(base_branch).await;
+            return Ok(Some(base_branch));
+        }
+        Ok(None)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn minimal_fetch_refspecs(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<FetchRefspecs>, Error> {
+        handle
+            .state::<Controller>()
+            .minimal_fetch_refspecs(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn newly_integrated_commits(
+        handle: AppHandle,
+        project_id: ProjectId,
+        since: String,
+    ) -> Result<Vec<BranchCommit>, Error> {
+        let since = git2::Oid::from_str(&since).map_err(|e| anyhow!(e))?;
+        handle
+            .state::<Controller>()
+            .newly_integrated_commits(project_id, since)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn branch_merge_statuses(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<BranchMergeStatus>, Error> {
+        handle
+            .state::<Controller>()
+            .branch_merge_statuses(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn branches_with_status(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<BranchWithStatus>, Error> {
+        handle
+            .state::<Controller>()
+            .branches_with_status(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn branch_commit_metrics(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<BranchCommitMetrics, Error> {
+        handle
+            .state::<Controller>()
+            .branch_commit_metrics(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn workspace_tree_without_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
     ) -> Result<String, Error> {
-        let oid = handle
+        let tree_id = handle
+            .state::<Controller>()
+            .workspace_tree_without_branch(project_id, branch_id)
+            .await?;
+        Ok(tree_id.to_string())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn reorder_stack_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        branch_names: Vec<String>,
+    ) -> Result<Vec<branch::Branch>, Error> {
+        let branches = handle
             .state::<Controller>()
-            .create_commit(project_id, branch, message, ownership.as_ref(), run_hooks)
+            .reorder_stack_branches(project_id, branch_id, &branch_names)
             .await?;
         emit_vbranches(&handle, project_id).await;
-        Ok(oid.to_string())
+        Ok(branches)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn stale_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+        older_than_seconds: u64,
+    ) -> Result<Vec<StaleBranch>, Error> {
+        handle
+            .state::<Controller>()
+            .stale_branches(project_id, Duration::from_secs(older_than_seconds))
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn stale_remote_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<StaleRemoteBranch>, Error> {
+        handle
+            .state::<Controller>()
+            .stale_remote_branches(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn parallel_integration_groups(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<Vec<BranchId>>, Error> {
+        handle
+            .state::<Controller>()
+            .parallel_integration_groups(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn suggested_integration_order(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<IntegrationOrder, Error> {
+        handle
+            .state::<Controller>()
+            .suggested_integration_order(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn emptied_by_integration(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<Vec<EmptiedCommit>, Error> {
+        handle
+            .state::<Controller>()
+            .emptied_by_integration(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn target_rewritten(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Option<TargetRewrite>, Error> {
+        handle
+            .state::<Controller>()
+            .target_rewritten(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn rebase_correspondence(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<RebaseCorrespondence, Error> {
+        handle
+            .state::<Controller>()
+            .rebase_correspondence(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn set_base_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: &str,
+        push_remote: Option<&str>, // optional different name of a remote to push to (defaults to same as the branch)
+    ) -> Result<BaseBranch, Error> {
+        let branch_name = format!("refs/remotes/{}", branch)
+            .parse()
+            .context("Invalid branch name")?;
+        let base_branch = handle
+            .state::<Controller>()
+            .set_base_branch(project_id, &branch_name)
+            .await?;
+        let base_branch = handle
+            .state::<assets::Proxy>()
+            .proxy_base_branch(base_branch)
+            .await;
+
+        // if they also sent a different push remote, set that too
+        if let Some(push_remote) = push_remote {
+            handle
+                .state::<Controller>()
+                .set_target_push_remote(project_id, push_remote)
+                .await?;
+        }
+        emit_vbranches(&handle, project_id).await;
+        Ok(base_branch)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn update_base_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<branch::Branch>, Error> {
+        let unapplied_branches = handle
+            .state::<Controller>()
+            .update_base_branch(project_id)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(unapplied_branches)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn update_virtual_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: branch::BranchUpdateRequest,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .update_virtual_branch(project_id, branch)
+            .await?;
+
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn set_branch_order(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_ids: Vec<BranchId>,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .set_branch_order(project_id, &branch_ids)
+            .await?;
+
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn delete_virtual_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .delete_virtual_branch(project_id, branch_id)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn apply_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: BranchId,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .apply_virtual_branch(project_id, branch, conflict_style)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn unapply_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: BranchId,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .unapply_virtual_branch(project_id, branch)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn unapply_ownership(
+        handle: AppHandle,
+        project_id: ProjectId,
+        ownership: BranchOwnershipClaims,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .unapply_ownership(project_id, &ownership)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn reset_files(
+        handle: AppHandle,
+        project_id: ProjectId,
+        files: &str,
+    ) -> Result<(), Error> {
+        // convert files to Vec<String>
+        let files = files
+            .split('\n')
+            .map(std::string::ToString::to_string)
+            .collect::<Vec<String>>();
+        handle
+            .state::<Controller>()
+            .reset_files(project_id, &files)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn push_virtual_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        with_force: bool,
+    ) -> Result<PushResult, Error> {
+        let result = handle
+            .state::<Controller>()
+            .push_virtual_branch(project_id, branch_id, with_force, Some(Some(branch_id)))
+            .await
+            .map_err(|err| err.context(Code::Unknown))?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(result)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn set_branch_push_remote(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        remote: Option<&str>,
+    ) -> Result<(), Error> {
+        handle
+            .state::<Controller>()
+            .set_branch_push_remote(project_id, branch_id, remote)
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn can_apply_virtual_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<bool, Error> {
+        handle
+            .state::<Controller>()
+            .can_apply_virtual_branch(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn can_apply_remote_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch: git::RemoteRefname,
+    ) -> Result<bool, Error> {
+        Ok(handle
+            .state::<Controller>()
+            .can_apply_remote_branch(project_id, &branch)
+            .await?)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn list_remote_commit_files(
+        handle: AppHandle,
+        project_id: ProjectId,
+        commit_oid: String,
+        rename_threshold: Option<u16>,
+        with_attribution: Option<bool>,
+    ) -> Result<Vec<RemoteBranchFile>, Error> {
+        let commit_oid = git2::Oid::from_str(&commit_oid).map_err(|e| anyhow!(e))?;
+        handle
+            .state::<Controller>()
+            .list_remote_commit_files(
+                project_id,
+                commit_oid,
+                rename_threshold,
+                with_attribution.unwrap_or_default(),
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn commit_binary_delta_size(
+        handle: AppHandle,
+        project_id: ProjectId,
+        commit_oid: String,
+        path: &path::Path,
+    ) -> Result<Option<u64>, Error> {
+        let commit_oid = git2::Oid::from_str(&commit_oid).map_err(|e| anyhow!(e))?;
+        handle
+            .state::<Controller>()
+            .commit_binary_delta_size(project_id, commit_oid, path)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn worktree_changes(
+        handle: AppHandle,
+        project_id: ProjectId,
+        pathspecs: Vec<String>,
+        with_submodules: Option<bool>,
+        include_ignored: Option<bool>,
+        rename_threshold: Option<u16>,
+        diff_limit_bytes: Option<usize>,
+    ) -> Result<WorktreeChanges, Error> {
+        handle
+            .state::<Controller>()
+            .worktree_changes(
+                project_id,
+                &pathspecs,
+                with_submodules.unwrap_or_default(),
+                include_ignored.unwrap_or_default(),
+                rename_threshold,
+                // keep what is sent to the frontend small even if huge files changed
+                Some(diff_limit_bytes.unwrap_or(git::diff::DEFAULT_DIFF_LIMIT_BYTES)),
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn file_diff(
+        handle: AppHandle,
+        project_id: ProjectId,
+        path: &path::Path,
+        context_lines: u32,
+        word_diff: Option<git::diff::WordDiff>,
+    ) -> Result<WorktreeFileDiff, Error> {
+        handle
+            .state::<Controller>()
+            .file_diff(
+                project_id,
+                path,
+                context_lines,
+                word_diff.unwrap_or_default(),
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn dirstat(handle: AppHandle, project_id: ProjectId) -> Result<Vec<DirStat>, Error> {
+        handle
+            .state::<Controller>()
+            .dirstat(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn porcelain_status(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<String, Error> {
+        handle
+            .state::<Controller>()
+            .porcelain_status(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn large_new_binaries(
+        handle: AppHandle,
+        project_id: ProjectId,
+        threshold_bytes: u64,
+    ) -> Result<Vec<LargeBinary>, Error> {
+        handle
+            .state::<Controller>()
+            .large_new_binaries(project_id, threshold_bytes)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn commits_touching_path(
+        handle: AppHandle,
+        project_id: ProjectId,
+        path: &path::Path,
+    ) -> Result<Vec<PathCommit>, Error> {
+        handle
+            .state::<Controller>()
+            .commits_touching_path(project_id, path)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn path_metadata(
+        handle: AppHandle,
+        project_id: ProjectId,
+        path: &path::Path,
+        at: Option<String>,
+    ) -> Result<Option<PathMetadata>, Error> {
+        let at = at
+            .map(|at| git2::Oid::from_str(&at).map_err(|e| anyhow!(e)))
+            .transpose()?;
+        handle
+            .state::<Controller>()
+            .path_metadata(project_id, path, at)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn force_push_safety(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<ForcePushSafety, Error> {
+        handle
+            .state::<Controller>()
+            .force_push_safety(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn diff_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_a: BranchId,
+        branch_b: BranchId,
+        context_lines: u32,
+    ) -> Result<Vec<RemoteBranchFile>, Error> {
+        handle
+            .state::<Controller>()
+            .diff_branches(project_id, branch_a, branch_b, context_lines)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn diff_against_remote(
+        handle: AppHandle,
+        project_id: ProjectId,
+        remote: &str,
+        branch: &str,
+        branch_id: Option<BranchId>,
+        context_lines: u32,
+        two_dot: Option<bool>,
+    ) -> Result<Vec<RemoteBranchFile>, Error> {
+        handle
+            .state::<Controller>()
+            .diff_against_remote(
+                project_id,
+                remote,
+                branch,
+                branch_id,
+                context_lines,
+                two_dot.unwrap_or_default(),
+            )
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn workspace_change_ids(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<WorkspaceChangeIds, Error> {
+        handle
+            .state::<Controller>()
+            .workspace_change_ids(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn branch_topological_order(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<Vec<BranchId>, Error> {
+        handle
+            .state::<Controller>()
+            .branch_topological_order(project_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn active_branches(
+        handle: AppHandle,
+        project_id: ProjectId,
+    ) -> Result<ActiveBranches, Error> {
+        handle
+            .state::<Controller>()
+            .active_branches(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn list_virtual_branches(
+    pub async fn stack_pr_descriptions(
         handle: AppHandle,
         project_id: ProjectId,
-    ) -> Result<VirtualBranches, Error> {
-        let (branches, skipped_files) = handle
+        branch_id: BranchId,
+    ) -> Result<Vec<PrDescription>, Error> {
+        handle
             .state::<Controller>()
-            .list_virtual_branches(project_id)
-            .await?;
-
-        let proxy = handle.state::<assets::Proxy>();
-        let branches = proxy.proxy_virtual_branches(branches).await;
-        Ok(VirtualBranches {
-            branches,
-            skipped_files,
-        })
+            .stack_pr_descriptions(project_id, branch_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn create_virtual_branch(
+    pub async fn pr_base_branch(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: branch::BranchCreateRequest,
-    ) -> Result<BranchId, Error> {
-        let branch_id = handle
+        branch_id: BranchId,
+    ) -> Result<PrBase, Error> {
+        handle
             .state::<Controller>()
-            .create_virtual_branch(project_id, &branch)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(branch_id)
+            .pr_base_branch(project_id, branch_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn create_virtual_branch_from_branch(
+    pub async fn branch_exclusive_commits(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: git::Refname,
-    ) -> Result<BranchId, Error> {
-        let branch_id = handle
+        branch_id: BranchId,
+    ) -> Result<Vec<BranchSegment>, Error> {
+        handle
             .state::<Controller>()
-            .create_virtual_branch_from_branch(project_id, &branch)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(branch_id)
+            .branch_exclusive_commits(project_id, branch_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn integrate_upstream_commits(
+    pub async fn branches_merge_base(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: BranchId,
-    ) -> Result<(), Error> {
-        handle
+        a: BranchId,
+        b: BranchId,
+    ) -> Result<String, Error> {
+        let merge_base = handle
             .state::<Controller>()
-            .integrate_upstream_commits(project_id, branch)
+            .branches_merge_base(project_id, a, b)
             .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+        Ok(merge_base.to_string())
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn get_base_branch_data(
+    pub async fn safe_amend_targets(
         handle: AppHandle,
         project_id: ProjectId,
-    ) -> Result<Option<BaseBranch>, Error> {
-        if let Ok(base_branch) = handle
+        file_path: &path::Path,
+        hunk: &str,
+    ) -> Result<AmendTargets, Error> {
+        let hunk: Hunk = hunk.parse().context("Invalid hunk")?;
+        handle
             .state::<Controller>()
-            .get_base_branch_data(project_id)
+            .safe_amend_targets(project_id, file_path, &hunk)
             .await
-        {
-            let proxy = handle.state::<assets::Proxy>();
-            let base_branch = proxy.proxy_base_branch(base_branch).await;
-            return Ok(Some(base_branch));
-        }
-        Ok(None)
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn set_base_branch(
+    pub async fn hunk_locks(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: &str,
-        push_remote: Option<&str>, // optional different name of a remote to push to (defaults to same as the branch)
-    ) -> Result<BaseBranch, Error> {
-        let branch_name = format!("refs/remotes/{}", branch)
-            .parse()
-            .context("Invalid branch name")?;
-        let base_branch = handle
+        allow_stale: bool,
+        context_lines: Option<u32>,
+        allow_partial: Option<bool>,
+    ) -> Result<HunkLocks, Error> {
+        handle
             .state::<Controller>()
-            .set_base_branch(project_id, &branch_name)
-            .await?;
-        let base_branch = handle
-            .state::<assets::Proxy>()
-            .proxy_base_branch(base_branch)
-            .await;
-
-        // if they also sent a different push remote, set that too
-        if let Some(push_remote) = push_remote {
-            handle
-                .state::<Controller>()
-                .set_target_push_remote(project_id, push_remote)
-                .await?;
-        }
-        emit_vbranches(&handle, project_id).await;
-        Ok(base_branch)
+            .hunk_locks(
+                project_id,
+                allow_stale,
+                context_lines,
+                allow_partial.unwrap_or_default(),
+            )
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn update_base_branch(
+    pub async fn commit_dependency_order(
         handle: AppHandle,
         project_id: ProjectId,
-    ) -> Result<Vec<branch::Branch>, Error> {
-        let unapplied_branches = handle
+        branch_id: BranchId,
+    ) -> Result<Vec<String>, Error> {
+        let commit_ids = handle
             .state::<Controller>()
-            .update_base_branch(project_id)
+            .commit_dependency_order(project_id, branch_id)
             .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(unapplied_branches)
+        Ok(commit_ids.iter().map(ToString::to_string).collect())
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn update_virtual_branch(
+    pub async fn hunk_apply_order(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: branch::BranchUpdateRequest,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<HunkGroup>, Error> {
         handle
             .state::<Controller>()
-            .update_virtual_branch(project_id, branch)
-            .await?;
-
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .hunk_apply_order(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn delete_virtual_branch(
+    pub async fn hunk_counts_by_branch(
         handle: AppHandle,
         project_id: ProjectId,
-        branch_id: BranchId,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<BranchHunkCounts>, Error> {
         handle
             .state::<Controller>()
-            .delete_virtual_branch(project_id, branch_id)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .hunk_counts_by_branch(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn apply_branch(
+    pub async fn cross_branch_locked_hunks(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: BranchId,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<CrossBranchLockedFile>, Error> {
         handle
             .state::<Controller>()
-            .apply_virtual_branch(project_id, branch)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .cross_branch_locked_hunks(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn unapply_branch(
+    pub async fn lock_impact_of_applying(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: BranchId,
-    ) -> Result<(), Error> {
+        branch_id: BranchId,
+    ) -> Result<Vec<LockImpact>, Error> {
         handle
             .state::<Controller>()
-            .unapply_virtual_branch(project_id, branch)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .lock_impact_of_applying(project_id, branch_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn unapply_ownership(
+    pub async fn unowned_files(
         handle: AppHandle,
         project_id: ProjectId,
-        ownership: BranchOwnershipClaims,
-    ) -> Result<(), Error> {
+    ) -> Result<Vec<UnownedFile>, Error> {
         handle
             .state::<Controller>()
-            .unapply_ownership(project_id, &ownership)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .unowned_files(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn reset_files(
+    pub async fn redundant_worktree_changes(
         handle: AppHandle,
         project_id: ProjectId,
-        files: &str,
-    ) -> Result<(), Error> {
-        // convert files to Vec<String>
-        let files = files
-            .split('\n')
-            .map(std::string::ToString::to_string)
-            .collect::<Vec<String>>();
+    ) -> Result<Vec<RedundantHunk>, Error> {
         handle
             .state::<Controller>()
-            .reset_files(project_id, &files)
-            .await?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .redundant_worktree_changes(project_id)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn push_virtual_branch(
+    pub async fn base_to_target_diff(
         handle: AppHandle,
         project_id: ProjectId,
         branch_id: BranchId,
-        with_force: bool,
-    ) -> Result<(), Error> {
+        context_lines: u32,
+    ) -> Result<SquashedDiff, Error> {
         handle
             .state::<Controller>()
-            .push_virtual_branch(project_id, branch_id, with_force, Some(Some(branch_id)))
+            .base_to_target_diff(project_id, branch_id, context_lines)
             .await
-            .map_err(|err| err.context(Code::Unknown))?;
-        emit_vbranches(&handle, project_id).await;
-        Ok(())
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn can_apply_virtual_branch(
+    pub async fn branch_squashed_diff(
         handle: AppHandle,
         project_id: ProjectId,
         branch_id: BranchId,
-    ) -> Result<bool, Error> {
+        context_lines: u32,
+    ) -> Result<SquashedDiff, Error> {
         handle
             .state::<Controller>()
-            .can_apply_virtual_branch(project_id, branch_id)
+            .branch_squashed_diff(project_id, branch_id, context_lines)
             .await
             .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn can_apply_remote_branch(
+    pub async fn commit_similarity(
         handle: AppHandle,
         project_id: ProjectId,
-        branch: git::RemoteRefname,
-    ) -> Result<bool, Error> {
-        Ok(handle
+        a: String,
+        b: String,
+    ) -> Result<f64, Error> {
+        let a = git2::Oid::from_str(&a).map_err(|e| anyhow!(e))?;
+        let b = git2::Oid::from_str(&b).map_err(|e| anyhow!(e))?;
+        handle
             .state::<Controller>()
-            .can_apply_remote_branch(project_id, &branch)
-            .await?)
+            .commit_similarity(project_id, a, b)
+            .await
+            .map_err(Into::into)
     }
 
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
-    pub async fn list_remote_commit_files(
+    pub async fn hunks_identical(
         handle: AppHandle,
         project_id: ProjectId,
-        commit_oid: String,
-    ) -> Result<Vec<RemoteBranchFile>, Error> {
-        let commit_oid = git2::Oid::from_str(&commit_oid).map_err(|e| anyhow!(e))?;
+        a: HunkRef,
+        b: HunkRef,
+    ) -> Result<HunkComparison, Error> {
         handle
             .state::<Controller>()
-            .list_remote_commit_files(project_id, commit_oid)
+            .hunks_identical(project_id, &a, &b)
             .await
             .map_err(Into::into)
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn ancestry_path(
+        handle: AppHandle,
+        project_id: ProjectId,
+        from: String,
+        to: String,
+        first_parent: bool,
+    ) -> Result<Option<Vec<String>>, Error> {
+        let from = git2::Oid::from_str(&from).map_err(|e| anyhow!(e))?;
+        let to = git2::Oid::from_str(&to).map_err(|e| anyhow!(e))?;
+        let path = handle
+            .state::<Controller>()
+            .ancestry_path(project_id, from, to, first_parent)
+            .await?;
+        Ok(path.map(|oids| oids.iter().map(ToString::to_string).collect()))
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn reset_virtual_branch(
@@ -341,11 +1116,12 @@ pub mod commands {
         project_id: ProjectId,
         branch_id: BranchId,
         target_commit_oid: String,
+        conflict_style: Option<ConflictStyle>,
     ) -> Result<Option<String>, Error> {
         let target_commit_oid = git2::Oid::from_str(&target_commit_oid).map_err(|e| anyhow!(e))?;
         let oid = handle
             .state::<Controller>()
-            .cherry_pick(project_id, branch_id, target_commit_oid)
+            .cherry_pick(project_id, branch_id, target_commit_oid, conflict_style)
             .await?;
         emit_vbranches(&handle, project_id).await;
         Ok(oid.map(|o| o.to_string()))
@@ -369,6 +1145,37 @@ pub mod commands {
         Ok(oid.to_string())
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn split_hunk(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        hunk: HunkIdentity,
+        old_lines: Vec<u32>,
+        new_lines: Vec<u32>,
+        first_commit: String,
+        second_commit: String,
+    ) -> Result<SplitHunks, Error> {
+        let first_commit = git2::Oid::from_str(&first_commit).map_err(|e| anyhow!(e))?;
+        let second_commit = git2::Oid::from_str(&second_commit).map_err(|e| anyhow!(e))?;
+        let split = handle
+            .state::<Controller>()
+            .split_hunk(
+                project_id,
+                branch_id,
+                &hunk,
+                &old_lines,
+                &new_lines,
+                first_commit,
+                second_commit,
+            )
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(split)
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn move_commit_file(
@@ -479,6 +1286,20 @@ pub mod commands {
         Ok(branch_data)
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn can_squash_branch(
+        handle: AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+    ) -> Result<Vec<SquashBlocker>, Error> {
+        handle
+            .state::<Controller>()
+            .can_squash_branch(project_id, branch_id)
+            .await
+            .map_err(Into::into)
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn squash_branch_commit(
@@ -496,18 +1317,52 @@ pub mod commands {
         Ok(())
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn preview_squash(
+        handle: tauri::AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        target_commit_oid: String,
+    ) -> Result<SquashPreview, Error> {
+        let target_commit_oid = git2::Oid::from_str(&target_commit_oid).map_err(|e| anyhow!(e))?;
+        handle
+            .state::<Controller>()
+            .preview_squash(project_id, branch_id, target_commit_oid)
+            .await
+            .map_err(Into::into)
+    }
+
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn preview_reorder(
+        handle: tauri::AppHandle,
+        project_id: ProjectId,
+        branch_id: BranchId,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<ReorderPreview, Error> {
+        handle
+            .state::<Controller>()
+            .preview_reorder(project_id, branch_id, from_index, to_index)
+            .await
+            .map_err(Into::into)
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn fetch_from_remotes(
         handle: tauri::AppHandle,
         project_id: ProjectId,
         action: Option<String>,
+        fetch_all: Option<bool>,
     ) -> Result<BaseBranch, Error> {
         let base_branch = handle
             .state::<Controller>()
             .fetch_from_remotes(
                 project_id,
                 Some(action.unwrap_or_else(|| "unknown".to_string())),
+                fetch_all.unwrap_or_default(),
             )
             .await?;
         emit_vbranches(&handle, project_id).await;
@@ -531,6 +1386,31 @@ pub mod commands {
         Ok(())
     }
 
+    #[tauri::command(async)]
+    #[instrument(skip(handle), err(Debug))]
+    pub async fn move_commit_between_branches(
+        handle: tauri::AppHandle,
+        project_id: ProjectId,
+        source_branch_id: BranchId,
+        commit_oid: String,
+        target_branch_id: BranchId,
+        position: usize,
+    ) -> Result<(), Error> {
+        let commit_oid = git2::Oid::from_str(&commit_oid).map_err(|e| anyhow!(e))?;
+        handle
+            .state::<Controller>()
+            .move_commit_between_branches(
+                project_id,
+                source_branch_id,
+                commit_oid,
+                target_branch_id,
+                position,
+            )
+            .await?;
+        emit_vbranches(&handle, project_id).await;
+        Ok(())
+    }
+
     #[tauri::command(async)]
     #[instrument(skip(handle), err(Debug))]
     pub async fn update_commit_message(